@@ -0,0 +1,203 @@
+//! Helpers for bundling rendered pages into archives
+//!
+//! * [render_to_zip] - Renders pages into a ZIP archive of numbered images
+//! * [render_to_zip_writer] - Renders pages into a ZIP archive written to the provided writer
+
+use std::{
+    collections::HashSet,
+    io::{Cursor, Seek, Write},
+};
+
+use thiserror::Error;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    image::{verify_pages, OutputEncoder, OutputFormat, PageArgs, PdfRenderError, RenderArgs},
+    info::PdfInfo,
+    shared::PageSelection,
+};
+
+/// Args for naming the files within the archive
+#[derive(Debug, Clone)]
+pub struct ZipArgs {
+    /// Pattern used for the file name of each page, `{page}` is replaced
+    /// with the page number and `{ext}` with the extension of the format
+    pub name_pattern: String,
+    /// Minimum number of digits for the page number, shorter page
+    /// numbers are padded with leading zeros
+    pub page_padding: usize,
+}
+
+impl Default for ZipArgs {
+    fn default() -> Self {
+        Self {
+            name_pattern: Self::DEFAULT_NAME_PATTERN.to_string(),
+            page_padding: 0,
+        }
+    }
+}
+
+impl ZipArgs {
+    pub const DEFAULT_NAME_PATTERN: &'static str = "page-{page}.{ext}";
+
+    pub fn set_name_pattern(mut self, name_pattern: impl Into<String>) -> Self {
+        self.name_pattern = name_pattern.into();
+        self
+    }
+
+    pub fn set_page_padding(mut self, page_padding: usize) -> Self {
+        self.page_padding = page_padding;
+        self
+    }
+
+    /// Creates the file name for the provided page
+    pub fn file_name(&self, page: u32, format: OutputFormat) -> String {
+        let page = format!("{page:0width$}", width = self.page_padding);
+
+        self.name_pattern
+            .replace("{page}", &page)
+            .replace("{ext}", format.extension())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PdfZipError {
+    #[error(transparent)]
+    Render(#[from] PdfRenderError),
+
+    #[error("failed to write archive: {0}")]
+    Archive(#[from] zip::result::ZipError),
+
+    #[error("failed to write archive: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Renders the selected pages into a ZIP archive, pages are rendered one
+/// at a time and written into the archive as soon as they are rendered so
+/// only a single rendered page is held in memory at once.
+///
+/// Pages selected more than once are only included once, in the position
+/// of their first selection. The images are stored without compression as
+/// the image formats are already compressed
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info
+/// * format - The output format for the pages
+/// * selection - The pages to include in the archive
+/// * args - Optional args for pdftocairo
+/// * zip_args - Args for naming the files
+pub async fn render_to_zip(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    selection: &PageSelection,
+    args: &RenderArgs,
+    zip_args: &ZipArgs,
+) -> Result<Vec<u8>, PdfZipError> {
+    let output = render_to_zip_writer(
+        Cursor::new(Vec::new()),
+        data,
+        info,
+        format,
+        selection,
+        args,
+        zip_args,
+    )
+    .await?;
+
+    Ok(output.into_inner())
+}
+
+/// Renders the provided pages into a ZIP archive written to the provided
+/// writer, see [render_to_zip]. Providing the finished writer.
+///
+/// Writing is blocking, use an in-memory writer or a writer that is
+/// safe to block on within the async runtime
+///
+/// ## Arguments
+/// * writer - Writer to write the archive to
+/// * data - The raw PDF file bytes
+/// * info - The PDF info
+/// * format - The output format for the pages
+/// * selection - The pages to include in the archive
+/// * args - Optional args for pdftocairo
+/// * zip_args - Args for naming the files
+pub async fn render_to_zip_writer<W: Write + Seek>(
+    writer: W,
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    selection: &PageSelection,
+    args: &RenderArgs,
+    zip_args: &ZipArgs,
+) -> Result<W, PdfZipError> {
+    let pages = selection
+        .resolve(info, args.empty_document)
+        .map_err(PdfRenderError::from)?;
+    let pages = unique_pages(pages);
+
+    args.validate(format).map_err(PdfRenderError::from)?;
+    verify_pages(data, info, args, &pages).await?;
+    let page_args = PageArgs::new(data, Some(info), args, &pages).await?;
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut archive = ZipWriter::new(writer);
+
+    let encoder = OutputEncoder::new(format, args);
+
+    for page in pages {
+        let output = page_args
+            .job(format, page)
+            .run(data)
+            .await
+            .map_err(PdfRenderError::from)?;
+        let output = encoder.encode(output)?;
+
+        archive.start_file(zip_args.file_name(page, format), options)?;
+        archive.write_all(&output)?;
+    }
+
+    Ok(archive.finish()?)
+}
+
+/// Removes the repeated pages keeping the first occurrence of each page,
+/// each page can only be written to the archive once
+fn unique_pages(pages: Vec<u32>) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    pages
+        .into_iter()
+        .filter(|page| seen.insert(*page))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{unique_pages, ZipArgs};
+    use crate::image::OutputFormat;
+
+    /// Tests repeated pages are only kept once in their first position
+    #[test]
+    fn test_unique_pages() {
+        assert_eq!(unique_pages(vec![3, 1, 3, 2, 1]), [3, 1, 2]);
+    }
+
+    /// Tests creating the file names for pages
+    #[test]
+    fn test_file_name() {
+        let args = ZipArgs::default();
+        assert_eq!(args.file_name(7, OutputFormat::Png), "page-7.png");
+
+        let args = args
+            .set_name_pattern("scans/{page}-of-doc.{ext}")
+            .set_page_padding(3);
+        assert_eq!(
+            args.file_name(7, OutputFormat::Jpeg),
+            "scans/007-of-doc.jpg"
+        );
+        assert_eq!(
+            args.file_name(1234, OutputFormat::Tiff),
+            "scans/1234-of-doc.tif"
+        );
+    }
+}
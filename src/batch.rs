@@ -0,0 +1,269 @@
+//! Helpers for rendering every page using a single render process
+//!
+//! * [render_all_pages_batched] - Renders all pages with one process and decodes them
+//! * [render_all_pages_raw_batched] - Renders all pages with one process providing the encoded bytes
+//!
+//! The other render functions spawn pdftocairo for each page and write the
+//! whole PDF to each of them, which is slow for large documents. These run
+//! pdftocairo once writing the pages into a temporary directory that is
+//! removed once the pages are read
+//!
+//! Args that are resolved against the size of each page (fractional crops
+//! and scales or [RenderArgs::auto_dpi]) cannot be used with a single
+//! process, those fall back to rendering each page separately
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    pin::pin,
+    time::Duration,
+};
+
+use futures_util::future::{select, Either};
+
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+use crate::{
+    image::{decode_page, render_all_pages},
+    image_crate::DynamicImage,
+};
+use crate::{
+    image::{
+        render_all_pages_raw, verify_all_pages, OutputEncoder, OutputFormat, PdfRenderError,
+        RenderArgs,
+    },
+    info::PdfInfo,
+    shared::{run_tool, TEMP_DIR_PREFIX},
+};
+
+/// Name of the output files within the temporary directory, pdftocairo and
+/// pdftoppm append the zero padded page number and extension (e.g. `page-007.png`)
+const OUTPUT_PREFIX: &str = "page";
+
+/// How often the size of the output files is checked while pdftocairo is running
+const OUTPUT_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Renders all the pages in the provided PDF using a single pdftocairo
+/// process, decoding each of the pages. Falls back to [render_all_pages]
+/// when the args are resolved for each page
+///
+/// See [render_all_pages_raw_batched]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * args - Optional args to pdftocairo
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_all_pages_batched(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    args: &RenderArgs,
+) -> Result<Vec<DynamicImage>, PdfRenderError> {
+    if args.is_relative() {
+        return render_all_pages(data, info, format, args).await;
+    }
+
+    let pages = render_batch(data, info, format, args).await?;
+    pages
+        .iter()
+        .map(|output| decode_page(output, format))
+        .collect()
+}
+
+/// Renders all the pages in the provided PDF using a single pdftocairo
+/// process, providing the encoded output bytes for each page.
+///
+/// The PDF is only written to pdftocairo once, the [RenderArgs::timeout]
+/// applies to rendering the whole document and the
+/// [RenderArgs::max_output_size] to each page. The output files are
+/// checked while pdftocairo is running and it is stopped as soon as a page
+/// exceeds the limit. Falls back to [render_all_pages_raw] when the args
+/// are resolved for each page
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * args - Optional args to pdftocairo
+pub async fn render_all_pages_raw_batched(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    args: &RenderArgs,
+) -> Result<Vec<Vec<u8>>, PdfRenderError> {
+    if args.is_relative() {
+        return render_all_pages_raw(data, info, format, args).await;
+    }
+
+    let encoder = OutputEncoder::new(format, args);
+    let pages = render_batch(data, info, format, args).await?;
+    pages
+        .into_iter()
+        .map(|output| encoder.encode(output))
+        .collect()
+}
+
+/// Renders all the pages with a single pdftocairo process, provides the
+/// output from pdftocairo for each page before it is re-encoded
+async fn render_batch(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    args: &RenderArgs,
+) -> Result<Vec<Vec<u8>>, PdfRenderError> {
+    args.validate(format)?;
+    let page_count = verify_all_pages(data, info, args).await?;
+    if page_count == 0 {
+        return Ok(Vec::new());
+    }
+
+    // Directory is removed when dropped, including on failure
+    let directory = tempfile::Builder::new()
+        .prefix(TEMP_DIR_PREFIX)
+        .tempdir()
+        .map_err(PdfRenderError::OutputDirectory)?;
+
+    let mut cli_args: Vec<OsString> = vec![
+        "-".into(),
+        directory.path().join(OUTPUT_PREFIX).into_os_string(),
+        "-f".into(),
+        "1".into(),
+        "-l".into(),
+        page_count.to_string().into(),
+    ];
+
+    let mut render_args = args.build_args();
+    format.push_arg(&mut render_args);
+    cli_args.extend(render_args.into_iter().map(OsString::from));
+
+    let run_options = args.run_options();
+    let run = run_tool(args.backend.program(), &cli_args, data, &run_options);
+
+    match args.max_output_size {
+        Some(limit) => {
+            // Stops pdftocairo when a page is too large rather than letting it
+            // write the remaining pages, dropping the run kills the process
+            let watch = watch_output_size(directory.path(), limit);
+            match select(pin!(run), pin!(watch)).await {
+                Either::Left((result, _)) => {
+                    result?;
+                }
+                Either::Right((err, _)) => return Err(err),
+            }
+        }
+        None => {
+            run.await?;
+        }
+    }
+
+    let mut pages = Vec::with_capacity(page_count as usize);
+
+    for page in 1..=page_count {
+        let path = output_path(directory.path(), format, page, page_count);
+        let output = tokio::fs::read(&path)
+            .await
+            .map_err(PdfRenderError::OutputDirectory)?;
+
+        if let Some(limit) = args.max_output_size {
+            if output.len() > limit {
+                return Err(PdfRenderError::OutputLimitExceeded(limit));
+            }
+        }
+
+        pages.push(output);
+    }
+
+    Ok(pages)
+}
+
+/// Checks the size of the files in the output directory until one of them
+/// exceeds the `limit`, providing the error for the exceeded limit
+///
+/// ## Arguments
+/// * directory - The directory pdftocairo is writing the pages to
+/// * limit - The maximum allowed size of each page
+async fn watch_output_size(directory: &Path, limit: usize) -> PdfRenderError {
+    loop {
+        tokio::time::sleep(OUTPUT_CHECK_INTERVAL).await;
+
+        if largest_file(directory)
+            .await
+            .is_some_and(|size| size > limit as u64)
+        {
+            return PdfRenderError::OutputLimitExceeded(limit);
+        }
+    }
+}
+
+/// Size of the largest file within the directory, [None] when the
+/// directory could not be read
+async fn largest_file(directory: &Path) -> Option<u64> {
+    let mut entries = tokio::fs::read_dir(directory).await.ok()?;
+    let mut largest = 0;
+
+    while let Some(entry) = entries.next_entry().await.ok()? {
+        // Files can be removed or replaced while reading the directory
+        if let Ok(metadata) = entry.metadata().await {
+            largest = largest.max(metadata.len());
+        }
+    }
+
+    Some(largest)
+}
+
+/// Path pdftocairo writes the provided page to, page numbers are zero
+/// padded to the number of digits in the last page number
+fn output_path(directory: &Path, format: OutputFormat, page: u32, page_count: u32) -> PathBuf {
+    let width = page_count.to_string().len();
+    let extension = format.render_format().extension();
+
+    directory.join(format!("{OUTPUT_PREFIX}-{page:0width$}.{extension}"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{largest_file, output_path, watch_output_size};
+    use crate::image::{OutputFormat, PdfRenderError};
+
+    /// Tests the page numbers are padded like pdftocairo
+    #[test]
+    fn test_output_path() {
+        let directory = Path::new("out");
+
+        assert_eq!(
+            output_path(directory, OutputFormat::Png, 3, 9),
+            directory.join("page-3.png")
+        );
+        assert_eq!(
+            output_path(directory, OutputFormat::Jpeg, 7, 120),
+            directory.join("page-007.jpg")
+        );
+        assert_eq!(
+            output_path(directory, OutputFormat::Tiff, 120, 120),
+            directory.join("page-120.tif")
+        );
+    }
+
+    /// Tests the output directory is watched until a file exceeds the limit
+    #[tokio::test]
+    async fn test_watch_output_size() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("page-1.png");
+
+        std::fs::write(&path, [0; 64]).unwrap();
+        assert_eq!(largest_file(directory.path()).await, Some(64));
+
+        let watch = tokio::time::timeout(
+            std::time::Duration::from_millis(200),
+            watch_output_size(directory.path(), 64),
+        );
+        assert!(watch.await.is_err());
+
+        std::fs::write(&path, [0; 65]).unwrap();
+        let err = watch_output_size(directory.path(), 64).await;
+        assert!(matches!(err, PdfRenderError::OutputLimitExceeded(64)));
+    }
+}
@@ -0,0 +1,421 @@
+//! Command line tool for processing PDF files, wraps the library
+//! functions and prints the results as JSON, errors are printed as
+//! JSON to stderr
+//!
+//! * `info` - Prints a summary of the PDF info
+//! * `render` - Renders pages to image files
+//! * `text` - Extracts the text from pages
+//! * `split` - Splits pages into separate PDF files using pdfseparate
+//! * `merge` - Merges PDF files into a single PDF file using pdfunite
+//! * `preview` - Prints a page as ASCII art or a sixel image, requires the
+//!   `terminal` feature
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use pdf_process::{
+    pdf_info, render_pages_raw_numbered, run_poppler_tool, text_all_pages_split, text_pages,
+    OutputFormat, Password, PdfInfoArgs, PdfTextArgs, PopplerTool, PopplerToolArgs, RenderArgs,
+    Resolution, TEMP_DIR_PREFIX,
+};
+#[cfg(feature = "terminal")]
+use pdf_process::{render_ascii_preview, render_sixel_preview};
+use serde_json::{json, Value};
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Extract info, text and images from PDF files using poppler"
+)]
+struct Args {
+    /// User password for the PDF
+    #[arg(long, global = true)]
+    password: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints a summary of the PDF info
+    Info {
+        /// PDF file to read
+        file: PathBuf,
+    },
+    /// Renders pages to image files
+    Render {
+        /// PDF file to read
+        file: PathBuf,
+        /// Directory to write the images to
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+        /// Pages to render, defaults to all pages
+        #[arg(short, long, value_delimiter = ',')]
+        pages: Vec<u32>,
+        /// Output image format
+        #[arg(short, long, value_enum, default_value_t = Format::Png)]
+        format: Format,
+        /// Resolution in pixels per inch
+        #[arg(short, long)]
+        resolution: Option<u32>,
+    },
+    /// Extracts the text from pages
+    Text {
+        /// PDF file to read
+        file: PathBuf,
+        /// Pages to extract, defaults to all pages
+        #[arg(short, long, value_delimiter = ',')]
+        pages: Vec<u32>,
+    },
+    /// Splits pages into separate PDF files
+    Split {
+        /// PDF file to read
+        file: PathBuf,
+        /// Directory to write the PDF files to
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+        /// Pages to split, defaults to all pages
+        #[arg(short, long, value_delimiter = ',')]
+        pages: Vec<u32>,
+    },
+    /// Merges PDF files into a single PDF file
+    Merge {
+        /// PDF files to merge, in order
+        #[arg(required = true, num_args = 2..)]
+        files: Vec<PathBuf>,
+        /// Path to write the merged PDF file to
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Prints a page as ASCII art or a sixel image
+    #[cfg(feature = "terminal")]
+    Preview {
+        /// PDF file to read
+        file: PathBuf,
+        /// Page to preview
+        #[arg(short, long, default_value_t = 1)]
+        page: u32,
+        /// Width in characters, or in pixels for sixel output
+        #[arg(short, long, default_value_t = 80)]
+        width: u32,
+        /// Print a sixel image instead of ASCII art
+        #[arg(long)]
+        sixel: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Png,
+    Jpeg,
+    Tiff,
+    #[cfg(feature = "webp")]
+    Webp,
+    #[cfg(feature = "avif")]
+    Avif,
+}
+
+impl Format {
+    fn output_format(&self) -> OutputFormat {
+        match self {
+            Self::Png => OutputFormat::Png,
+            Self::Jpeg => OutputFormat::Jpeg,
+            Self::Tiff => OutputFormat::Tiff,
+            #[cfg(feature = "webp")]
+            Self::Webp => OutputFormat::WebP,
+            #[cfg(feature = "avif")]
+            Self::Avif => OutputFormat::Avif,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+
+    match run(args).await {
+        // Previews are printed directly
+        Ok(Value::Null) => ExitCode::SUCCESS,
+        Ok(value) => {
+            println!("{value:#}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{:#}", json!({ "error": err }));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<Value, String> {
+    let password = args.password.map(Password::user);
+
+    let mut info_args = PdfInfoArgs::default().set_raw_dates(true);
+    if let Some(password) = password.clone() {
+        info_args = info_args.set_password(password);
+    }
+
+    match args.command {
+        Command::Info { file } => {
+            let data = read(&file).await?;
+            let info = pdf_info(&data, &info_args)
+                .await
+                .map_err(|err| err.to_string())?;
+
+            serde_json::to_value(info.summary()).map_err(|err| err.to_string())
+        }
+
+        Command::Render {
+            file,
+            output,
+            pages,
+            format,
+            resolution,
+        } => {
+            let data = read(&file).await?;
+            let info = pdf_info(&data, &info_args)
+                .await
+                .map_err(|err| err.to_string())?;
+
+            let pages = if pages.is_empty() {
+                let page_count = info
+                    .pages()
+                    .and_then(Result::ok)
+                    .ok_or("page count is unknown")?;
+                (1..=page_count).collect()
+            } else {
+                pages
+            };
+
+            let mut render_args = RenderArgs::default();
+            if let Some(resolution) = resolution {
+                render_args = render_args.set_resolution(Resolution::uniform(resolution));
+            }
+            if let Some(password) = password {
+                render_args = render_args.set_password(password);
+            }
+
+            let images = render_pages_raw_numbered(
+                &data,
+                &info,
+                format.output_format(),
+                pages,
+                &render_args,
+            )
+            .await
+            .map_err(|err| err.to_string())?;
+
+            let stem = file
+                .file_stem()
+                .map(|value| value.to_string_lossy().to_string())
+                .unwrap_or_else(|| "page".to_string());
+
+            let mut files = Vec::with_capacity(images.len());
+            for (page, image) in images {
+                let path = output.join(format!(
+                    "{stem}-{page}.{}",
+                    format.output_format().extension()
+                ));
+                tokio::fs::write(&path, image)
+                    .await
+                    .map_err(|err| format!("failed to write {}: {err}", path.display()))?;
+
+                files.push(json!({ "page": page, "path": path }));
+            }
+
+            Ok(json!({ "files": files }))
+        }
+
+        Command::Text { file, pages } => {
+            let data = read(&file).await?;
+
+            let mut text_args = PdfTextArgs::default().set_trim_trailing_page(true);
+            if let Some(password) = password {
+                text_args = text_args.set_password(password);
+            }
+
+            let text = if pages.is_empty() {
+                text_all_pages_split(&data, &text_args).await
+            } else {
+                let info = pdf_info(&data, &info_args)
+                    .await
+                    .map_err(|err| err.to_string())?;
+                text_pages(&data, &info, pages, &text_args).await
+            }
+            .map_err(|err| err.to_string())?;
+
+            Ok(json!({ "pages": text }))
+        }
+
+        Command::Split {
+            file,
+            output,
+            pages,
+        } => {
+            if password.is_some() {
+                return Err("pdfseparate does not support passwords".to_string());
+            }
+
+            let stem = file
+                .file_stem()
+                .map(|value| value.to_string_lossy().to_string())
+                .unwrap_or_else(|| "page".to_string());
+
+            // pdfseparate only writes to files, the pages are written to a
+            // directory that is removed when dropped and moved once complete
+            let directory = temp_dir()?;
+
+            let pages = if pages.is_empty() {
+                // Split every page with a single process, pdfseparate
+                // replaces the %d with the page number
+                let pattern = directory.path().join("%d.pdf");
+                separate(&[file.as_os_str(), pattern.as_os_str()]).await?;
+                separated_pages(directory.path()).await?
+            } else {
+                for page in &pages {
+                    let temp_path = directory.path().join(format!("{page}.pdf"));
+                    let page_arg = page.to_string();
+                    separate(&[
+                        "-f".as_ref(),
+                        page_arg.as_ref(),
+                        "-l".as_ref(),
+                        page_arg.as_ref(),
+                        file.as_os_str(),
+                        temp_path.as_os_str(),
+                    ])
+                    .await?;
+                }
+                pages
+            };
+
+            let mut files = Vec::with_capacity(pages.len());
+            for page in pages {
+                let temp_path = directory.path().join(format!("{page}.pdf"));
+                let path = output.join(format!("{stem}-{page}.pdf"));
+                copy(&temp_path, &path).await?;
+
+                files.push(json!({ "page": page, "path": path }));
+            }
+
+            Ok(json!({ "files": files }))
+        }
+
+        Command::Merge { files, output } => {
+            if password.is_some() {
+                return Err("pdfunite does not support passwords".to_string());
+            }
+
+            // pdfunite only writes to files, the output is written to a
+            // directory that is removed when dropped and moved once complete
+            let directory = temp_dir()?;
+            let temp_path = directory.path().join("merged.pdf");
+
+            let mut cli_args: Vec<&OsStr> = files.iter().map(|file| file.as_os_str()).collect();
+            cli_args.push(temp_path.as_os_str());
+
+            run_poppler_tool(
+                PopplerTool::PdfUnite,
+                &cli_args,
+                &[],
+                &PopplerToolArgs::default(),
+            )
+            .await
+            .map_err(|err| err.to_string())?;
+
+            copy(&temp_path, &output).await?;
+
+            Ok(json!({ "path": output, "files": files }))
+        }
+
+        #[cfg(feature = "terminal")]
+        Command::Preview {
+            file,
+            page,
+            width,
+            sixel,
+        } => {
+            let data = read(&file).await?;
+            let info = pdf_info(&data, &info_args)
+                .await
+                .map_err(|err| err.to_string())?;
+
+            let mut render_args = RenderArgs::default();
+            if let Some(password) = password {
+                render_args = render_args.set_password(password);
+            }
+
+            let preview = if sixel {
+                render_sixel_preview(&data, &info, page, width, &render_args).await
+            } else {
+                render_ascii_preview(&data, &info, page, width, &render_args).await
+            }
+            .map_err(|err| err.to_string())?;
+
+            print!("{preview}");
+            Ok(Value::Null)
+        }
+    }
+}
+
+async fn read(file: &Path) -> Result<Vec<u8>, String> {
+    tokio::fs::read(file)
+        .await
+        .map_err(|err| format!("failed to read {}: {err}", file.display()))
+}
+
+async fn separate(cli_args: &[&OsStr]) -> Result<(), String> {
+    run_poppler_tool(
+        PopplerTool::PdfSeparate,
+        cli_args,
+        &[],
+        &PopplerToolArgs::default(),
+    )
+    .await
+    .map(|_| ())
+    .map_err(|err| err.to_string())
+}
+
+/// Page numbers of the files pdfseparate wrote to the directory in order
+async fn separated_pages(directory: &Path) -> Result<Vec<u32>, String> {
+    let mut entries = tokio::fs::read_dir(directory)
+        .await
+        .map_err(|err| format!("failed to read output directory: {err}"))?;
+
+    let mut pages = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|err| format!("failed to read output directory: {err}"))?
+    {
+        let name = entry.file_name();
+        if let Some(page) = name
+            .to_str()
+            .and_then(|name| name.strip_suffix(".pdf"))
+            .and_then(|page| page.parse::<u32>().ok())
+        {
+            pages.push(page);
+        }
+    }
+
+    pages.sort_unstable();
+    Ok(pages)
+}
+
+async fn copy(from: &Path, to: &Path) -> Result<(), String> {
+    tokio::fs::copy(from, to)
+        .await
+        .map(|_| ())
+        .map_err(|err| format!("failed to write {}: {err}", to.display()))
+}
+
+fn temp_dir() -> Result<tempfile::TempDir, String> {
+    tempfile::Builder::new()
+        .prefix(TEMP_DIR_PREFIX)
+        .tempdir()
+        .map_err(|err| format!("failed to create output directory: {err}"))
+}
@@ -0,0 +1,344 @@
+//! Limits on the resources used by a whole operation
+//!
+//! * [Budget] - Limits shared by every process run for an operation
+//!
+//! The timeouts and output limits on the args apply to each process on
+//! their own, rendering a large document runs one process per page so a
+//! single request can still use a lot of resources. A [Budget] is shared
+//! by every process started with the args it is set on, once any limit is
+//! reached no more processes are started and the operation fails with
+//! [BudgetExceeded] reporting how far it got
+
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+/// Limits on the processes run for an operation. Clones of the budget
+/// share the same usage, create a new budget for each operation
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    /// Maximum number of processes that may be started
+    pub max_processes: Option<u32>,
+    /// Maximum total time the processes may run for, the running time of
+    /// each process is added together including processes running at the
+    /// same time
+    pub max_cpu_time: Option<Duration>,
+    /// Maximum total number of bytes the processes may output
+    pub max_output_bytes: Option<u64>,
+    /// Time the whole operation must finish by
+    pub deadline: Option<Instant>,
+    /// Usage shared between the clones of the budget
+    usage: Arc<BudgetCounters>,
+}
+
+/// Usage counters shared between the clones of a [Budget]
+#[derive(Debug, Default)]
+struct BudgetCounters {
+    processes: AtomicU32,
+    completed: AtomicU32,
+    cpu_time_micros: AtomicU64,
+    output_bytes: AtomicU64,
+}
+
+/// Limit of a [Budget] that was reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    Processes,
+    CpuTime,
+    OutputBytes,
+    Deadline,
+}
+
+impl Display for BudgetLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Processes => "process",
+            Self::CpuTime => "cpu time",
+            Self::OutputBytes => "output size",
+            Self::Deadline => "deadline",
+        })
+    }
+}
+
+/// Resources used so far by the processes of a [Budget]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetUsage {
+    /// Number of processes started
+    pub processes: u32,
+    /// Number of processes that finished successfully
+    pub completed: u32,
+    /// Total running time of the finished processes
+    pub cpu_time: Duration,
+    /// Total bytes output by the successful processes
+    pub output_bytes: u64,
+}
+
+/// Error for an operation that reached one of the limits of its [Budget]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "{limit} budget exceeded after {} of {} processes completed",
+    usage.completed,
+    usage.processes
+)]
+pub struct BudgetExceeded {
+    /// The limit that was reached
+    pub limit: BudgetLimit,
+    /// Usage at the time the limit was reached
+    pub usage: BudgetUsage,
+}
+
+impl Budget {
+    pub fn set_max_processes(mut self, max_processes: u32) -> Self {
+        self.max_processes = Some(max_processes);
+        self
+    }
+
+    pub fn set_max_cpu_time(mut self, max_cpu_time: Duration) -> Self {
+        self.max_cpu_time = Some(max_cpu_time);
+        self
+    }
+
+    pub fn set_max_output_bytes(mut self, max_output_bytes: u64) -> Self {
+        self.max_output_bytes = Some(max_output_bytes);
+        self
+    }
+
+    pub fn set_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the deadline to the provided duration from now
+    pub fn set_timeout(self, timeout: Duration) -> Self {
+        self.set_deadline(Instant::now() + timeout)
+    }
+
+    /// Resources used so far by the processes of the budget
+    pub fn usage(&self) -> BudgetUsage {
+        BudgetUsage {
+            processes: self.usage.processes.load(Ordering::Acquire),
+            completed: self.usage.completed.load(Ordering::Acquire),
+            cpu_time: Duration::from_micros(self.usage.cpu_time_micros.load(Ordering::Acquire)),
+            output_bytes: self.usage.output_bytes.load(Ordering::Acquire),
+        }
+    }
+
+    /// Error for reaching the provided limit with the current usage
+    pub(crate) fn exceeded(&self, limit: BudgetLimit) -> BudgetExceeded {
+        BudgetExceeded {
+            limit,
+            usage: self.usage(),
+        }
+    }
+
+    /// Checks the limits that have been reached by the usage so far
+    fn check_usage(&self) -> Result<(), BudgetExceeded> {
+        let usage = self.usage();
+
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(self.exceeded(BudgetLimit::Deadline));
+        }
+
+        if self.max_cpu_time.is_some_and(|max| usage.cpu_time >= max) {
+            return Err(self.exceeded(BudgetLimit::CpuTime));
+        }
+
+        if self
+            .max_output_bytes
+            .is_some_and(|max| usage.output_bytes >= max)
+        {
+            return Err(self.exceeded(BudgetLimit::OutputBytes));
+        }
+
+        Ok(())
+    }
+
+    /// Reserves a process from the budget before it is started, fails when
+    /// any limit has already been reached
+    pub(crate) fn start_process(&self) -> Result<(), BudgetExceeded> {
+        self.check_usage()?;
+
+        if let Some(max_processes) = self.max_processes {
+            let reserved = self.usage.processes.fetch_update(
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                |processes| (processes < max_processes).then_some(processes + 1),
+            );
+
+            if reserved.is_err() {
+                return Err(self.exceeded(BudgetLimit::Processes));
+            }
+        } else {
+            self.usage.processes.fetch_add(1, Ordering::AcqRel);
+        }
+
+        Ok(())
+    }
+
+    /// Records the usage of a finished process, fails when the process
+    /// took the usage over a limit
+    ///
+    /// ## Arguments
+    /// * elapsed - How long the process ran for
+    /// * output_bytes - Number of bytes the process output
+    /// * success - Whether the process finished successfully
+    pub(crate) fn finish_process(
+        &self,
+        elapsed: Duration,
+        output_bytes: usize,
+        success: bool,
+    ) -> Result<(), BudgetExceeded> {
+        self.usage
+            .cpu_time_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::AcqRel);
+
+        if !success {
+            return Ok(());
+        }
+
+        self.usage
+            .output_bytes
+            .fetch_add(output_bytes as u64, Ordering::AcqRel);
+        self.usage.completed.fetch_add(1, Ordering::AcqRel);
+
+        let usage = self.usage();
+        if self.max_cpu_time.is_some_and(|max| usage.cpu_time > max) {
+            return Err(self.exceeded(BudgetLimit::CpuTime));
+        }
+
+        if self
+            .max_output_bytes
+            .is_some_and(|max| usage.output_bytes > max)
+        {
+            return Err(self.exceeded(BudgetLimit::OutputBytes));
+        }
+
+        Ok(())
+    }
+
+    /// Longest a process started now may run for along with the limit
+    /// that bounds it, [None] when neither time limit is set
+    pub(crate) fn remaining_time(&self) -> Option<(Duration, BudgetLimit)> {
+        let deadline = self.deadline.map(|deadline| {
+            (
+                deadline.saturating_duration_since(Instant::now()),
+                BudgetLimit::Deadline,
+            )
+        });
+        let cpu_time = self.max_cpu_time.map(|max| {
+            (
+                max.saturating_sub(self.usage().cpu_time),
+                BudgetLimit::CpuTime,
+            )
+        });
+
+        match (deadline, cpu_time) {
+            (Some(deadline), Some(cpu_time)) => Some(if cpu_time.0 < deadline.0 {
+                cpu_time
+            } else {
+                deadline
+            }),
+            (deadline, cpu_time) => deadline.or(cpu_time),
+        }
+    }
+
+    /// Most bytes a process started now may output, [None] when
+    /// the output is not limited
+    pub(crate) fn remaining_output(&self) -> Option<usize> {
+        self.max_output_bytes.map(|max| {
+            let remaining = max.saturating_sub(self.usage().output_bytes);
+            usize::try_from(remaining).unwrap_or(usize::MAX)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{Budget, BudgetLimit};
+
+    /// Tests processes are refused once the process limit is reached
+    #[test]
+    fn test_process_limit() {
+        let budget = Budget::default().set_max_processes(2);
+        let shared = budget.clone();
+
+        assert!(budget.start_process().is_ok());
+        assert!(shared
+            .finish_process(Duration::from_millis(5), 10, true)
+            .is_ok());
+        assert!(shared.start_process().is_ok());
+
+        let err = budget.start_process().unwrap_err();
+        assert_eq!(err.limit, BudgetLimit::Processes);
+        assert_eq!(err.usage.processes, 2);
+        assert_eq!(err.usage.completed, 1);
+        assert_eq!(err.usage.output_bytes, 10);
+    }
+
+    /// Tests the output and time usage is added across processes
+    #[test]
+    fn test_usage_limits() {
+        let budget = Budget::default()
+            .set_max_output_bytes(100)
+            .set_max_cpu_time(Duration::from_secs(1));
+
+        assert_eq!(budget.remaining_output(), Some(100));
+        budget.start_process().unwrap();
+        budget
+            .finish_process(Duration::from_millis(600), 60, true)
+            .unwrap();
+        assert_eq!(budget.remaining_output(), Some(40));
+        assert_eq!(
+            budget.remaining_time(),
+            Some((Duration::from_millis(400), BudgetLimit::CpuTime))
+        );
+
+        budget.start_process().unwrap();
+        let err = budget
+            .finish_process(Duration::from_millis(100), 50, true)
+            .unwrap_err();
+        assert_eq!(err.limit, BudgetLimit::OutputBytes);
+
+        // Failed processes still use time but not output
+        let budget = Budget::default().set_max_cpu_time(Duration::from_secs(1));
+        budget.start_process().unwrap();
+        budget
+            .finish_process(Duration::from_secs(1), 500, false)
+            .unwrap();
+        assert_eq!(budget.usage().output_bytes, 0);
+        assert_eq!(
+            budget.start_process().unwrap_err().limit,
+            BudgetLimit::CpuTime
+        );
+    }
+
+    /// Tests no processes are started after the deadline
+    #[test]
+    fn test_deadline() {
+        let budget = Budget::default().set_deadline(Instant::now());
+        assert_eq!(
+            budget.start_process().unwrap_err().limit,
+            BudgetLimit::Deadline
+        );
+
+        let budget = Budget::default().set_timeout(Duration::from_secs(60));
+        assert!(budget.start_process().is_ok());
+        assert!(matches!(
+            budget.remaining_time(),
+            Some((_, BudgetLimit::Deadline))
+        ));
+        assert_eq!(Budget::default().remaining_time(), None);
+    }
+}
@@ -0,0 +1,142 @@
+//! Rendering a single page to multiple formats at once
+//!
+//! * [render_page_bundle] - Render a page to several formats concurrently
+
+use futures_util::future::try_join_all;
+
+use crate::{
+    image::{
+        page_job, verify_pages, OutputEncoder, OutputFormat, PageArgs, PdfRenderError, RenderArgs,
+        RenderArgsError,
+    },
+    info::PdfInfo,
+    vector::{validate_vector_args, vector_job},
+};
+
+/// Output format requested from [render_page_bundle]
+#[derive(Debug, Clone)]
+pub enum BundleFormat {
+    /// Raster image in the provided format
+    Raster(OutputFormat, RenderArgs),
+    /// SVG vector image
+    Svg(RenderArgs),
+}
+
+impl BundleFormat {
+    /// Args used for rendering this format
+    pub fn args(&self) -> &RenderArgs {
+        match self {
+            Self::Raster(_, args) | Self::Svg(args) => args,
+        }
+    }
+
+    /// Checks the args can be used for this format
+    fn validate(&self) -> Result<(), RenderArgsError> {
+        match self {
+            Self::Raster(format, args) => args.validate(*format),
+            Self::Svg(args) => validate_vector_args(args, "-svg"),
+        }
+    }
+}
+
+/// Rendered output for a [BundleFormat]
+#[derive(Debug, Clone)]
+pub enum BundleOutput {
+    /// Encoded raster image bytes from pdftocairo
+    Raster(OutputFormat, Vec<u8>),
+    /// SVG document
+    Svg(String),
+}
+
+impl BundleOutput {
+    /// MIME type of the output
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Raster(format, _) => format.mime_type(),
+            Self::Svg(_) => "image/svg+xml",
+        }
+    }
+
+    /// Raw bytes of the output
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            Self::Raster(_, bytes) => bytes,
+            Self::Svg(svg) => svg.as_bytes(),
+        }
+    }
+}
+
+/// Renders a single page to each of the provided formats, each format is
+/// rendered by its own pdftocairo process running concurrently from the
+/// same PDF bytes. Outputs are provided in the same order as the formats.
+///
+/// The encryption state and page bounds are checked once using the
+/// args of the first format
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * page - The page to render
+/// * formats - The formats to render the page as
+pub async fn render_page_bundle(
+    data: &[u8],
+    info: &PdfInfo,
+    page: u32,
+    formats: &[BundleFormat],
+) -> Result<Vec<BundleOutput>, PdfRenderError> {
+    let Some(first) = formats.first() else {
+        return Ok(Vec::new());
+    };
+
+    for format in formats {
+        format.validate()?;
+    }
+
+    verify_pages(data, info, first.args(), &[page]).await?;
+
+    // Processes are killed as soon as any of them fail
+    try_join_all(formats.iter().map(|format| async move {
+        let page_args = PageArgs::new(data, Some(info), format.args(), &[page]).await?;
+        let args = page_args.args(page);
+
+        Ok(match format {
+            BundleFormat::Raster(format, _) => {
+                let output = page_job(*format, page, &args).run(data).await?;
+                let output = OutputEncoder::new(*format, &args).encode(output)?;
+                BundleOutput::Raster(*format, output)
+            }
+            BundleFormat::Svg(_) => {
+                let output = vector_job("-svg", page, page, &args).run(data).await?;
+                let svg = String::from_utf8(output).map_err(|err| {
+                    PdfRenderError::PdfRenderFailure(format!("svg output is not valid utf8: {err}"))
+                })?;
+                BundleOutput::Svg(svg)
+            }
+        })
+    }))
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BundleFormat, BundleOutput};
+    use crate::image::{JpegOptions, OutputFormat, RenderArgs};
+
+    /// Tests the args of each format are validated
+    #[test]
+    fn test_bundle_validate() {
+        let jpeg = RenderArgs::default().set_jpeg_options(JpegOptions::default());
+
+        assert!(BundleFormat::Raster(OutputFormat::Jpeg, jpeg.clone())
+            .validate()
+            .is_ok());
+        assert!(BundleFormat::Raster(OutputFormat::Png, jpeg.clone())
+            .validate()
+            .is_err());
+        assert!(BundleFormat::Svg(jpeg).validate().is_err());
+
+        let output = BundleOutput::Svg("<svg/>".to_string());
+        assert_eq!(output.mime_type(), "image/svg+xml");
+        assert_eq!(output.bytes(), b"<svg/>");
+    }
+}
@@ -0,0 +1,418 @@
+//! Helpers for classifying how PDF files were produced
+//!
+//! * [classify_document] - Classify a PDF as born digital, scanned, hybrid, form or presentation
+
+use std::time::Duration;
+
+use futures_util::future::try_join3;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    fonts::{pdf_font_list, PdfFontEntry, PdfFontsArgs, PdfFontsError},
+    images::{pdf_image_list, PdfImageEntry, PdfImageKind, PdfImagesArgs, PdfImagesError},
+    info::{pdf_info, pdf_page_count, PageDimensions, PdfInfo, PdfInfoArgs, PdfInfoError},
+    shared::Password,
+};
+
+/// Minimum resolution an image covering the page must have to be treated
+/// as a scan of the page, lower resolutions are usually backgrounds
+const MIN_SCAN_PPI: f32 = 72.0;
+
+/// Allowed difference between the aspect ratio of an image and the page
+/// for the image to be treated as covering the whole page
+const SCAN_ASPECT_TOLERANCE: f32 = 0.05;
+
+/// Broad class of a PDF file used for routing documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum DocumentClass {
+    /// Text and graphics created digitally (e.g. exported from a word processor)
+    BornDigital,
+    /// Every page is a scanned image, possibly with an OCR text layer
+    Scanned,
+    /// Mix of scanned pages and digital content
+    Hybrid,
+    /// Contains an interactive form (AcroForm or XFA)
+    Form,
+    /// Slides exported from presentation software
+    Presentation,
+}
+
+/// Kind of software that produced the file, detected from the
+/// Producer and Creator fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum DocumentGenerator {
+    /// Scanner or OCR software
+    Scanner,
+    /// Presentation software
+    Presentation,
+    /// Any other or missing generator
+    Unknown,
+}
+
+/// Lowercase fragments of the Producer or Creator for scanner and OCR software
+const SCANNER_GENERATORS: &[&str] = &[
+    "scan",
+    "paper capture",
+    "finereader",
+    "omnipage",
+    "readiris",
+    "ocrmypdf",
+    "tesseract",
+    "naps2",
+    "kofax",
+];
+
+/// Lowercase fragments of the Producer or Creator for presentation software
+const PRESENTATION_GENERATORS: &[&str] = &["powerpoint", "keynote", "impress", "google slides"];
+
+impl DocumentGenerator {
+    /// Detects the generator from the Producer and Creator fields
+    pub fn detect(producer: Option<&str>, creator: Option<&str>) -> Self {
+        let value = [producer, creator]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+
+        let matches =
+            |fragments: &[&str]| fragments.iter().any(|fragment| value.contains(fragment));
+
+        if matches(SCANNER_GENERATORS) {
+            Self::Scanner
+        } else if matches(PRESENTATION_GENERATORS) {
+            Self::Presentation
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Detects the generator from the PDF info
+    pub fn from_info(info: &PdfInfo) -> Self {
+        Self::detect(info.producer(), info.creator())
+    }
+}
+
+/// Signals collected from a PDF file that decide its [DocumentClass]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DocumentSignals {
+    /// Number of pages in the file
+    pub page_count: u32,
+    /// Pages that are covered by a single image
+    pub scanned_pages: Vec<u32>,
+    /// Pages that are wider than they are tall
+    pub landscape_pages: u32,
+    /// Number of fonts excluding OCR text layer fonts
+    pub font_count: usize,
+    /// Whether an OCR text layer font is present
+    pub ocr_text_layer: bool,
+    /// Whether the file has an interactive form
+    pub has_form: bool,
+    /// Software that produced the file
+    pub generator: DocumentGenerator,
+}
+
+impl DocumentSignals {
+    /// Collects the signals from the output of pdfinfo (with the per page
+    /// details for every page), pdfimages and pdffonts
+    pub fn new(info: &PdfInfo, images: &[PdfImageEntry], fonts: &[PdfFontEntry]) -> Self {
+        let page_count = info.pages().and_then(Result::ok).unwrap_or_default();
+        let pages =
+            (1..=page_count).filter_map(|page| Some((page, info.page_dimensions(page)?.ok()?)));
+
+        let mut scanned_pages = Vec::new();
+        let mut landscape_pages = 0;
+
+        for (page, dimensions) in pages {
+            let (width, height) = dimensions.rotated_size();
+            if width > height {
+                landscape_pages += 1;
+            }
+
+            let scanned = images
+                .iter()
+                .filter(|image| image.page == page && image.kind == PdfImageKind::Image)
+                .any(|image| covers_page(image, &dimensions));
+
+            if scanned {
+                scanned_pages.push(page);
+            }
+        }
+
+        let has_form = info.form().is_some_and(|form| form != "none");
+
+        Self {
+            page_count,
+            scanned_pages,
+            landscape_pages,
+            font_count: fonts
+                .iter()
+                .filter(|font| !font.is_ocr_text_layer())
+                .count(),
+            ocr_text_layer: fonts.iter().any(PdfFontEntry::is_ocr_text_layer),
+            has_form,
+            generator: DocumentGenerator::from_info(info),
+        }
+    }
+
+    /// Decides the class of the document from the signals
+    pub fn class(&self) -> DocumentClass {
+        if self.has_form {
+            return DocumentClass::Form;
+        }
+
+        let scanned = self.scanned_pages.len() as u32;
+        let all_pages = |count: u32| self.page_count > 0 && count == self.page_count;
+
+        if all_pages(scanned) {
+            // Digital text on top of the scans (e.g. stamped headers)
+            return if self.font_count > 0 {
+                DocumentClass::Hybrid
+            } else {
+                DocumentClass::Scanned
+            };
+        }
+
+        if scanned > 0 {
+            return DocumentClass::Hybrid;
+        }
+
+        // Scans that could not be matched to the page size (e.g. split into strips)
+        if self.generator == DocumentGenerator::Scanner && self.font_count == 0 {
+            return DocumentClass::Scanned;
+        }
+
+        if self.generator == DocumentGenerator::Presentation || all_pages(self.landscape_pages) {
+            return DocumentClass::Presentation;
+        }
+
+        DocumentClass::BornDigital
+    }
+}
+
+/// Checks whether the image has the aspect ratio of the page at a
+/// resolution that suggests it was scanned, images are placed in the
+/// unrotated page space so both orientations are accepted
+fn covers_page(image: &PdfImageEntry, dimensions: &PageDimensions) -> bool {
+    let (page_width, page_height) = (dimensions.width_pts / 72.0, dimensions.height_pts / 72.0);
+    let (width, height) = (image.width as f32, image.height as f32);
+
+    if page_width <= 0.0 || page_height <= 0.0 || height <= 0.0 {
+        return false;
+    }
+
+    [(width, height), (height, width)]
+        .into_iter()
+        .any(|(width, height)| {
+            let aspect_difference = (width / height) / (page_width / page_height) - 1.0;
+
+            aspect_difference.abs() <= SCAN_ASPECT_TOLERANCE
+                && width / page_width >= MIN_SCAN_PPI
+                && height / page_height >= MIN_SCAN_PPI
+        })
+}
+
+/// Classification of a PDF file along with the signals that decided it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DocumentClassification {
+    /// Class of the document
+    pub class: DocumentClass,
+    /// Signals used to decide the class
+    pub signals: DocumentSignals,
+}
+
+#[derive(Debug, Error)]
+pub enum ClassifyError {
+    #[error(transparent)]
+    Info(#[from] PdfInfoError),
+
+    #[error(transparent)]
+    Images(#[from] PdfImagesError),
+
+    #[error(transparent)]
+    Fonts(#[from] PdfFontsError),
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ClassifyArgs {
+    /// Password for the PDF
+    pub password: Option<Password>,
+    /// Optional maximum time each of the tools may take
+    pub timeout: Option<Duration>,
+}
+
+impl ClassifyArgs {
+    pub fn set_password(mut self, password: Password) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn info_args(&self) -> PdfInfoArgs {
+        PdfInfoArgs {
+            password: self.password.clone(),
+            timeout: self.timeout,
+            ..Default::default()
+        }
+    }
+
+    fn images_args(&self) -> PdfImagesArgs {
+        PdfImagesArgs {
+            password: self.password.clone(),
+            timeout: self.timeout,
+            ..Default::default()
+        }
+    }
+
+    fn fonts_args(&self) -> PdfFontsArgs {
+        PdfFontsArgs {
+            password: self.password.clone(),
+            timeout: self.timeout,
+            ..Default::default()
+        }
+    }
+}
+
+/// Classifies the provided PDF file by combining the generator from the
+/// Producer and Creator, the embedded images from pdfimages and the fonts
+/// from pdffonts. The tools are run concurrently once the page count is known
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Password and timeout for the tools
+pub async fn classify_document(
+    data: &[u8],
+    args: &ClassifyArgs,
+) -> Result<DocumentClassification, ClassifyError> {
+    let info_args = args.info_args();
+    let page_count = pdf_page_count(data, &info_args).await?;
+
+    // Per page details are needed to match images to the page size
+    let info_args = info_args.set_first_page(1).set_last_page(page_count.max(1));
+
+    let (info, images, fonts) = try_join3(
+        async { Ok::<_, ClassifyError>(pdf_info(data, &info_args).await?) },
+        async { Ok(pdf_image_list(data, &args.images_args()).await?) },
+        async { Ok(pdf_font_list(data, &args.fonts_args()).await?) },
+    )
+    .await?;
+
+    let signals = DocumentSignals::new(&info, &images, &fonts);
+
+    Ok(DocumentClassification {
+        class: signals.class(),
+        signals,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DocumentClass, DocumentGenerator, DocumentSignals};
+    use crate::{
+        fonts::PdfFontEntry,
+        images::{PdfImageEntry, PdfImageKind},
+        info::parse_pdf_info,
+    };
+
+    fn image(page: u32, width: u32, height: u32) -> PdfImageEntry {
+        PdfImageEntry {
+            page,
+            num: 0,
+            kind: PdfImageKind::Image,
+            width,
+            height,
+            color: "gray".to_string(),
+            components: 1,
+            bits_per_component: 8,
+            encoding: "jpeg".to_string(),
+            object: None,
+            x_ppi: None,
+            y_ppi: None,
+        }
+    }
+
+    fn font(name: &str) -> PdfFontEntry {
+        PdfFontEntry {
+            name: Some(name.to_string()),
+            font_type: "TrueType".to_string(),
+            encoding: "WinAnsi".to_string(),
+            embedded: true,
+            subset: true,
+            unicode: true,
+            object: None,
+        }
+    }
+
+    const INFO: &str = "\
+Producer:        Producer
+Form:            none
+Pages:           2
+Page    1 size:  612 x 792 pts (letter)
+Page    1 rot:   0
+Page    2 size:  612 x 792 pts (letter)
+Page    2 rot:   0
+";
+
+    /// Tests detecting the generator from the producer and creator
+    #[test]
+    fn test_detect_generator() {
+        assert_eq!(
+            DocumentGenerator::detect(Some("ABBYY FineReader 15"), None),
+            DocumentGenerator::Scanner
+        );
+        assert_eq!(
+            DocumentGenerator::detect(Some("macOS Quartz PDFContext"), Some("Keynote")),
+            DocumentGenerator::Presentation
+        );
+        assert_eq!(
+            DocumentGenerator::detect(Some("LibreOffice 7.6"), Some("Writer")),
+            DocumentGenerator::Unknown
+        );
+        assert_eq!(
+            DocumentGenerator::detect(None, None),
+            DocumentGenerator::Unknown
+        );
+    }
+
+    /// Tests classifying from the collected signals
+    #[test]
+    fn test_classify_signals() {
+        let info = parse_pdf_info(INFO).unwrap();
+
+        // Letter pages scanned at 300 ppi with an OCR text layer
+        let scans = [image(1, 2550, 3300), image(2, 2550, 3300)];
+        let signals = DocumentSignals::new(&info, &scans, &[font("GlyphLessFont")]);
+        assert_eq!(signals.scanned_pages, vec![1, 2]);
+        assert!(signals.ocr_text_layer);
+        assert_eq!(signals.class(), DocumentClass::Scanned);
+
+        // Scanned page with a digital page
+        let signals = DocumentSignals::new(&info, &scans[..1], &[font("Arial")]);
+        assert_eq!(signals.class(), DocumentClass::Hybrid);
+
+        // Small images do not cover the page
+        let signals = DocumentSignals::new(&info, &[image(1, 64, 64)], &[font("Arial")]);
+        assert!(signals.scanned_pages.is_empty());
+        assert_eq!(signals.class(), DocumentClass::BornDigital);
+
+        let info =
+            parse_pdf_info(&INFO.replace("Form:            none", "Form: AcroForm")).unwrap();
+        let signals = DocumentSignals::new(&info, &[], &[font("Arial")]);
+        assert_eq!(signals.class(), DocumentClass::Form);
+
+        let info = parse_pdf_info(&INFO.replace("612 x 792", "960 x 540")).unwrap();
+        let signals = DocumentSignals::new(&info, &[], &[font("Arial")]);
+        assert_eq!(signals.landscape_pages, 2);
+        assert_eq!(signals.class(), DocumentClass::Presentation);
+    }
+}
@@ -0,0 +1,181 @@
+//! Compatibility with older releases of poppler
+//!
+//! * [PopplerCompat::detect] - Get the options supported by the installed poppler tools
+//! * [PopplerCompat::render_args] - Remove the render options the installed tools do not support
+//!
+//! Distributions ship poppler releases that are several years apart, the
+//! newer command line options are rejected by older tools which fails the
+//! whole process. [PopplerCompat] checks the options against the detected
+//! version so they can be dropped or reported before anything is run.
+//!
+//! Only the render options with a fallback are covered, the output parsers
+//! are tested against the output of the installed poppler release
+
+use std::fmt::Display;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+    image::RenderArgs,
+    version::{poppler_version, PopplerVersion, PopplerVersionError},
+};
+
+/// Command line option that is not supported by every poppler release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum PopplerOption {
+    /// `pdftocairo -jpegopt` from [RenderArgs::jpeg_options]
+    JpegOptions,
+    /// `pdftoppm -thinlinemode` from [SplashOptions::thin_line_mode](crate::image::SplashOptions::thin_line_mode)
+    ThinLineMode,
+}
+
+impl PopplerOption {
+    /// Every option that depends on the poppler version
+    pub const ALL: &[PopplerOption] = &[Self::JpegOptions, Self::ThinLineMode];
+
+    /// Name of the tool the option is passed to
+    pub fn tool(&self) -> &'static str {
+        match self {
+            Self::JpegOptions => "pdftocairo",
+            Self::ThinLineMode => "pdftoppm",
+        }
+    }
+
+    /// Command line flag for the option
+    pub fn flag(&self) -> &'static str {
+        match self {
+            Self::JpegOptions => "-jpegopt",
+            Self::ThinLineMode => "-thinlinemode",
+        }
+    }
+
+    /// First poppler release that supports the option
+    pub fn since(&self) -> PopplerVersion {
+        match self {
+            Self::JpegOptions => PopplerVersion::new(0, 62, 0),
+            Self::ThinLineMode => PopplerVersion::new(0, 24, 0),
+        }
+    }
+}
+
+impl Display for PopplerOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.tool(), self.flag())
+    }
+}
+
+/// Options supported by a specific poppler release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PopplerCompat {
+    /// Version of poppler the options are checked against
+    pub version: PopplerVersion,
+}
+
+impl PopplerCompat {
+    pub fn new(version: PopplerVersion) -> Self {
+        Self { version }
+    }
+
+    /// Creates the compatibility for the installed poppler tools,
+    /// see [poppler_version]
+    pub async fn detect() -> Result<Self, PopplerVersionError> {
+        poppler_version().await.map(Self::new)
+    }
+
+    /// Whether the option is supported by the poppler version
+    pub fn supports(&self, option: PopplerOption) -> bool {
+        self.version >= option.since()
+    }
+
+    /// Options set on the provided args that are not supported
+    /// by the poppler version
+    ///
+    /// ## Arguments
+    /// * args - The render args to check
+    pub fn unsupported_render_options(&self, args: &RenderArgs) -> Vec<PopplerOption> {
+        let thin_line_mode = args
+            .splash_options
+            .is_some_and(|options| options.thin_line_mode.is_some());
+
+        [
+            (PopplerOption::JpegOptions, args.jpeg_options.is_some()),
+            (PopplerOption::ThinLineMode, thin_line_mode),
+        ]
+        .into_iter()
+        .filter(|(option, used)| *used && !self.supports(*option))
+        .map(|(option, _)| option)
+        .collect()
+    }
+
+    /// Copy of the provided args with the options that are not supported
+    /// by the poppler version removed, the tools fall back to their
+    /// defaults for the removed options
+    ///
+    /// ## Arguments
+    /// * args - The render args to adapt
+    pub fn render_args(&self, args: &RenderArgs) -> RenderArgs {
+        let mut args = args.clone();
+
+        for option in self.unsupported_render_options(&args) {
+            match option {
+                PopplerOption::JpegOptions => args.jpeg_options = None,
+                PopplerOption::ThinLineMode => {
+                    if let Some(options) = args.splash_options.as_mut() {
+                        options.thin_line_mode = None;
+                    }
+                }
+            }
+        }
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PopplerCompat, PopplerOption};
+    use crate::{
+        image::{JpegOptions, RenderArgs, RenderBackend, SplashOptions, ThinLineMode},
+        version::PopplerVersion,
+    };
+
+    /// Tests options are only supported from their first release
+    #[test]
+    fn test_supports() {
+        let old = PopplerCompat::new(PopplerVersion::new(0, 61, 1));
+        assert!(!old.supports(PopplerOption::JpegOptions));
+        assert!(old.supports(PopplerOption::ThinLineMode));
+
+        let new = PopplerCompat::new(PopplerVersion::new(22, 2, 0));
+        assert!(PopplerOption::ALL
+            .iter()
+            .all(|option| new.supports(*option)));
+    }
+
+    /// Tests unsupported options are removed from the render args
+    #[test]
+    fn test_render_args() {
+        let args = RenderArgs::default()
+            .set_jpeg_options(JpegOptions::default())
+            .set_backend(RenderBackend::Splash)
+            .set_splash_options(SplashOptions::default().set_thin_line_mode(ThinLineMode::Shape));
+
+        let old = PopplerCompat::new(PopplerVersion::new(0, 20, 0));
+        assert_eq!(
+            old.unsupported_render_options(&args),
+            [PopplerOption::JpegOptions, PopplerOption::ThinLineMode]
+        );
+
+        let adapted = old.render_args(&args);
+        assert!(adapted.jpeg_options.is_none());
+        assert!(adapted.splash_options.unwrap().thin_line_mode.is_none());
+        assert!(old.unsupported_render_options(&adapted).is_empty());
+
+        let new = PopplerCompat::new(PopplerVersion::new(24, 2, 0));
+        assert!(new.unsupported_render_options(&args).is_empty());
+        assert_eq!(new.render_args(&args).build_args(), args.build_args());
+    }
+}
@@ -0,0 +1,259 @@
+//! Helpers for retrying renders at lower resolutions
+//!
+//! * [render_single_page_raw_degrading] - Renders a page, retrying at lower resolutions when limits are hit
+//! * [DegradePolicy] - How far and how quickly the resolution is lowered
+//!
+//! Oversized pages (e.g. large drawings or posters) can exceed the
+//! [RenderArgs::max_output_size] or run cairo out of memory at the requested
+//! resolution. Retrying at a lower resolution gives a usable, less detailed
+//! render instead of an error, which is preferable for previews
+
+use crate::{
+    image::{
+        page_job, verify_pages, Crop, OutputEncoder, OutputFormat, PageArgs, PdfRenderError,
+        RenderArgs, Resolution,
+    },
+    info::PdfInfo,
+    shared::Rect,
+};
+
+/// Policy for retrying a render at progressively lower resolutions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DegradePolicy {
+    /// Factor the resolution is multiplied by for each retry, between 0.0 and 1.0
+    pub factor: f32,
+    /// Lowest resolution in pixels per inch to retry at
+    pub min_ppi: u32,
+    /// Maximum number of renders including the first attempt
+    pub max_attempts: u32,
+}
+
+impl Default for DegradePolicy {
+    fn default() -> Self {
+        Self {
+            factor: Self::DEFAULT_FACTOR,
+            min_ppi: Self::DEFAULT_MIN_PPI,
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+}
+
+impl DegradePolicy {
+    pub const DEFAULT_FACTOR: f32 = 0.5;
+    pub const DEFAULT_MIN_PPI: u32 = 18;
+    pub const DEFAULT_MAX_ATTEMPTS: u32 = 4;
+
+    pub fn set_factor(mut self, factor: f32) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    pub fn set_min_ppi(mut self, min_ppi: u32) -> Self {
+        self.min_ppi = min_ppi;
+        self
+    }
+
+    pub fn set_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Resolution to retry at after a render at the provided resolution
+    /// failed, [None] once the resolution cannot be lowered any further
+    ///
+    /// ## Arguments
+    /// * resolution - The resolution of the failed render
+    pub fn next_resolution(&self, resolution: Resolution) -> Option<Resolution> {
+        if !(self.factor > 0.0 && self.factor < 1.0) {
+            return None;
+        }
+
+        let lower = |ppi: u32| ((ppi as f32 * self.factor).floor() as u32).max(self.min_ppi.max(1));
+        let next = Resolution::new(lower(resolution.x_ppi()), lower(resolution.y_ppi()));
+
+        if next.x_ppi() >= resolution.x_ppi() && next.y_ppi() >= resolution.y_ppi() {
+            return None;
+        }
+
+        Some(next)
+    }
+
+    /// Whether the error was caused by the size of the render and may
+    /// succeed at a lower resolution
+    pub fn is_size_error(error: &PdfRenderError) -> bool {
+        match error {
+            PdfRenderError::OutputLimitExceeded(_) => true,
+            PdfRenderError::PdfRenderFailure(message) => {
+                message.to_ascii_lowercase().contains("memory")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Details of a render that succeeded at a lower resolution
+#[derive(Debug, Clone, PartialEq)]
+pub struct Degradation {
+    /// The page number
+    pub page: u32,
+    /// Resolution the page was requested at
+    pub requested: Resolution,
+    /// Resolution the page was rendered at
+    pub resolution: Resolution,
+    /// Number of renders including the successful one
+    pub attempts: u32,
+    /// Error from the last failed render
+    pub reason: String,
+}
+
+/// Output of a render that may have been retried at a lower resolution
+#[derive(Debug, Clone)]
+pub struct DegradedRender {
+    /// The encoded output from pdftocairo
+    pub output: Vec<u8>,
+    /// Details of the lowered resolution, [None] when the page
+    /// rendered at the requested resolution
+    pub degradation: Option<Degradation>,
+}
+
+/// Renders a specific page from the provided PDF file, providing the raw
+/// output bytes from pdftocairo. When the render exceeds the
+/// [RenderArgs::max_output_size] or runs out of memory it is retried at
+/// lower resolutions following the policy.
+///
+/// Renders scaled to a specific size using [RenderArgs::scale_to] are not
+/// retried as the resolution does not affect their size. A crop is scaled
+/// with the resolution so the same region of the page is rendered
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * page - The page to render
+/// * args - Optional args to pdftocairo
+/// * policy - Policy for lowering the resolution
+pub async fn render_single_page_raw_degrading(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    page: u32,
+    args: &RenderArgs,
+    policy: &DegradePolicy,
+) -> Result<DegradedRender, PdfRenderError> {
+    args.validate(format)?;
+    verify_pages(data, info, args, &[page]).await?;
+
+    let page_args = PageArgs::new(data, Some(info), args, &[page]).await?;
+    let mut args = page_args.args(page).into_owned();
+
+    let requested = args.resolution.unwrap_or_default();
+    let mut resolution = requested;
+    let mut attempts = 1;
+    let mut reason = None;
+
+    loop {
+        let error = match page_job(format, page, &args).run(data).await {
+            Ok(output) => {
+                return Ok(DegradedRender {
+                    output: OutputEncoder::new(format, &args).encode(output)?,
+                    degradation: reason.map(|reason| Degradation {
+                        page,
+                        requested,
+                        resolution,
+                        attempts,
+                        reason,
+                    }),
+                })
+            }
+            Err(err) => PdfRenderError::from(err),
+        };
+
+        if attempts >= policy.max_attempts
+            || args.scale_to.is_some()
+            || !DegradePolicy::is_size_error(&error)
+        {
+            return Err(error);
+        }
+
+        let Some(next) = policy.next_resolution(resolution) else {
+            return Err(error);
+        };
+
+        args.crop = args.crop.map(|crop| scale_crop(crop, resolution, next));
+        args.resolution = Some(next);
+        resolution = next;
+        attempts += 1;
+        reason = Some(error.to_string());
+    }
+}
+
+/// Scales a crop in output pixels from one resolution to another
+fn scale_crop(crop: Crop, from: Resolution, to: Resolution) -> Crop {
+    let rect = crop.rect();
+    let x = to.x_ppi() as f32 / from.x_ppi() as f32;
+    let y = to.y_ppi() as f32 / from.y_ppi() as f32;
+
+    Crop::from_rect(Rect::new(
+        rect.origin.x * x,
+        rect.origin.y * y,
+        rect.size.width * x,
+        rect.size.height * y,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{scale_crop, DegradePolicy};
+    use crate::image::{Crop, PdfRenderError, Resolution};
+
+    /// Tests the resolution is lowered until the minimum is reached
+    #[test]
+    fn test_next_resolution() {
+        let policy = DegradePolicy::default();
+
+        let mut steps = Vec::new();
+        let mut resolution = Resolution::uniform(150);
+        while let Some(next) = policy.next_resolution(resolution) {
+            steps.push(next.x_ppi());
+            resolution = next;
+        }
+        assert_eq!(steps, vec![75, 37, 18]);
+
+        assert_eq!(
+            policy.next_resolution(Resolution::new(300, 100)),
+            Some(Resolution::new(150, 50))
+        );
+
+        // Factors that would not lower the resolution never retry
+        let policy = policy.set_factor(1.0);
+        assert_eq!(policy.next_resolution(Resolution::uniform(150)), None);
+    }
+
+    /// Tests only size related errors are retried
+    #[test]
+    fn test_is_size_error() {
+        assert!(DegradePolicy::is_size_error(
+            &PdfRenderError::OutputLimitExceeded(1024)
+        ));
+        assert!(DegradePolicy::is_size_error(
+            &PdfRenderError::PdfRenderFailure("cairo error: out of memory".to_string())
+        ));
+        assert!(!DegradePolicy::is_size_error(
+            &PdfRenderError::PdfRenderFailure("Syntax Error".to_string())
+        ));
+        assert!(!DegradePolicy::is_size_error(&PdfRenderError::PdfEncrypted));
+    }
+
+    /// Tests crops keep covering the same region at lower resolutions
+    #[test]
+    fn test_scale_crop() {
+        let crop = scale_crop(
+            Crop::new(100, 200, 300, 400),
+            Resolution::uniform(150),
+            Resolution::uniform(75),
+        );
+        let rect = crop.rect();
+        assert_eq!((rect.origin.x, rect.origin.y), (50.0, 100.0));
+        assert_eq!((rect.size.width, rect.size.height), (150.0, 200.0));
+    }
+}
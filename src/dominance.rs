@@ -0,0 +1,285 @@
+//! Helpers for measuring how much of each page is made up of images
+//!
+//! * [page_image_dominance] - Gets the image coverage, text coverage and image dominance of each page
+//!
+//! The score combines the area covered by the images from `pdfimages -list`
+//! with the number of characters from `pdftotext`, pages close to 1.0 are
+//! mostly images (e.g. scans that need OCR or a higher render resolution)
+//! while pages close to 0.0 are mostly text
+
+use std::time::Duration;
+
+use futures_util::future::try_join3;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    images::{pdf_image_list, PdfImageEntry, PdfImageKind, PdfImagesArgs, PdfImagesError},
+    info::{pdf_info, pdf_page_count, PageDimensions, PdfInfoArgs, PdfInfoError},
+    shared::{EmptyDocumentPolicy, Password},
+    stats::document_stats,
+    text::{PdfTextArgs, PdfTextError},
+};
+
+/// Approximate area of the page in PDF points covered by a single character
+/// of body text including the spacing between characters and lines
+pub const CHARACTER_AREA: f32 = 70.0;
+
+/// Image and text composition of a single page
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PageImageDominance {
+    /// Page number
+    pub page: u32,
+    /// Number of images drawn on the page, masks are not included
+    pub image_count: usize,
+    /// Fraction of the page area covered by images between 0.0 and 1.0,
+    /// overlapping images are counted once for each image
+    pub image_coverage: f32,
+    /// Number of non-whitespace characters
+    pub characters: usize,
+    /// Estimated fraction of the page area covered by text between 0.0
+    /// and 1.0 based on [CHARACTER_AREA]
+    pub text_coverage: f32,
+    /// Share of the covered area that is images between 0.0 and 1.0,
+    /// 0.0 for blank pages
+    pub dominance: f32,
+}
+
+impl PageImageDominance {
+    /// Computes the composition of a page from the images drawn on it and
+    /// the number of characters in its text
+    ///
+    /// ## Arguments
+    /// * page - The page number
+    /// * dimensions - The dimensions of the page
+    /// * images - The images drawn on the page, images for other pages are ignored
+    /// * characters - The number of non-whitespace characters on the page
+    pub fn new(
+        page: u32,
+        dimensions: &PageDimensions,
+        images: &[PdfImageEntry],
+        characters: usize,
+    ) -> Self {
+        let page_area = dimensions.width_pts * dimensions.height_pts;
+
+        let images: Vec<&PdfImageEntry> = images
+            .iter()
+            .filter(|image| {
+                image.page == page
+                    && matches!(image.kind, PdfImageKind::Image | PdfImageKind::Stencil)
+            })
+            .collect();
+
+        let image_area: f32 = images
+            .iter()
+            .filter_map(|image| image.drawn_size())
+            .map(|size| size.width * size.height)
+            .sum();
+
+        let coverage = |area: f32| {
+            if page_area > 0.0 {
+                (area / page_area).clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        };
+
+        let image_coverage = coverage(image_area);
+        let text_coverage = coverage(characters as f32 * CHARACTER_AREA);
+
+        let covered = image_coverage + text_coverage;
+        let dominance = if covered > 0.0 {
+            image_coverage / covered
+        } else {
+            0.0
+        };
+
+        Self {
+            page,
+            image_count: images.len(),
+            image_coverage,
+            characters,
+            text_coverage,
+            dominance,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ImageDominanceError {
+    #[error(transparent)]
+    Info(#[from] PdfInfoError),
+
+    #[error(transparent)]
+    Images(#[from] PdfImagesError),
+
+    #[error(transparent)]
+    Text(#[from] PdfTextError),
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ImageDominanceArgs {
+    /// Password for the PDF
+    pub password: Option<Password>,
+    /// Optional maximum time each of the tools may take
+    pub timeout: Option<Duration>,
+    /// How documents with zero pages are handled
+    pub empty_document: EmptyDocumentPolicy,
+}
+
+impl ImageDominanceArgs {
+    pub fn set_password(mut self, password: Password) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_empty_document(mut self, empty_document: EmptyDocumentPolicy) -> Self {
+        self.empty_document = empty_document;
+        self
+    }
+
+    fn info_args(&self) -> PdfInfoArgs {
+        PdfInfoArgs {
+            password: self.password.clone(),
+            timeout: self.timeout,
+            ..Default::default()
+        }
+    }
+
+    fn images_args(&self) -> PdfImagesArgs {
+        PdfImagesArgs {
+            password: self.password.clone(),
+            timeout: self.timeout,
+            ..Default::default()
+        }
+    }
+
+    fn text_args(&self) -> PdfTextArgs {
+        PdfTextArgs {
+            password: self.password.clone(),
+            timeout: self.timeout,
+            ..Default::default()
+        }
+    }
+}
+
+/// Measures the image and text composition of every page in the provided
+/// PDF file using pdfinfo for the page sizes, pdfimages for the images and
+/// pdftotext for the characters. The tools are run concurrently once the
+/// page count is known
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Password and timeout for the tools
+pub async fn page_image_dominance(
+    data: &[u8],
+    args: &ImageDominanceArgs,
+) -> Result<Vec<PageImageDominance>, ImageDominanceError> {
+    let info_args = args.info_args();
+    let page_count = pdf_page_count(data, &info_args).await?;
+    let page_count = args
+        .empty_document
+        .check(page_count)
+        .map_err(PdfInfoError::from)?;
+    if page_count == 0 {
+        return Ok(Vec::new());
+    }
+
+    // Per page details are needed for the page sizes
+    let info_args = info_args.set_first_page(1).set_last_page(page_count);
+
+    let (info, images, stats) = try_join3(
+        async { Ok::<_, ImageDominanceError>(pdf_info(data, &info_args).await?) },
+        async { Ok(pdf_image_list(data, &args.images_args()).await?) },
+        async { Ok(document_stats(data, &args.text_args()).await?) },
+    )
+    .await?;
+
+    (1..=page_count)
+        .map(|page| {
+            let dimensions = info
+                .page_dimensions(page)
+                .ok_or(PdfInfoError::PageDimensionsUnavailable(page))??;
+            let characters = stats
+                .pages
+                .get(page as usize - 1)
+                .map(|stats| stats.characters)
+                .unwrap_or_default();
+
+            Ok(PageImageDominance::new(
+                page,
+                &dimensions,
+                &images,
+                characters,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::PageImageDominance;
+    use crate::{
+        images::{PdfImageEntry, PdfImageKind},
+        info::PageDimensions,
+    };
+
+    fn image(page: u32, kind: PdfImageKind, width: u32, height: u32, ppi: u32) -> PdfImageEntry {
+        PdfImageEntry {
+            page,
+            num: 0,
+            kind,
+            width,
+            height,
+            color: "gray".to_string(),
+            components: 1,
+            bits_per_component: 8,
+            encoding: "jpeg".to_string(),
+            object: None,
+            x_ppi: Some(ppi),
+            y_ppi: Some(ppi),
+        }
+    }
+
+    /// Tests the dominance of scanned, text and mixed pages
+    #[test]
+    fn test_image_dominance() {
+        let letter = PageDimensions {
+            width_pts: 612.0,
+            height_pts: 792.0,
+            rotation: 0,
+        };
+
+        // Page sized scan with its soft mask, no text layer
+        let images = [
+            image(1, PdfImageKind::Image, 2550, 3300, 300),
+            image(1, PdfImageKind::SoftMask, 2550, 3300, 300),
+        ];
+        let scan = PageImageDominance::new(1, &letter, &images, 0);
+        assert_eq!(scan.image_count, 1);
+        assert!((scan.image_coverage - 1.0).abs() < 0.001);
+        assert_eq!(scan.dominance, 1.0);
+
+        // Text only page, images on other pages are ignored
+        let text = PageImageDominance::new(2, &letter, &images, 3000);
+        assert_eq!(text.image_count, 0);
+        assert_eq!(text.dominance, 0.0);
+        assert!(text.text_coverage > 0.3);
+
+        // Half page photo with a few paragraphs
+        let images = [image(3, PdfImageKind::Image, 1275, 1650, 300)];
+        let mixed = PageImageDominance::new(3, &letter, &images, 1000);
+        assert!((mixed.image_coverage - 0.25).abs() < 0.001);
+        assert!(mixed.dominance > 0.5 && mixed.dominance < 0.9);
+
+        let blank = PageImageDominance::new(4, &letter, &[], 0);
+        assert_eq!(blank.dominance, 0.0);
+    }
+}
@@ -0,0 +1,278 @@
+//! Helpers for choosing the resolution to render pages at
+//!
+//! * [auto_resolution] - Chooses the resolution for a specific page
+//! * [AutoDpi] - Target used to choose the resolution, also used by
+//!   [RenderArgs::set_auto_dpi](crate::RenderArgs::set_auto_dpi)
+//!
+//! A fixed resolution renders large drawings (e.g. A0) at huge sizes and small
+//! pages (e.g. A6 receipts) with too few pixels, the resolution is instead
+//! chosen from the physical size of the page, the resolution of any scanned
+//! image covering the page and a target output size
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+    image::{PdfRenderError, Resolution},
+    images::{pdf_image_list, PdfImageEntry, PdfImageKind, PdfImagesArgs},
+    info::{page_dimensions, PageDimensions, PdfInfo, PdfInfoArgs},
+    shared::{Password, POINTS_PER_INCH},
+};
+
+/// Fraction of the page an image must cover to be treated as a scan of the page
+const SCAN_COVERAGE: f32 = 0.8;
+
+/// Target used to choose the resolution of each page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AutoDpi {
+    /// Length in pixels of the longest edge of the rendered page to aim
+    /// for, [None] to use [AutoDpi::DEFAULT_PPI]
+    pub target_long_edge: Option<u32>,
+    /// Lowest resolution in pixels per inch
+    pub min_ppi: u32,
+    /// Highest resolution in pixels per inch
+    pub max_ppi: u32,
+    /// Whether to render pages covered by a scanned image at the resolution
+    /// of the scan, avoids resampling the scan
+    pub match_scans: bool,
+    /// Maximum number of pixels in the rendered page, the resolution is
+    /// lowered to fit even when below [AutoDpi::min_ppi]
+    pub max_pixels: Option<u64>,
+}
+
+impl Default for AutoDpi {
+    fn default() -> Self {
+        Self {
+            target_long_edge: Some(Self::DEFAULT_LONG_EDGE),
+            min_ppi: Self::DEFAULT_MIN_PPI,
+            max_ppi: Self::DEFAULT_MAX_PPI,
+            match_scans: false,
+            max_pixels: Some(Self::DEFAULT_MAX_PIXELS),
+        }
+    }
+}
+
+impl AutoDpi {
+    /// Resolution used when there is no target size or scan
+    pub const DEFAULT_PPI: u32 = 150;
+    pub const DEFAULT_LONG_EDGE: u32 = 2000;
+    pub const DEFAULT_MIN_PPI: u32 = 72;
+    pub const DEFAULT_MAX_PPI: u32 = 600;
+    /// 50 megapixels, roughly A4 at 800 PPI
+    pub const DEFAULT_MAX_PIXELS: u64 = 50_000_000;
+
+    pub fn set_target_long_edge(mut self, target_long_edge: Option<u32>) -> Self {
+        self.target_long_edge = target_long_edge;
+        self
+    }
+
+    pub fn set_min_ppi(mut self, min_ppi: u32) -> Self {
+        self.min_ppi = min_ppi;
+        self
+    }
+
+    pub fn set_max_ppi(mut self, max_ppi: u32) -> Self {
+        self.max_ppi = max_ppi;
+        self
+    }
+
+    pub fn set_match_scans(mut self, match_scans: bool) -> Self {
+        self.match_scans = match_scans;
+        self
+    }
+
+    pub fn set_max_pixels(mut self, max_pixels: Option<u64>) -> Self {
+        self.max_pixels = max_pixels;
+        self
+    }
+
+    /// Chooses the resolution for a page, the resolution of the scan is
+    /// preferred over the target size when [AutoDpi::match_scans] is enabled.
+    /// The result is clamped between the min and max resolutions then
+    /// lowered to fit the [AutoDpi::max_pixels]
+    ///
+    /// ## Arguments
+    /// * dimensions - The dimensions of the page
+    /// * scan_ppi - Resolution of a scanned image covering the page, see [scan_ppi]
+    pub fn resolution(&self, dimensions: &PageDimensions, scan_ppi: Option<u32>) -> Resolution {
+        let size = dimensions.display_size();
+        let (width, height) = (size.width / POINTS_PER_INCH, size.height / POINTS_PER_INCH);
+        let long_edge = width.max(height);
+
+        let ppi = match (scan_ppi.filter(|_| self.match_scans), self.target_long_edge) {
+            (Some(scan_ppi), _) => scan_ppi as f32,
+            (None, Some(target)) if long_edge > 0.0 => target as f32 / long_edge,
+            _ => Self::DEFAULT_PPI as f32,
+        };
+
+        let mut ppi = ppi.clamp(self.min_ppi as f32, self.max_ppi.max(self.min_ppi) as f32);
+
+        if let Some(max_pixels) = self.max_pixels {
+            let area = width * height;
+            if area > 0.0 {
+                ppi = ppi.min((max_pixels as f32 / area).sqrt());
+            }
+        }
+
+        Resolution::uniform((ppi.floor() as u32).max(1))
+    }
+}
+
+/// Resolution of a scanned image covering the provided page, [None] when
+/// no image covers at least 80% of the page
+///
+/// ## Arguments
+/// * page - The page number
+/// * dimensions - The dimensions of the page
+/// * images - The images from [pdf_image_list], images for other pages are ignored
+pub fn scan_ppi(page: u32, dimensions: &PageDimensions, images: &[PdfImageEntry]) -> Option<u32> {
+    let page_area = dimensions.width_pts * dimensions.height_pts;
+
+    images
+        .iter()
+        .filter(|image| image.page == page && image.kind == PdfImageKind::Image)
+        .filter(|image| {
+            image
+                .drawn_size()
+                .is_some_and(|size| size.width * size.height >= page_area * SCAN_COVERAGE)
+        })
+        .filter_map(|image| Some(image.x_ppi?.min(image.y_ppi?)))
+        .max()
+}
+
+/// Chooses the resolution to render a specific page at using the provided
+/// target. The page dimensions are taken from the info when it contains
+/// the per page details, otherwise pdfinfo is used. The images on the page
+/// are only listed when [AutoDpi::match_scans] is enabled
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info that may already contain the page dimensions
+/// * page - The page to choose the resolution for
+/// * target - The target used to choose the resolution
+/// * password - Optional password for the PDF
+pub async fn auto_resolution(
+    data: &[u8],
+    info: &PdfInfo,
+    page: u32,
+    target: &AutoDpi,
+    password: Option<&Password>,
+) -> Result<Resolution, PdfRenderError> {
+    let dimensions = match info.page_dimensions(page) {
+        Some(dimensions) => dimensions?,
+        None => {
+            let mut info_args = PdfInfoArgs::default();
+            if let Some(password) = password {
+                info_args = info_args.set_password(password.clone());
+            }
+            page_dimensions(data, page, &info_args).await?
+        }
+    };
+
+    let scan_ppi = if target.match_scans {
+        let mut images_args = PdfImagesArgs::default()
+            .set_first_page(page)
+            .set_last_page(page);
+        if let Some(password) = password {
+            images_args = images_args.set_password(password.clone());
+        }
+
+        let images = pdf_image_list(data, &images_args).await?;
+        scan_ppi(page, &dimensions, &images)
+    } else {
+        None
+    };
+
+    Ok(target.resolution(&dimensions, scan_ppi))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{scan_ppi, AutoDpi};
+    use crate::{
+        image::Resolution,
+        images::{PdfImageEntry, PdfImageKind},
+        info::PageDimensions,
+    };
+
+    fn page(width_mm: f32, height_mm: f32) -> PageDimensions {
+        PageDimensions {
+            width_pts: width_mm / 25.4 * 72.0,
+            height_pts: height_mm / 25.4 * 72.0,
+            rotation: 0,
+        }
+    }
+
+    /// Tests the resolution follows the page size and is clamped
+    #[test]
+    fn test_auto_resolution() {
+        let target = AutoDpi::default();
+
+        // A4 long edge of 11.7 inches
+        let resolution = target.resolution(&page(210.0, 297.0), None);
+        assert_eq!(resolution, Resolution::uniform(171));
+
+        // A6 receipts get more pixels per inch, A0 drawings fewer
+        let a6 = target.resolution(&page(105.0, 148.0), None);
+        let a0 = target.resolution(&page(841.0, 1189.0), None);
+        assert_eq!(a6, Resolution::uniform(343));
+        assert_eq!(a0, Resolution::uniform(72));
+
+        // Clamped to the max then lowered to fit the pixel budget
+        let target = target.set_target_long_edge(Some(100_000)).set_max_ppi(1200);
+        assert_eq!(
+            target.resolution(&page(210.0, 297.0), None),
+            Resolution::uniform(719)
+        );
+
+        let target = AutoDpi::default().set_target_long_edge(None);
+        assert_eq!(
+            target.resolution(&page(210.0, 297.0), None),
+            Resolution::uniform(AutoDpi::DEFAULT_PPI)
+        );
+    }
+
+    /// Tests pages covered by a scan use the resolution of the scan
+    #[test]
+    fn test_scan_resolution() {
+        let letter = PageDimensions {
+            width_pts: 612.0,
+            height_pts: 792.0,
+            rotation: 0,
+        };
+
+        let scan = PdfImageEntry {
+            page: 1,
+            num: 0,
+            kind: PdfImageKind::Image,
+            width: 2550,
+            height: 3300,
+            color: "gray".to_string(),
+            components: 1,
+            bits_per_component: 8,
+            encoding: "jpeg".to_string(),
+            object: None,
+            x_ppi: Some(300),
+            y_ppi: Some(300),
+        };
+        let logo = PdfImageEntry {
+            width: 300,
+            height: 300,
+            ..scan.clone()
+        };
+
+        assert_eq!(
+            scan_ppi(1, &letter, &[logo.clone(), scan.clone()]),
+            Some(300)
+        );
+        assert_eq!(scan_ppi(1, &letter, &[logo]), None);
+        assert_eq!(scan_ppi(2, &letter, std::slice::from_ref(&scan)), None);
+
+        let target = AutoDpi::default().set_match_scans(true);
+        assert_eq!(
+            target.resolution(&letter, scan_ppi(1, &letter, &[scan])),
+            Resolution::uniform(300)
+        );
+    }
+}
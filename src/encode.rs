@@ -0,0 +1,540 @@
+//! Helpers for encoding rendered images
+//!
+//! * [encode_image] - Encodes a rendered image optionally stamping it with [SourceMetadata]
+
+use std::io::Cursor;
+
+#[cfg(feature = "avif")]
+use crate::image_crate::codecs::avif::AvifEncoder;
+#[cfg(feature = "webp")]
+use crate::image_crate::codecs::webp::WebPEncoder;
+use crate::image_crate::{
+    codecs::{
+        jpeg::JpegEncoder,
+        png::{CompressionType, FilterType, PngEncoder},
+    },
+    DynamicImage, ImageError,
+};
+
+use crate::{
+    image::{OutputFormat, Resolution},
+    info::PdfInfo,
+};
+
+/// Metadata describing where a rendered image came from, embedded into
+/// encoded images so they can be traced back to their source page
+#[derive(Debug, Default, Clone)]
+pub struct SourceMetadata {
+    /// Title of the source document
+    pub title: Option<String>,
+    /// Producer of the source document
+    pub producer: Option<String>,
+    /// Page number the image was rendered from
+    pub page: Option<u32>,
+    /// Resolution the page was rendered at
+    pub resolution: Option<Resolution>,
+}
+
+impl SourceMetadata {
+    /// Creates source metadata using the title and producer from
+    /// the provided PDF info
+    pub fn from_info(info: &PdfInfo) -> Self {
+        Self {
+            title: info.title().map(str::to_string),
+            producer: info.producer().map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    pub fn set_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn set_producer(mut self, producer: impl Into<String>) -> Self {
+        self.producer = Some(producer.into());
+        self
+    }
+
+    pub fn set_page(mut self, page: u32) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    pub fn set_resolution(mut self, resolution: Resolution) -> Self {
+        self.resolution = Some(resolution);
+        self
+    }
+}
+
+/// Compression level used when encoding PNG images
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PngCompression {
+    /// Default compression level
+    Default,
+    /// Fast, minimal compression
+    #[default]
+    Fast,
+    /// High compression level
+    Best,
+}
+
+impl PngCompression {
+    fn compression_type(&self) -> CompressionType {
+        match self {
+            Self::Default => CompressionType::Default,
+            Self::Fast => CompressionType::Fast,
+            Self::Best => CompressionType::Best,
+        }
+    }
+}
+
+/// Filter applied to scanlines when encoding PNG images
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PngFilter {
+    /// No filtering, best for low color count images
+    NoFilter,
+    /// Filters based on the previous pixel in the same scanline
+    Sub,
+    /// Filters based on the scanline above
+    Up,
+    /// Filters based on the average of the left and above pixels
+    Avg,
+    /// Filters using the Paeth predictor
+    Paeth,
+    /// Picks the best filter for each scanline
+    #[default]
+    Adaptive,
+}
+
+impl PngFilter {
+    fn filter_type(&self) -> FilterType {
+        match self {
+            Self::NoFilter => FilterType::NoFilter,
+            Self::Sub => FilterType::Sub,
+            Self::Up => FilterType::Up,
+            Self::Avg => FilterType::Avg,
+            Self::Paeth => FilterType::Paeth,
+            Self::Adaptive => FilterType::Adaptive,
+        }
+    }
+}
+
+/// Options for encoding images
+#[derive(Debug, Clone, Copy)]
+pub struct EncodeOptions {
+    /// Compression level for PNG output
+    pub png_compression: PngCompression,
+    /// Scanline filter for PNG output
+    pub png_filter: PngFilter,
+    /// Quality for JPEG output from 1 to 100
+    pub jpeg_quality: u8,
+    /// Quality for AVIF output from 1 to 100
+    #[cfg(feature = "avif")]
+    pub avif_quality: u8,
+    /// Encoder speed for AVIF output from 1 (slowest) to 10 (fastest)
+    #[cfg(feature = "avif")]
+    pub avif_speed: u8,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            png_compression: PngCompression::default(),
+            png_filter: PngFilter::default(),
+            jpeg_quality: Self::DEFAULT_JPEG_QUALITY,
+            #[cfg(feature = "avif")]
+            avif_quality: crate::image::AvifOptions::DEFAULT_QUALITY,
+            #[cfg(feature = "avif")]
+            avif_speed: crate::image::AvifOptions::DEFAULT_SPEED,
+        }
+    }
+}
+
+impl EncodeOptions {
+    pub const DEFAULT_JPEG_QUALITY: u8 = 75;
+
+    pub fn set_png_compression(mut self, png_compression: PngCompression) -> Self {
+        self.png_compression = png_compression;
+        self
+    }
+
+    pub fn set_png_filter(mut self, png_filter: PngFilter) -> Self {
+        self.png_filter = png_filter;
+        self
+    }
+
+    /// Sets the JPEG quality, clamped to the range 1 to 100
+    pub fn set_jpeg_quality(mut self, jpeg_quality: u8) -> Self {
+        self.jpeg_quality = jpeg_quality.clamp(1, 100);
+        self
+    }
+
+    /// Sets the AVIF quality, clamped to the range 1 to 100
+    #[cfg(feature = "avif")]
+    pub fn set_avif_quality(mut self, avif_quality: u8) -> Self {
+        self.avif_quality = avif_quality.clamp(1, 100);
+        self
+    }
+
+    /// Sets the AVIF encoder speed, clamped to the range 1 to 10
+    #[cfg(feature = "avif")]
+    pub fn set_avif_speed(mut self, avif_speed: u8) -> Self {
+        self.avif_speed = avif_speed.clamp(1, 10);
+        self
+    }
+}
+
+/// Encodes the provided image into the provided format
+///
+/// When `metadata` is provided it is embedded into the output, as tEXt
+/// chunks for PNG and as an EXIF segment for JPEG. Other formats are
+/// encoded without metadata
+///
+/// ## Arguments
+/// * image - The image to encode
+/// * format - The format to encode the image as
+/// * options - Encoder options for the format
+/// * metadata - Optional source metadata to embed
+pub fn encode_image(
+    image: &DynamicImage,
+    format: OutputFormat,
+    options: &EncodeOptions,
+    metadata: Option<&SourceMetadata>,
+) -> Result<Vec<u8>, ImageError> {
+    let mut output = Vec::new();
+
+    match format {
+        OutputFormat::Png => image.write_with_encoder(PngEncoder::new_with_quality(
+            &mut output,
+            options.png_compression.compression_type(),
+            options.png_filter.filter_type(),
+        ))?,
+        OutputFormat::Jpeg => image.write_with_encoder(JpegEncoder::new_with_quality(
+            &mut output,
+            options.jpeg_quality,
+        ))?,
+        OutputFormat::Tiff => {
+            image.write_to(&mut Cursor::new(&mut output), format.image_format())?
+        }
+        #[cfg(feature = "webp")]
+        OutputFormat::WebP => image.write_with_encoder(WebPEncoder::new_lossless(&mut output))?,
+        #[cfg(feature = "avif")]
+        OutputFormat::Avif => image.write_with_encoder(AvifEncoder::new_with_speed_quality(
+            &mut output,
+            options.avif_speed,
+            options.avif_quality,
+        ))?,
+    }
+
+    let metadata = match metadata {
+        Some(value) => value,
+        None => return Ok(output),
+    };
+
+    let output = match format {
+        OutputFormat::Png => stamp_png(output, metadata),
+        OutputFormat::Jpeg => stamp_jpeg(output, metadata),
+        _ => output,
+    };
+
+    Ok(output)
+}
+
+/// Length of the PNG signature
+const PNG_SIGNATURE_LENGTH: usize = 8;
+/// Length of the IHDR chunk including its length, type and CRC
+const PNG_IHDR_LENGTH: usize = 25;
+
+/// Inserts tEXt chunks for the metadata directly after the IHDR chunk
+fn stamp_png(mut output: Vec<u8>, metadata: &SourceMetadata) -> Vec<u8> {
+    let mut entries: Vec<(&str, String)> = Vec::new();
+
+    if let Some(title) = metadata.title.as_ref() {
+        entries.push(("Title", title.clone()));
+    }
+
+    if let Some(producer) = metadata.producer.as_ref() {
+        entries.push(("Producer", producer.clone()));
+    }
+
+    if let Some(page) = metadata.page {
+        entries.push(("Page", page.to_string()));
+    }
+
+    if let Some(resolution) = metadata.resolution.as_ref() {
+        entries.push((
+            "Resolution",
+            format!("{}x{}", resolution.x_ppi(), resolution.y_ppi()),
+        ));
+    }
+
+    let mut chunks = Vec::new();
+    for (keyword, text) in entries {
+        let mut data = Vec::with_capacity(keyword.len() + text.len() + 1);
+        data.extend_from_slice(keyword.as_bytes());
+        data.push(0);
+        // tEXt chunks are Latin-1, replace anything outside of it
+        data.extend(
+            text.chars()
+                .map(|value| u8::try_from(value).unwrap_or(b'?')),
+        );
+
+        write_png_chunk(&mut chunks, b"tEXt", &data);
+    }
+
+    let offset = PNG_SIGNATURE_LENGTH + PNG_IHDR_LENGTH;
+    output.splice(offset..offset, chunks);
+    output
+}
+
+/// Writes a PNG chunk with its length and CRC
+fn write_png_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    output.extend_from_slice(kind);
+    output.extend_from_slice(data);
+    output.extend_from_slice(&hasher.finalize().to_be_bytes());
+}
+
+/// TIFF tag for the name of the source document
+const TAG_DOCUMENT_NAME: u16 = 0x010D;
+/// TIFF tag for the horizontal resolution
+const TAG_X_RESOLUTION: u16 = 0x011A;
+/// TIFF tag for the vertical resolution
+const TAG_Y_RESOLUTION: u16 = 0x011B;
+/// TIFF tag for the unit of the resolution
+const TAG_RESOLUTION_UNIT: u16 = 0x0128;
+/// TIFF tag for the page number
+const TAG_PAGE_NUMBER: u16 = 0x0129;
+/// TIFF tag for the software that produced the source
+const TAG_SOFTWARE: u16 = 0x0131;
+
+/// Maximum length of ASCII values, keeps the segment within the u16 length limit
+const MAX_ASCII_LENGTH: usize = 1024;
+
+/// Value of a single EXIF IFD entry
+enum ExifValue {
+    Ascii(String),
+    Short(Vec<u16>),
+    Rational(u32, u32),
+}
+
+/// Inserts an APP1 EXIF segment for the metadata after the SOI and
+/// APP0 (JFIF) markers
+fn stamp_jpeg(mut output: Vec<u8>, metadata: &SourceMetadata) -> Vec<u8> {
+    let mut entries: Vec<(u16, ExifValue)> = Vec::new();
+
+    if let Some(title) = metadata.title.as_ref() {
+        entries.push((TAG_DOCUMENT_NAME, ExifValue::Ascii(title.clone())));
+    }
+
+    if let Some(resolution) = metadata.resolution.as_ref() {
+        entries.push((TAG_X_RESOLUTION, ExifValue::Rational(resolution.x_ppi(), 1)));
+        entries.push((TAG_Y_RESOLUTION, ExifValue::Rational(resolution.y_ppi(), 1)));
+        // Resolution is in inches
+        entries.push((TAG_RESOLUTION_UNIT, ExifValue::Short(vec![2])));
+    }
+
+    if let Some(page) = metadata.page {
+        // Total number of pages is unknown
+        let page = u16::try_from(page).unwrap_or(u16::MAX);
+        entries.push((TAG_PAGE_NUMBER, ExifValue::Short(vec![page, 0])));
+    }
+
+    if let Some(producer) = metadata.producer.as_ref() {
+        entries.push((TAG_SOFTWARE, ExifValue::Ascii(producer.clone())));
+    }
+
+    let tiff = build_exif_tiff(&entries);
+
+    let mut segment = Vec::with_capacity(tiff.len() + 10);
+    segment.extend_from_slice(&[0xFF, 0xE1]);
+    // Length includes the length bytes and the Exif header
+    segment.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+    segment.extend_from_slice(b"Exif\0\0");
+    segment.extend_from_slice(&tiff);
+
+    // Skip the SOI marker
+    let mut offset = 2;
+
+    // Skip the APP0 JFIF segment which is expected to come first
+    if output.get(offset..offset + 2) == Some(&[0xFF, 0xE0]) {
+        if let Some(length) = output.get(offset + 2..offset + 4) {
+            offset += 2 + u16::from_be_bytes([length[0], length[1]]) as usize;
+        }
+    }
+
+    output.splice(offset..offset, segment);
+    output
+}
+
+/// Builds a little endian TIFF structure containing a single IFD
+/// with the provided entries
+fn build_exif_tiff(entries: &[(u16, ExifValue)]) -> Vec<u8> {
+    // Header + entry count + entries + next IFD offset
+    let ifd_end = 8 + 2 + entries.len() * 12 + 4;
+
+    let mut ifd = Vec::new();
+    let mut data = Vec::new();
+
+    ifd.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+
+    for (tag, value) in entries {
+        let (kind, count, bytes): (u16, usize, Vec<u8>) = match value {
+            ExifValue::Ascii(value) => {
+                let mut bytes: Vec<u8> = value
+                    .bytes()
+                    .filter(u8::is_ascii)
+                    .take(MAX_ASCII_LENGTH)
+                    .collect();
+                bytes.push(0);
+                (2, bytes.len(), bytes)
+            }
+            ExifValue::Short(values) => (
+                3,
+                values.len(),
+                values
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect(),
+            ),
+            ExifValue::Rational(numerator, denominator) => {
+                let mut bytes = numerator.to_le_bytes().to_vec();
+                bytes.extend_from_slice(&denominator.to_le_bytes());
+                (5, 1, bytes)
+            }
+        };
+
+        ifd.extend_from_slice(&tag.to_le_bytes());
+        ifd.extend_from_slice(&kind.to_le_bytes());
+        ifd.extend_from_slice(&(count as u32).to_le_bytes());
+
+        if bytes.len() <= 4 {
+            // Small values are stored inline padded to 4 bytes
+            let mut inline = [0u8; 4];
+            inline[..bytes.len()].copy_from_slice(&bytes);
+            ifd.extend_from_slice(&inline);
+        } else {
+            let offset = (ifd_end + data.len()) as u32;
+            ifd.extend_from_slice(&offset.to_le_bytes());
+            data.extend_from_slice(&bytes);
+
+            // Values must start on a word boundary
+            if data.len() % 2 != 0 {
+                data.push(0);
+            }
+        }
+    }
+
+    // No next IFD
+    ifd.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut output = Vec::with_capacity(ifd_end + data.len());
+    output.extend_from_slice(b"II*\0");
+    output.extend_from_slice(&8u32.to_le_bytes());
+    output.extend_from_slice(&ifd);
+    output.extend_from_slice(&data);
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode_image, EncodeOptions, PngCompression, SourceMetadata};
+    use crate::{
+        image::{OutputFormat, Resolution},
+        image_crate::{load_from_memory_with_format, DynamicImage, ImageFormat},
+    };
+
+    fn metadata() -> SourceMetadata {
+        SourceMetadata::default()
+            .set_title("Test pdf")
+            .set_producer("Test producer")
+            .set_page(2)
+            .set_resolution(Resolution::uniform(150))
+    }
+
+    /// Tests metadata is stamped into PNG output and the image still decodes
+    #[test]
+    fn test_stamp_png() {
+        let image = DynamicImage::new_rgb8(4, 4);
+        let output = encode_image(
+            &image,
+            OutputFormat::Png,
+            &EncodeOptions::default(),
+            Some(&metadata()),
+        )
+        .unwrap();
+
+        let text = String::from_utf8_lossy(&output);
+        assert!(text.contains("tEXtTitle\0Test pdf"));
+        assert!(text.contains("tEXtPage\x002"));
+        assert!(text.contains("tEXtResolution\x00150x150"));
+
+        let decoded = load_from_memory_with_format(&output, ImageFormat::Png).unwrap();
+        assert_eq!(decoded.width(), 4);
+    }
+
+    /// Tests metadata is stamped into JPEG output and the image still decodes
+    #[test]
+    fn test_stamp_jpeg() {
+        let image = DynamicImage::new_rgb8(4, 4);
+        let output = encode_image(
+            &image,
+            OutputFormat::Jpeg,
+            &EncodeOptions::default(),
+            Some(&metadata()),
+        )
+        .unwrap();
+
+        let text = String::from_utf8_lossy(&output);
+        assert!(text.contains("Exif\0\0II*\0"));
+        assert!(text.contains("Test pdf\0"));
+        assert!(text.contains("Test producer\0"));
+
+        let decoded = load_from_memory_with_format(&output, ImageFormat::Jpeg).unwrap();
+        assert_eq!(decoded.width(), 4);
+    }
+
+    /// Tests the encoder options change the encoded output
+    #[test]
+    fn test_encode_options() {
+        let image = DynamicImage::new_rgb8(64, 64);
+
+        let low = EncodeOptions::default().set_jpeg_quality(1);
+        let high = EncodeOptions::default().set_jpeg_quality(100);
+        let low = encode_image(&image, OutputFormat::Jpeg, &low, None).unwrap();
+        let high = encode_image(&image, OutputFormat::Jpeg, &high, None).unwrap();
+        assert_ne!(low, high);
+
+        let best = EncodeOptions::default().set_png_compression(PngCompression::Best);
+        let output = encode_image(&image, OutputFormat::Png, &best, None).unwrap();
+        let decoded = load_from_memory_with_format(&output, ImageFormat::Png).unwrap();
+        assert_eq!(decoded.width(), 64);
+    }
+
+    /// Tests images are re-encoded as WebP and AVIF
+    #[cfg(any(feature = "webp", feature = "avif"))]
+    #[test]
+    fn test_encode_post_formats() {
+        let image = DynamicImage::new_rgb8(16, 16);
+        let options = EncodeOptions::default();
+
+        #[cfg(feature = "webp")]
+        {
+            let output = encode_image(&image, OutputFormat::WebP, &options, None).unwrap();
+            assert!(output.starts_with(b"RIFF") && &output[8..12] == b"WEBP");
+            let decoded = load_from_memory_with_format(&output, ImageFormat::WebP).unwrap();
+            assert_eq!(decoded.width(), 16);
+        }
+
+        #[cfg(feature = "avif")]
+        {
+            let output = encode_image(&image, OutputFormat::Avif, &options, None).unwrap();
+            assert_eq!(&output[4..12], b"ftypavif");
+        }
+    }
+}
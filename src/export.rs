@@ -0,0 +1,434 @@
+//! Helpers for exporting PDF files into packages for offline reading
+//!
+//! * [export_html] - Combines the page images and text into a single self-contained HTML document
+//! * [export_epub] - Combines the page images and text into an EPUB package (Requires the `zip` feature)
+//!
+//! Each page of the PDF becomes a page of the package containing the rendered
+//! page image followed by the text of the page, the text is extracted with its
+//! layout kept by default. The images are embedded using the provided
+//! [OutputFormat], use PNG or JPEG as TIFF is not supported by most readers
+
+#[cfg(feature = "zip")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures_util::future::try_join;
+use thiserror::Error;
+
+use crate::{
+    image::{render_all_pages_raw, OutputFormat, PdfRenderError, RenderArgs},
+    info::{info_page_count, PdfInfo},
+    text::{text_pages, PdfTextArgs, PdfTextError},
+};
+
+/// Options for exporting a package
+pub struct ExportOptions {
+    /// Args for rendering the page images
+    pub render_args: RenderArgs,
+    /// Args for extracting the text
+    pub text_args: PdfTextArgs,
+    /// Format to embed the page images as
+    pub format: OutputFormat,
+    /// Title for the package, defaults to the PDF title
+    pub title: Option<String>,
+    /// Language of the package content
+    pub language: String,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            render_args: Default::default(),
+            text_args: PdfTextArgs::default().set_layout(true),
+            format: Default::default(),
+            title: None,
+            language: Self::DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+impl ExportOptions {
+    pub const DEFAULT_LANGUAGE: &'static str = "en";
+
+    pub fn set_render_args(mut self, render_args: RenderArgs) -> Self {
+        self.render_args = render_args;
+        self
+    }
+
+    pub fn set_text_args(mut self, text_args: PdfTextArgs) -> Self {
+        self.text_args = text_args;
+        self
+    }
+
+    pub fn set_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn set_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn set_language(mut self, language: impl Into<String>) -> Self {
+        self.language = language.into();
+        self
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    Render(#[from] PdfRenderError),
+
+    #[error(transparent)]
+    Text(#[from] PdfTextError),
+
+    #[cfg(feature = "zip")]
+    #[error("failed to write package: {0}")]
+    Archive(#[from] zip::result::ZipError),
+
+    #[cfg(feature = "zip")]
+    #[error("failed to write package: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Rendered image and text for a single page
+struct ExportPage {
+    number: u32,
+    image: Vec<u8>,
+    text: String,
+}
+
+/// Renders the images and extracts the text for all the pages concurrently
+async fn export_pages(
+    data: &[u8],
+    info: &PdfInfo,
+    options: &ExportOptions,
+) -> Result<Vec<ExportPage>, ExportError> {
+    let page_count = info_page_count(info)
+        .and_then(|page_count| options.render_args.empty_document.check(page_count))
+        .map_err(PdfRenderError::from)?;
+
+    let (images, texts) = try_join(
+        async {
+            render_all_pages_raw(data, info, options.format, &options.render_args)
+                .await
+                .map_err(ExportError::from)
+        },
+        async {
+            text_pages(data, info, (1..=page_count).collect(), &options.text_args)
+                .await
+                .map_err(ExportError::from)
+        },
+    )
+    .await?;
+
+    Ok(images
+        .into_iter()
+        .zip(texts)
+        .zip(1..)
+        .map(|((image, text), number)| ExportPage {
+            number,
+            image,
+            text,
+        })
+        .collect())
+}
+
+/// Gets the title for the package
+fn export_title(info: &PdfInfo, options: &ExportOptions) -> String {
+    options
+        .title
+        .clone()
+        .or_else(|| info.title().map(str::to_string))
+        .unwrap_or_else(|| "Document".to_string())
+}
+
+/// Exports the provided PDF as a single self-contained HTML document with
+/// the page images embedded as data URLs
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info
+/// * options - Options for the export
+pub async fn export_html(
+    data: &[u8],
+    info: &PdfInfo,
+    options: &ExportOptions,
+) -> Result<String, ExportError> {
+    let pages = export_pages(data, info, options).await?;
+    let title = escape_html(&export_title(info, options));
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+        <style>\n{PAGE_STYLE}</style>\n</head>\n<body>\n",
+        escape_html(&options.language)
+    );
+
+    for page in pages {
+        let image = format!(
+            "data:{};base64,{}",
+            options.format.mime_type(),
+            base64_encode(&page.image)
+        );
+        html.push_str(&page_section(page.number, &image, &page.text));
+    }
+
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+/// Exports the provided PDF as an EPUB 3 package with a page of the
+/// package for each page of the PDF
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info
+/// * options - Options for the export
+#[cfg(feature = "zip")]
+pub async fn export_epub(
+    data: &[u8],
+    info: &PdfInfo,
+    options: &ExportOptions,
+) -> Result<Vec<u8>, ExportError> {
+    use std::io::{Cursor, Write};
+
+    use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+    use crate::shared::fnv1a_64;
+
+    let pages = export_pages(data, info, options).await?;
+    let title = escape_html(&export_title(info, options));
+    let language = escape_html(&options.language);
+    let extension = options.format.extension();
+
+    // Stable across Rust versions so the same file keeps its identifier
+    let identifier = format!("urn:pdf-process:{:016x}", fnv1a_64(data));
+
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+
+    // The mimetype must be the first file in the package
+    archive.start_file("mimetype", stored)?;
+    archive.write_all(b"application/epub+zip")?;
+
+    archive.start_file("META-INF/container.xml", stored)?;
+    archive.write_all(EPUB_CONTAINER.as_bytes())?;
+
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    let mut nav = String::new();
+
+    for page in &pages {
+        let number = page.number;
+        let image = format!("images/page-{number}.{extension}");
+
+        archive.start_file(format!("OEBPS/{image}"), stored)?;
+        archive.write_all(&page.image)?;
+
+        archive.start_file(format!("OEBPS/page-{number}.xhtml"), stored)?;
+        archive.write_all(
+            format!(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n\
+                <html xmlns=\"http://www.w3.org/1999/xhtml\" lang=\"{language}\" xml:lang=\"{language}\">\n\
+                <head>\n<title>Page {number}</title>\n<style>\n{PAGE_STYLE}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+                page_section(number, &image, &page.text)
+            )
+            .as_bytes(),
+        )?;
+
+        manifest.push_str(&format!(
+            "<item id=\"page-{number}\" href=\"page-{number}.xhtml\" media-type=\"application/xhtml+xml\"/>\n\
+            <item id=\"image-{number}\" href=\"{image}\" media-type=\"{}\"/>\n",
+            options.format.mime_type()
+        ));
+        spine.push_str(&format!("<itemref idref=\"page-{number}\"/>\n"));
+        nav.push_str(&format!(
+            "<li><a href=\"page-{number}.xhtml\">Page {number}</a></li>\n"
+        ));
+    }
+
+    archive.start_file("OEBPS/nav.xhtml", stored)?;
+    archive.write_all(
+        format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n\
+            <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">\n\
+            <head>\n<title>{title}</title>\n</head>\n<body>\n<nav epub:type=\"toc\">\n<ol>\n{nav}</ol>\n</nav>\n</body>\n</html>\n"
+        )
+        .as_bytes(),
+    )?;
+
+    archive.start_file("OEBPS/content.opf", stored)?;
+    archive.write_all(
+        format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+            <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"id\">\n\
+            <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+            <dc:identifier id=\"id\">{identifier}</dc:identifier>\n\
+            <dc:title>{title}</dc:title>\n\
+            <dc:language>{language}</dc:language>\n\
+            <meta property=\"dcterms:modified\">{}</meta>\n\
+            </metadata>\n<manifest>\n\
+            <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+            {manifest}</manifest>\n<spine>\n{spine}</spine>\n</package>\n",
+            utc_timestamp(SystemTime::now())
+        )
+        .as_bytes(),
+    )?;
+
+    Ok(archive.finish()?.into_inner())
+}
+
+/// Container file pointing readers to the package document
+#[cfg(feature = "zip")]
+const EPUB_CONTAINER: &str = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+<rootfiles>\n\
+<rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/>\n\
+</rootfiles>\n\
+</container>\n";
+
+/// Styles for the page sections
+const PAGE_STYLE: &str = "section.page { margin: 0 auto 2em; max-width: 60em; }\n\
+section.page img { display: block; width: 100%; height: auto; }\n\
+section.page pre { white-space: pre-wrap; }\n";
+
+/// Creates the section for a single page
+fn page_section(number: u32, image_src: &str, text: &str) -> String {
+    format!(
+        "<section class=\"page\" id=\"page-{number}\">\n\
+        <img src=\"{image_src}\" alt=\"Page {number}\"/>\n\
+        <pre>{}</pre>\n\
+        </section>\n",
+        escape_html(text)
+    )
+}
+
+/// Escapes text for use within HTML and XML content or attributes
+fn escape_html(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+
+    for char in value.chars() {
+        match char {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            // Control characters other than whitespace are not allowed in XML
+            char if char.is_control() && !matches!(char, '\n' | '\t') => {}
+            char => output.push(char),
+        }
+    }
+
+    output
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes the bytes as padded standard base64
+fn base64_encode(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let value = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (value >> (18 - index * 6)) & 0x3F;
+                output.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
+
+/// Formats the time as a UTC timestamp in the format "YYYY-MM-DDThh:mm:ssZ"
+#[cfg(feature = "zip")]
+fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|value| value.as_secs())
+        .unwrap_or_default();
+
+    let days = (seconds / 86_400) as i64;
+    let time_of_day = seconds % 86_400;
+
+    // Converts the days since the epoch into a civil date
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time_of_day / 3600,
+        (time_of_day % 3600) / 60,
+        time_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{base64_encode, escape_html, page_section};
+
+    /// Tests encoding base64 with each amount of padding
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(&[0xFF, 0xFE]), "//4=");
+    }
+
+    /// Tests escaping text for HTML
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<b>\"A\" & 'B'</b>\u{c}\n"),
+            "&lt;b&gt;&quot;A&quot; &amp; &#39;B&#39;&lt;/b&gt;\n"
+        );
+    }
+
+    /// Tests the page section contains the escaped text
+    #[test]
+    fn test_page_section() {
+        let section = page_section(2, "images/page-2.png", "1 < 2");
+        assert!(section.starts_with("<section class=\"page\" id=\"page-2\">"));
+        assert!(section.contains("<img src=\"images/page-2.png\" alt=\"Page 2\"/>"));
+        assert!(section.contains("<pre>1 &lt; 2</pre>"));
+    }
+
+    /// Tests formatting timestamps
+    #[test]
+    #[cfg(feature = "zip")]
+    fn test_utc_timestamp() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        use super::utc_timestamp;
+
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            utc_timestamp(UNIX_EPOCH + Duration::from_secs(1_709_251_199)),
+            "2024-02-29T23:59:59Z"
+        );
+    }
+}
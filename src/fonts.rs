@@ -0,0 +1,348 @@
+//! Helpers for listing the fonts used by PDF files
+//!
+//! * [pdf_font_list] - Lists the fonts using `pdffonts`
+
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    info::PermissionDenied,
+    shared::{run_tool, ChildError, Password, ProcessPriority, RunOptions, StderrSink, ToolError},
+};
+
+/// Font used within a PDF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PdfFontEntry {
+    /// Name of the font, [None] for fonts without a name
+    pub name: Option<String>,
+    /// Type of the font (e.g. "Type 1", "TrueType", "CID Type 0C")
+    pub font_type: String,
+    /// Encoding of the font (e.g. "WinAnsi", "Identity-H")
+    pub encoding: String,
+    /// Whether the font is embedded in the file
+    pub embedded: bool,
+    /// Whether the font is a subset
+    pub subset: bool,
+    /// Whether the font has a ToUnicode map
+    pub unicode: bool,
+    /// Object number and generation of the font
+    pub object: Option<(u32, u32)>,
+}
+
+impl PdfFontEntry {
+    /// Whether this is the invisible font OCR tools (e.g. Tesseract and
+    /// OCRmyPDF) use for the text layer of scanned pages
+    pub fn is_ocr_text_layer(&self) -> bool {
+        self.name
+            .as_deref()
+            .is_some_and(|name| name.ends_with("GlyphLessFont"))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PdfFontsError {
+    #[error("failed to spawn pdffonts: {0}")]
+    SpawnProcess(std::io::Error),
+
+    #[error("failed to write pdf bytes: {0}")]
+    WritePdf(std::io::Error),
+
+    #[error("failed to get output: {0}")]
+    WaitOutput(std::io::Error),
+
+    #[error("output exceeded the maximum allowed size of {0} bytes")]
+    OutputLimitExceeded(usize),
+
+    #[error("pdffonts did not finish within {0:?}")]
+    Timeout(Duration),
+
+    #[error("pdffonts was aborted")]
+    Aborted,
+
+    #[error("failed to get pdffonts exit code: {0}")]
+    PdfFontsFailure(String),
+
+    #[error("pdffonts reported permission error: {0}")]
+    PermissionError(PermissionDenied),
+
+    #[error("pdf is encrypted and no password was provided")]
+    PdfEncrypted,
+
+    #[error("incorrect password was provided")]
+    IncorrectPassword,
+
+    #[error("file is not a pdf")]
+    NotPdfFile,
+
+    #[error("font list entry is malformed: {0}")]
+    MalformedEntry(String),
+}
+
+impl From<ChildError> for PdfFontsError {
+    fn from(value: ChildError) -> Self {
+        match value {
+            ChildError::WriteInput(err) => Self::WritePdf(err),
+            ChildError::ReadOutput(err) | ChildError::WriteOutput(err) => Self::WaitOutput(err),
+            ChildError::OutputLimitExceeded(limit) => Self::OutputLimitExceeded(limit),
+            ChildError::Timeout(timeout) => Self::Timeout(timeout),
+        }
+    }
+}
+
+impl From<ToolError> for PdfFontsError {
+    fn from(value: ToolError) -> Self {
+        match value {
+            ToolError::Spawn(err) => Self::SpawnProcess(err),
+            ToolError::Child(err) => err.into(),
+            ToolError::NotPdfFile => Self::NotPdfFile,
+            ToolError::PdfEncrypted => Self::PdfEncrypted,
+            ToolError::IncorrectPassword => Self::IncorrectPassword,
+            ToolError::PermissionError(err) => Self::PermissionError(err),
+            ToolError::PageOutOfBounds(page, page_count) => Self::PdfFontsFailure(format!(
+                "page {page} is outside the number of available pages {page_count}"
+            )),
+            ToolError::PageCountUnknown => {
+                Self::PdfFontsFailure("page count is missing or invalid".to_string())
+            }
+            ToolError::EmptyDocument => {
+                Self::PdfFontsFailure("pdf does not contain any pages".to_string())
+            }
+            ToolError::Failure(err) | ToolError::Task(err) => Self::PdfFontsFailure(err),
+            ToolError::Aborted => Self::Aborted,
+            ToolError::BudgetExceeded(err) => Self::PdfFontsFailure(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PdfFontsArgs {
+    /// Password for the PDF
+    pub password: Option<Password>,
+    /// First page to list fonts for
+    pub first_page: Option<u32>,
+    /// Last page to list fonts for
+    pub last_page: Option<u32>,
+    /// Optional maximum time listing the fonts may take
+    pub timeout: Option<Duration>,
+    /// Optional destination for the pdffonts stderr instead of keeping it in memory
+    pub stderr: Option<StderrSink>,
+    /// Optional lowered CPU and IO priority to run pdffonts at
+    pub priority: Option<ProcessPriority>,
+}
+
+impl PdfFontsArgs {
+    pub fn set_password(mut self, password: Password) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    pub fn set_first_page(mut self, first_page: u32) -> Self {
+        self.first_page = Some(first_page);
+        self
+    }
+
+    pub fn set_last_page(mut self, last_page: u32) -> Self {
+        self.last_page = Some(last_page);
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_stderr(mut self, stderr: StderrSink) -> Self {
+        self.stderr = Some(stderr);
+        self
+    }
+
+    pub fn set_priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Options for running pdffonts
+    pub(crate) fn run_options(&self) -> RunOptions {
+        RunOptions {
+            max_output_size: None,
+            timeout: self.timeout,
+            password: self.password.clone(),
+            stderr: self.stderr.clone(),
+            priority: self.priority,
+            budget: None,
+        }
+    }
+
+    /// Builds an argument list from all the options, the password is passed
+    /// to the tool separately so it is not included
+    pub fn build_args(&self) -> Vec<String> {
+        let mut out = Vec::new();
+
+        if let Some(first_page) = self.first_page {
+            out.push("-f".to_string());
+            out.push(first_page.to_string());
+        }
+
+        if let Some(last_page) = self.last_page {
+            out.push("-l".to_string());
+            out.push(last_page.to_string());
+        }
+
+        out
+    }
+}
+
+/// Lists the fonts used by the provided PDF file
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Extra args to provide to pdffonts
+pub async fn pdf_font_list(
+    data: &[u8],
+    args: &PdfFontsArgs,
+) -> Result<Vec<PdfFontEntry>, PdfFontsError> {
+    // Take input from stdin
+    let mut cli_args = args.build_args();
+    cli_args.push("-".to_string());
+
+    let output = run_tool("pdffonts", &cli_args, data, &args.run_options()).await?;
+
+    let value = String::from_utf8_lossy(&output.stdout);
+    parse_font_list(&value)
+}
+
+/// Parses the table output of `pdffonts`
+fn parse_font_list(value: &str) -> Result<Vec<PdfFontEntry>, PdfFontsError> {
+    let mut lines = value.lines();
+
+    // Header is followed by a separator line made of dashed columns
+    // which gives the width of each column
+    _ = lines.next();
+    let Some(separator) = lines.next() else {
+        return Ok(Vec::new());
+    };
+
+    let columns: Vec<(usize, usize)> = separator
+        .split(' ')
+        .scan(0, |start, column| {
+            let range = (*start, *start + column.len());
+            *start += column.len() + 1;
+            Some(range)
+        })
+        .filter(|(start, end)| start != end)
+        .collect();
+
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_font_entry(line, &columns))
+        .collect()
+}
+
+/// Parses a single row of the font list in the format:
+/// `name type encoding emb sub uni object ID`
+///
+/// The name, type and encoding may contain spaces so the fixed width
+/// columns from the separator are used for those
+fn parse_font_entry(line: &str, columns: &[(usize, usize)]) -> Result<PdfFontEntry, PdfFontsError> {
+    let malformed = || PdfFontsError::MalformedEntry(line.to_string());
+
+    if columns.len() < 7 {
+        return Err(malformed());
+    }
+
+    let column = |index: usize| -> &str {
+        let (start, end) = columns[index];
+        line.get(start..end.min(line.len())).unwrap_or("").trim()
+    };
+
+    // Flags and object numbers are whitespace separated after the encoding
+    let (_, encoding_end) = columns[2];
+    let rest: Vec<&str> = line
+        .get(encoding_end..)
+        .ok_or_else(malformed)?
+        .split_whitespace()
+        .collect();
+
+    if rest.len() < 3 {
+        return Err(malformed());
+    }
+
+    let flag = |value: &str| -> Result<bool, PdfFontsError> {
+        match value {
+            "yes" => Ok(true),
+            "no" => Ok(false),
+            _ => Err(malformed()),
+        }
+    };
+
+    let name = column(0);
+    let object = match (rest.get(3), rest.get(4)) {
+        (Some(number), Some(generation)) => number.parse().ok().zip(generation.parse().ok()),
+        _ => None,
+    };
+
+    Ok(PdfFontEntry {
+        name: (!name.is_empty() && name != "[none]").then(|| name.to_string()),
+        font_type: column(1).to_string(),
+        encoding: column(2).to_string(),
+        embedded: flag(rest[0])?,
+        subset: flag(rest[1])?,
+        unicode: flag(rest[2])?,
+        object,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_font_list;
+
+    /// Tests parsing the font list output
+    #[test]
+    fn test_parse_font_list() {
+        let output = "\
+name                                 type              encoding         emb sub uni object ID
+------------------------------------ ----------------- ---------------- --- --- --- ---------
+ABCDEF+Liberation Serif              TrueType          WinAnsi          yes yes yes      8  0
+Helvetica                            Type 1            Standard         no  no  no      12  0
+[none]                               Type 3            Custom           yes no  no      15  0
+GlyphLessFont                        CID TrueType      Identity-H       yes no  yes     20  0
+";
+
+        let fonts = parse_font_list(output).unwrap();
+        assert_eq!(fonts.len(), 4);
+
+        assert_eq!(fonts[0].name.as_deref(), Some("ABCDEF+Liberation Serif"));
+        assert_eq!(fonts[0].font_type, "TrueType");
+        assert!(fonts[0].embedded && fonts[0].subset && fonts[0].unicode);
+        assert_eq!(fonts[0].object, Some((8, 0)));
+
+        assert_eq!(fonts[1].font_type, "Type 1");
+        assert!(!fonts[1].embedded);
+
+        assert_eq!(fonts[2].name, None);
+        assert!(!fonts[2].is_ocr_text_layer());
+
+        assert_eq!(fonts[3].encoding, "Identity-H");
+        assert!(fonts[3].is_ocr_text_layer());
+    }
+
+    /// Tests an empty list and malformed rows
+    #[test]
+    fn test_parse_font_list_malformed() {
+        assert!(parse_font_list("").unwrap().is_empty());
+
+        let header = "name type\n---- ----\n";
+        assert!(parse_font_list(header).unwrap().is_empty());
+
+        let output = "\
+name       type    encoding emb sub uni object ID
+---------- ------- -------- --- --- --- ---------
+Helvetica  Type 1  Standard maybe no no 12 0
+";
+        assert!(parse_font_list(output).is_err());
+    }
+}
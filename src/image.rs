@@ -1,35 +1,158 @@
 //! Helpers for rendering images from PDF files
 //!  
 //! * [render_all_pages] - Renders all pages in the PDF file
+//! * [render_all_pages_stream] - Renders all pages providing each page as soon as it is ready
 //! * [render_pages] - Renders a specific set of pages
 //! * [render_single_page] - Renders a specific page
-
-use std::process::Stdio;
-
-use futures_util::{stream::FuturesOrdered, TryStreamExt};
-use image::{DynamicImage, ImageError, ImageFormat};
+//! * [render_all_pages_as], [render_pages_as], [render_single_page_as] - Typed pixel buffer variants
+//! * [render_page_rgba], [render_pages_rgba] - Raw 8-bit RGBA pixels without the image types
+//! * [render_all_pages_raw], [render_pages_raw], [render_single_page_raw] - Encoded bytes without decoding
+//! * [render_pages_numbered], [render_pages_raw_numbered] - Renders pages paired with their page numbers
+//! * [render_pages_with], [render_pages_raw_with] - Renders pages each using their own args
+//! * [render_pages_partial], [render_pages_raw_partial] - Renders pages keeping the completed pages on failure
+//! * [render_page_selection], [render_page_selection_raw] - Renders a [PageSelection] of pages
+//! * [render_page_to_writer] - Streams the encoded output for a page into an [AsyncWrite]
+//! * [info_and_first_page], [info_and_first_page_raw] - Reads the info while rendering the first page
+//!
+//! The decoding functions require the `image` (or `image-0-24`) feature, the
+//! raw variants are always available
+
+use std::{borrow::Cow, collections::HashMap, time::Duration};
+
+use futures_util::future::try_join;
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+use futures_util::{stream, Stream, StreamExt};
 use thiserror::Error;
-use tokio::{io::AsyncWriteExt, process::Command};
-
-use crate::{info::PdfInfo, shared::Password};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    budget::{Budget, BudgetExceeded},
+    dpi::AutoDpi,
+    images::{pdf_image_list, PdfImagesArgs, PdfImagesError},
+    info::{
+        info_page_count, pdf_info, verify_document, PageDimensions, PageTransform, PdfInfo,
+        PdfInfoArgs, PdfInfoError, PermissionDenied,
+    },
+    shared::{
+        for_each_page, for_each_page_partial, run_jobs, ChildError, EmptyDocumentPolicy, FanOut,
+        PageSelection, PartialResult, Password, ProcessPriority, Rect, RunOptions, Size,
+        StderrSink, ToolError, ToolJob, POINTS_PER_INCH,
+    },
+    vector::{PaperOptions, PrintOptions},
+};
+
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+use crate::image_crate::{
+    DynamicImage, GrayImage, ImageBuffer, ImageError, ImageFormat, Luma, LumaA, Pixel, Rgb, Rgba,
+    RgbaImage,
+};
 
 /// Arguments for rendering
-#[derive(Default)]
+#[derive(Debug, Clone)]
 pub struct RenderArgs {
     /// Optional custom resolution to render at, defaults to 150 PPI
     pub resolution: Option<Resolution>,
     /// Optionally scale to a specific size
     pub scale_to: Option<ScaleTo>,
+    /// Optionally choose the resolution for each page from its size,
+    /// cannot be used with a resolution or scale
+    pub auto_dpi: Option<AutoDpi>,
 
     /// Area to render
     pub render_area: Option<RenderArea>,
+    /// Region of the rendered page to output, in output pixels
+    pub crop: Option<Crop>,
     /// Rendered page content colors
     pub render_color: Option<RenderColor>,
     /// Rendered page color
     pub page_color: Option<PageColor>,
+    /// Encoding options for JPEG output, only valid when
+    /// rendering as [OutputFormat::Jpeg]
+    pub jpeg_options: Option<JpegOptions>,
+    /// Compression for TIFF output, only valid when
+    /// rendering as [OutputFormat::Tiff]
+    pub tiff_compression: Option<TiffCompression>,
+    /// Encoding options for AVIF output, only valid when
+    /// rendering as [OutputFormat::Avif]
+    #[cfg(feature = "avif")]
+    pub avif_options: Option<AvifOptions>,
+    /// Print options for PostScript and PDF output, only valid when
+    /// rendering using [render_to_ps](crate::render_to_ps) or
+    /// [render_to_pdf](crate::render_to_pdf)
+    pub print_options: Option<PrintOptions>,
+    /// Paper size and fitting for PostScript, PDF and SVG output
+    pub paper_options: Option<PaperOptions>,
+    /// Poppler tool used to render raster output, defaults to pdftocairo
+    pub backend: RenderBackend,
+    /// Rendering options only supported by the [RenderBackend::Splash] backend
+    pub splash_options: Option<SplashOptions>,
 
     /// Password for the PDF
     pub password: Option<Password>,
+
+    /// Optional maximum size in bytes for the rendered output of
+    /// a single page, rendering is aborted once this is exceeded
+    pub max_output_size: Option<usize>,
+
+    /// Whether to check the encryption state and page bounds using the
+    /// [PdfInfo] before rendering specific pages, when disabled the errors
+    /// reported by pdftocairo are relied on instead. Defaults to true
+    pub verify: bool,
+
+    /// How rendering every page handles documents with zero pages
+    pub empty_document: EmptyDocumentPolicy,
+
+    /// Whether to only render repeated page numbers once, the rendered
+    /// page is cloned for each occurrence
+    pub dedup_pages: bool,
+
+    /// Optional maximum time rendering a single page may take
+    pub timeout: Option<Duration>,
+
+    /// Optional destination for the pdftocairo stderr instead of keeping it in memory
+    pub stderr: Option<StderrSink>,
+
+    /// Optional lowered CPU and IO priority to run pdftocairo at
+    pub priority: Option<ProcessPriority>,
+
+    /// Optional limits on all the pdftocairo processes of the operation,
+    /// shared with any other args the budget is set on
+    pub budget: Option<Budget>,
+
+    /// Strategy for rendering multiple pages
+    pub fan_out: FanOut,
+}
+
+impl Default for RenderArgs {
+    fn default() -> Self {
+        Self {
+            resolution: None,
+            scale_to: None,
+            auto_dpi: None,
+            render_area: None,
+            crop: None,
+            render_color: None,
+            page_color: None,
+            jpeg_options: None,
+            tiff_compression: None,
+            #[cfg(feature = "avif")]
+            avif_options: None,
+            print_options: None,
+            paper_options: None,
+            backend: RenderBackend::default(),
+            splash_options: None,
+            password: None,
+            max_output_size: None,
+            verify: true,
+            empty_document: EmptyDocumentPolicy::default(),
+            dedup_pages: false,
+            timeout: None,
+            stderr: None,
+            priority: None,
+            budget: None,
+            fan_out: FanOut::default(),
+        }
+    }
 }
 
 impl RenderArgs {
@@ -43,11 +166,21 @@ impl RenderArgs {
         self
     }
 
+    pub fn set_auto_dpi(mut self, auto_dpi: AutoDpi) -> Self {
+        self.auto_dpi = Some(auto_dpi);
+        self
+    }
+
     pub fn set_render_area(mut self, render_area: RenderArea) -> Self {
         self.render_area = Some(render_area);
         self
     }
 
+    pub fn set_crop(mut self, crop: Crop) -> Self {
+        self.crop = Some(crop);
+        self
+    }
+
     pub fn set_render_color(mut self, render_color: RenderColor) -> Self {
         self.render_color = Some(render_color);
         self
@@ -58,12 +191,165 @@ impl RenderArgs {
         self
     }
 
+    pub fn set_jpeg_options(mut self, jpeg_options: JpegOptions) -> Self {
+        self.jpeg_options = Some(jpeg_options);
+        self
+    }
+
+    pub fn set_tiff_compression(mut self, tiff_compression: TiffCompression) -> Self {
+        self.tiff_compression = Some(tiff_compression);
+        self
+    }
+
+    #[cfg(feature = "avif")]
+    pub fn set_avif_options(mut self, avif_options: AvifOptions) -> Self {
+        self.avif_options = Some(avif_options);
+        self
+    }
+
+    pub fn set_print_options(mut self, print_options: PrintOptions) -> Self {
+        self.print_options = Some(print_options);
+        self
+    }
+
+    pub fn set_paper_options(mut self, paper_options: PaperOptions) -> Self {
+        self.paper_options = Some(paper_options);
+        self
+    }
+
+    pub fn set_backend(mut self, backend: RenderBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn set_splash_options(mut self, splash_options: SplashOptions) -> Self {
+        self.splash_options = Some(splash_options);
+        self
+    }
+
     pub fn set_password(mut self, password: Password) -> Self {
         self.password = Some(password);
         self
     }
 
-    /// Builds an argument list from all the options
+    pub fn set_max_output_size(mut self, max_output_size: usize) -> Self {
+        self.max_output_size = Some(max_output_size);
+        self
+    }
+
+    pub fn set_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    pub fn set_empty_document(mut self, empty_document: EmptyDocumentPolicy) -> Self {
+        self.empty_document = empty_document;
+        self
+    }
+
+    pub fn set_dedup_pages(mut self, dedup_pages: bool) -> Self {
+        self.dedup_pages = dedup_pages;
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_stderr(mut self, stderr: StderrSink) -> Self {
+        self.stderr = Some(stderr);
+        self
+    }
+
+    pub fn set_priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn set_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn set_fan_out(mut self, fan_out: FanOut) -> Self {
+        self.fan_out = fan_out;
+        self
+    }
+
+    /// Options for running pdftocairo
+    pub(crate) fn run_options(&self) -> RunOptions {
+        RunOptions {
+            max_output_size: self.max_output_size,
+            timeout: self.timeout,
+            password: self.password.clone(),
+            stderr: self.stderr.clone(),
+            priority: self.priority,
+            budget: self.budget.clone(),
+        }
+    }
+
+    /// Whether the crop, scale or resolution are relative to the page size
+    /// and must be resolved for each page using [RenderArgs::resolve]
+    pub fn is_relative(&self) -> bool {
+        self.crop.is_some_and(|crop| crop.is_fraction())
+            || self.scale_to.is_some_and(|scale_to| scale_to.is_fraction())
+            || self.auto_dpi.is_some()
+    }
+
+    /// Resolves a relative crop and scale into pixels and the automatic
+    /// resolution for the provided page
+    ///
+    /// ## Arguments
+    /// * dimensions - The dimensions of the page being rendered
+    pub fn resolve(&self, dimensions: &PageDimensions) -> Self {
+        self.resolve_page(dimensions, None)
+    }
+
+    /// Resolves the args for the provided page, using the resolution of
+    /// a scan covering the page when [AutoDpi::match_scans] is enabled
+    pub(crate) fn resolve_page(&self, dimensions: &PageDimensions, scan_ppi: Option<u32>) -> Self {
+        let mut args = self.clone();
+        if let Some(auto_dpi) = args.auto_dpi.take() {
+            args.resolution = Some(auto_dpi.resolution(dimensions, scan_ppi));
+        }
+
+        args.scale_to = args.scale_to.map(|scale_to| scale_to.resolve(dimensions));
+
+        let output = args.output_size(dimensions);
+        args.crop = args.crop.map(|crop| crop.resolve(output));
+        args
+    }
+
+    /// Size of the rendered page in pixels before cropping, assumes
+    /// the page is rendered using the media box
+    ///
+    /// ## Arguments
+    /// * dimensions - The dimensions of the page being rendered
+    pub fn output_size(&self, dimensions: &PageDimensions) -> Size {
+        let display = dimensions.display_size();
+        let scale_to = self
+            .scale_to
+            .map(|scale_to| scale_to.resolve(dimensions))
+            .map(|scale_to| (scale_to.x as f32, scale_to.y as f32));
+
+        match scale_to {
+            Some((x, y)) if x > 0.0 && y > 0.0 => Size::new(x, y),
+            Some((x, _)) if x > 0.0 => Size::new(x, (x * display.height / display.width).ceil()),
+            Some((_, y)) if y > 0.0 => Size::new((y * display.width / display.height).ceil(), y),
+            _ => {
+                let resolution = self.resolution.unwrap_or_default();
+                Size::new(
+                    (display.width * resolution.x as f32 / POINTS_PER_INCH).ceil(),
+                    (display.height * resolution.y as f32 / POINTS_PER_INCH).ceil(),
+                )
+            }
+        }
+    }
+
+    /// Builds an argument list from all the options, the password is passed
+    /// to the tool separately so it is not included. Relative crops and
+    /// scales are only included once resolved using [RenderArgs::resolve]
     pub fn build_args(&self) -> Vec<String> {
         let mut out = Vec::new();
 
@@ -79,6 +365,10 @@ impl RenderArgs {
             render_area.push_arg(&mut out);
         }
 
+        if let Some(crop) = self.crop.as_ref() {
+            crop.push_arg(&mut out);
+        }
+
         if let Some(render_color) = self.render_color.as_ref() {
             render_color.push_arg(&mut out);
         }
@@ -87,12 +377,307 @@ impl RenderArgs {
             page_color.push_arg(&mut out);
         }
 
-        if let Some(password) = self.password.as_ref() {
-            password.push_arg(&mut out);
+        if let Some(jpeg_options) = self.jpeg_options.as_ref() {
+            jpeg_options.push_arg(&mut out);
+        }
+
+        if let Some(tiff_compression) = self.tiff_compression.as_ref() {
+            tiff_compression.push_arg(&mut out);
+        }
+
+        if let Some(print_options) = self.print_options.as_ref() {
+            print_options.push_arg(&mut out);
+        }
+
+        if let Some(paper_options) = self.paper_options.as_ref() {
+            paper_options.push_arg(&mut out);
+        }
+
+        if let Some(splash_options) = self.splash_options.as_ref() {
+            splash_options.push_arg(&mut out);
         }
 
         out
     }
+
+    /// Checks the combination of options can be used when rendering as the
+    /// provided format, called by the render functions before running
+    /// pdftocairo so incompatible options are reported up front
+    ///
+    /// ## Arguments
+    /// * format - The output format to render as
+    pub fn validate(&self, format: OutputFormat) -> Result<(), RenderArgsError> {
+        let unsupported = |option: &'static str| {
+            Err(RenderArgsError::UnsupportedFormat {
+                option,
+                format: format.name().to_string(),
+            })
+        };
+
+        if matches!(self.page_color, Some(PageColor::Transparent))
+            && matches!(format, OutputFormat::Jpeg)
+        {
+            return unsupported("transparent page color");
+        }
+
+        if matches!(self.render_color, Some(RenderColor::Monochrome))
+            && matches!(format, OutputFormat::Jpeg)
+        {
+            return unsupported("monochrome render color");
+        }
+
+        if let Some(jpeg_options) = self.jpeg_options.as_ref() {
+            if !matches!(format, OutputFormat::Jpeg) {
+                return unsupported("jpeg options");
+            }
+
+            if let Some(quality) = jpeg_options.quality.filter(|quality| *quality > 100) {
+                return Err(RenderArgsError::JpegQuality(quality));
+            }
+        }
+
+        if self.tiff_compression.is_some() && !matches!(format, OutputFormat::Tiff) {
+            return unsupported("tiff compression");
+        }
+
+        #[cfg(feature = "avif")]
+        if let Some(avif_options) = self.avif_options.as_ref() {
+            if !matches!(format, OutputFormat::Avif) {
+                return unsupported("avif options");
+            }
+
+            if !(1..=100).contains(&avif_options.quality) {
+                return Err(RenderArgsError::AvifQuality(avif_options.quality));
+            }
+
+            if !(1..=10).contains(&avif_options.speed) {
+                return Err(RenderArgsError::AvifSpeed(avif_options.speed));
+            }
+        }
+
+        if self.print_options.is_some() {
+            return unsupported("print options");
+        }
+
+        if self.paper_options.is_some() {
+            return unsupported("paper options");
+        }
+
+        self.validate_backend()?;
+        self.validate_scale()
+    }
+
+    /// Checks the options are supported by the chosen backend
+    pub(crate) fn validate_backend(&self) -> Result<(), RenderArgsError> {
+        match self.backend {
+            RenderBackend::Cairo if self.splash_options.is_some() => {
+                Err(RenderArgsError::SplashOptions)
+            }
+            RenderBackend::Splash if matches!(self.page_color, Some(PageColor::Transparent)) => {
+                Err(RenderArgsError::UnsupportedBackend {
+                    option: "transparent page color",
+                    backend: self.backend.program(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the crop, scale and resolution options do not conflict and
+    /// that relative crops and scales are within range
+    pub(crate) fn validate_scale(&self) -> Result<(), RenderArgsError> {
+        if let Some(fraction) = self.crop.and_then(|crop| crop.fraction) {
+            let (min, max) = (fraction.min(), fraction.max());
+            if min.x < 0.0 || min.y < 0.0 || max.x > 1.0 || max.y > 1.0 {
+                return Err(RenderArgsError::CropFraction);
+            }
+        }
+
+        if let Some(auto_dpi) = self.auto_dpi {
+            if self.resolution.is_some() || self.scale_to.is_some() {
+                return Err(RenderArgsError::AutoDpiConflict);
+            }
+
+            if auto_dpi.min_ppi == 0 || auto_dpi.min_ppi > auto_dpi.max_ppi {
+                return Err(RenderArgsError::AutoDpiRange(
+                    auto_dpi.min_ppi,
+                    auto_dpi.max_ppi,
+                ));
+            }
+        }
+
+        let Some(scale_to) = self.scale_to else {
+            return Ok(());
+        };
+
+        if let Some(fraction) = scale_to.fraction {
+            if fraction.is_nan() || fraction <= 0.0 {
+                return Err(RenderArgsError::ScaleFraction);
+            }
+        }
+
+        // pdftocairo ignores the resolution when scaling
+        if self.resolution.is_some() {
+            return Err(RenderArgsError::ResolutionWithScale);
+        }
+
+        // Crops in pixels must fit within the scaled size, only known when
+        // both the width and height are provided
+        if let Some(crop) = self.crop.filter(|crop| !crop.is_fraction()) {
+            if !scale_to.is_fraction() && scale_to.x > 0 && scale_to.y > 0 {
+                let (x, y) = (scale_to.x as u32, scale_to.y as u32);
+                if crop.x.saturating_add(crop.width) > x || crop.y.saturating_add(crop.height) > y {
+                    return Err(RenderArgsError::CropOutsideScale(crop.rect(), x, y));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Args for listing the images of the PDF being rendered
+    fn images_args(&self) -> PdfImagesArgs {
+        PdfImagesArgs {
+            password: self.password.clone(),
+            timeout: self.timeout,
+            priority: self.priority,
+            ..Default::default()
+        }
+    }
+
+    /// Args for reading the page info of the PDF being rendered
+    fn info_args(&self) -> PdfInfoArgs {
+        let mut info_args = PdfInfoArgs::default();
+        if let Some(password) = self.password.clone() {
+            info_args = info_args.set_password(password);
+        }
+        if let Some(timeout) = self.timeout {
+            info_args = info_args.set_timeout(timeout);
+        }
+        if let Some(priority) = self.priority {
+            info_args = info_args.set_priority(priority);
+        }
+        if let Some(budget) = self.budget.clone() {
+            info_args = info_args.set_budget(budget);
+        }
+        info_args
+    }
+}
+
+/// Compression used for TIFF output
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TiffCompression {
+    /// No compression
+    #[default]
+    None,
+    /// PackBits run length encoding
+    PackBits,
+    /// JPEG compression, lossy
+    Jpeg,
+    /// Lempel-Ziv-Welch compression
+    Lzw,
+    /// Deflate (zlib) compression
+    Deflate,
+}
+
+impl TiffCompression {
+    pub fn push_arg(&self, args: &mut Vec<String>) {
+        args.push("-tiffcompression".to_string());
+
+        args.push(
+            match self {
+                Self::None => "none",
+                Self::PackBits => "packbits",
+                Self::Jpeg => "jpeg",
+                Self::Lzw => "lzw",
+                Self::Deflate => "deflate",
+            }
+            .to_string(),
+        );
+    }
+}
+
+/// Encoding options for JPEG output
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JpegOptions {
+    /// Quality between 0 and 100, uses the pdftocairo default of 75 when [None]
+    pub quality: Option<u8>,
+    /// Whether to use progressive encoding
+    pub progressive: bool,
+    /// Whether to compute optimal Huffman tables, produces smaller
+    /// files at the cost of encoding time
+    pub optimize: bool,
+}
+
+impl JpegOptions {
+    pub fn set_quality(mut self, quality: u8) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    pub fn set_progressive(mut self, progressive: bool) -> Self {
+        self.progressive = progressive;
+        self
+    }
+
+    pub fn set_optimize(mut self, optimize: bool) -> Self {
+        self.optimize = optimize;
+        self
+    }
+
+    pub fn push_arg(&self, args: &mut Vec<String>) {
+        let flag = |value: bool| if value { "y" } else { "n" };
+
+        let mut value = format!(
+            "progressive={},optimize={}",
+            flag(self.progressive),
+            flag(self.optimize)
+        );
+
+        if let Some(quality) = self.quality {
+            value = format!("quality={quality},{value}");
+        }
+
+        args.push("-jpegopt".to_string());
+        args.push(value);
+    }
+}
+
+/// Encoding options for AVIF output, the page is rendered as PNG
+/// and re-encoded using `ravif`
+#[cfg(feature = "avif")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AvifOptions {
+    /// Quality between 1 and 100
+    pub quality: u8,
+    /// Encoder speed between 1 (slowest, smallest output) and 10 (fastest)
+    pub speed: u8,
+}
+
+#[cfg(feature = "avif")]
+impl Default for AvifOptions {
+    fn default() -> Self {
+        Self {
+            quality: Self::DEFAULT_QUALITY,
+            speed: Self::DEFAULT_SPEED,
+        }
+    }
+}
+
+#[cfg(feature = "avif")]
+impl AvifOptions {
+    pub const DEFAULT_QUALITY: u8 = 80;
+    pub const DEFAULT_SPEED: u8 = 8;
+
+    pub fn set_quality(mut self, quality: u8) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn set_speed(mut self, speed: u8) -> Self {
+        self.speed = speed;
+        self
+    }
 }
 
 /// Color to use as the background of pages
@@ -113,6 +698,152 @@ impl PageColor {
     }
 }
 
+/// Poppler tool used to render pages to raster formats
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RenderBackend {
+    /// Render using pdftocairo
+    #[default]
+    Cairo,
+    /// Render using pdftoppm and the splash rasterizer, supports the
+    /// [SplashOptions] but not a transparent [PageColor]
+    Splash,
+}
+
+impl RenderBackend {
+    /// Name of the poppler tool for the backend
+    pub fn program(&self) -> &'static str {
+        match self {
+            Self::Cairo => "pdftocairo",
+            Self::Splash => "pdftoppm",
+        }
+    }
+}
+
+/// How the splash rasterizer draws lines thinner than a pixel
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ThinLineMode {
+    /// Lines are drawn at their actual width and may disappear
+    #[default]
+    None,
+    /// Lines are drawn at least one pixel wide without antialiasing
+    Solid,
+    /// Lines are drawn at least one pixel wide keeping the antialiased shape
+    Shape,
+}
+
+impl ThinLineMode {
+    pub fn push_arg(&self, args: &mut Vec<String>) {
+        args.push("-thinlinemode".to_string());
+
+        args.push(
+            match self {
+                Self::None => "none",
+                Self::Solid => "solid",
+                Self::Shape => "shape",
+            }
+            .to_string(),
+        );
+    }
+}
+
+/// Rendering options for the [RenderBackend::Splash] backend, options
+/// left as [None] use the pdftoppm defaults
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SplashOptions {
+    /// How lines thinner than a pixel are drawn, [ThinLineMode::Solid] and
+    /// [ThinLineMode::Shape] keep faint hairlines visible at low resolutions
+    pub thin_line_mode: Option<ThinLineMode>,
+    /// Whether to antialias text
+    pub text_antialias: Option<bool>,
+    /// Whether to antialias vector graphics
+    pub vector_antialias: Option<bool>,
+    /// Whether to rasterize fonts using FreeType, text is only
+    /// antialiased when FreeType is enabled
+    pub freetype: Option<bool>,
+}
+
+impl SplashOptions {
+    pub fn set_thin_line_mode(mut self, thin_line_mode: ThinLineMode) -> Self {
+        self.thin_line_mode = Some(thin_line_mode);
+        self
+    }
+
+    pub fn set_text_antialias(mut self, text_antialias: bool) -> Self {
+        self.text_antialias = Some(text_antialias);
+        self
+    }
+
+    pub fn set_vector_antialias(mut self, vector_antialias: bool) -> Self {
+        self.vector_antialias = Some(vector_antialias);
+        self
+    }
+
+    /// Sets both the text and vector antialiasing from the config
+    pub fn set_antialias(mut self, antialias: AntialiasConfig) -> Self {
+        self.text_antialias = Some(antialias.text);
+        self.vector_antialias = Some(antialias.vector);
+        self
+    }
+
+    pub fn set_freetype(mut self, freetype: bool) -> Self {
+        self.freetype = Some(freetype);
+        self
+    }
+
+    pub fn push_arg(&self, args: &mut Vec<String>) {
+        let flag = |value: bool| if value { "yes" } else { "no" };
+
+        if let Some(thin_line_mode) = self.thin_line_mode.as_ref() {
+            thin_line_mode.push_arg(args);
+        }
+
+        if let Some(text_antialias) = self.text_antialias {
+            args.push("-aa".to_string());
+            args.push(flag(text_antialias).to_string());
+        }
+
+        if let Some(vector_antialias) = self.vector_antialias {
+            args.push("-aaVector".to_string());
+            args.push(flag(vector_antialias).to_string());
+        }
+
+        if let Some(freetype) = self.freetype {
+            args.push("-freetype".to_string());
+            args.push(flag(freetype).to_string());
+        }
+    }
+}
+
+/// Separate text and vector antialiasing for the [RenderBackend::Splash]
+/// backend, line art drawings usually need different settings than text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AntialiasConfig {
+    /// Whether to antialias text
+    pub text: bool,
+    /// Whether to antialias vector graphics
+    pub vector: bool,
+}
+
+impl AntialiasConfig {
+    /// Antialias both text and vector graphics, the pdftoppm default
+    pub const ALL: AntialiasConfig = AntialiasConfig {
+        text: true,
+        vector: true,
+    };
+
+    /// Antialias text but keep the edges of line art drawings sharp
+    pub const TEXT_ONLY: AntialiasConfig = AntialiasConfig {
+        text: true,
+        vector: false,
+    };
+
+    /// Disable antialiasing for both text and vector graphics
+    pub const NONE: AntialiasConfig = AntialiasConfig {
+        text: false,
+        vector: false,
+    };
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Antialias {
     /// Use the default antialiasing for the target device.
@@ -183,12 +914,18 @@ impl RenderArea {
     }
 }
 
+/// Region of the rendered page to output, the position and size are
+/// in pixels of the rendered output (After the resolution or scaling
+/// is applied) with the origin at the top left of the page
 #[derive(Debug, Clone, Copy)]
 pub struct Crop {
     x: u32,
     y: u32,
     width: u32,
     height: u32,
+    /// Region as fractions of the rendered page size, resolved
+    /// into pixels once the page size is known
+    fraction: Option<Rect>,
 }
 
 impl Crop {
@@ -198,6 +935,39 @@ impl Crop {
             y,
             width,
             height,
+            fraction: None,
+        }
+    }
+
+    /// Creates a crop relative to the size of the rendered page, each value
+    /// is a fraction between 0.0 and 1.0 of the page width or height
+    /// (e.g. 0.5 for half of the page). Resolved against the size of each
+    /// page when rendering
+    pub fn fraction(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            fraction: Some(Rect::new(x, y, width, height)),
+            ..Self::new(0, 0, 0, 0)
+        }
+    }
+
+    /// Whether the crop is relative to the size of the rendered page
+    pub fn is_fraction(&self) -> bool {
+        self.fraction.is_some()
+    }
+
+    /// Resolves a relative crop into output pixels
+    ///
+    /// ## Arguments
+    /// * output - The size of the rendered page in pixels
+    pub fn resolve(&self, output: Size) -> Self {
+        match self.fraction {
+            Some(fraction) => Self::from_rect(Rect::new(
+                fraction.origin.x * output.width,
+                fraction.origin.y * output.height,
+                fraction.size.width * output.width,
+                fraction.size.height * output.height,
+            )),
+            None => *self,
         }
     }
 
@@ -205,11 +975,77 @@ impl Crop {
         Self::new(x, y, size, size)
     }
 
-    pub fn push_arg(&self, args: &mut Vec<String>) {
-        args.push("-x".to_string());
-        args.push(self.x.to_string());
+    /// Creates a crop from a rectangle in output pixels, the rectangle
+    /// is expanded outwards to whole pixels
+    pub fn from_rect(rect: Rect) -> Self {
+        let (min, max) = (rect.min(), rect.max());
+        let (x, y) = (min.x.max(0.0).floor(), min.y.max(0.0).floor());
+
+        Self::new(
+            x as u32,
+            y as u32,
+            (max.x.ceil() - x).max(0.0) as u32,
+            (max.y.ceil() - y).max(0.0) as u32,
+        )
+    }
 
-        args.push("-y".to_string());
+    /// Creates a crop from a rectangle in PDF points with the origin at the
+    /// bottom left of the unrotated page (PDF user space), the rectangle is
+    /// converted to output pixels taking the page rotation into account.
+    ///
+    /// Assumes the page is rendered using the media box at the provided
+    /// resolution
+    ///
+    /// ## Arguments
+    /// * rect - The region to crop in PDF points
+    /// * dimensions - The dimensions of the page being rendered
+    /// * resolution - The resolution the page is rendered at
+    pub fn from_pdf_rect(rect: Rect, dimensions: &PageDimensions, resolution: Resolution) -> Self {
+        let transform = resolution.page_transform(dimensions);
+        Self::from_rect(transform.pdf_to_pixels_rect(rect))
+    }
+
+    /// Creates a crop from a rectangle in PDF points with the origin at the
+    /// top left of the page as displayed, the coordinates used by
+    /// `pdftotext -bbox` and [BoundingBox](crate::BoundingBox)
+    ///
+    /// Assumes the page is rendered using the media box at the provided
+    /// resolution
+    ///
+    /// ## Arguments
+    /// * rect - The region to crop in PDF points
+    /// * dimensions - The dimensions of the page being rendered
+    /// * resolution - The resolution the page is rendered at
+    pub fn from_text_rect(rect: Rect, dimensions: &PageDimensions, resolution: Resolution) -> Self {
+        let transform = resolution.page_transform(dimensions);
+        Self::from_rect(transform.text_to_pixels_rect(rect))
+    }
+
+    /// Crop region as a rectangle in output pixels, for relative crops
+    /// this is the region as fractions of the page size
+    pub fn rect(&self) -> Rect {
+        if let Some(fraction) = self.fraction {
+            return fraction;
+        }
+
+        Rect::new(
+            self.x as f32,
+            self.y as f32,
+            self.width as f32,
+            self.height as f32,
+        )
+    }
+
+    pub fn push_arg(&self, args: &mut Vec<String>) {
+        // Relative crops need the page size, see [RenderArgs::resolve]
+        if self.is_fraction() {
+            return;
+        }
+
+        args.push("-x".to_string());
+        args.push(self.x.to_string());
+
+        args.push("-y".to_string());
         args.push(self.y.to_string());
 
         args.push("-W".to_string());
@@ -227,6 +1063,9 @@ pub struct ScaleTo {
     x: i32,
     /// The Y bounds to scale to fit within
     y: i32,
+    /// Scale relative to the page size, resolved into
+    /// pixels once the page size is known
+    fraction: Option<f32>,
 }
 
 impl Default for ScaleTo {
@@ -239,20 +1078,50 @@ impl ScaleTo {
     pub const MAINTAIN_ASPECT_RATIO: i32 = -1;
 
     pub fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
-    }
-
-    pub fn x(x: i32) -> Self {
         Self {
             x,
-            y: Self::MAINTAIN_ASPECT_RATIO,
+            y,
+            fraction: None,
         }
     }
 
+    pub fn x(x: i32) -> Self {
+        Self::new(x, Self::MAINTAIN_ASPECT_RATIO)
+    }
+
     pub fn y(y: i32) -> Self {
+        Self::new(Self::MAINTAIN_ASPECT_RATIO, y)
+    }
+
+    /// Scales relative to the size of the page in PDF points, a fraction of
+    /// 1.0 renders one pixel per point (e.g. 0.5 renders a 612x792 point page
+    /// at 306x396 pixels). Resolved against the size of each page when rendering
+    pub fn fraction(fraction: f32) -> Self {
         Self {
-            x: Self::MAINTAIN_ASPECT_RATIO,
-            y,
+            fraction: Some(fraction),
+            ..Self::default()
+        }
+    }
+
+    /// Whether the scale is relative to the size of the page
+    pub fn is_fraction(&self) -> bool {
+        self.fraction.is_some()
+    }
+
+    /// Resolves a relative scale into pixels
+    ///
+    /// ## Arguments
+    /// * dimensions - The dimensions of the page being rendered
+    pub fn resolve(&self, dimensions: &PageDimensions) -> Self {
+        match self.fraction {
+            Some(fraction) => {
+                let display = dimensions.display_size();
+                Self::new(
+                    (display.width * fraction).round() as i32,
+                    (display.height * fraction).round() as i32,
+                )
+            }
+            None => *self,
         }
     }
 
@@ -261,6 +1130,11 @@ impl ScaleTo {
     }
 
     pub fn push_arg(&self, args: &mut Vec<String>) {
+        // Relative scales need the page size, see [RenderArgs::resolve]
+        if self.is_fraction() {
+            return;
+        }
+
         args.push("-scale-to-x".to_string());
         args.push(self.x.to_string());
 
@@ -269,7 +1143,7 @@ impl ScaleTo {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Resolution {
     /// X resolution in pixels per inch
     x: u32,
@@ -300,6 +1174,69 @@ impl Resolution {
         Self::new(size, size)
     }
 
+    /// X resolution in pixels per inch
+    pub fn x_ppi(&self) -> u32 {
+        self.x
+    }
+
+    /// Y resolution in pixels per inch
+    pub fn y_ppi(&self) -> u32 {
+        self.y
+    }
+
+    /// Uniform resolution closest to rendering the page at the provided
+    /// width in pixels, the page dimensions are read from the info
+    ///
+    /// ## Arguments
+    /// * info - The PDF info, must contain the per page details for pages
+    ///   other than the first (See [PdfInfoArgs::set_first_page])
+    /// * page - The page that will be rendered
+    /// * width_px - The target width in pixels
+    pub fn for_target_width(
+        info: &PdfInfo,
+        page: u32,
+        width_px: u32,
+    ) -> Result<Self, PdfInfoError> {
+        let size = target_page_size(info, page)?;
+        Ok(Self::for_target(size.width, width_px))
+    }
+
+    /// Uniform resolution closest to rendering the page at the provided
+    /// height in pixels, the page dimensions are read from the info
+    ///
+    /// ## Arguments
+    /// * info - The PDF info, must contain the per page details for pages
+    ///   other than the first (See [PdfInfoArgs::set_first_page])
+    /// * page - The page that will be rendered
+    /// * height_px - The target height in pixels
+    pub fn for_target_height(
+        info: &PdfInfo,
+        page: u32,
+        height_px: u32,
+    ) -> Result<Self, PdfInfoError> {
+        let size = target_page_size(info, page)?;
+        Ok(Self::for_target(size.height, height_px))
+    }
+
+    /// Resolution that renders the provided length in points at the
+    /// provided number of pixels
+    fn for_target(points: f32, pixels: u32) -> Self {
+        let ppi = pixels as f32 * POINTS_PER_INCH / points;
+        Self::uniform((ppi.round() as u32).max(1))
+    }
+
+    /// Transform between the coordinate spaces of a page rendered
+    /// at this resolution
+    pub fn page_transform(&self, dimensions: &PageDimensions) -> PageTransform {
+        let display = dimensions.display_size();
+        let pixels = Size::new(
+            display.width * self.x as f32 / POINTS_PER_INCH,
+            display.height * self.y as f32 / POINTS_PER_INCH,
+        );
+
+        PageTransform::for_pixel_size(dimensions, pixels)
+    }
+
     pub fn push_arg(&self, args: &mut Vec<String>) {
         args.push("-rx".to_string());
         args.push(self.x.to_string());
@@ -309,10 +1246,25 @@ impl Resolution {
     }
 }
 
+/// Displayed size of the page for [Resolution::for_target_width], errors
+/// when the size is missing from the info or empty
+fn target_page_size(info: &PdfInfo, page: u32) -> Result<Size, PdfInfoError> {
+    let dimensions = info
+        .page_dimensions(page)
+        .ok_or(PdfInfoError::PageDimensionsUnavailable(page))??;
+    let size = dimensions.display_size();
+
+    if size.width <= 0.0 || size.height <= 0.0 {
+        return Err(PdfInfoError::PageDimensionsUnavailable(page));
+    }
+
+    Ok(size)
+}
+
 /// Output formats for pdftocairo, the program
 /// supports other formats but we only use these
 /// types
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum OutputFormat {
     /// Portable Network Graphics (PNG)
     Png,
@@ -321,29 +1273,203 @@ pub enum OutputFormat {
     Jpeg,
     /// Tagged Image File Format (TIFF)
     Tiff,
+    /// Lossless WebP, rendered as PNG then re-encoded
+    #[cfg(feature = "webp")]
+    WebP,
+    /// AV1 Image File Format (AVIF), rendered as PNG then re-encoded
+    /// using the [AvifOptions]
+    #[cfg(feature = "avif")]
+    Avif,
 }
 
 impl OutputFormat {
     pub fn push_arg(&self, args: &mut Vec<String>) {
-        args.push(match self {
-            OutputFormat::Png => "-png".to_string(),
+        args.push(match self.render_format() {
             OutputFormat::Jpeg => "-jpeg".to_string(),
             OutputFormat::Tiff => "-tiff".to_string(),
+            _ => "-png".to_string(),
         });
     }
 
+    /// Format pdftocairo renders as, formats pdftocairo cannot
+    /// produce are rendered as PNG and re-encoded
+    pub fn render_format(&self) -> OutputFormat {
+        match self {
+            #[cfg(feature = "webp")]
+            OutputFormat::WebP => OutputFormat::Png,
+            #[cfg(feature = "avif")]
+            OutputFormat::Avif => OutputFormat::Png,
+            format => *format,
+        }
+    }
+
+    /// MIME type for the format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "image/png",
+            OutputFormat::Jpeg => "image/jpeg",
+            OutputFormat::Tiff => "image/tiff",
+            #[cfg(feature = "webp")]
+            OutputFormat::WebP => "image/webp",
+            #[cfg(feature = "avif")]
+            OutputFormat::Avif => "image/avif",
+        }
+    }
+
+    /// Name of the format
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg => "jpeg",
+            OutputFormat::Tiff => "tiff",
+            #[cfg(feature = "webp")]
+            OutputFormat::WebP => "webp",
+            #[cfg(feature = "avif")]
+            OutputFormat::Avif => "avif",
+        }
+    }
+
+    /// File extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg => "jpg",
+            OutputFormat::Tiff => "tif",
+            #[cfg(feature = "webp")]
+            OutputFormat::WebP => "webp",
+            #[cfg(feature = "avif")]
+            OutputFormat::Avif => "avif",
+        }
+    }
+
+    #[cfg(any(feature = "image", feature = "image-0-24"))]
     pub fn image_format(&self) -> ImageFormat {
         match self {
             OutputFormat::Png => ImageFormat::Png,
             OutputFormat::Jpeg => ImageFormat::Jpeg,
             OutputFormat::Tiff => ImageFormat::Tiff,
+            #[cfg(feature = "webp")]
+            OutputFormat::WebP => ImageFormat::WebP,
+            #[cfg(feature = "avif")]
+            OutputFormat::Avif => ImageFormat::Avif,
+        }
+    }
+}
+
+/// Re-encodes the output from pdftocairo into formats it cannot
+/// produce directly, other formats are provided unchanged
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OutputEncoder {
+    format: OutputFormat,
+    #[cfg(feature = "avif")]
+    avif_options: AvifOptions,
+}
+
+impl OutputEncoder {
+    pub(crate) fn new(format: OutputFormat, args: &RenderArgs) -> Self {
+        #[cfg(not(feature = "avif"))]
+        let _ = args;
+
+        Self {
+            format,
+            #[cfg(feature = "avif")]
+            avif_options: args.avif_options.unwrap_or_default(),
         }
     }
+
+    /// Encodes the output from pdftocairo into the output format
+    pub(crate) fn encode(&self, output: Vec<u8>) -> Result<Vec<u8>, PdfRenderError> {
+        if self.format.render_format() == self.format {
+            return Ok(output);
+        }
+
+        self.reencode(output)
+    }
+
+    #[cfg(any(feature = "webp", feature = "avif"))]
+    fn reencode(&self, output: Vec<u8>) -> Result<Vec<u8>, PdfRenderError> {
+        let options = crate::encode::EncodeOptions::default();
+        #[cfg(feature = "avif")]
+        let options = options
+            .set_avif_quality(self.avif_options.quality)
+            .set_avif_speed(self.avif_options.speed);
+
+        let image = decode_page(&output, self.format)?;
+        crate::encode::encode_image(&image, self.format, &options, None)
+            .map_err(PdfRenderError::Image)
+    }
+
+    #[cfg(not(any(feature = "webp", feature = "avif")))]
+    fn reencode(&self, output: Vec<u8>) -> Result<Vec<u8>, PdfRenderError> {
+        Ok(output)
+    }
+}
+
+/// Options in [RenderArgs] that cannot be used together or with the
+/// requested output format
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum RenderArgsError {
+    #[error("{option} cannot be used with {format} output")]
+    UnsupportedFormat {
+        option: &'static str,
+        format: String,
+    },
+
+    #[error("jpeg quality {0} must be between 0 and 100")]
+    JpegQuality(u8),
+
+    #[error("avif quality {0} must be between 1 and 100")]
+    AvifQuality(u8),
+
+    #[error("avif speed {0} must be between 1 and 10")]
+    AvifSpeed(u8),
+
+    #[error("duplex can only be used with ps output")]
+    DuplexFormat,
+
+    #[error("paper width and height must be greater than 0")]
+    PaperSize,
+
+    #[error("paper size cannot be used with the original page sizes")]
+    PaperSizeWithOriginal,
+
+    #[error("fractional crop must be within 0.0 and 1.0")]
+    CropFraction,
+
+    #[error("fractional scale must be greater than 0.0")]
+    ScaleFraction,
+
+    #[error("resolution cannot be used with scale to, the scale overrides the resolution")]
+    ResolutionWithScale,
+
+    #[error("automatic resolution cannot be used with a resolution or scale")]
+    AutoDpiConflict,
+
+    #[error("automatic resolution range {0} to {1} ppi is invalid")]
+    AutoDpiRange(u32, u32),
+
+    #[error("crop {0:?} is outside the scaled size {1}x{2}")]
+    CropOutsideScale(Rect, u32, u32),
+
+    #[error("first page {0} is after the last page {1}")]
+    PageRange(u32, u32),
+
+    #[error("relative crop and scale require rendering a single page")]
+    RelativeMultiplePages,
+
+    #[error("{option} cannot be used with {backend}")]
+    UnsupportedBackend {
+        option: &'static str,
+        backend: &'static str,
+    },
+
+    #[error("splash options can only be used with the splash backend")]
+    SplashOptions,
 }
 
 #[derive(Debug, Error)]
 pub enum PdfRenderError {
-    #[error("failed to spawn pdftocairo: {0}")]
+    #[error("failed to spawn render tool: {0}")]
     SpawnProcess(std::io::Error),
 
     #[error("failed to write pdf bytes: {0}")]
@@ -352,12 +1478,31 @@ pub enum PdfRenderError {
     #[error("failed to get output: {0}")]
     WaitOutput(std::io::Error),
 
-    #[error("failed to get pdftocairo exit code: {0}")]
+    #[error("failed to write output: {0}")]
+    WriteOutput(std::io::Error),
+
+    #[error("failed to use output directory: {0}")]
+    OutputDirectory(std::io::Error),
+
+    #[error("output exceeded the maximum allowed size of {0} bytes")]
+    OutputLimitExceeded(usize),
+
+    #[error("render tool did not finish within {0:?}")]
+    Timeout(Duration),
+
+    #[error("render tool was aborted")]
+    Aborted,
+
+    #[error(transparent)]
+    BudgetExceeded(BudgetExceeded),
+
+    #[error("failed to get render tool exit code: {0}")]
     PdfRenderFailure(String),
 
-    #[error("pdftocairo reported permission error: {0}")]
-    PermissionError(String),
+    #[error("render tool reported permission error: {0}")]
+    PermissionError(PermissionDenied),
 
+    #[cfg(any(feature = "image", feature = "image-0-24"))]
     #[error(transparent)]
     Image(ImageError),
 
@@ -367,6 +1512,9 @@ pub enum PdfRenderError {
     #[error("page info page count is missing or invalid, pdf likely invalid")]
     PageCountUnknown,
 
+    #[error("pdf does not contain any pages")]
+    EmptyDocument,
+
     #[error("pdf is encrypted and no password was provided")]
     PdfEncrypted,
 
@@ -375,6 +1523,72 @@ pub enum PdfRenderError {
 
     #[error("file is not a pdf")]
     NotPdfFile,
+
+    #[error("invalid render args: {0}")]
+    InvalidArgs(#[from] RenderArgsError),
+
+    #[error("failed to combine tiff pages: {0}")]
+    TiffMerge(String),
+
+    #[error(transparent)]
+    Info(PdfInfoError),
+
+    #[error(transparent)]
+    Images(PdfImagesError),
+}
+
+impl From<PdfInfoError> for PdfRenderError {
+    fn from(value: PdfInfoError) -> Self {
+        match value {
+            PdfInfoError::PdfEncrypted => Self::PdfEncrypted,
+            PdfInfoError::IncorrectPassword => Self::IncorrectPassword,
+            PdfInfoError::NotPdfFile => Self::NotPdfFile,
+            PdfInfoError::EmptyDocument => Self::EmptyDocument,
+            err => Self::Info(err),
+        }
+    }
+}
+
+impl From<PdfImagesError> for PdfRenderError {
+    fn from(value: PdfImagesError) -> Self {
+        match value {
+            PdfImagesError::PdfEncrypted => Self::PdfEncrypted,
+            PdfImagesError::IncorrectPassword => Self::IncorrectPassword,
+            PdfImagesError::NotPdfFile => Self::NotPdfFile,
+            err => Self::Images(err),
+        }
+    }
+}
+
+impl From<ChildError> for PdfRenderError {
+    fn from(value: ChildError) -> Self {
+        match value {
+            ChildError::WriteInput(err) => Self::WritePdf(err),
+            ChildError::ReadOutput(err) => Self::WaitOutput(err),
+            ChildError::WriteOutput(err) => Self::WriteOutput(err),
+            ChildError::OutputLimitExceeded(limit) => Self::OutputLimitExceeded(limit),
+            ChildError::Timeout(timeout) => Self::Timeout(timeout),
+        }
+    }
+}
+
+impl From<ToolError> for PdfRenderError {
+    fn from(value: ToolError) -> Self {
+        match value {
+            ToolError::Spawn(err) => Self::SpawnProcess(err),
+            ToolError::Child(err) => err.into(),
+            ToolError::NotPdfFile => Self::NotPdfFile,
+            ToolError::PdfEncrypted => Self::PdfEncrypted,
+            ToolError::IncorrectPassword => Self::IncorrectPassword,
+            ToolError::PageOutOfBounds(page, page_count) => Self::PageOutOfBounds(page, page_count),
+            ToolError::PageCountUnknown => Self::PageCountUnknown,
+            ToolError::EmptyDocument => Self::EmptyDocument,
+            ToolError::PermissionError(err) => Self::PermissionError(err),
+            ToolError::Failure(err) | ToolError::Task(err) => Self::PdfRenderFailure(err),
+            ToolError::Aborted => Self::Aborted,
+            ToolError::BudgetExceeded(err) => Self::BudgetExceeded(err),
+        }
+    }
 }
 
 /// Renders all the pages in the provided PDF in parallel.
@@ -386,24 +1600,89 @@ pub enum PdfRenderError {
 /// * info - The PDF info to use for the page count and encryption state
 /// * format - The output format to render as
 /// * args - Optional args to pdftocairo
+#[cfg(any(feature = "image", feature = "image-0-24"))]
 pub async fn render_all_pages(
     data: &[u8],
     info: &PdfInfo,
     format: OutputFormat,
     args: &RenderArgs,
 ) -> Result<Vec<DynamicImage>, PdfRenderError> {
-    // Get the page count
-    let page_count = info
-        .pages()
-        .ok_or(PdfRenderError::PageCountUnknown)?
-        .map_err(|_| PdfRenderError::PageCountUnknown)?;
+    args.validate(format)?;
+    let page_count = verify_all_pages(data, info, args).await?;
+    let pages: Vec<u32> = (1..=page_count).collect();
+    let page_args = PageArgs::new(data, Some(info), args, &pages).await?;
 
     // Render all the pages individually
-    (1..=page_count)
-        .map(|page| render_page(data, format, page, args))
-        .collect::<FuturesOrdered<_>>()
-        .try_collect()
-        .await
+    for_each_page(
+        data,
+        pages,
+        false,
+        args.fan_out,
+        |page| page_args.job(format, page),
+        move |output| decode_page(&output, format),
+    )
+    .await
+}
+
+/// Order the pages are provided in by [render_all_pages_stream]
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StreamOrder {
+    /// Pages are provided in page order, a finished page waits for
+    /// the pages before it (Default)
+    #[default]
+    Ordered,
+    /// Pages are provided as soon as they finish rendering
+    Unordered,
+}
+
+/// Renders all the pages in the provided PDF in parallel, providing a
+/// [Stream] of the page numbers and images that yields each page as soon
+/// as it is ready rather than once every page has finished.
+///
+/// The document is verified before the stream is provided. At most
+/// [FanOut::Buffered] pages are rendered at once, the other fan out
+/// strategies render every page at once on the task polling the stream.
+/// Dropping the stream kills any renders that are still running
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * args - Optional args to pdftocairo
+/// * order - Order to provide the pages in
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_all_pages_stream<'a>(
+    data: &'a [u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    args: &RenderArgs,
+    order: StreamOrder,
+) -> Result<impl Stream<Item = Result<(u32, DynamicImage), PdfRenderError>> + 'a, PdfRenderError> {
+    args.validate(format)?;
+    let page_count = verify_all_pages(data, info, args).await?;
+    let pages: Vec<u32> = (1..=page_count).collect();
+    let page_args = PageArgs::new(data, Some(info), args, &pages).await?;
+
+    let jobs: Vec<(u32, ToolJob)> = pages
+        .into_iter()
+        .map(|page| (page, page_args.job(format, page)))
+        .collect();
+
+    let limit = match args.fan_out {
+        FanOut::Buffered(limit) => limit.max(1),
+        FanOut::Ordered | FanOut::Spawned => jobs.len().max(1),
+    };
+
+    let renders = stream::iter(jobs).map(move |(page, job)| async move {
+        let output = job.run(data).await?;
+        Ok::<_, PdfRenderError>((page, decode_page(&output, format)?))
+    });
+
+    Ok(match order {
+        StreamOrder::Ordered => renders.buffered(limit).left_stream(),
+        StreamOrder::Unordered => renders.buffer_unordered(limit).right_stream(),
+    })
 }
 
 /// Renders all the provided pages in parallel
@@ -416,6 +1695,7 @@ pub async fn render_all_pages(
 /// * format - The output format to render as
 /// * pages - The list of page numbers to render
 /// * args - Optional args to pdftocairo
+#[cfg(any(feature = "image", feature = "image-0-24"))]
 pub async fn render_pages(
     data: &[u8],
     info: &PdfInfo,
@@ -423,142 +1703,1331 @@ pub async fn render_pages(
     pages: Vec<u32>,
     args: &RenderArgs,
 ) -> Result<Vec<DynamicImage>, PdfRenderError> {
-    // Get the page count
-    let page_count = info
-        .pages()
-        .ok_or(PdfRenderError::PageCountUnknown)?
-        .map_err(|_| PdfRenderError::PageCountUnknown)?;
-
-    // Validate requested pages
-    for page in &pages {
-        if *page > page_count {
-            return Err(PdfRenderError::PageOutOfBounds(*page, page_count));
-        }
-    }
+    args.validate(format)?;
+    verify_pages(data, info, args, &pages).await?;
+    let page_args = PageArgs::new(data, Some(info), args, &pages).await?;
 
     // Render all the pages individually
-    pages
-        .into_iter()
-        .map(|page| render_page(data, format, page, args))
-        .collect::<FuturesOrdered<_>>()
-        .try_collect()
-        .await
+    for_each_page(
+        data,
+        pages,
+        args.dedup_pages,
+        args.fan_out,
+        |page| page_args.job(format, page),
+        move |output| decode_page(&output, format),
+    )
+    .await
 }
 
-/// Renders a single page from a PDF file
+/// Renders all the provided pages in parallel, keeping the pages that
+/// rendered before any failure. Once a page fails no more pages are
+/// rendered and the pages still rendering are stopped
+///
+/// See [render_pages]
 ///
 /// ## Arguments
 /// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
 /// * format - The output format to render as
-/// * page - The page to render
+/// * pages - The list of page numbers to render
 /// * args - Optional args to pdftocairo
-pub async fn render_single_page(
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_pages_partial(
     data: &[u8],
     info: &PdfInfo,
     format: OutputFormat,
-    page: u32,
+    pages: Vec<u32>,
     args: &RenderArgs,
-) -> Result<DynamicImage, PdfRenderError> {
-    // Get the page count
-    let page_count = info
-        .pages()
-        .ok_or(PdfRenderError::PageCountUnknown)?
-        .map_err(|_| PdfRenderError::PageCountUnknown)?;
+) -> PartialResult<DynamicImage, PdfRenderError> {
+    let page_args = match prepare_pages(data, info, format, &pages, args).await {
+        Ok(page_args) => page_args,
+        Err(err) => return PartialResult::failed(err),
+    };
+
+    for_each_page_partial(
+        data,
+        pages,
+        args.dedup_pages,
+        args.fan_out,
+        |page| page_args.job(format, page),
+        move |output| decode_page(&output, format),
+    )
+    .await
+}
 
-    // Validate chosen page
-    if page > page_count {
-        return Err(PdfRenderError::PageOutOfBounds(page, page_count));
-    }
+/// Renders all the provided pages in parallel, providing each rendered
+/// page paired with its page number in the requested order
+///
+/// See [render_pages]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * pages - The list of page numbers to render
+/// * args - Optional args to pdftocairo
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_pages_numbered(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    pages: Vec<u32>,
+    args: &RenderArgs,
+) -> Result<Vec<(u32, DynamicImage)>, PdfRenderError> {
+    let images = render_pages(data, info, format, pages.clone(), args).await?;
+    Ok(pages.into_iter().zip(images).collect())
+}
+
+/// Renders the selected pages in parallel, providing each rendered
+/// page paired with its page number in the selected order
+///
+/// See [render_pages]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * selection - The pages to render
+/// * args - Optional args to pdftocairo
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_page_selection(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    selection: &PageSelection,
+    args: &RenderArgs,
+) -> Result<Vec<(u32, DynamicImage)>, PdfRenderError> {
+    let pages = selection.resolve(info, args.empty_document)?;
+    render_pages_numbered(data, info, format, pages, args).await
+}
 
-    render_page(data, format, page, args).await
+/// Renders all the provided pages in parallel, each page is rendered
+/// using its own args (e.g. the first page at a high resolution and
+/// the rest as thumbnails)
+///
+/// The password, verification and fan out strategy are taken from
+/// the args of the first page
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * pages - The page numbers to render paired with their args
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_pages_with(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    pages: Vec<(u32, RenderArgs)>,
+) -> Result<Vec<DynamicImage>, PdfRenderError> {
+    let Some((jobs, fan_out)) = page_jobs_with(data, info, format, &pages).await? else {
+        return Ok(Vec::new());
+    };
+
+    run_jobs(data, jobs, fan_out, move |output| {
+        decode_page(&output, format)
+    })
+    .await
 }
 
-/// Renders the provided page from a pdf file using `pdftocairo`
-async fn render_page(
+/// Renders a single page from a PDF file
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * format - The output format to render as
+/// * page - The page to render
+/// * args - Optional args to pdftocairo
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_single_page(
     data: &[u8],
+    info: &PdfInfo,
     format: OutputFormat,
     page: u32,
     args: &RenderArgs,
 ) -> Result<DynamicImage, PdfRenderError> {
-    let mut cli_args = args.build_args();
-    format.push_arg(&mut cli_args);
-
-    let mut child = Command::new("pdftocairo")
-        // Take input from stdin and provide to stdout
-        .args(["-", "-"])
-        // Specify first and last pages
-        .args([
-            "-singlefile",
-            "-f",
-            &page.to_string(),
-            "-l",
-            &page.to_string(),
-        ])
-        // Add optional args and output format
-        .args(cli_args)
-        // Pipe input and output for use
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(PdfRenderError::SpawnProcess)?;
-
-    child
-        .stdin
-        .as_mut()
-        // Should always have stdin when using .stdin(Stdio::piped())
-        .expect("progress missing stdin after being piped")
-        .write_all(data)
-        .await
-        .map_err(PdfRenderError::WritePdf)?;
-
-    let output = child
-        .wait_with_output()
-        .await
-        .map_err(PdfRenderError::WaitOutput)?;
+    verify_pages(data, info, args, &[page]).await?;
 
-    // Handle info failure
-    if !output.status.success() {
-        let value = String::from_utf8_lossy(&output.stderr);
+    render_page(data, info, format, page, args).await
+}
 
-        if value.contains("May not be a PDF file") {
-            return Err(PdfRenderError::NotPdfFile);
-        }
+/// Renders all the pages in the provided PDF in parallel, providing
+/// the encoded output bytes from pdftocairo without decoding them.
+///
+/// See [render_all_pages]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * args - Optional args to pdftocairo
+pub async fn render_all_pages_raw(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    args: &RenderArgs,
+) -> Result<Vec<Vec<u8>>, PdfRenderError> {
+    args.validate(format)?;
+    let page_count = verify_all_pages(data, info, args).await?;
+    let pages: Vec<u32> = (1..=page_count).collect();
+    let page_args = PageArgs::new(data, Some(info), args, &pages).await?;
+    let encoder = OutputEncoder::new(format, args);
 
-        if value.contains("Incorrect password") {
-            return Err(if args.password.is_none() {
-                PdfRenderError::PdfEncrypted
-            } else {
-                PdfRenderError::IncorrectPassword
-            });
-        }
+    // Render all the pages individually
+    for_each_page(
+        data,
+        pages,
+        false,
+        args.fan_out,
+        |page| page_args.job(format, page),
+        move |output| encoder.encode(output),
+    )
+    .await
+}
 
-        let code = output.status.code();
+/// Renders all the provided pages in parallel, providing the encoded
+/// output bytes from pdftocairo without decoding them.
+///
+/// See [render_pages]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * pages - The list of page numbers to render
+/// * args - Optional args to pdftocairo
+pub async fn render_pages_raw(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    pages: Vec<u32>,
+    args: &RenderArgs,
+) -> Result<Vec<Vec<u8>>, PdfRenderError> {
+    args.validate(format)?;
+    verify_pages(data, info, args, &pages).await?;
+    let page_args = PageArgs::new(data, Some(info), args, &pages).await?;
+    let encoder = OutputEncoder::new(format, args);
 
-        match code {
-            Some(3) => return Err(PdfRenderError::PermissionError(value.to_string())),
-            _ => return Err(PdfRenderError::PdfRenderFailure(value.to_string())),
-        }
-    }
+    // Render all the pages individually
+    for_each_page(
+        data,
+        pages,
+        args.dedup_pages,
+        args.fan_out,
+        |page| page_args.job(format, page),
+        move |output| encoder.encode(output),
+    )
+    .await
+}
 
-    let image = image::load_from_memory_with_format(&output.stdout, format.image_format())
-        .map_err(PdfRenderError::Image)?;
+/// Renders all the provided pages in parallel, providing the encoded
+/// output bytes of the pages that rendered before any failure
+///
+/// See [render_pages_partial]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * pages - The list of page numbers to render
+/// * args - Optional args to pdftocairo
+pub async fn render_pages_raw_partial(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    pages: Vec<u32>,
+    args: &RenderArgs,
+) -> PartialResult<Vec<u8>, PdfRenderError> {
+    let page_args = match prepare_pages(data, info, format, &pages, args).await {
+        Ok(page_args) => page_args,
+        Err(err) => return PartialResult::failed(err),
+    };
+    let encoder = OutputEncoder::new(format, args);
+
+    for_each_page_partial(
+        data,
+        pages,
+        args.dedup_pages,
+        args.fan_out,
+        |page| page_args.job(format, page),
+        move |output| encoder.encode(output),
+    )
+    .await
+}
+
+/// Validates the args and pages before rendering them, resolving
+/// the args for each of the pages
+async fn prepare_pages<'a>(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    pages: &[u32],
+    args: &'a RenderArgs,
+) -> Result<PageArgs<'a>, PdfRenderError> {
+    args.validate(format)?;
+    verify_pages(data, info, args, pages).await?;
+    PageArgs::new(data, Some(info), args, pages).await
+}
 
-    Ok(image)
+/// Renders all the provided pages in parallel, providing the encoded
+/// output bytes paired with the page number in the requested order
+///
+/// See [render_pages_raw]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * pages - The list of page numbers to render
+/// * args - Optional args to pdftocairo
+pub async fn render_pages_raw_numbered(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    pages: Vec<u32>,
+    args: &RenderArgs,
+) -> Result<Vec<(u32, Vec<u8>)>, PdfRenderError> {
+    let images = render_pages_raw(data, info, format, pages.clone(), args).await?;
+    Ok(pages.into_iter().zip(images).collect())
+}
+
+/// Renders the selected pages in parallel, providing the encoded output
+/// bytes paired with the page number in the selected order
+///
+/// See [render_page_selection]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * selection - The pages to render
+/// * args - Optional args to pdftocairo
+pub async fn render_page_selection_raw(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    selection: &PageSelection,
+    args: &RenderArgs,
+) -> Result<Vec<(u32, Vec<u8>)>, PdfRenderError> {
+    let pages = selection.resolve(info, args.empty_document)?;
+    render_pages_raw_numbered(data, info, format, pages, args).await
+}
+
+/// Renders all the provided pages in parallel each using their own
+/// args, providing the encoded output bytes from pdftocairo without
+/// decoding them.
+///
+/// See [render_pages_with]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * pages - The page numbers to render paired with their args
+pub async fn render_pages_raw_with(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    pages: Vec<(u32, RenderArgs)>,
+) -> Result<Vec<Vec<u8>>, PdfRenderError> {
+    let Some((jobs, fan_out)) = page_jobs_with(data, info, format, &pages).await? else {
+        return Ok(Vec::new());
+    };
+
+    let outputs = run_jobs(data, jobs, fan_out, Ok::<_, PdfRenderError>).await?;
+
+    outputs
+        .into_iter()
+        .zip(&pages)
+        .map(|(output, (_, args))| OutputEncoder::new(format, args).encode(output))
+        .collect()
+}
+
+/// Validates the args of each page and creates the jobs for rendering
+/// them, provides [None] when there are no pages to render
+async fn page_jobs_with(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    pages: &[(u32, RenderArgs)],
+) -> Result<Option<(Vec<ToolJob>, FanOut)>, PdfRenderError> {
+    let Some((_, first)) = pages.first() else {
+        return Ok(None);
+    };
+
+    for (_, args) in pages {
+        args.validate(format)?;
+    }
+
+    let page_numbers: Vec<u32> = pages.iter().map(|(page, _)| *page).collect();
+    verify_pages(data, info, first, &page_numbers).await?;
+
+    let mut jobs = Vec::with_capacity(pages.len());
+    for (page, args) in pages {
+        let page_args = PageArgs::new(data, Some(info), args, &[*page]).await?;
+        jobs.push(page_args.job(format, *page));
+    }
+
+    Ok(Some((jobs, first.fan_out)))
+}
+
+/// Renders all the pages in the provided PDF into a single multi-page
+/// TIFF file, pages are rendered in parallel and combined in order.
+///
+/// Use [RenderArgs::set_tiff_compression] to choose the compression
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * args - Optional args to pdftocairo
+pub async fn render_all_pages_tiff_multipage(
+    data: &[u8],
+    info: &PdfInfo,
+    args: &RenderArgs,
+) -> Result<Vec<u8>, PdfRenderError> {
+    let pages = render_all_pages_raw(data, info, OutputFormat::Tiff, args).await?;
+    crate::tiff::merge_pages(&pages).map_err(PdfRenderError::TiffMerge)
+}
+
+/// Renders a single page from a PDF file, providing the encoded
+/// output bytes from pdftocairo without decoding them.
+///
+/// See [render_single_page]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * format - The output format to render as
+/// * page - The page to render
+/// * args - Optional args to pdftocairo
+pub async fn render_single_page_raw(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    page: u32,
+    args: &RenderArgs,
+) -> Result<Vec<u8>, PdfRenderError> {
+    verify_pages(data, info, args, &[page]).await?;
+
+    let output = render_page_raw(data, Some(info), format, page, args).await?;
+    OutputEncoder::new(format, args).encode(output)
+}
+
+/// Renders a single page from a PDF file, streaming the encoded output
+/// from pdftocairo into the `writer` as it is produced instead of
+/// collecting the whole image in memory. Provides the number of bytes
+/// written.
+///
+/// The [RenderArgs::max_output_size] applies to the streamed output, output
+/// written before a failure is not removed from the writer. Formats that
+/// pdftocairo cannot produce (WebP and AVIF) must be re-encoded so are
+/// rendered in memory before being written
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * format - The output format to render as
+/// * page - The page to render
+/// * args - Optional args to pdftocairo
+/// * writer - Destination for the encoded output
+pub async fn render_page_to_writer<W>(
+    data: &[u8],
+    format: OutputFormat,
+    page: u32,
+    args: &RenderArgs,
+    mut writer: W,
+) -> Result<usize, PdfRenderError>
+where
+    W: AsyncWrite + Unpin,
+{
+    args.validate(format)?;
+    let page_args = PageArgs::new(data, None, args, &[page]).await?;
+    let job = page_args.job(format, page);
+
+    if format.render_format() != format {
+        let output = OutputEncoder::new(format, args).encode(job.run(data).await?)?;
+        writer
+            .write_all(&output)
+            .await
+            .map_err(PdfRenderError::WriteOutput)?;
+        writer.flush().await.map_err(PdfRenderError::WriteOutput)?;
+        return Ok(output.len());
+    }
+
+    Ok(job.run_to(data, &mut writer).await?)
+}
+
+/// Reads the PDF info while speculatively rendering the first page,
+/// the render is cancelled if the info cannot be read (For example
+/// when the PDF is encrypted)
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * format - The output format to render as
+/// * args - Optional args to pdftocairo, the password is also used for pdfinfo
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn info_and_first_page(
+    data: &[u8],
+    format: OutputFormat,
+    args: &RenderArgs,
+) -> Result<(PdfInfo, DynamicImage), PdfRenderError> {
+    let (info, output) = info_and_first_page_output(data, format, args).await?;
+    let image = decode_page(&output, format)?;
+
+    Ok((info, image))
+}
+
+/// Reads the PDF info while speculatively rendering the first page,
+/// providing the encoded output bytes from pdftocairo without decoding them.
+///
+/// See [info_and_first_page]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * format - The output format to render as
+/// * args - Optional args to pdftocairo, the password is also used for pdfinfo
+pub async fn info_and_first_page_raw(
+    data: &[u8],
+    format: OutputFormat,
+    args: &RenderArgs,
+) -> Result<(PdfInfo, Vec<u8>), PdfRenderError> {
+    let (info, output) = info_and_first_page_output(data, format, args).await?;
+    let output = OutputEncoder::new(format, args).encode(output)?;
+
+    Ok((info, output))
+}
+
+/// Reads the PDF info while rendering the first page, provides the
+/// output from pdftocairo before it is re-encoded
+async fn info_and_first_page_output(
+    data: &[u8],
+    format: OutputFormat,
+    args: &RenderArgs,
+) -> Result<(PdfInfo, Vec<u8>), PdfRenderError> {
+    let info_args = args.info_args();
+
+    // Render is dropped (killing pdftocairo) as soon as either fails
+    try_join(
+        async {
+            pdf_info(data, &info_args)
+                .await
+                .map_err(PdfRenderError::from)
+        },
+        render_page_raw(data, None, format, 1, args),
+    )
+    .await
+}
+
+/// Checks the encryption state and that the pages are within the page
+/// count of the PDF, skipped when [RenderArgs::verify] is disabled
+pub(crate) async fn verify_pages(
+    data: &[u8],
+    info: &PdfInfo,
+    args: &RenderArgs,
+    pages: &[u32],
+) -> Result<(), PdfRenderError> {
+    if !args.verify {
+        return Ok(());
+    }
+
+    verify_document(data, info, args.password.as_ref(), pages).await?;
+    Ok(())
+}
+
+/// Checks the encryption state providing the page count of the PDF, only
+/// the page count is read when [RenderArgs::verify] is disabled. The page
+/// count is checked against the [RenderArgs::empty_document] policy
+pub(crate) async fn verify_all_pages(
+    data: &[u8],
+    info: &PdfInfo,
+    args: &RenderArgs,
+) -> Result<u32, PdfRenderError> {
+    let page_count = if args.verify {
+        verify_document(data, info, args.password.as_ref(), &[]).await?
+    } else {
+        info_page_count(info)?
+    };
+
+    Ok(args.empty_document.check(page_count)?)
+}
+
+/// Pixel types that rendered pages can be converted into, allows
+/// rendering directly into a known [ImageBuffer] layout
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub trait RenderPixel: Pixel {
+    /// Converts the decoded image into a buffer of this pixel type,
+    /// no conversion is performed when the image already uses it
+    fn from_dynamic(image: DynamicImage) -> ImageBuffer<Self, Vec<Self::Subpixel>>;
+}
+
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+impl RenderPixel for Rgba<u8> {
+    fn from_dynamic(image: DynamicImage) -> ImageBuffer<Self, Vec<Self::Subpixel>> {
+        image.into_rgba8()
+    }
+}
+
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+impl RenderPixel for Rgb<u8> {
+    fn from_dynamic(image: DynamicImage) -> ImageBuffer<Self, Vec<Self::Subpixel>> {
+        image.into_rgb8()
+    }
+}
+
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+impl RenderPixel for Luma<u8> {
+    fn from_dynamic(image: DynamicImage) -> ImageBuffer<Self, Vec<Self::Subpixel>> {
+        image.into_luma8()
+    }
+}
+
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+impl RenderPixel for LumaA<u8> {
+    fn from_dynamic(image: DynamicImage) -> ImageBuffer<Self, Vec<Self::Subpixel>> {
+        image.into_luma_alpha8()
+    }
+}
+
+/// Renders all the pages in the provided PDF in parallel into
+/// buffers of the pixel type `P`
+///
+/// See [render_all_pages]
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_all_pages_as<P: RenderPixel>(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    args: &RenderArgs,
+) -> Result<Vec<ImageBuffer<P, Vec<P::Subpixel>>>, PdfRenderError> {
+    let images = render_all_pages(data, info, format, args).await?;
+    Ok(images.into_iter().map(P::from_dynamic).collect())
+}
+
+/// Renders all the provided pages in parallel into buffers of the
+/// pixel type `P`
+///
+/// See [render_pages]
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_pages_as<P: RenderPixel>(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    pages: Vec<u32>,
+    args: &RenderArgs,
+) -> Result<Vec<ImageBuffer<P, Vec<P::Subpixel>>>, PdfRenderError> {
+    let images = render_pages(data, info, format, pages, args).await?;
+    Ok(images.into_iter().map(P::from_dynamic).collect())
+}
+
+/// Renders a single page from a PDF file into a buffer of the
+/// pixel type `P`
+///
+/// See [render_single_page]
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_single_page_as<P: RenderPixel>(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    page: u32,
+    args: &RenderArgs,
+) -> Result<ImageBuffer<P, Vec<P::Subpixel>>, PdfRenderError> {
+    render_single_page(data, info, format, page, args)
+        .await
+        .map(P::from_dynamic)
+}
+
+/// Renders a single page from a PDF file as 8-bit RGBA
+///
+/// See [render_single_page]
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_single_page_rgba8(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    page: u32,
+    args: &RenderArgs,
+) -> Result<RgbaImage, PdfRenderError> {
+    render_single_page_as(data, info, format, page, args).await
+}
+
+/// Renders a single page from a PDF file as 8-bit grayscale, combine
+/// with [RenderColor::Grayscale] to avoid a color conversion
+///
+/// See [render_single_page]
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_single_page_luma8(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    page: u32,
+    args: &RenderArgs,
+) -> Result<GrayImage, PdfRenderError> {
+    render_single_page_as(data, info, format, page, args).await
+}
+
+/// Raw 8-bit RGBA pixels of a rendered page, rows are stored from top to
+/// bottom with 4 bytes per pixel and no padding between rows. Allows the
+/// pixels to be passed to GPU or video pipelines without the image types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaPixels {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Pixel data in RGBA order
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaPixels {
+    /// Number of bytes in each row
+    pub fn stride(&self) -> usize {
+        self.width as usize * 4
+    }
+
+    /// RGBA value of the pixel at the provided position
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let start = y as usize * self.stride() + x as usize * 4;
+        self.pixels.get(start..start + 4)?.try_into().ok()
+    }
+}
+
+/// Renders a single page from a PDF file as raw 8-bit RGBA pixels, the
+/// page is rendered as PNG so transparency from [PageColor::Transparent]
+/// is kept
+///
+/// See [render_single_page]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * page - The page to render
+/// * args - Optional args to pdftocairo
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_page_rgba(
+    data: &[u8],
+    info: &PdfInfo,
+    page: u32,
+    args: &RenderArgs,
+) -> Result<RgbaPixels, PdfRenderError> {
+    let output = render_single_page_raw(data, info, OutputFormat::Png, page, args).await?;
+    decode_rgba(&output)
+}
+
+/// Renders all the provided pages in parallel as raw 8-bit RGBA pixels,
+/// the pages are decoded as they finish rendering
+///
+/// See [render_page_rgba]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * pages - The list of page numbers to render
+/// * args - Optional args to pdftocairo
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub async fn render_pages_rgba(
+    data: &[u8],
+    info: &PdfInfo,
+    pages: Vec<u32>,
+    args: &RenderArgs,
+) -> Result<Vec<RgbaPixels>, PdfRenderError> {
+    let format = OutputFormat::Png;
+    args.validate(format)?;
+    verify_pages(data, info, args, &pages).await?;
+    let page_args = PageArgs::new(data, Some(info), args, &pages).await?;
+
+    for_each_page(
+        data,
+        pages,
+        args.dedup_pages,
+        args.fan_out,
+        |page| page_args.job(format, page),
+        |output| decode_rgba(&output),
+    )
+    .await
+}
+
+/// Decodes the PNG output from `pdftocairo` into raw RGBA pixels
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+fn decode_rgba(output: &[u8]) -> Result<RgbaPixels, PdfRenderError> {
+    let image = decode_page(output, OutputFormat::Png)?.into_rgba8();
+
+    Ok(RgbaPixels {
+        width: image.width(),
+        height: image.height(),
+        pixels: image.into_raw(),
+    })
+}
+
+/// Renders the provided page from a pdf file using `pdftocairo`
+/// and decodes the output
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+async fn render_page(
+    data: &[u8],
+    info: &PdfInfo,
+    format: OutputFormat,
+    page: u32,
+    args: &RenderArgs,
+) -> Result<DynamicImage, PdfRenderError> {
+    let output = render_page_raw(data, Some(info), format, page, args).await?;
+    decode_page(&output, format)
+}
+
+/// Decodes the encoded output from `pdftocairo`
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub(crate) fn decode_page(
+    output: &[u8],
+    format: OutputFormat,
+) -> Result<DynamicImage, PdfRenderError> {
+    crate::image_crate::load_from_memory_with_format(output, format.render_format().image_format())
+        .map_err(PdfRenderError::Image)
+}
+
+/// Renders the provided page from a pdf file using `pdftocairo`
+/// providing the encoded output, formats pdftocairo cannot produce
+/// are provided as PNG until encoded using an [OutputEncoder]. The
+/// info is used for the page dimensions of relative args when provided
+pub(crate) async fn render_page_raw(
+    data: &[u8],
+    info: Option<&PdfInfo>,
+    format: OutputFormat,
+    page: u32,
+    args: &RenderArgs,
+) -> Result<Vec<u8>, PdfRenderError> {
+    args.validate(format)?;
+    let page_args = PageArgs::new(data, info, args, &[page]).await?;
+    Ok(page_args.job(format, page).run(data).await?)
+}
+
+/// Render args for each page, relative crops and scales are
+/// resolved against the size of each page
+pub(crate) struct PageArgs<'a> {
+    args: &'a RenderArgs,
+    /// Dimensions of each page, only read when the args are relative
+    dimensions: HashMap<u32, PageDimensions>,
+    /// Resolution of the scan covering each page, only read when
+    /// matching the resolution of scans using [AutoDpi::match_scans]
+    scan_ppi: HashMap<u32, u32>,
+}
+
+impl<'a> PageArgs<'a> {
+    /// Reads the dimensions of the provided pages when the args are
+    /// relative, dimensions missing from the info are read using pdfinfo
+    ///
+    /// ## Arguments
+    /// * data - The raw PDF file bytes
+    /// * info - Optional PDF info that may already contain the dimensions
+    /// * args - The args to resolve
+    /// * pages - The pages that will be rendered
+    pub(crate) async fn new(
+        data: &[u8],
+        info: Option<&PdfInfo>,
+        args: &'a RenderArgs,
+        pages: &[u32],
+    ) -> Result<Self, PdfRenderError> {
+        let mut dimensions = HashMap::new();
+
+        if args.is_relative() {
+            let mut missing = Vec::new();
+
+            for &page in pages {
+                match info.and_then(|info| info.page_dimensions(page)) {
+                    Some(value) => _ = dimensions.insert(page, value?),
+                    None => missing.push(page),
+                }
+            }
+
+            if let (Some(&first), Some(&last)) = (missing.iter().min(), missing.iter().max()) {
+                let info_args = args.info_args().set_first_page(first).set_last_page(last);
+                let info = pdf_info(data, &info_args).await?;
+
+                for page in missing {
+                    let value = info
+                        .page_dimensions(page)
+                        .ok_or(PdfInfoError::PageDimensionsUnavailable(page))??;
+                    dimensions.insert(page, value);
+                }
+            }
+        }
+
+        let mut scan_ppi = HashMap::new();
+
+        if args.auto_dpi.is_some_and(|auto_dpi| auto_dpi.match_scans) {
+            if let (Some(&first), Some(&last)) = (pages.iter().min(), pages.iter().max()) {
+                let images_args = args.images_args().set_first_page(first).set_last_page(last);
+                let images = pdf_image_list(data, &images_args).await?;
+
+                for (&page, dimensions) in &dimensions {
+                    if let Some(value) = crate::dpi::scan_ppi(page, dimensions, &images) {
+                        scan_ppi.insert(page, value);
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            args,
+            dimensions,
+            scan_ppi,
+        })
+    }
+
+    /// Args for rendering the provided page
+    pub(crate) fn args(&self, page: u32) -> Cow<'a, RenderArgs> {
+        match self.dimensions.get(&page) {
+            Some(dimensions) => Cow::Owned(
+                self.args
+                    .resolve_page(dimensions, self.scan_ppi.get(&page).copied()),
+            ),
+            None => Cow::Borrowed(self.args),
+        }
+    }
+
+    /// Creates the job for rendering the provided page
+    pub(crate) fn job(&self, format: OutputFormat, page: u32) -> ToolJob {
+        page_job(format, page, &self.args(page))
+    }
+}
+
+/// Creates the job for rendering the provided page using the backend
+/// from the args
+pub(crate) fn page_job(format: OutputFormat, page: u32, args: &RenderArgs) -> ToolJob {
+    // Take input from stdin and provide to stdout, pdftoppm writes
+    // to stdout when no output root is given
+    let mut cli_args = match args.backend {
+        RenderBackend::Cairo => vec!["-".to_string(), "-".to_string()],
+        RenderBackend::Splash => vec!["-".to_string()],
+    };
+
+    // Specify first and last pages
+    cli_args.extend([
+        "-singlefile".to_string(),
+        "-f".to_string(),
+        page.to_string(),
+        "-l".to_string(),
+        page.to_string(),
+    ]);
+
+    // Add optional args and output format
+    cli_args.extend(args.build_args());
+    format.push_arg(&mut cli_args);
+
+    ToolJob {
+        program: args.backend.program(),
+        args: cli_args,
+        options: args.run_options(),
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{render_page, PdfRenderError, RenderArgs};
+    use std::time::Duration;
+
+    use super::{
+        page_job, render_page_raw, verify_pages, AntialiasConfig, Crop, JpegOptions, OutputFormat,
+        PageColor, PdfRenderError, RenderArea, RenderArgs, RenderArgsError, RenderBackend,
+        RenderColor, Resolution, ScaleTo, SplashOptions, ThinLineMode, TiffCompression,
+    };
+    use crate::{
+        dpi::AutoDpi,
+        info::{parse_pdf_info, PageDimensions, PdfInfoError},
+        shared::{Password, ProcessPriority, Rect},
+    };
+
+    /// Tests the crop region is included in the arguments
+    #[test]
+    fn test_crop_args() {
+        let args = RenderArgs::default()
+            .set_render_area(RenderArea::CropBox)
+            .set_crop(Crop::new(10, 20, 300, 400));
+
+        assert_eq!(
+            args.build_args(),
+            ["-cropbox", "-x", "10", "-y", "20", "-W", "300", "-H", "400"]
+        );
+        assert!(RenderArgs::default().build_args().is_empty());
+    }
+
+    /// Tests converting crops to and from rectangles
+    #[test]
+    fn test_crop_rect() {
+        let crop = Crop::from_rect(Rect::new(10.4, 20.6, 99.2, 50.0));
+        assert_eq!(crop.rect(), Rect::new(10.0, 20.0, 100.0, 51.0));
+    }
+
+    /// Tests the jpeg options arguments and that they are only allowed
+    /// for jpeg output
+    #[test]
+    fn test_jpeg_options() {
+        let args = RenderArgs::default()
+            .set_jpeg_options(JpegOptions::default().set_quality(90).set_progressive(true));
+
+        assert_eq!(
+            args.build_args(),
+            ["-jpegopt", "quality=90,progressive=y,optimize=n"]
+        );
+        assert!(args.validate(OutputFormat::Jpeg).is_ok());
+        assert!(matches!(
+            args.validate(OutputFormat::Png),
+            Err(RenderArgsError::UnsupportedFormat { .. })
+        ));
+
+        let args = RenderArgs::default().set_jpeg_options(JpegOptions::default().set_quality(101));
+        assert_eq!(
+            args.validate(OutputFormat::Jpeg),
+            Err(RenderArgsError::JpegQuality(101))
+        );
+        assert!(RenderArgs::default().validate(OutputFormat::Png).is_ok());
+    }
+
+    /// Tests the AVIF options are only accepted for AVIF output
+    #[cfg(feature = "avif")]
+    #[test]
+    fn test_avif_options() {
+        use super::AvifOptions;
+
+        let args = RenderArgs::default().set_avif_options(AvifOptions::default().set_quality(60));
+        assert!(args.build_args().is_empty());
+        assert!(args.validate(OutputFormat::Avif).is_ok());
+        assert!(matches!(
+            args.validate(OutputFormat::Png),
+            Err(RenderArgsError::UnsupportedFormat { .. })
+        ));
+
+        let args = RenderArgs::default().set_avif_options(AvifOptions::default().set_speed(0));
+        assert_eq!(
+            args.validate(OutputFormat::Avif),
+            Err(RenderArgsError::AvifSpeed(0))
+        );
+
+        let mut cli_args = Vec::new();
+        OutputFormat::Avif.push_arg(&mut cli_args);
+        assert_eq!(cli_args, ["-png"]);
+        assert_eq!(OutputFormat::Avif.render_format(), OutputFormat::Png);
+    }
+
+    /// Tests options that conflict with each other or the format are rejected
+    #[test]
+    fn test_validate() {
+        let args = RenderArgs::default().set_page_color(PageColor::Transparent);
+        assert!(args.validate(OutputFormat::Png).is_ok());
+        assert!(args.validate(OutputFormat::Tiff).is_ok());
+        assert_eq!(
+            args.validate(OutputFormat::Jpeg),
+            Err(RenderArgsError::UnsupportedFormat {
+                option: "transparent page color",
+                format: "jpeg".to_string()
+            })
+        );
+
+        let args = RenderArgs::default().set_render_color(RenderColor::Monochrome);
+        assert!(args.validate(OutputFormat::Png).is_ok());
+        assert!(args.validate(OutputFormat::Jpeg).is_err());
+
+        let args = RenderArgs::default()
+            .set_resolution(Resolution::uniform(300))
+            .set_scale_to(ScaleTo::x(800));
+        assert_eq!(
+            args.validate(OutputFormat::Png),
+            Err(RenderArgsError::ResolutionWithScale)
+        );
+
+        let args = RenderArgs::default()
+            .set_scale_to(ScaleTo::new(800, 600))
+            .set_crop(Crop::new(400, 300, 400, 300));
+        assert!(args.validate(OutputFormat::Png).is_ok());
+
+        let args = args.set_crop(Crop::new(500, 300, 400, 300));
+        assert!(matches!(
+            args.validate(OutputFormat::Png),
+            Err(RenderArgsError::CropOutsideScale(_, 800, 600))
+        ));
+
+        // Unknown scaled height cannot be checked
+        let args = args.set_scale_to(ScaleTo::x(800));
+        assert!(args.validate(OutputFormat::Png).is_ok());
+    }
+
+    /// Tests the info args keep the password, timeout and priority
+    #[test]
+    fn test_info_args() {
+        let args = RenderArgs::default()
+            .set_password(Password::user("secret"))
+            .set_timeout(Duration::from_secs(5))
+            .set_priority(ProcessPriority::background());
+        let info_args = args.info_args();
+
+        assert!(info_args.password.is_some());
+        assert_eq!(info_args.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(info_args.priority, Some(ProcessPriority::background()));
+    }
+
+    /// Tests the splash backend runs pdftoppm with its options
+    #[test]
+    fn test_splash_backend() {
+        let options = SplashOptions::default()
+            .set_thin_line_mode(ThinLineMode::Solid)
+            .set_vector_antialias(false);
+        let args = RenderArgs::default()
+            .set_backend(RenderBackend::Splash)
+            .set_splash_options(options);
+        assert!(args.validate(OutputFormat::Png).is_ok());
+
+        let job = page_job(OutputFormat::Png, 2, &args);
+        assert_eq!(job.program, "pdftoppm");
+        assert_eq!(
+            job.args,
+            [
+                "-",
+                "-singlefile",
+                "-f",
+                "2",
+                "-l",
+                "2",
+                "-thinlinemode",
+                "solid",
+                "-aaVector",
+                "no",
+                "-png"
+            ]
+        );
+
+        let options = SplashOptions::default()
+            .set_antialias(AntialiasConfig::TEXT_ONLY)
+            .set_freetype(true);
+        let args = args.set_splash_options(options);
+        assert_eq!(
+            args.build_args(),
+            ["-aa", "yes", "-aaVector", "no", "-freetype", "yes"]
+        );
+
+        let job = page_job(OutputFormat::Png, 2, &RenderArgs::default());
+        assert_eq!(job.program, "pdftocairo");
+        assert_eq!(&job.args[..2], ["-", "-"]);
+
+        // Splash options require the splash backend
+        let cairo = args.clone().set_backend(RenderBackend::Cairo);
+        assert_eq!(
+            cairo.validate(OutputFormat::Png),
+            Err(RenderArgsError::SplashOptions)
+        );
+
+        let transparent = args.set_page_color(PageColor::Transparent);
+        assert_eq!(
+            transparent.validate(OutputFormat::Png),
+            Err(RenderArgsError::UnsupportedBackend {
+                option: "transparent page color",
+                backend: "pdftoppm"
+            })
+        );
+    }
+
+    /// Tests crops in PDF points account for the resolution and rotation
+    #[test]
+    fn test_crop_pdf_rect() {
+        let page = |rotation| PageDimensions {
+            width_pts: 612.0,
+            height_pts: 792.0,
+            rotation,
+        };
+
+        // One inch square one inch from the top left of the unrotated page
+        let rect = Rect::new(72.0, 648.0, 72.0, 72.0);
+        let resolution = Resolution::uniform(144);
+
+        let crop = Crop::from_pdf_rect(rect, &page(0), resolution);
+        assert_eq!(crop.rect(), Rect::new(144.0, 144.0, 144.0, 144.0));
+
+        // Top left of the unrotated page is the top right once rotated
+        let crop = Crop::from_pdf_rect(rect, &page(90), resolution);
+        assert_eq!(crop.rect(), Rect::new(1296.0, 144.0, 144.0, 144.0));
+
+        let crop = Crop::from_text_rect(rect, &page(0), Resolution::uniform(72));
+        assert_eq!(crop.rect(), rect);
+    }
+
+    /// Tests the tiff compression is only allowed for tiff output
+    #[test]
+    fn test_tiff_compression() {
+        let args = RenderArgs::default().set_tiff_compression(TiffCompression::Lzw);
+        assert_eq!(args.build_args(), ["-tiffcompression", "lzw"]);
+        assert!(args.validate(OutputFormat::Tiff).is_ok());
+        assert!(args.validate(OutputFormat::Png).is_err());
+    }
+
+    /// Tests the automatic resolution is resolved for each page
+    #[test]
+    fn test_auto_dpi_args() {
+        let page = PageDimensions {
+            width_pts: 720.0,
+            height_pts: 1440.0,
+            rotation: 0,
+        };
+
+        let args = RenderArgs::default().set_auto_dpi(AutoDpi::default());
+        assert!(args.is_relative());
+        assert!(args.validate(OutputFormat::Png).is_ok());
+        assert_eq!(
+            args.resolve(&page).build_args(),
+            ["-rx", "100", "-ry", "100"]
+        );
+
+        let args = args.set_resolution(Resolution::uniform(150));
+        assert_eq!(
+            args.validate(OutputFormat::Png),
+            Err(RenderArgsError::AutoDpiConflict)
+        );
+
+        let args = RenderArgs::default().set_auto_dpi(AutoDpi::default().set_min_ppi(700));
+        assert!(args.validate(OutputFormat::Png).is_err());
+    }
+
+    /// Tests relative crops and scales are resolved against the page size
+    #[test]
+    fn test_relative_args() {
+        let page = PageDimensions {
+            width_pts: 612.0,
+            height_pts: 792.0,
+            rotation: 90,
+        };
+
+        let args = RenderArgs::default()
+            .set_scale_to(ScaleTo::fraction(0.5))
+            .set_crop(Crop::fraction(0.5, 0.0, 0.5, 0.25));
+        assert!(args.is_relative());
+        assert!(args.validate(OutputFormat::Png).is_ok());
+
+        // Relative values are left out until resolved
+        assert!(args.build_args().is_empty());
+
+        let resolved = args.resolve(&page);
+        assert!(!resolved.is_relative());
+        assert_eq!(
+            resolved.build_args(),
+            [
+                "-scale-to-x",
+                "396",
+                "-scale-to-y",
+                "306",
+                "-x",
+                "198",
+                "-y",
+                "0",
+                "-W",
+                "198",
+                "-H",
+                "77"
+            ]
+        );
+
+        // Crops are resolved against the rendered size at the resolution
+        let args = RenderArgs::default()
+            .set_resolution(Resolution::uniform(144))
+            .set_crop(Crop::fraction(0.0, 0.0, 0.5, 0.5));
+        assert_eq!(
+            args.resolve(&page).crop.unwrap().rect(),
+            Rect::new(0.0, 0.0, 792.0, 612.0)
+        );
+
+        let args = RenderArgs::default().set_crop(Crop::fraction(0.5, 0.5, 0.75, 0.5));
+        assert!(args.validate(OutputFormat::Png).is_err());
+        let args = RenderArgs::default().set_scale_to(ScaleTo::fraction(0.0));
+        assert!(args.validate(OutputFormat::Png).is_err());
+    }
 
     /// Tests invalid files are handled
     #[tokio::test]
     async fn test_invalid_file() {
-        let value = &[b'A'];
+        let value = b"A";
         let args = RenderArgs::default();
-        let err = render_page(value, crate::image::OutputFormat::Jpeg, 1, &args)
+        let err = render_page_raw(value, None, crate::image::OutputFormat::Jpeg, 1, &args)
             .await
             .unwrap_err();
         assert!(matches!(err, PdfRenderError::NotPdfFile));
     }
+
+    /// Tests the resolution is calculated from the page size
+    #[test]
+    fn test_resolution_for_target() {
+        let info = parse_pdf_info(
+            "Pages: 2\nEncrypted: no\nPage    1 size: 612 x 792 pts (letter)\nPage    1 rot:  0\n\
+            Page    2 size: 595.276 x 841.89 pts (A4)\nPage    2 rot:  90\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Resolution::for_target_width(&info, 1, 1275).unwrap(),
+            Resolution::uniform(150)
+        );
+        assert_eq!(
+            Resolution::for_target_height(&info, 1, 792).unwrap(),
+            Resolution::uniform(72)
+        );
+
+        // Rotated pages use the displayed width
+        assert_eq!(
+            Resolution::for_target_width(&info, 2, 1754).unwrap(),
+            Resolution::uniform(150)
+        );
+
+        assert!(matches!(
+            Resolution::for_target_width(&info, 3, 1000),
+            Err(PdfInfoError::PageDimensionsUnavailable(3))
+        ));
+    }
+
+    /// Tests page bounds are only checked when verification is enabled
+    #[tokio::test]
+    async fn test_verify_pages() {
+        let info = parse_pdf_info("Pages: 2\nEncrypted: no\n").unwrap();
+
+        let args = RenderArgs::default();
+        let err = verify_pages(&[], &info, &args, &[3]).await.unwrap_err();
+        assert!(matches!(err, PdfRenderError::PageOutOfBounds(3, 2)));
+
+        let args = RenderArgs::default().set_verify(false);
+        verify_pages(&[], &info, &args, &[3]).await.unwrap();
+    }
+
+    /// Tests converting decoded images into typed pixel buffers
+    #[cfg(any(feature = "image", feature = "image-0-24"))]
+    #[test]
+    fn test_render_pixel() {
+        use super::RenderPixel;
+        use crate::image_crate::{DynamicImage, Luma, Rgba};
+
+        let image = DynamicImage::new_rgb8(4, 2);
+
+        let rgba = Rgba::<u8>::from_dynamic(image.clone());
+        assert_eq!(rgba.dimensions(), (4, 2));
+        assert_eq!(rgba.as_raw().len(), 4 * 2 * 4);
+
+        let gray = Luma::<u8>::from_dynamic(image);
+        assert_eq!(gray.as_raw().len(), 4 * 2);
+    }
+
+    /// Tests decoding the PNG output into raw RGBA pixels
+    #[cfg(any(feature = "image", feature = "image-0-24"))]
+    #[test]
+    fn test_decode_rgba() {
+        use super::decode_rgba;
+        use crate::image_crate::{ImageFormat, Rgba, RgbaImage};
+        use std::io::Cursor;
+
+        let mut image = RgbaImage::new(3, 2);
+        image.put_pixel(2, 1, Rgba([10, 20, 30, 0]));
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let pixels = decode_rgba(&png).unwrap();
+        assert_eq!((pixels.width, pixels.height), (3, 2));
+        assert_eq!(pixels.stride(), 12);
+        assert_eq!(pixels.pixels.len(), 3 * 2 * 4);
+        assert_eq!(pixels.pixel(2, 1), Some([10, 20, 30, 0]));
+        assert_eq!(pixels.pixel(3, 0), None);
+    }
 }
@@ -0,0 +1,366 @@
+//! Helpers for listing the images embedded in PDF files
+//!
+//! * [pdf_image_list] - Lists the embedded images using `pdfimages -list`
+
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    info::PermissionDenied,
+    shared::{
+        run_tool, ChildError, Password, ProcessPriority, RunOptions, Size, StderrSink, ToolError,
+        POINTS_PER_INCH,
+    },
+};
+
+/// Type of an embedded image
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum PdfImageKind {
+    /// Regular image
+    Image,
+    /// Image mask
+    Mask,
+    /// Soft mask (Alpha channel) for another image
+    SoftMask,
+    /// Stencil mask
+    Stencil,
+    /// Type that is not known
+    Unknown(String),
+}
+
+impl PdfImageKind {
+    fn parse(value: &str) -> Self {
+        match value {
+            "image" => Self::Image,
+            "mask" => Self::Mask,
+            "smask" => Self::SoftMask,
+            "stencil" => Self::Stencil,
+            value => Self::Unknown(value.to_string()),
+        }
+    }
+}
+
+/// Image embedded within a PDF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PdfImageEntry {
+    /// Page the image is drawn on
+    pub page: u32,
+    /// Number of the image within the file
+    pub num: u32,
+    /// Type of the image
+    pub kind: PdfImageKind,
+    /// Width of the image in pixels
+    pub width: u32,
+    /// Height of the image in pixels
+    pub height: u32,
+    /// Color space of the image (e.g. "rgb", "gray", "cmyk")
+    pub color: String,
+    /// Number of color components
+    pub components: u32,
+    /// Bits per color component
+    pub bits_per_component: u32,
+    /// Encoding of the image data (e.g. "jpeg", "image", "jbig2")
+    pub encoding: String,
+    /// Object number and generation of the image, [None] for inline images
+    pub object: Option<(u32, u32)>,
+    /// Horizontal resolution the image is drawn at in pixels per inch
+    pub x_ppi: Option<u32>,
+    /// Vertical resolution the image is drawn at in pixels per inch
+    pub y_ppi: Option<u32>,
+}
+
+impl PdfImageEntry {
+    /// Size the image is drawn at on the page in PDF points, [None] when
+    /// the resolution is unknown
+    pub fn drawn_size(&self) -> Option<Size> {
+        let x_ppi = self.x_ppi.filter(|value| *value > 0)?;
+        let y_ppi = self.y_ppi.filter(|value| *value > 0)?;
+
+        Some(Size::new(
+            self.width as f32 / x_ppi as f32 * POINTS_PER_INCH,
+            self.height as f32 / y_ppi as f32 * POINTS_PER_INCH,
+        ))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PdfImagesError {
+    #[error("failed to spawn pdfimages: {0}")]
+    SpawnProcess(std::io::Error),
+
+    #[error("failed to write pdf bytes: {0}")]
+    WritePdf(std::io::Error),
+
+    #[error("failed to get output: {0}")]
+    WaitOutput(std::io::Error),
+
+    #[error("output exceeded the maximum allowed size of {0} bytes")]
+    OutputLimitExceeded(usize),
+
+    #[error("pdfimages did not finish within {0:?}")]
+    Timeout(Duration),
+
+    #[error("pdfimages was aborted")]
+    Aborted,
+
+    #[error("failed to get pdfimages exit code: {0}")]
+    PdfImagesFailure(String),
+
+    #[error("pdfimages reported permission error: {0}")]
+    PermissionError(PermissionDenied),
+
+    #[error("pdf is encrypted and no password was provided")]
+    PdfEncrypted,
+
+    #[error("incorrect password was provided")]
+    IncorrectPassword,
+
+    #[error("file is not a pdf")]
+    NotPdfFile,
+
+    #[error("image list entry is malformed: {0}")]
+    MalformedEntry(String),
+}
+
+impl From<ChildError> for PdfImagesError {
+    fn from(value: ChildError) -> Self {
+        match value {
+            ChildError::WriteInput(err) => Self::WritePdf(err),
+            ChildError::ReadOutput(err) | ChildError::WriteOutput(err) => Self::WaitOutput(err),
+            ChildError::OutputLimitExceeded(limit) => Self::OutputLimitExceeded(limit),
+            ChildError::Timeout(timeout) => Self::Timeout(timeout),
+        }
+    }
+}
+
+impl From<ToolError> for PdfImagesError {
+    fn from(value: ToolError) -> Self {
+        match value {
+            ToolError::Spawn(err) => Self::SpawnProcess(err),
+            ToolError::Child(err) => err.into(),
+            ToolError::NotPdfFile => Self::NotPdfFile,
+            ToolError::PdfEncrypted => Self::PdfEncrypted,
+            ToolError::IncorrectPassword => Self::IncorrectPassword,
+            ToolError::PermissionError(err) => Self::PermissionError(err),
+            ToolError::PageOutOfBounds(page, page_count) => Self::PdfImagesFailure(format!(
+                "page {page} is outside the number of available pages {page_count}"
+            )),
+            ToolError::PageCountUnknown => {
+                Self::PdfImagesFailure("page count is missing or invalid".to_string())
+            }
+            ToolError::EmptyDocument => {
+                Self::PdfImagesFailure("pdf does not contain any pages".to_string())
+            }
+            ToolError::Failure(err) | ToolError::Task(err) => Self::PdfImagesFailure(err),
+            ToolError::Aborted => Self::Aborted,
+            ToolError::BudgetExceeded(err) => Self::PdfImagesFailure(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PdfImagesArgs {
+    /// Password for the PDF
+    pub password: Option<Password>,
+    /// First page to list images for
+    pub first_page: Option<u32>,
+    /// Last page to list images for
+    pub last_page: Option<u32>,
+    /// Optional maximum time listing the images may take
+    pub timeout: Option<Duration>,
+    /// Optional destination for the pdfimages stderr instead of keeping it in memory
+    pub stderr: Option<StderrSink>,
+    /// Optional lowered CPU and IO priority to run pdfimages at
+    pub priority: Option<ProcessPriority>,
+}
+
+impl PdfImagesArgs {
+    pub fn set_password(mut self, password: Password) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    pub fn set_first_page(mut self, first_page: u32) -> Self {
+        self.first_page = Some(first_page);
+        self
+    }
+
+    pub fn set_last_page(mut self, last_page: u32) -> Self {
+        self.last_page = Some(last_page);
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_stderr(mut self, stderr: StderrSink) -> Self {
+        self.stderr = Some(stderr);
+        self
+    }
+
+    pub fn set_priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Options for running pdfimages
+    pub(crate) fn run_options(&self) -> RunOptions {
+        RunOptions {
+            max_output_size: None,
+            timeout: self.timeout,
+            password: self.password.clone(),
+            stderr: self.stderr.clone(),
+            priority: self.priority,
+            budget: None,
+        }
+    }
+
+    /// Builds an argument list from all the options, the password is passed
+    /// to the tool separately so it is not included
+    pub fn build_args(&self) -> Vec<String> {
+        let mut out = Vec::new();
+
+        if let Some(first_page) = self.first_page {
+            out.push("-f".to_string());
+            out.push(first_page.to_string());
+        }
+
+        if let Some(last_page) = self.last_page {
+            out.push("-l".to_string());
+            out.push(last_page.to_string());
+        }
+
+        out
+    }
+}
+
+/// Lists the images embedded in the provided PDF file, images drawn on
+/// multiple pages are listed once for each page
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Extra args to provide to pdfimages
+pub async fn pdf_image_list(
+    data: &[u8],
+    args: &PdfImagesArgs,
+) -> Result<Vec<PdfImageEntry>, PdfImagesError> {
+    // List the images and take input from stdin
+    let mut cli_args = vec!["-list".to_string()];
+    cli_args.extend(args.build_args());
+    cli_args.push("-".to_string());
+
+    let output = run_tool("pdfimages", &cli_args, data, &args.run_options()).await?;
+
+    let value = String::from_utf8_lossy(&output.stdout);
+    parse_image_list(&value)
+}
+
+/// Parses the table output of `pdfimages -list`
+fn parse_image_list(value: &str) -> Result<Vec<PdfImageEntry>, PdfImagesError> {
+    value
+        .lines()
+        // Skip the header and the separator line
+        .skip(2)
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_image_entry)
+        .collect()
+}
+
+/// Parses a single row of the image list in the format:
+/// `page num type width height color comp bpc enc interp object ID x-ppi y-ppi size ratio`
+fn parse_image_entry(line: &str) -> Result<PdfImageEntry, PdfImagesError> {
+    let malformed = || PdfImagesError::MalformedEntry(line.to_string());
+
+    let columns: Vec<&str> = line.split_whitespace().collect();
+    if columns.len() < 10 {
+        return Err(malformed());
+    }
+
+    let number = |index: usize| -> Result<u32, PdfImagesError> {
+        columns[index].parse().map_err(|_| malformed())
+    };
+
+    // Inline images have "[inline]" in place of the object number and generation
+    let object = match (columns.get(10), columns.get(11)) {
+        (Some(number), Some(generation)) => number.parse().ok().zip(generation.parse().ok()),
+        _ => None,
+    };
+
+    // Resolution is read from the end as the object column width varies,
+    // followed by the size and ratio columns
+    let ppi = |offset: usize| -> Option<u32> {
+        if columns.len() < 14 {
+            return None;
+        }
+        columns[columns.len() - offset].parse().ok()
+    };
+
+    Ok(PdfImageEntry {
+        page: number(0)?,
+        num: number(1)?,
+        kind: PdfImageKind::parse(columns[2]),
+        width: number(3)?,
+        height: number(4)?,
+        color: columns[5].to_string(),
+        components: number(6)?,
+        bits_per_component: number(7)?,
+        encoding: columns[8].to_string(),
+        object,
+        x_ppi: ppi(4),
+        y_ppi: ppi(3),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_image_list, PdfImageKind};
+    use crate::shared::Size;
+
+    /// Tests parsing the image list output
+    #[test]
+    fn test_parse_image_list() {
+        let output = "\
+page   num  type   width height color comp bpc  enc interp  object ID x-ppi y-ppi size ratio
+--------------------------------------------------------------------------------------------
+   1     0 image     640   480  rgb     3   8  jpeg   no        12  0    96    96 42.1K 4.7%
+   1     1 smask     640   480  gray    1   8  image  no        13  0    96    96 1.2K 0.4%
+   3     2 image      16    16  gray    1   1  ccitt  no   [inline]     72    72   32B 12%
+";
+
+        let entries = parse_image_list(output).unwrap();
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].page, 1);
+        assert_eq!(entries[0].kind, PdfImageKind::Image);
+        assert_eq!((entries[0].width, entries[0].height), (640, 480));
+        assert_eq!(entries[0].encoding, "jpeg");
+        assert_eq!(entries[0].object, Some((12, 0)));
+        assert_eq!((entries[0].x_ppi, entries[0].y_ppi), (Some(96), Some(96)));
+        assert_eq!(entries[0].drawn_size(), Some(Size::new(480.0, 360.0)));
+
+        assert_eq!(entries[1].kind, PdfImageKind::SoftMask);
+        assert_eq!(entries[1].components, 1);
+
+        assert_eq!(entries[2].page, 3);
+        assert_eq!(entries[2].bits_per_component, 1);
+        assert_eq!(entries[2].object, None);
+        assert_eq!(entries[2].x_ppi, Some(72));
+    }
+
+    /// Tests an empty list and malformed rows
+    #[test]
+    fn test_parse_image_list_malformed() {
+        let header = "page   num  type\n-----------------\n";
+        assert!(parse_image_list(header).unwrap().is_empty());
+
+        let output = format!("{header}   1     0 image  wide\n");
+        assert!(parse_image_list(&output).is_err());
+    }
+}
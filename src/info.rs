@@ -1,21 +1,78 @@
 //! Helpers getting info about PDF files
 //!  
 //! * [pdf_info] - Get info from a PDF file
+//! * [page_dimensions] - Get the dimensions of a specific page
+//! * [pdf_encryption_state] - Check whether a PDF file is encrypted and if a password is required
+//! * [pdf_is_encrypted] - Check whether a PDF file is encrypted
+//! * [classify_password] - Check whether a password is the user or owner password
+//! * [pdf_page_count] - Get the page count, without spawning pdfinfo for well-formed files
 
-use std::{collections::HashMap, num::ParseIntError, process::Stdio};
+use std::{collections::HashMap, num::ParseIntError, time::Duration};
 
+use bitflags::bitflags;
+use futures_util::future::try_join;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
 use thiserror::Error;
-use tokio::{io::AsyncWriteExt, process::Command};
 
-use crate::shared::Password;
+use crate::{
+    budget::Budget,
+    probe::quick_page_count,
+    shared::{
+        run_tool, ChildError, Password, Point, ProcessPriority, Rect, RunOptions, Size, StderrSink,
+        ToolError, POINTS_PER_INCH,
+    },
+};
+
+/// Algorithm used to encrypt a PDF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionAlgorithm {
+    /// RC4, pdfinfo does not report whether a 40 or 128 bit key is used
+    Rc4,
+    /// AES with a 128 bit key
+    Aes128,
+    /// AES with a 256 bit key
+    Aes256,
+    /// Algorithm that is not known
+    Unknown(String),
+}
+
+impl EncryptionAlgorithm {
+    fn parse(value: &str) -> Self {
+        match value {
+            "RC4" => Self::Rc4,
+            "AES" => Self::Aes128,
+            "AES-256" => Self::Aes256,
+            value => Self::Unknown(value.to_string()),
+        }
+    }
+
+    /// Length of the encryption key in bits when known
+    pub fn key_length(&self) -> Option<u32> {
+        match self {
+            Self::Aes128 => Some(128),
+            Self::Aes256 => Some(256),
+            Self::Rc4 | Self::Unknown(_) => None,
+        }
+    }
+}
 
 /// Pdf file may be "encrypted" but still readable
 #[derive(Debug)]
 pub struct PdfInfoEncryption {
     /// Whether encryption is enabled
     encrypted: bool,
-    /// Encryption options that are set
-    options: HashMap<String, String>,
+    /// Whether printing is allowed
+    print: bool,
+    /// Whether copying text and graphics is allowed
+    copy: bool,
+    /// Whether changing the document is allowed
+    change: bool,
+    /// Whether adding and changing annotations is allowed
+    add_notes: bool,
+    /// Raw encryption algorithm name
+    algorithm: Option<String>,
 }
 
 impl PdfInfoEncryption {
@@ -24,50 +81,286 @@ impl PdfInfoEncryption {
     }
 
     pub fn is_print_allowed(&self) -> bool {
-        let value = match self.options.get("print") {
-            Some(value) => value,
-            None => return true,
-        };
+        self.print
+    }
 
-        value == "yes"
+    /// pdfinfo does not report high resolution printing separately, [None]
+    /// when the file is encrypted and printing is allowed as high resolution
+    /// printing may still be denied
+    pub fn is_high_res_print_allowed(&self) -> Option<bool> {
+        if !self.encrypted {
+            return Some(true);
+        }
+
+        // High resolution printing is never allowed without printing
+        (!self.print).then_some(false)
     }
 
     pub fn is_copy_allowed(&self) -> bool {
-        let value = match self.options.get("copy") {
-            Some(value) => value,
-            None => return true,
-        };
+        self.copy
+    }
 
-        value == "yes"
+    /// pdfinfo does not report extraction for accessibility separately,
+    /// [None] when the file is encrypted
+    pub fn is_accessibility_allowed(&self) -> Option<bool> {
+        self.unreported_permission()
     }
 
     pub fn is_change_allowed(&self) -> bool {
-        let value = match self.options.get("change") {
-            Some(value) => value,
-            None => return true,
-        };
+        self.change
+    }
 
-        value == "yes"
+    /// pdfinfo does not report document assembly separately,
+    /// [None] when the file is encrypted
+    pub fn is_assemble_allowed(&self) -> Option<bool> {
+        self.unreported_permission()
     }
 
     pub fn is_add_notes_allowed(&self) -> bool {
-        let value = match self.options.get("addNotes") {
-            Some(value) => value,
-            None => return true,
-        };
+        self.add_notes
+    }
+
+    /// pdfinfo does not report form filling separately,
+    /// [None] when the file is encrypted
+    pub fn is_fill_forms_allowed(&self) -> Option<bool> {
+        self.unreported_permission()
+    }
+
+    /// Permissions pdfinfo does not report are only known to be
+    /// allowed when the file is not encrypted
+    fn unreported_permission(&self) -> Option<bool> {
+        (!self.encrypted).then_some(true)
+    }
+
+    pub fn algorithm(&self) -> Option<EncryptionAlgorithm> {
+        self.algorithm.as_deref().map(EncryptionAlgorithm::parse)
+    }
+
+    pub fn algorithm_raw(&self) -> Option<&str> {
+        self.algorithm.as_deref()
+    }
 
-        value == "yes"
+    /// Length of the encryption key in bits when known
+    pub fn key_length(&self) -> Option<u32> {
+        self.algorithm()
+            .and_then(|algorithm| algorithm.key_length())
     }
-    pub fn algorithm(&self) -> Option<&str> {
-        self.options.get("algorithm").map(|value| value.as_str())
+
+    /// Checks whether the provided permission is granted
+    pub fn is_allowed(&self, permission: PdfPermission) -> bool {
+        match permission {
+            PdfPermission::Print => self.print,
+            PdfPermission::Copy => self.copy,
+            PdfPermission::Change => self.change,
+            PdfPermission::AddNotes => self.add_notes,
+        }
+    }
+
+    /// Permissions that are not granted by the file
+    pub fn denied_permissions(&self) -> Vec<PdfPermission> {
+        PdfPermission::ALL
+            .into_iter()
+            .filter(|permission| !self.is_allowed(*permission))
+            .collect()
+    }
+
+    /// Permissions granted by the file as flags, the flags for permissions
+    /// pdfinfo does not report are only set when the file is not encrypted
+    pub fn permissions(&self) -> Permissions {
+        if !self.encrypted {
+            return Permissions::all();
+        }
+
+        PdfPermission::ALL
+            .into_iter()
+            .filter(|permission| self.is_allowed(*permission))
+            .map(Permissions::from)
+            .collect()
+    }
+}
+
+bitflags! {
+    /// Permissions granted by the PDF encryption, see [PdfInfoEncryption::permissions].
+    ///
+    /// pdfinfo only reports the print, copy, change and add notes permissions,
+    /// for encrypted files the high resolution printing, form filling,
+    /// extraction for accessibility and assembly flags are never set as
+    /// they are not known
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize))]
+    pub struct Permissions: u16 {
+        /// Printing the document
+        const PRINT = 1;
+        /// Printing the document at full resolution
+        const HIGH_RES_PRINT = 1 << 1;
+        /// Copying text and graphics from the document
+        const COPY = 1 << 2;
+        /// Changing the document
+        const MODIFY = 1 << 3;
+        /// Adding and changing annotations
+        const ANNOTATE = 1 << 4;
+        /// Filling in form fields
+        const FILL_FORMS = 1 << 5;
+        /// Extracting text and graphics for accessibility
+        const EXTRACT_FOR_ACCESSIBILITY = 1 << 6;
+        /// Inserting, rotating and deleting pages
+        const ASSEMBLE = 1 << 7;
+    }
+}
+
+impl From<PdfPermission> for Permissions {
+    /// Flag for the permission reported by pdfinfo
+    fn from(value: PdfPermission) -> Self {
+        match value {
+            PdfPermission::Print => Self::PRINT,
+            PdfPermission::Copy => Self::COPY,
+            PdfPermission::Change => Self::MODIFY,
+            PdfPermission::AddNotes => Self::ANNOTATE,
+        }
+    }
+}
+
+/// Permission reported by pdfinfo that can be restricted by the
+/// PDF encryption, opening the file with the owner password
+/// bypasses all of these restrictions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum PdfPermission {
+    /// Printing the document
+    Print,
+    /// Copying text and graphics from the document
+    Copy,
+    /// Changing the document
+    Change,
+    /// Adding and changing annotations
+    AddNotes,
+}
+
+impl PdfPermission {
+    /// All the permissions in the order pdfinfo reports them
+    pub const ALL: [PdfPermission; 4] = [Self::Print, Self::Copy, Self::Change, Self::AddNotes];
+
+    /// Finds the permission mentioned by a poppler permission error message
+    /// e.g "Permission Error: Copying of text from this document is not allowed."
+    fn from_message(message: &str) -> Option<Self> {
+        let message = message.to_ascii_lowercase();
+
+        if message.contains("copying") {
+            Some(Self::Copy)
+        } else if message.contains("printing") {
+            Some(Self::Print)
+        } else if message.contains("changing") || message.contains("modif") {
+            Some(Self::Change)
+        } else if message.contains("annotat") || message.contains("notes") {
+            Some(Self::AddNotes)
+        } else {
+            None
+        }
+    }
+
+    /// Permission the poppler tool checks before running
+    fn for_tool(program: &str) -> Option<Self> {
+        match program {
+            "pdftotext" | "pdfimages" => Some(Self::Copy),
+            "pdftops" => Some(Self::Print),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PdfPermission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Print => "print",
+            Self::Copy => "copy",
+            Self::Change => "change",
+            Self::AddNotes => "addNotes",
+        })
+    }
+}
+
+/// Details of a permission error reported by a poppler tool (Exit code 3).
+///
+/// Permission restrictions only apply when the file is opened with the
+/// user password (or no password), providing the owner password instead
+/// bypasses them so callers can prompt for the owner password
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionDenied {
+    /// Permission that blocked the operation when known
+    permission: Option<PdfPermission>,
+    /// Message reported by the tool
+    message: String,
+}
+
+impl PermissionDenied {
+    /// Creates the details from the stderr output of the tool, the
+    /// permission is taken from the message or the tool that was run
+    pub(crate) fn from_output(program: &str, message: &str) -> Self {
+        let message = message.trim();
+        let permission =
+            PdfPermission::from_message(message).or_else(|| PdfPermission::for_tool(program));
+
+        Self {
+            permission,
+            message: message.to_string(),
+        }
+    }
+
+    /// Narrows the blocked permission using the permissions reported by
+    /// pdfinfo, when the permission is unknown and only a single permission
+    /// is denied that permission is assumed to be the cause
+    pub fn with_encryption(mut self, encryption: &PdfInfoEncryption) -> Self {
+        if self.permission.is_none() {
+            if let [permission] = encryption.denied_permissions().as_slice() {
+                self.permission = Some(*permission);
+            }
+        }
+
+        self
+    }
+
+    /// Permission that blocked the operation
+    pub fn permission(&self) -> Option<PdfPermission> {
+        self.permission
+    }
+
+    /// Message reported by the tool
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Whether providing the owner password would bypass the restriction,
+    /// poppler skips all permission checks when the owner password is used
+    pub fn owner_password_bypasses(&self) -> bool {
+        true
+    }
+}
+
+impl std::fmt::Display for PermissionDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.permission {
+            Some(permission) => write!(f, "{permission} permission is not granted")?,
+            None => f.write_str("operation is not permitted")?,
+        }
+
+        if !self.message.is_empty() {
+            write!(f, " ({})", self.message)?;
+        }
+
+        f.write_str(", providing the owner password bypasses this restriction")
     }
 }
 
 /// Parses the fields from the pdfinfo response
+///
+/// yes (print:yes copy:no change:no addNotes:no algorithm:AES-256)
 fn parse_pdf_info_encryption(output: &str) -> Result<PdfInfoEncryption, PdfInfoError> {
-    let (encrypted, options) = output
-        .split_once(' ')
-        .ok_or(PdfInfoError::MalformedEncryptionOptions)?;
+    let (encrypted, options) = match output.split_once(' ') {
+        Some(value) => value,
+        // Unencrypted files have no options
+        None if output == "no" => (output, "()"),
+        None => return Err(PdfInfoError::MalformedEncryptionOptions),
+    };
     let encrypted = parse_bool(encrypted);
 
     // Strip the braces
@@ -78,25 +371,489 @@ fn parse_pdf_info_encryption(output: &str) -> Result<PdfInfoEncryption, PdfInfoE
         .strip_suffix(')')
         .ok_or(PdfInfoError::MalformedEncryptionOptions)?;
 
-    let parts = options.split_whitespace();
-
-    let options = parts
+    let options: HashMap<&str, &str> = options
+        .split_whitespace()
         .filter_map(|value| {
             let (key, value) = value.split_once(':')?;
-            let value = value.trim_start();
-            Some((key.to_string(), value.to_string()))
+            Some((key, value.trim_start()))
         })
         .collect();
 
-    // yes (print:yes copy:no change:no addNotes:no algorithm:AES-256)
+    // Permissions that are not specified are allowed
+    let permission = |key: &str| options.get(key).is_none_or(|value| parse_bool(value));
+
+    Ok(PdfInfoEncryption {
+        encrypted,
+        print: permission("print"),
+        copy: permission("copy"),
+        change: permission("change"),
+        add_notes: permission("addNotes"),
+        algorithm: options.get("algorithm").map(|value| value.to_string()),
+    })
+}
 
-    Ok(PdfInfoEncryption { encrypted, options })
+/// Dimensions of a page without rendering it
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PageDimensions {
+    /// Width of the page in PDF points (1/72 inch)
+    pub width_pts: f32,
+    /// Height of the page in PDF points (1/72 inch)
+    pub height_pts: f32,
+    /// Clockwise rotation of the page in degrees
+    pub rotation: u32,
 }
 
-#[derive(Debug)]
+impl PageDimensions {
+    /// Gets the transform between the coordinate spaces of the page
+    /// when rendered at the provided resolution
+    pub fn transform(&self, ppi: f32) -> PageTransform {
+        PageTransform::new(self, ppi)
+    }
+
+    /// Gets the size of the page in PDF points before rotation
+    pub fn size(&self) -> Size {
+        Size::new(self.width_pts, self.height_pts)
+    }
+
+    /// Gets the size of the page in PDF points as displayed, see
+    /// [PageDimensions::rotated_size]
+    pub fn display_size(&self) -> Size {
+        let (width, height) = self.rotated_size();
+        Size::new(width, height)
+    }
+
+    /// Gets the width and height of the page as displayed, swapping
+    /// the width and height for pages rotated by 90 or 270 degrees
+    pub fn rotated_size(&self) -> (f32, f32) {
+        if self.rotation % 180 == 90 {
+            (self.height_pts, self.width_pts)
+        } else {
+            (self.width_pts, self.height_pts)
+        }
+    }
+
+    /// Gets the orientation of the page as displayed, taking the
+    /// page rotation into account
+    pub fn orientation(&self) -> PageOrientation {
+        let (width, height) = self.rotated_size();
+
+        if width > height {
+            PageOrientation::Landscape
+        } else if height > width {
+            PageOrientation::Portrait
+        } else {
+            PageOrientation::Square
+        }
+    }
+}
+
+/// Converts coordinates between the spaces used for a page:
+/// * PDF user space - PDF points with the origin at the bottom left of
+///   the unrotated page and y increasing upwards
+/// * Text space - PDF points with the origin at the top left of the page
+///   as displayed (after rotation), as used by `pdftotext -bbox`
+///   and [BoundingBox](crate::BoundingBox)
+/// * Pixel space - Pixels of the rendered page with the origin at the top
+///   left of the page as displayed
+///
+/// The media box is assumed to start at the origin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageTransform {
+    /// Width of the unrotated page in PDF points
+    width: f32,
+    /// Height of the unrotated page in PDF points
+    height: f32,
+    /// Clockwise rotation of the page, one of 0, 90, 180 or 270
+    rotation: u32,
+    /// Pixels per PDF point on the x and y axis of the rendered page
+    scale: (f32, f32),
+}
+
+impl PageTransform {
+    /// Creates a transform for a page rendered at the provided resolution
+    ///
+    /// ## Arguments
+    /// * dimensions - The dimensions of the page
+    /// * ppi - The resolution the page was rendered at in pixels per inch
+    pub fn new(dimensions: &PageDimensions, ppi: f32) -> Self {
+        let scale = ppi / POINTS_PER_INCH;
+        Self::with_scale(dimensions, (scale, scale))
+    }
+
+    /// Creates a transform for a page rendered to the provided pixel
+    /// size, for renders scaled to a size rather than a resolution
+    ///
+    /// ## Arguments
+    /// * dimensions - The dimensions of the page
+    /// * pixels - The size of the rendered page in pixels
+    pub fn for_pixel_size(dimensions: &PageDimensions, pixels: Size) -> Self {
+        let display = dimensions.display_size();
+        Self::with_scale(
+            dimensions,
+            (pixels.width / display.width, pixels.height / display.height),
+        )
+    }
+
+    fn with_scale(dimensions: &PageDimensions, scale: (f32, f32)) -> Self {
+        Self {
+            width: dimensions.width_pts,
+            height: dimensions.height_pts,
+            rotation: dimensions.rotation % 360,
+            scale,
+        }
+    }
+
+    /// Converts a point in PDF user space to text space
+    pub fn pdf_to_text(&self, point: Point) -> Point {
+        let (width, height) = (self.width, self.height);
+        let Point { x, y } = point;
+
+        match self.rotation {
+            90 => Point::new(y, x),
+            180 => Point::new(width - x, y),
+            270 => Point::new(height - y, width - x),
+            _ => Point::new(x, height - y),
+        }
+    }
+
+    /// Converts a point in text space to PDF user space
+    pub fn text_to_pdf(&self, point: Point) -> Point {
+        let (width, height) = (self.width, self.height);
+        let Point { x, y } = point;
+
+        match self.rotation {
+            90 => Point::new(y, x),
+            180 => Point::new(width - x, y),
+            270 => Point::new(width - y, height - x),
+            _ => Point::new(x, height - y),
+        }
+    }
+
+    /// Converts a point in text space to pixel space
+    pub fn text_to_pixels(&self, point: Point) -> Point {
+        Point::new(point.x * self.scale.0, point.y * self.scale.1)
+    }
+
+    /// Converts a point in pixel space to text space
+    pub fn pixels_to_text(&self, point: Point) -> Point {
+        Point::new(point.x / self.scale.0, point.y / self.scale.1)
+    }
+
+    /// Converts a point in PDF user space to pixel space
+    pub fn pdf_to_pixels(&self, point: Point) -> Point {
+        self.text_to_pixels(self.pdf_to_text(point))
+    }
+
+    /// Converts a point in pixel space to PDF user space
+    pub fn pixels_to_pdf(&self, point: Point) -> Point {
+        self.text_to_pdf(self.pixels_to_text(point))
+    }
+
+    /// Converts a rectangle in PDF user space to text space
+    pub fn pdf_to_text_rect(&self, rect: Rect) -> Rect {
+        Self::map_rect(rect, |point| self.pdf_to_text(point))
+    }
+
+    /// Converts a rectangle in text space to PDF user space
+    pub fn text_to_pdf_rect(&self, rect: Rect) -> Rect {
+        Self::map_rect(rect, |point| self.text_to_pdf(point))
+    }
+
+    /// Converts a rectangle in text space to pixel space
+    pub fn text_to_pixels_rect(&self, rect: Rect) -> Rect {
+        Self::map_rect(rect, |point| self.text_to_pixels(point))
+    }
+
+    /// Converts a rectangle in pixel space to text space
+    pub fn pixels_to_text_rect(&self, rect: Rect) -> Rect {
+        Self::map_rect(rect, |point| self.pixels_to_text(point))
+    }
+
+    /// Converts a rectangle in PDF user space to pixel space
+    pub fn pdf_to_pixels_rect(&self, rect: Rect) -> Rect {
+        Self::map_rect(rect, |point| self.pdf_to_pixels(point))
+    }
+
+    /// Converts a rectangle in pixel space to PDF user space
+    pub fn pixels_to_pdf_rect(&self, rect: Rect) -> Rect {
+        Self::map_rect(rect, |point| self.pixels_to_pdf(point))
+    }
+
+    /// Maps the corners of the rectangle, the corners may swap
+    /// so the result is rebuilt from the mapped corners
+    fn map_rect(rect: Rect, map: impl Fn(Point) -> Point) -> Rect {
+        Rect::from_corners(map(rect.min()), map(rect.max()))
+    }
+}
+
+/// Orientation of a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageOrientation {
+    /// Page is taller than it is wide
+    Portrait,
+    /// Page is wider than it is tall
+    Landscape,
+    /// Page width and height are equal
+    Square,
+}
+
+/// Date from the PDF metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PdfDate {
+    pub year: u16,
+    /// Month from 1 to 12
+    pub month: u8,
+    /// Day of the month from 1 to 31
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Offset from UTC in minutes, [None] when the timezone is unknown
+    pub utc_offset_minutes: Option<i16>,
+}
+
+impl PdfDate {
+    /// Parses a date in the raw PDF date format "D:YYYYMMDDHHmmSSOHH'mm'"
+    /// as provided by pdfinfo when using [PdfInfoArgs::set_raw_dates], all
+    /// parts after the year are optional
+    pub fn parse(value: &str) -> Result<Self, PdfInfoError> {
+        let value = value.trim();
+        let value = value.strip_prefix("D:").unwrap_or(value);
+
+        // Splits the timezone from the date and time digits
+        let (digits, timezone) = value
+            .find(['Z', '+', '-'])
+            .map_or((value, ""), |index| value.split_at(index));
+
+        if digits.len() < 4 || !digits.bytes().all(|value| value.is_ascii_digit()) {
+            return Err(PdfInfoError::MalformedDate(value.to_string()));
+        }
+
+        // Parses the optional 2 digit field at the provided offset
+        let field = |offset: usize, default: u8, max: u8| -> Result<u8, PdfInfoError> {
+            let value = match digits.get(offset..offset + 2) {
+                Some(value) => value.parse::<u8>().unwrap_or(u8::MAX),
+                None => return Ok(default),
+            };
+
+            if value > max {
+                return Err(PdfInfoError::MalformedDate(digits.to_string()));
+            }
+
+            Ok(value)
+        };
+
+        let year = digits[..4]
+            .parse::<u16>()
+            .map_err(|_| PdfInfoError::MalformedDate(value.to_string()))?;
+        let month = field(4, 1, 12)?.max(1);
+        let day = field(6, 1, 31)?.max(1);
+        let hour = field(8, 0, 23)?;
+        let minute = field(10, 0, 59)?;
+        let second = field(12, 0, 59)?;
+
+        let utc_offset_minutes = parse_utc_offset(timezone)
+            .ok_or_else(|| PdfInfoError::MalformedDate(value.to_string()))?;
+
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            utc_offset_minutes,
+        })
+    }
+}
+
+/// Parses the timezone portion of a PDF date ("Z", "+HH'mm'", "-HH'mm")
+/// into an offset in minutes, an empty timezone is unknown
+fn parse_utc_offset(value: &str) -> Option<Option<i16>> {
+    let (sign, value) = match value.chars().next() {
+        None => return Some(None),
+        Some('Z') => return Some(Some(0)),
+        Some('+') => (1, &value[1..]),
+        Some('-') => (-1, &value[1..]),
+        Some(_) => return None,
+    };
+
+    let mut parts = value.split('\'').filter(|value| !value.is_empty());
+    let hours = parts
+        .next()
+        .map_or(Some(0), |value| value.parse::<i16>().ok())?;
+    let minutes = parts
+        .next()
+        .map_or(Some(0), |value| value.parse::<i16>().ok())?;
+
+    // Out of range offsets from malformed metadata
+    if !(0..=23).contains(&hours) || !(0..=59).contains(&minutes) {
+        return None;
+    }
+
+    Some(Some(sign * (hours * 60 + minutes)))
+}
+
+/// Summary of the most commonly used PDF info fields
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PdfSummary {
+    pub pages: Option<u32>,
+    pub title: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub author: Option<String>,
+    pub creator: Option<String>,
+    pub producer: Option<String>,
+    /// Only available when using [PdfInfoArgs::set_raw_dates]
+    pub creation_date: Option<PdfDate>,
+    /// Only available when using [PdfInfoArgs::set_raw_dates]
+    pub mod_date: Option<PdfDate>,
+    pub encrypted: Option<bool>,
+    /// Dimensions of the first page
+    pub page_size: Option<PageDimensions>,
+    pub pdf_version: Option<String>,
+}
+
+/// Field reported by pdfinfo, see [PdfInfo::diff]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum InfoField {
+    Title,
+    Subject,
+    Keywords,
+    Author,
+    Creator,
+    Producer,
+    CreationDate,
+    ModDate,
+    Pages,
+    Encrypted,
+    PdfVersion,
+    /// Size of the first page
+    PageSize,
+    /// Rotation of the first page
+    PageRotation,
+    FileSize,
+    Form,
+    Tagged,
+    JavaScript,
+    Optimized,
+    CustomMetadata,
+    MetadataStream,
+    UserProperties,
+    Suspects,
+    /// Any other field using the name reported by pdfinfo, includes the
+    /// per page details (e.g. "Page 2 size")
+    Other(String),
+}
+
+impl InfoField {
+    /// Gets the field for a pdfinfo key
+    fn from_key(key: &str) -> Self {
+        match key {
+            "Title" => Self::Title,
+            "Subject" => Self::Subject,
+            "Keywords" => Self::Keywords,
+            "Author" => Self::Author,
+            "Creator" => Self::Creator,
+            "Producer" => Self::Producer,
+            "CreationDate" => Self::CreationDate,
+            "ModDate" => Self::ModDate,
+            "Pages" => Self::Pages,
+            "Encrypted" => Self::Encrypted,
+            "PDF version" => Self::PdfVersion,
+            "Page size" => Self::PageSize,
+            "Page rot" => Self::PageRotation,
+            "File size" => Self::FileSize,
+            "Form" => Self::Form,
+            "Tagged" => Self::Tagged,
+            "JavaScript" => Self::JavaScript,
+            "Optimized" => Self::Optimized,
+            "Custom Metadata" => Self::CustomMetadata,
+            "Metadata Stream" => Self::MetadataStream,
+            "UserProperties" => Self::UserProperties,
+            "Suspects" => Self::Suspects,
+            key => Self::Other(key.to_string()),
+        }
+    }
+}
+
+/// Change to a single field between two versions of a PDF
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct InfoFieldChange {
+    /// The field that changed
+    pub field: InfoField,
+    /// Raw value in the previous version, [None] when the field was added
+    pub old: Option<String>,
+    /// Raw value in the new version, [None] when the field was removed
+    pub new: Option<String>,
+}
+
+impl InfoFieldChange {
+    /// Whether the field is only present in the new version
+    pub fn is_added(&self) -> bool {
+        self.old.is_none()
+    }
+
+    /// Whether the field is only present in the previous version
+    pub fn is_removed(&self) -> bool {
+        self.new.is_none()
+    }
+}
+
+/// Parses a page size in the pdfinfo format "612 x 792 pts (letter)"
+fn parse_page_size(value: &str) -> Result<(f32, f32), PdfInfoError> {
+    let mut parts = value.split_whitespace();
+
+    let width = parts
+        .next()
+        .and_then(|value| value.parse::<f32>().ok())
+        .ok_or(PdfInfoError::MalformedPageSize)?;
+
+    if parts.next() != Some("x") {
+        return Err(PdfInfoError::MalformedPageSize);
+    }
+
+    let height = parts
+        .next()
+        .and_then(|value| value.parse::<f32>().ok())
+        .ok_or(PdfInfoError::MalformedPageSize)?;
+
+    Ok((width, height))
+}
+
+#[derive(Debug, Clone)]
 pub struct PdfInfo {
     /// Data parsed from the pdfinfo cli
     data: HashMap<String, String>,
+    /// Whether the dates were requested in the raw PDF format
+    raw_dates: bool,
+}
+
+/// Fields that pdfinfo reports as "yes" or "no"
+const BOOL_FIELDS: &[&str] = &[
+    "Custom Metadata",
+    "Metadata Stream",
+    "Tagged",
+    "UserProperties",
+    "Suspects",
+    "JavaScript",
+    "Optimized",
+];
+
+/// Info field with a value that could not be parsed
+#[derive(Debug)]
+pub struct InfoDiagnostic {
+    /// Name of the field
+    pub field: String,
+    /// Raw value of the field
+    pub raw: String,
+    /// Error that occurred while parsing the value
+    pub error: PdfInfoError,
 }
 
 impl PdfInfo {
@@ -200,6 +957,161 @@ impl PdfInfo {
     pub fn pdf_version(&self) -> Option<&str> {
         self.data("PDF version")
     }
+
+    /// Obtains the dimensions of a specific page.
+    ///
+    /// Per page details are only available for pages within the range requested
+    /// using [PdfInfoArgs::set_first_page] and [PdfInfoArgs::set_last_page], without
+    /// them only the first page is available
+    pub fn page_dimensions(&self, page: u32) -> Option<Result<PageDimensions, PdfInfoError>> {
+        let (size, rotation) = match (
+            self.data(&format!("Page {page} size")),
+            self.data(&format!("Page {page} rot")),
+        ) {
+            (Some(size), rotation) => (size, rotation),
+            // The document page size and rotation are from the first page
+            (None, _) if page == 1 => (self.page_size()?, self.page_rot()),
+            _ => return None,
+        };
+
+        Some(parse_page_dimensions(size, rotation))
+    }
+
+    /// Parses the creation date, requires the dates to be requested
+    /// using [PdfInfoArgs::set_raw_dates]
+    pub fn creation_date_parsed(&self) -> Option<Result<PdfDate, PdfInfoError>> {
+        self.creation_date().map(PdfDate::parse)
+    }
+
+    /// Parses the modification date, requires the dates to be requested
+    /// using [PdfInfoArgs::set_raw_dates]
+    pub fn mod_date_parsed(&self) -> Option<Result<PdfDate, PdfInfoError>> {
+        self.mod_date().map(PdfDate::parse)
+    }
+
+    /// Attempts to parse all the typed fields collecting the fields that
+    /// could not be parsed along with their raw values, useful for finding
+    /// producers that generate malformed metadata.
+    ///
+    /// Dates are only checked when [PdfInfoArgs::set_raw_dates] was used
+    pub fn diagnostics(&self) -> Vec<InfoDiagnostic> {
+        let mut diagnostics: Vec<InfoDiagnostic> = self
+            .data
+            .iter()
+            .filter_map(|(key, value)| {
+                let result = match key.as_str() {
+                    "Pages" => value
+                        .parse::<u32>()
+                        .map(drop)
+                        .map_err(PdfInfoError::InvalidPageCount),
+                    "Encrypted" => parse_pdf_info_encryption(value).map(drop),
+                    "CreationDate" | "ModDate" if self.raw_dates => PdfDate::parse(value).map(drop),
+                    key if BOOL_FIELDS.contains(&key) => match value.as_str() {
+                        "yes" | "no" => Ok(()),
+                        value => Err(PdfInfoError::MalformedBool(value.to_string())),
+                    },
+                    // Document and per page sizes and rotations
+                    key if key.starts_with("Page ") && key.ends_with(" size") => {
+                        parse_page_size(value).map(drop)
+                    }
+                    key if key.starts_with("Page ") && key.ends_with(" rot") => {
+                        parse_page_rotation(value).map(drop)
+                    }
+                    _ => Ok(()),
+                };
+
+                result.err().map(|error| InfoDiagnostic {
+                    field: key.clone(),
+                    raw: value.clone(),
+                    error,
+                })
+            })
+            .collect();
+
+        diagnostics.sort_by(|a, b| a.field.cmp(&b.field));
+        diagnostics
+    }
+
+    /// Compares the fields with the info of another version of the document,
+    /// providing the fields that were added, removed or changed ordered by
+    /// field. The raw values are compared so both should be read with the
+    /// same [PdfInfoArgs]
+    ///
+    /// ## Arguments
+    /// * other - Info for the new version of the document
+    pub fn diff(&self, other: &PdfInfo) -> Vec<InfoFieldChange> {
+        let mut changes: Vec<InfoFieldChange> = self
+            .data
+            .keys()
+            .chain(
+                other
+                    .data
+                    .keys()
+                    .filter(|key| !self.data.contains_key(*key)),
+            )
+            .filter_map(|key| {
+                let old = self.data(key);
+                let new = other.data(key);
+                if old == new {
+                    return None;
+                }
+
+                Some(InfoFieldChange {
+                    field: InfoField::from_key(key),
+                    old: old.map(str::to_string),
+                    new: new.map(str::to_string),
+                })
+            })
+            .collect();
+
+        changes.sort_by(|a, b| a.field.cmp(&b.field));
+        changes
+    }
+
+    /// Creates an owned summary of the most commonly used fields,
+    /// fields that are missing or fail to parse are [None]
+    pub fn summary(&self) -> PdfSummary {
+        PdfSummary {
+            pages: self.pages().and_then(Result::ok),
+            title: self.title().map(str::to_string),
+            subject: self.subject().map(str::to_string),
+            keywords: self.keywords().map(str::to_string),
+            author: self.author().map(str::to_string),
+            creator: self.creator().map(str::to_string),
+            producer: self.producer().map(str::to_string),
+            creation_date: self.creation_date_parsed().and_then(Result::ok),
+            mod_date: self.mod_date_parsed().and_then(Result::ok),
+            encrypted: self.encrypted(),
+            page_size: self.page_dimensions(1).and_then(Result::ok),
+            pdf_version: self.pdf_version().map(str::to_string),
+        }
+    }
+}
+
+/// Parses the page dimensions from the page size and rotation values
+fn parse_page_dimensions(
+    size: &str,
+    rotation: Option<&str>,
+) -> Result<PageDimensions, PdfInfoError> {
+    let (width_pts, height_pts) = parse_page_size(size)?;
+    let rotation = match rotation {
+        Some(value) => parse_page_rotation(value)?,
+        None => 0,
+    };
+
+    Ok(PageDimensions {
+        width_pts,
+        height_pts,
+        rotation,
+    })
+}
+
+/// Parses a page rotation in degrees
+fn parse_page_rotation(value: &str) -> Result<u32, PdfInfoError> {
+    value
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| PdfInfoError::MalformedPageRotation)
 }
 
 #[derive(Debug, Error)]
@@ -212,12 +1124,24 @@ pub enum PdfInfoError {
     #[error("failed to get output: {0}")]
     WaitOutput(std::io::Error),
 
+    #[error("output exceeded the maximum allowed size of {0} bytes")]
+    OutputLimitExceeded(usize),
+
+    #[error("pdfinfo did not finish within {0:?}")]
+    Timeout(Duration),
+
+    #[error("pdfinfo was aborted")]
+    Aborted,
+
     #[error("invalid page count: {0}")]
     InvalidPageCount(ParseIntError),
 
     #[error("failed to get pdfinfo exit code: {0}")]
     PdfInfoFailure(String),
 
+    #[error("pdfinfo reported permission error: {0}")]
+    PermissionError(PermissionDenied),
+
     #[error("pdf file is encrypted")]
     PdfEncrypted,
 
@@ -229,12 +1153,78 @@ pub enum PdfInfoError {
 
     #[error("encryption options are malformed")]
     MalformedEncryptionOptions,
+
+    #[error("page size is malformed")]
+    MalformedPageSize,
+
+    #[error("page rotation is malformed")]
+    MalformedPageRotation,
+
+    #[error("page {0} dimensions are unavailable")]
+    PageDimensionsUnavailable(u32),
+
+    #[error("pdf does not contain any pages")]
+    EmptyDocument,
+
+    #[error("date is malformed: {0}")]
+    MalformedDate(String),
+
+    #[error("expected yes or no: {0}")]
+    MalformedBool(String),
+}
+
+impl From<ChildError> for PdfInfoError {
+    fn from(value: ChildError) -> Self {
+        match value {
+            ChildError::WriteInput(err) => Self::WritePdf(err),
+            ChildError::ReadOutput(err) | ChildError::WriteOutput(err) => Self::WaitOutput(err),
+            ChildError::OutputLimitExceeded(limit) => Self::OutputLimitExceeded(limit),
+            ChildError::Timeout(timeout) => Self::Timeout(timeout),
+        }
+    }
+}
+
+impl From<ToolError> for PdfInfoError {
+    fn from(value: ToolError) -> Self {
+        match value {
+            ToolError::Spawn(err) => Self::SpawnProcess(err),
+            ToolError::Child(err) => err.into(),
+            ToolError::NotPdfFile => Self::NotPdfFile,
+            ToolError::PdfEncrypted => Self::PdfEncrypted,
+            ToolError::IncorrectPassword => Self::IncorrectPassword,
+            ToolError::PermissionError(err) => Self::PermissionError(err),
+            ToolError::PageOutOfBounds(page, page_count) => Self::PdfInfoFailure(format!(
+                "page {page} is outside the number of available pages {page_count}"
+            )),
+            ToolError::PageCountUnknown => {
+                Self::PdfInfoFailure("page count is missing or invalid".to_string())
+            }
+            ToolError::EmptyDocument => Self::EmptyDocument,
+            ToolError::Failure(err) | ToolError::Task(err) => Self::PdfInfoFailure(err),
+            ToolError::Aborted => Self::Aborted,
+            ToolError::BudgetExceeded(err) => Self::PdfInfoFailure(err.to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct PdfInfoArgs {
     /// Password for the PDF
     pub password: Option<Password>,
+    /// First page to print per page details for
+    pub first_page: Option<u32>,
+    /// Last page to print per page details for
+    pub last_page: Option<u32>,
+    /// Print dates in the raw PDF format so they can be parsed
+    pub raw_dates: bool,
+    /// Optional maximum time reading the info may take
+    pub timeout: Option<Duration>,
+    /// Optional destination for the pdfinfo stderr instead of keeping it in memory
+    pub stderr: Option<StderrSink>,
+    /// Optional lowered CPU and IO priority to run pdfinfo at
+    pub priority: Option<ProcessPriority>,
+    /// Optional limits shared with any other args the budget is set on
+    pub budget: Option<Budget>,
 }
 
 impl PdfInfoArgs {
@@ -243,12 +1233,70 @@ impl PdfInfoArgs {
         self
     }
 
-    /// Builds an argument list from all the options
+    pub fn set_first_page(mut self, first_page: u32) -> Self {
+        self.first_page = Some(first_page);
+        self
+    }
+
+    pub fn set_last_page(mut self, last_page: u32) -> Self {
+        self.last_page = Some(last_page);
+        self
+    }
+
+    pub fn set_raw_dates(mut self, raw_dates: bool) -> Self {
+        self.raw_dates = raw_dates;
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_stderr(mut self, stderr: StderrSink) -> Self {
+        self.stderr = Some(stderr);
+        self
+    }
+
+    pub fn set_priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn set_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Options for running pdfinfo
+    pub(crate) fn run_options(&self) -> RunOptions {
+        RunOptions {
+            max_output_size: None,
+            timeout: self.timeout,
+            password: self.password.clone(),
+            stderr: self.stderr.clone(),
+            priority: self.priority,
+            budget: self.budget.clone(),
+        }
+    }
+
+    /// Builds an argument list from all the options, the password is passed
+    /// to the tool separately so it is not included
     pub fn build_args(&self) -> Vec<String> {
         let mut out = Vec::new();
 
-        if let Some(password) = self.password.as_ref() {
-            password.push_arg(&mut out);
+        if let Some(first_page) = self.first_page {
+            out.push("-f".to_string());
+            out.push(first_page.to_string());
+        }
+
+        if let Some(last_page) = self.last_page {
+            out.push("-l".to_string());
+            out.push(last_page.to_string());
+        }
+
+        if self.raw_dates {
+            out.push("-rawdates".to_string());
         }
 
         out
@@ -261,53 +1309,241 @@ impl PdfInfoArgs {
 /// * data - The raw PDF file bytes
 /// * args - Extra args to provide to pdfinfo
 pub async fn pdf_info(bytes: &[u8], args: &PdfInfoArgs) -> Result<PdfInfo, PdfInfoError> {
-    let cli_args = args.build_args();
-
-    let mut child = Command::new("pdfinfo")
-        .args(["-"] /* PASS PDF THROUGH STDIN */)
-        .args(cli_args)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(PdfInfoError::SpawnProcess)?;
-
-    // UNWRAP SAFETY: The child process is guaranteed to have a stdin as .stdin(Stdio::piped()) was called
-    child
-        .stdin
-        .as_mut()
-        .unwrap()
-        .write_all(bytes)
-        .await
-        .map_err(PdfInfoError::WritePdf)?;
+    // Take input from stdin
+    let mut cli_args = vec!["-".to_string()];
+    cli_args.extend(args.build_args());
 
-    let output = child
-        .wait_with_output()
-        .await
-        .map_err(PdfInfoError::WaitOutput)?;
+    let output = run_tool("pdfinfo", &cli_args, bytes, &args.run_options()).await?;
 
-    // Handle info failure
-    if !output.status.success() {
-        let value = String::from_utf8_lossy(&output.stderr);
+    let value = String::from_utf8_lossy(&output.stdout);
+
+    let mut info = parse_pdf_info(&value)?;
+    info.raw_dates = args.raw_dates;
+
+    Ok(info)
+}
 
-        if value.contains("May not be a PDF file") {
-            return Err(PdfInfoError::NotPdfFile);
+/// Encryption state of a PDF file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncryptionState {
+    /// File is not encrypted
+    Unencrypted,
+    /// File is encrypted with an empty user password, it can be
+    /// opened without providing a password
+    EmptyUserPassword,
+    /// File is encrypted and a password is required to open it
+    PasswordRequired,
+}
+
+impl EncryptionState {
+    pub fn is_encrypted(&self) -> bool {
+        !matches!(self, Self::Unencrypted)
+    }
+
+    pub fn is_password_required(&self) -> bool {
+        matches!(self, Self::PasswordRequired)
+    }
+}
+
+/// Determines the encryption state of the provided PDF file without
+/// a password
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+pub async fn pdf_encryption_state(bytes: &[u8]) -> Result<EncryptionState, PdfInfoError> {
+    match pdf_info(bytes, &PdfInfoArgs::default()).await {
+        Ok(info) => Ok(if info.encrypted().unwrap_or_default() {
+            EncryptionState::EmptyUserPassword
+        } else {
+            EncryptionState::Unencrypted
+        }),
+        Err(PdfInfoError::PdfEncrypted) => Ok(EncryptionState::PasswordRequired),
+        Err(err) => Err(err),
+    }
+}
+
+/// Which password a candidate was accepted as by [classify_password]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum PasswordMatch {
+    /// Candidate does not open the file
+    Neither,
+    /// Candidate is the user password
+    User,
+    /// Candidate is the owner password
+    Owner,
+    /// Candidate is both the user and owner password
+    Both,
+}
+
+impl PasswordMatch {
+    fn new(user: bool, owner: bool) -> Self {
+        match (user, owner) {
+            (true, true) => Self::Both,
+            (true, false) => Self::User,
+            (false, true) => Self::Owner,
+            (false, false) => Self::Neither,
         }
+    }
+
+    pub fn is_user(&self) -> bool {
+        matches!(self, Self::User | Self::Both)
+    }
+
+    pub fn is_owner(&self) -> bool {
+        matches!(self, Self::Owner | Self::Both)
+    }
+}
 
-        if value.contains("Incorrect password") {
-            return Err(if args.password.is_none() {
-                PdfInfoError::PdfEncrypted
-            } else {
-                PdfInfoError::IncorrectPassword
-            });
+/// Determines whether the `candidate` works as the user password, the owner
+/// password, both, or neither by probing pdfinfo with each of the password
+/// flags
+///
+/// Poppler falls back to the empty user password when the owner password
+/// is wrong, so for files that can be opened without a password (and
+/// unencrypted files) every candidate is accepted as the owner password.
+/// Check [pdf_encryption_state] first when this matters
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * candidate - The password to check
+pub async fn classify_password(
+    bytes: &[u8],
+    candidate: impl Into<String>,
+) -> Result<PasswordMatch, PdfInfoError> {
+    let candidate = candidate.into();
+
+    let (user, owner) = try_join(
+        opens_with_password(bytes, Password::user(candidate.clone())),
+        opens_with_password(bytes, Password::owner(candidate)),
+    )
+    .await?;
+
+    Ok(PasswordMatch::new(user, owner))
+}
+
+/// Checks whether pdfinfo can open the file with the provided password
+async fn opens_with_password(bytes: &[u8], password: Password) -> Result<bool, PdfInfoError> {
+    let args = PdfInfoArgs::default().set_password(password);
+
+    match pdf_info(bytes, &args).await {
+        Ok(_) => Ok(true),
+        Err(PdfInfoError::IncorrectPassword) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Checks whether a password is required to open the PDF file described by
+/// `info` when no `password` is provided. Files encrypted with an empty user
+/// password are probed with pdfinfo as they can be opened without a password
+pub(crate) async fn is_password_required(
+    bytes: &[u8],
+    info: &PdfInfo,
+    password: Option<&Password>,
+) -> bool {
+    if password.is_some() || !info.encrypted().unwrap_or_default() {
+        return false;
+    }
+
+    // Probe failures are left for the actual operation to report
+    pdf_encryption_state(bytes)
+        .await
+        .is_ok_and(|state| state.is_password_required())
+}
+
+/// Gets the page count from the provided PDF info
+pub(crate) fn info_page_count(info: &PdfInfo) -> Result<u32, ToolError> {
+    info.pages()
+        .ok_or(ToolError::PageCountUnknown)?
+        .map_err(|_| ToolError::PageCountUnknown)
+}
+
+/// Gate shared by the render and text functions that take a [PdfInfo],
+/// checks the PDF can be opened and that the pages are within the page
+/// count before any processes are spawned for the pages. Provides the
+/// page count of the PDF
+///
+/// Encryption is handled the same way by all the functions:
+/// * With a [PdfInfo] the file is checked here, encrypted files are only
+///   rejected when no password is provided and the file cannot be opened
+///   with an empty password
+/// * Without a [PdfInfo] (or when verification is disabled) the failure
+///   reported by the poppler tool is used instead
+///
+/// Both report [ToolError::PdfEncrypted] when a password is required but
+/// missing and [ToolError::IncorrectPassword] when the password is wrong
+///
+/// ## Arguments
+/// * bytes - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * password - The password that will be used to open the file
+/// * pages - The pages that will be used
+pub(crate) async fn verify_document(
+    bytes: &[u8],
+    info: &PdfInfo,
+    password: Option<&Password>,
+    pages: &[u32],
+) -> Result<u32, ToolError> {
+    if is_password_required(bytes, info, password).await {
+        return Err(ToolError::PdfEncrypted);
+    }
+
+    let page_count = info_page_count(info)?;
+
+    for page in pages {
+        if *page > page_count {
+            return Err(ToolError::PageOutOfBounds(*page, page_count));
         }
+    }
+
+    Ok(page_count)
+}
+
+/// Checks whether the provided PDF file is encrypted, this includes files
+/// encrypted with an empty user password. Use [pdf_encryption_state] to tell
+/// whether a password is actually required
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+pub async fn pdf_is_encrypted(bytes: &[u8]) -> Result<bool, PdfInfoError> {
+    pdf_encryption_state(bytes)
+        .await
+        .map(|state| state.is_encrypted())
+}
 
-        return Err(PdfInfoError::PdfInfoFailure(value.to_string()));
+/// Gets the page count of the provided PDF file, reads the page tree
+/// directly when possible (See [quick_page_count]) otherwise falls back
+/// to pdfinfo
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Extra args to provide to pdfinfo when falling back
+pub async fn pdf_page_count(bytes: &[u8], args: &PdfInfoArgs) -> Result<u32, PdfInfoError> {
+    if let Some(page_count) = quick_page_count(bytes) {
+        return Ok(page_count);
     }
 
-    let value = String::from_utf8_lossy(&output.stdout);
+    let info = pdf_info(bytes, args).await?;
+    Ok(info_page_count(&info)?)
+}
 
-    parse_pdf_info(&value)
+/// Gets the dimensions of a specific page without rendering it, uses
+/// the per page details from pdfinfo
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * page - The page to get the dimensions of
+/// * args - Extra args to provide to pdfinfo, the page range is replaced
+pub async fn page_dimensions(
+    bytes: &[u8],
+    page: u32,
+    args: &PdfInfoArgs,
+) -> Result<PageDimensions, PdfInfoError> {
+    let args = args.clone().set_first_page(page).set_last_page(page);
+    let info = pdf_info(bytes, &args).await?;
+
+    info.page_dimensions(page)
+        .ok_or(PdfInfoError::PageDimensionsUnavailable(page))?
 }
 
 fn parse_bool(value: &str) -> bool {
@@ -315,27 +1551,46 @@ fn parse_bool(value: &str) -> bool {
 }
 
 /// Parses the fields from the pdfinfo response
-fn parse_pdf_info(output: &str) -> Result<PdfInfo, PdfInfoError> {
+pub(crate) fn parse_pdf_info(output: &str) -> Result<PdfInfo, PdfInfoError> {
     let data = output
         .lines()
         .filter_map(|line| {
             let (key, value) = line.split_once(':')?;
             let value = value.trim_start();
-            Some((key.to_string(), value.to_string()))
+            Some((normalize_key(key), value.to_string()))
         })
         .collect();
 
-    Ok(PdfInfo { data })
+    Ok(PdfInfo {
+        data,
+        raw_dates: false,
+    })
+}
+
+/// Per page keys are padded to align the page numbers ("Page    1 size")
+/// this collapses the whitespace so they can be looked up ("Page 1 size")
+fn normalize_key(key: &str) -> String {
+    if key.starts_with("Page ") {
+        key.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        key.to_string()
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{parse_pdf_info, pdf_info, PdfInfoArgs};
+    use super::{
+        is_password_required, parse_pdf_info, pdf_info, EncryptionAlgorithm, InfoField,
+        InfoFieldChange, PageDimensions, PageOrientation, PageTransform, PdfDate, PdfInfoArgs,
+        PdfInfoError, PdfPermission, PermissionDenied, Permissions,
+    };
+    use crate::shared::Password;
+    use crate::shared::{Point, Rect, Size};
 
     /// Tests against an invalid file
     #[tokio::test]
     async fn test_invalid_file() {
-        let value = &[b'A'];
+        let value = b"A";
         let err = pdf_info(value, &PdfInfoArgs::default()).await.unwrap_err();
         assert!(matches!(err, crate::info::PdfInfoError::NotPdfFile));
     }
@@ -399,4 +1654,365 @@ PDF version:     1.2
         assert_eq!(output.optimized(), Some(true));
         assert_eq!(output.pdf_version(), Some("1.2"));
     }
+
+    /// Tests parsing per page dimensions
+    #[test]
+    fn test_parsing_page_dimensions() {
+        let value = r#"
+Pages:           3
+Page size:       612 x 792 pts (letter)
+Page rot:        0
+Page    1 size:  612 x 792 pts (letter)
+Page    1 rot:   0
+Page    2 size:  595.28 x 841.89 pts (A4)
+Page    2 rot:   90
+        "#;
+        let output = parse_pdf_info(value).unwrap();
+
+        assert_eq!(
+            output.page_dimensions(1).unwrap().unwrap(),
+            PageDimensions {
+                width_pts: 612.0,
+                height_pts: 792.0,
+                rotation: 0
+            }
+        );
+        assert_eq!(
+            output.page_dimensions(2).unwrap().unwrap(),
+            PageDimensions {
+                width_pts: 595.28,
+                height_pts: 841.89,
+                rotation: 90
+            }
+        );
+        assert!(output.page_dimensions(3).is_none());
+    }
+
+    /// Tests converting between the page coordinate spaces
+    #[test]
+    fn test_page_transform() {
+        let page = |rotation| PageDimensions {
+            width_pts: 612.0,
+            height_pts: 792.0,
+            rotation,
+        };
+
+        // Point near the top left of the unrotated page
+        let point = Point::new(72.0, 720.0);
+
+        let transform = page(0).transform(144.0);
+        assert_eq!(transform.pdf_to_text(point), Point::new(72.0, 72.0));
+        assert_eq!(transform.pdf_to_pixels(point), Point::new(144.0, 144.0));
+
+        // Rotated pages move the top left of the page to the top right
+        assert_eq!(
+            page(90).transform(72.0).pdf_to_text(point),
+            Point::new(720.0, 72.0)
+        );
+        assert_eq!(
+            page(180).transform(72.0).pdf_to_text(point),
+            Point::new(540.0, 720.0)
+        );
+        assert_eq!(
+            page(270).transform(72.0).pdf_to_text(point),
+            Point::new(72.0, 540.0)
+        );
+
+        for rotation in [0, 90, 180, 270] {
+            let transform = page(rotation).transform(150.0);
+            let pixels = transform.pdf_to_pixels(point);
+            let back = transform.pixels_to_pdf(pixels);
+            assert!((back.x - point.x).abs() < 0.01 && (back.y - point.y).abs() < 0.01);
+        }
+
+        // Rectangles stay normalized after flipping the y axis
+        let rect = Rect::new(72.0, 700.0, 100.0, 20.0);
+        assert_eq!(
+            page(0).transform(72.0).pdf_to_text_rect(rect),
+            Rect::new(72.0, 72.0, 100.0, 20.0)
+        );
+
+        // Scaled renders use the rendered size
+        let transform = PageTransform::for_pixel_size(&page(90), Size::new(396.0, 306.0));
+        assert_eq!(
+            transform.text_to_pixels(Point::new(792.0, 612.0)),
+            Point::new(396.0, 306.0)
+        );
+    }
+
+    /// Tests the page orientation accounts for the rotation
+    #[test]
+    fn test_page_orientation() {
+        let page = |width_pts, height_pts, rotation| PageDimensions {
+            width_pts,
+            height_pts,
+            rotation,
+        };
+
+        assert_eq!(
+            page(612.0, 792.0, 0).orientation(),
+            PageOrientation::Portrait
+        );
+        assert_eq!(
+            page(612.0, 792.0, 90).orientation(),
+            PageOrientation::Landscape
+        );
+        assert_eq!(
+            page(612.0, 792.0, 180).orientation(),
+            PageOrientation::Portrait
+        );
+        assert_eq!(
+            page(612.0, 792.0, 270).orientation(),
+            PageOrientation::Landscape
+        );
+        assert_eq!(
+            page(792.0, 612.0, 90).orientation(),
+            PageOrientation::Portrait
+        );
+        assert_eq!(
+            page(500.0, 500.0, 90).orientation(),
+            PageOrientation::Square
+        );
+    }
+
+    /// Tests parsing raw PDF dates
+    #[test]
+    fn test_parsing_dates() {
+        let date = PdfDate::parse("D:19960825210020+12'00'").unwrap();
+        assert_eq!(
+            date,
+            PdfDate {
+                year: 1996,
+                month: 8,
+                day: 25,
+                hour: 21,
+                minute: 0,
+                second: 20,
+                utc_offset_minutes: Some(720)
+            }
+        );
+
+        let date = PdfDate::parse("D:20240102Z").unwrap();
+        assert_eq!((date.year, date.month, date.day), (2024, 1, 2));
+        assert_eq!(date.utc_offset_minutes, Some(0));
+
+        let date = PdfDate::parse("D:2024-05'30").unwrap();
+        assert_eq!((date.year, date.month), (2024, 1));
+        assert_eq!(date.utc_offset_minutes, Some(-330));
+
+        let date = PdfDate::parse("D:20240102153000").unwrap();
+        assert_eq!(date.utc_offset_minutes, None);
+
+        assert!(PdfDate::parse("Sun Aug 25 21:00:20 1996 NZST").is_err());
+        assert!(PdfDate::parse("D:20241399").is_err());
+    }
+
+    /// Tests out of range timezone offsets are reported as malformed
+    /// instead of overflowing
+    #[test]
+    fn test_parsing_date_offset_range() {
+        for value in [
+            "D:20240101+600'00'",
+            "D:20240101-24'00'",
+            "D:20240101+05'60'",
+            "D:20240101+32767'00'",
+        ] {
+            assert!(matches!(
+                PdfDate::parse(value),
+                Err(PdfInfoError::MalformedDate(_))
+            ));
+        }
+
+        let date = PdfDate::parse("D:20240101-23'59'").unwrap();
+        assert_eq!(date.utc_offset_minutes, Some(-1439));
+    }
+
+    /// Tests collecting diagnostics for malformed fields
+    #[test]
+    fn test_diagnostics() {
+        let value = r#"
+Title:           Test pdf
+CreationDate:    Sun Aug 25 21:00:20 1996 NZST
+Pages:           many
+Tagged:          maybe
+Page size:       540 x 738 pts
+Page    1 size:  wide x tall pts
+Page    1 rot:   0
+        "#;
+        let mut info = parse_pdf_info(value).unwrap();
+
+        let diagnostics = info.diagnostics();
+        let fields: Vec<&str> = diagnostics
+            .iter()
+            .map(|value| value.field.as_str())
+            .collect();
+        assert_eq!(fields, ["Page 1 size", "Pages", "Tagged"]);
+        assert_eq!(diagnostics[1].raw, "many");
+        assert!(matches!(
+            diagnostics[1].error,
+            PdfInfoError::InvalidPageCount(_)
+        ));
+
+        // Dates are only checked when using raw dates
+        info.raw_dates = true;
+        let diagnostics = info.diagnostics();
+        assert_eq!(diagnostics[0].field, "CreationDate");
+    }
+
+    /// Tests creating a summary from the info
+    #[test]
+    fn test_summary() {
+        let value = r#"
+Title:           Test pdf
+Author:          Test author
+Producer:        Test producer
+CreationDate:    D:19960825210020+12'00'
+ModDate:         Sun Aug 25 21:00:20 1996 NZST
+Pages:           16
+Encrypted:       no
+Page size:       540 x 738 pts
+PDF version:     1.2
+        "#;
+        let summary = parse_pdf_info(value).unwrap().summary();
+
+        assert_eq!(summary.pages, Some(16));
+        assert_eq!(summary.title.as_deref(), Some("Test pdf"));
+        assert_eq!(summary.author.as_deref(), Some("Test author"));
+        assert_eq!(summary.subject, None);
+        assert_eq!(summary.creation_date.map(|date| date.year), Some(1996));
+        assert_eq!(summary.mod_date, None);
+        assert_eq!(summary.encrypted, Some(false));
+        assert_eq!(summary.page_size.map(|size| size.width_pts), Some(540.0));
+        assert_eq!(summary.pdf_version.as_deref(), Some("1.2"));
+    }
+
+    /// Tests the changed fields between two versions are listed
+    #[test]
+    fn test_diff() {
+        let old = parse_pdf_info(
+            "Title: Draft\nAuthor: Test author\nPages: 2\nPDF version: 1.4\nPage    2 size: 612 x 792 pts\n",
+        )
+        .unwrap();
+        let new = parse_pdf_info(
+            "Title: Final\nPages: 2\nProducer: Test producer\nPDF version: 1.4\nPage    2 size: 595 x 842 pts\n",
+        )
+        .unwrap();
+
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                InfoFieldChange {
+                    field: InfoField::Title,
+                    old: Some("Draft".to_string()),
+                    new: Some("Final".to_string()),
+                },
+                InfoFieldChange {
+                    field: InfoField::Author,
+                    old: Some("Test author".to_string()),
+                    new: None,
+                },
+                InfoFieldChange {
+                    field: InfoField::Producer,
+                    old: None,
+                    new: Some("Test producer".to_string()),
+                },
+                InfoFieldChange {
+                    field: InfoField::Other("Page 2 size".to_string()),
+                    old: Some("612 x 792 pts".to_string()),
+                    new: Some("595 x 842 pts".to_string()),
+                },
+            ]
+        );
+
+        let changes = old.diff(&new);
+        assert!(changes[1].is_removed());
+        assert!(changes[2].is_added());
+    }
+
+    /// Tests parsing the encryption details
+    #[test]
+    fn test_parsing_encryption() {
+        let output = parse_pdf_info(
+            "Encrypted: yes (print:yes copy:no change:no addNotes:yes algorithm:AES-256)",
+        )
+        .unwrap();
+        let encryption = output.encryption().unwrap().unwrap();
+
+        assert!(encryption.is_encrypted());
+        assert!(encryption.is_print_allowed());
+        assert_eq!(encryption.is_high_res_print_allowed(), None);
+        assert!(!encryption.is_copy_allowed());
+        assert_eq!(encryption.is_accessibility_allowed(), None);
+        assert!(!encryption.is_change_allowed());
+        assert_eq!(encryption.is_assemble_allowed(), None);
+        assert!(encryption.is_add_notes_allowed());
+        assert_eq!(encryption.is_fill_forms_allowed(), None);
+        assert_eq!(encryption.algorithm(), Some(EncryptionAlgorithm::Aes256));
+        assert_eq!(encryption.algorithm_raw(), Some("AES-256"));
+        assert_eq!(encryption.key_length(), Some(256));
+
+        // Permissions pdfinfo does not report are left unset
+        assert_eq!(
+            encryption.permissions(),
+            Permissions::PRINT | Permissions::ANNOTATE
+        );
+
+        let output = parse_pdf_info("Encrypted: no").unwrap();
+        let encryption = output.encryption().unwrap().unwrap();
+        assert_eq!(encryption.permissions(), Permissions::all());
+        assert!(!encryption.is_encrypted());
+        assert_eq!(encryption.is_assemble_allowed(), Some(true));
+        assert_eq!(encryption.algorithm(), None);
+
+        // Denied printing also denies high resolution printing
+        let output =
+            parse_pdf_info("Encrypted: yes (print:no copy:yes change:yes addNotes:yes)").unwrap();
+        let encryption = output.encryption().unwrap().unwrap();
+        assert_eq!(encryption.is_high_res_print_allowed(), Some(false));
+    }
+
+    /// Tests identifying the permission that blocked an operation
+    #[test]
+    fn test_permission_denied() {
+        let denied = PermissionDenied::from_output(
+            "pdftotext",
+            "Permission Error: Copying of text from this document is not allowed.\n",
+        );
+        assert_eq!(denied.permission(), Some(PdfPermission::Copy));
+        assert!(denied.owner_password_bypasses());
+        assert_eq!(
+            denied.to_string(),
+            "copy permission is not granted (Permission Error: Copying of text from this \
+             document is not allowed.), providing the owner password bypasses this restriction"
+        );
+
+        // Unrecognized messages fall back to the permission checked by the tool
+        let denied = PermissionDenied::from_output("pdfimages", "Erreur de permission");
+        assert_eq!(denied.permission(), Some(PdfPermission::Copy));
+
+        // Unknown permissions are narrowed from the pdfinfo encryption details
+        let denied = PermissionDenied::from_output("pdftocairo", "");
+        assert_eq!(denied.permission(), None);
+
+        let output =
+            parse_pdf_info("Encrypted: yes (print:no copy:yes change:yes addNotes:yes)").unwrap();
+        let encryption = output.encryption().unwrap().unwrap();
+        assert_eq!(encryption.denied_permissions(), vec![PdfPermission::Print]);
+
+        let denied = denied.with_encryption(&encryption);
+        assert_eq!(denied.permission(), Some(PdfPermission::Print));
+    }
+
+    /// Tests a password is not required for unencrypted files or when
+    /// a password is already provided
+    #[tokio::test]
+    async fn test_password_not_required() {
+        let info = parse_pdf_info("Encrypted: no").unwrap();
+        assert!(!is_password_required(&[], &info, None).await);
+
+        let info = parse_pdf_info("Encrypted: yes (print:yes copy:yes)").unwrap();
+        let password = Password::user("password");
+        assert!(!is_password_required(&[], &info, Some(&password)).await);
+    }
 }
@@ -0,0 +1,97 @@
+//! Helpers for detecting the language of the text on each page
+//!
+//! * [text_page_languages] - Detects the language of every page in the PDF
+//! * [detect_language] - Detects the language of already extracted text
+//!
+//! Detection uses trigram statistics from the `whatlang` crate, short pages
+//! (e.g. a single heading) may not provide enough text for a reliable result
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::text::{text_all_pages_split, PdfTextArgs, PdfTextError};
+
+/// Language detected for a page
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PageLanguage {
+    /// The page number
+    pub page: u32,
+    /// ISO 639-3 code of the detected language (e.g. "eng"), [None] when
+    /// the page has no text or the language could not be detected, pages
+    /// without text are likely scanned and may need OCR
+    pub language: Option<&'static str>,
+    /// Confidence of the detection between 0.0 and 1.0
+    pub confidence: f64,
+    /// Whether the detector considers the result reliable
+    pub reliable: bool,
+}
+
+/// Detects the language of the text on every page of the provided PDF
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Args for extracting the text
+pub async fn text_page_languages(
+    data: &[u8],
+    args: &PdfTextArgs,
+) -> Result<Vec<PageLanguage>, PdfTextError> {
+    let pages = text_all_pages_split(data, args).await?;
+
+    Ok(pages
+        .iter()
+        .enumerate()
+        .map(|(index, text)| detect_language(index as u32 + 1, text))
+        .collect())
+}
+
+/// Detects the language of the provided page text
+///
+/// ## Arguments
+/// * page - The page number the text is from
+/// * text - The text of the page
+pub fn detect_language(page: u32, text: &str) -> PageLanguage {
+    match whatlang::detect(text) {
+        Some(info) => PageLanguage {
+            page,
+            language: Some(info.lang().code()),
+            confidence: info.confidence(),
+            reliable: info.is_reliable(),
+        },
+        None => PageLanguage {
+            page,
+            language: None,
+            confidence: 0.0,
+            reliable: false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::detect_language;
+
+    /// Tests the language of each page text is detected
+    #[test]
+    fn test_detect_language() {
+        let english = detect_language(
+            1,
+            "The invoice must be paid within thirty days of the date shown above, \
+            otherwise a late payment fee will be added to the next statement.",
+        );
+        assert_eq!(english.page, 1);
+        assert_eq!(english.language, Some("eng"));
+        assert!(english.confidence > 0.5);
+
+        let german = detect_language(
+            2,
+            "Die Rechnung ist innerhalb von dreißig Tagen nach dem oben genannten \
+            Datum zu bezahlen, andernfalls wird eine Mahngebühr berechnet.",
+        );
+        assert_eq!(german.language, Some("deu"));
+
+        let empty = detect_language(3, "  \n");
+        assert_eq!(empty.language, None);
+        assert!(!empty.reliable);
+    }
+}
@@ -1,14 +1,158 @@
+#[cfg(feature = "zip")]
+pub mod archive;
+pub mod batch;
+pub mod budget;
+pub mod bundle;
+pub mod classify;
+#[cfg(feature = "compat")]
+pub mod compat;
+pub mod degrade;
+pub mod dominance;
+pub mod dpi;
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub mod encode;
+pub mod export;
+pub mod fonts;
 pub mod image;
+pub mod images;
 pub mod info;
+#[cfg(feature = "language")]
+pub mod language;
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub mod monochrome;
+pub mod preview;
+pub mod probe;
+pub mod processor;
+#[cfg(feature = "qpdf")]
+pub mod qpdf;
+pub mod redaction;
+pub mod search;
+#[cfg(feature = "service")]
+pub mod service;
 pub mod shared;
+pub mod sidecar;
+pub mod signature;
+pub mod stats;
+pub mod styled;
+#[cfg(feature = "terminal")]
+pub mod terminal;
 pub mod text;
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub mod thumbnail;
+mod tiff;
+pub mod tool;
+pub mod vector;
+pub mod version;
+pub mod words;
 
+// The image crate version used for decoding, 0.25 is preferred when both are enabled
+#[cfg(feature = "image")]
+use ::image as image_crate;
+#[cfg(all(feature = "image-0-24", not(feature = "image")))]
+use image_0_24 as image_crate;
+
+#[cfg(feature = "zip")]
+pub use archive::{render_to_zip, render_to_zip_writer, PdfZipError, ZipArgs};
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub use batch::render_all_pages_batched;
+pub use batch::render_all_pages_raw_batched;
+pub use budget::{Budget, BudgetExceeded, BudgetLimit, BudgetUsage};
+pub use bundle::{render_page_bundle, BundleFormat, BundleOutput};
+pub use classify::{
+    classify_document, ClassifyArgs, ClassifyError, DocumentClass, DocumentClassification,
+    DocumentGenerator, DocumentSignals,
+};
+#[cfg(feature = "compat")]
+pub use compat::{PopplerCompat, PopplerOption};
+pub use degrade::{render_single_page_raw_degrading, Degradation, DegradePolicy, DegradedRender};
+pub use dominance::{
+    page_image_dominance, ImageDominanceArgs, ImageDominanceError, PageImageDominance,
+};
+pub use dpi::{auto_resolution, scan_ppi, AutoDpi};
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub use encode::{encode_image, EncodeOptions, PngCompression, PngFilter, SourceMetadata};
+#[cfg(feature = "zip")]
+pub use export::export_epub;
+pub use export::{export_html, ExportError, ExportOptions};
+pub use fonts::{pdf_font_list, PdfFontEntry, PdfFontsArgs, PdfFontsError};
+#[cfg(feature = "avif")]
+pub use image::AvifOptions;
+#[cfg(any(feature = "image", feature = "image-0-24"))]
 pub use image::{
-    render_all_pages, render_pages, render_single_page, Antialias, Crop, OutputFormat, PageColor,
-    PdfRenderError, RenderArea, RenderArgs, RenderColor, Resolution, ScaleTo,
+    info_and_first_page, render_all_pages, render_all_pages_as, render_all_pages_stream,
+    render_page_rgba, render_page_selection, render_pages, render_pages_as, render_pages_numbered,
+    render_pages_partial, render_pages_rgba, render_pages_with, render_single_page,
+    render_single_page_as, render_single_page_luma8, render_single_page_rgba8, RenderPixel,
+    StreamOrder,
+};
+pub use image::{
+    info_and_first_page_raw, render_all_pages_raw, render_all_pages_tiff_multipage,
+    render_page_selection_raw, render_page_to_writer, render_pages_raw, render_pages_raw_numbered,
+    render_pages_raw_partial, render_pages_raw_with, render_single_page_raw, Antialias,
+    AntialiasConfig, Crop, JpegOptions, OutputFormat, PageColor, PdfRenderError, RenderArea,
+    RenderArgs, RenderArgsError, RenderBackend, RenderColor, Resolution, RgbaPixels, ScaleTo,
+    SplashOptions, ThinLineMode, TiffCompression,
+};
+pub use images::{pdf_image_list, PdfImageEntry, PdfImageKind, PdfImagesArgs, PdfImagesError};
+pub use info::{
+    classify_password, page_dimensions, pdf_encryption_state, pdf_info, pdf_is_encrypted,
+    pdf_page_count, EncryptionAlgorithm, EncryptionState, InfoDiagnostic, InfoField,
+    InfoFieldChange, PageDimensions, PageOrientation, PageTransform, PasswordMatch, PdfDate,
+    PdfInfo, PdfInfoArgs, PdfInfoEncryption, PdfInfoError, PdfPermission, PdfSummary,
+    PermissionDenied, Permissions,
+};
+#[cfg(feature = "language")]
+pub use language::{detect_language, text_page_languages, PageLanguage};
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub use monochrome::{
+    binarize, render_single_page_monochrome, Binarization, MonochromeDepth, MonochromeOptions,
+    MonochromePixels,
 };
-pub use info::{pdf_info, PdfInfo, PdfInfoArgs, PdfInfoError};
-pub use shared::{Password, Secret};
+pub use preview::{generate_preview, ImageOrBytes, Preview, PreviewError, PreviewOptions};
+pub use probe::{probe_pdf_header, quick_page_count, PdfProbe, PDF_PROBE_LENGTH};
+pub use processor::{
+    JanitorStats, JobId, JobInfo, PdfProcessor, ProcessorConfig, DEFAULT_RENDER_CACHE_SIZE,
+};
+#[cfg(feature = "qpdf")]
+pub use qpdf::{reencrypt_pdf, strip_metadata, PdfSecurity, QpdfError};
+pub use redaction::{verify_redaction, BoundingBox, RedactionHit};
+pub use search::{find_first, FindOptions, MatchOptions, SearchPattern, TextMatch, TextMatcher};
+#[cfg(feature = "service")]
+pub use service::{PolicyViolation, PreviewBundle, PreviewService, ServiceError, ServicePolicy};
+pub use shared::{
+    configure_tool_paths, EmptyDocumentPolicy, FanOut, PageSelection, PartialResult, Password,
+    PasswordError, Point, ProcessPriority, Rect, Secret, Size, StderrSink, ToolPaths,
+    POINTS_PER_INCH, STDERR_RETAIN_LIMIT, TEMP_DIR_PREFIX,
+};
+#[cfg(feature = "json")]
+pub use sidecar::page_records_json_lines;
+pub use sidecar::{page_records, PageRecord, SidecarError, SidecarOptions};
+pub use signature::{changed_pages, page_signatures, PageSignature};
+pub use stats::{document_stats, DocumentStats, PageStats};
+pub use styled::{
+    text_styled_pages, PdfStyledTextArgs, PdfStyledTextError, StyledLine, StyledPage, StyledRun,
+};
+#[cfg(feature = "terminal")]
+pub use terminal::{ascii_preview, render_ascii_preview, render_sixel_preview, sixel_preview};
 pub use text::{
-    text_all_pages, text_all_pages_split, text_pages, text_single_page, PdfTextArgs, PdfTextError,
+    text_all_pages, text_all_pages_bytes, text_all_pages_split, text_encodings, text_excerpt,
+    text_page_selection, text_pager, text_pages, text_pages_numbered, text_pages_partial,
+    text_pages_with_provenance, text_single_page, text_single_page_bytes, BidiMode, Eol,
+    ExtractedText, PageBreak, PdfTextArgs, PdfTextError, TextEncoding, TextNormalization,
+    TextPager,
+};
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+pub use thumbnail::render_thumbnail_strip;
+pub use tool::{run_poppler_tool, PopplerTool, PopplerToolArgs, PopplerToolError, ToolOutput};
+pub use vector::{
+    render_page_svg, render_pages_svg, render_to_eps, render_to_pdf, render_to_ps, PaperOptions,
+    PaperSize, PrintOptions,
+};
+pub use version::{
+    poppler_version, render_provenance, text_provenance, ExtractionMethod, PopplerVersion,
+    PopplerVersionError, RenderProvenance, TextProvenance,
+};
+pub use words::{
+    text_tsv, text_with_boxes, text_with_layout, LayoutBlock, LayoutFlow, LayoutLine, LayoutPage,
+    TsvLevel, TsvRecord, Word, WordPage,
 };
@@ -0,0 +1,339 @@
+//! Helpers for rendering pages as black and white
+//!
+//! * [render_single_page_monochrome] - Renders a page as black and white pixels
+//! * [binarize] - Converts a grayscale image into black and white pixels
+//!
+//! Fax and archival output needs pages with only black and white pixels,
+//! `pdftocairo -mono` chooses how grays are converted itself. The page can
+//! instead be rendered as grayscale and converted using a threshold or one
+//! of the dithering algorithms from [Binarization]
+
+use crate::{
+    image::{render_single_page_luma8, OutputFormat, PdfRenderError, RenderArgs, RenderColor},
+    image_crate::GrayImage,
+    info::PdfInfo,
+};
+
+/// 4x4 Bayer matrix used for ordered dithering
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How grayscale pixels are converted to black and white
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binarization {
+    /// Rendered using [RenderColor::Monochrome], pdftocairo converts
+    /// the page itself
+    Renderer,
+    /// Pixels darker than the threshold become black
+    Threshold(u8),
+    /// Floyd-Steinberg error diffusion, keeps the tone of photos and shading
+    FloydSteinberg,
+    /// Atkinson error diffusion, only diffuses part of the error so it
+    /// keeps more contrast than [Binarization::FloydSteinberg]
+    Atkinson,
+    /// Ordered dithering using a 4x4 Bayer matrix, produces a regular
+    /// pattern that compresses well
+    Ordered,
+}
+
+impl Default for Binarization {
+    fn default() -> Self {
+        Self::Threshold(MonochromeOptions::DEFAULT_THRESHOLD)
+    }
+}
+
+/// Number of bits used for each black and white pixel
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MonochromeDepth {
+    /// 1 bit per pixel packed 8 pixels per byte with the leftmost pixel
+    /// in the most significant bit, 1 is black. Rows are padded to a
+    /// whole byte, the layout used by fax encoders and PBM
+    One,
+    /// 8 bits per pixel, 0 is black and 255 is white
+    #[default]
+    Eight,
+}
+
+/// Options for converting a page to black and white
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MonochromeOptions {
+    /// How grays are converted to black and white
+    pub binarization: Binarization,
+    /// Number of bits for each pixel of the output
+    pub bit_depth: MonochromeDepth,
+}
+
+impl MonochromeOptions {
+    /// Threshold used by [Binarization::default]
+    pub const DEFAULT_THRESHOLD: u8 = 128;
+
+    pub fn set_binarization(mut self, binarization: Binarization) -> Self {
+        self.binarization = binarization;
+        self
+    }
+
+    pub fn set_bit_depth(mut self, bit_depth: MonochromeDepth) -> Self {
+        self.bit_depth = bit_depth;
+        self
+    }
+}
+
+/// Black and white pixels of a rendered page, rows are stored from top
+/// to bottom in the layout of the [MonochromeDepth]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonochromePixels {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Number of bits for each pixel
+    pub bit_depth: MonochromeDepth,
+    /// Pixel data
+    pub pixels: Vec<u8>,
+}
+
+impl MonochromePixels {
+    /// Creates the pixels from whether each pixel is black, in rows
+    /// from top to bottom
+    fn from_black(
+        width: u32,
+        height: u32,
+        bit_depth: MonochromeDepth,
+        black: impl Iterator<Item = bool>,
+    ) -> Self {
+        let mut pixels = Self {
+            width,
+            height,
+            bit_depth,
+            pixels: Vec::new(),
+        };
+        pixels.pixels = vec![0; pixels.stride() * height as usize];
+
+        let stride = pixels.stride();
+        for (index, black) in black.enumerate() {
+            let (x, y) = (index % width as usize, index / width as usize);
+
+            match bit_depth {
+                MonochromeDepth::One if black => {
+                    pixels.pixels[y * stride + x / 8] |= 0x80 >> (x % 8);
+                }
+                MonochromeDepth::One => {}
+                MonochromeDepth::Eight => {
+                    pixels.pixels[y * stride + x] = if black { 0 } else { 255 };
+                }
+            }
+        }
+
+        pixels
+    }
+
+    /// Number of bytes in each row
+    pub fn stride(&self) -> usize {
+        match self.bit_depth {
+            MonochromeDepth::One => (self.width as usize).div_ceil(8),
+            MonochromeDepth::Eight => self.width as usize,
+        }
+    }
+
+    /// Whether the pixel at the provided position is black
+    pub fn is_black(&self, x: u32, y: u32) -> Option<bool> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let row = y as usize * self.stride();
+        match self.bit_depth {
+            MonochromeDepth::One => {
+                let byte = self.pixels[row + x as usize / 8];
+                Some(byte & (0x80 >> (x % 8)) != 0)
+            }
+            MonochromeDepth::Eight => Some(self.pixels[row + x as usize] < 128),
+        }
+    }
+}
+
+/// Converts a grayscale image into black and white pixels. Images
+/// rendered using [Binarization::Renderer] are already black and white
+/// and are converted using the default threshold
+///
+/// ## Arguments
+/// * image - The grayscale image
+/// * options - How to convert the image
+pub fn binarize(image: &GrayImage, options: &MonochromeOptions) -> MonochromePixels {
+    let (width, height) = image.dimensions();
+    let luma = image.pixels().map(|pixel| pixel.0[0]);
+
+    match options.binarization {
+        Binarization::Renderer | Binarization::Threshold(_) => {
+            let threshold = match options.binarization {
+                Binarization::Threshold(threshold) => threshold,
+                _ => MonochromeOptions::DEFAULT_THRESHOLD,
+            };
+
+            MonochromePixels::from_black(
+                width,
+                height,
+                options.bit_depth,
+                luma.map(|value| value < threshold),
+            )
+        }
+        Binarization::Ordered => {
+            let black = luma.enumerate().map(|(index, value)| {
+                let (x, y) = (index % width as usize, index / width as usize);
+                let threshold = BAYER_4X4[y % 4][x % 4] as u16 * 16 + 8;
+                (value as u16) < threshold
+            });
+            MonochromePixels::from_black(width, height, options.bit_depth, black)
+        }
+        Binarization::FloydSteinberg => {
+            // (x offset, y offset, sixteenths of the error)
+            let spread = [(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)];
+            let black = diffuse(image, &spread, 16);
+            MonochromePixels::from_black(width, height, options.bit_depth, black.into_iter())
+        }
+        Binarization::Atkinson => {
+            // (x offset, y offset, eighths of the error), only 6/8 of the error is spread
+            let spread = [
+                (1, 0, 1),
+                (2, 0, 1),
+                (-1, 1, 1),
+                (0, 1, 1),
+                (1, 1, 1),
+                (0, 2, 1),
+            ];
+            let black = diffuse(image, &spread, 8);
+            MonochromePixels::from_black(width, height, options.bit_depth, black.into_iter())
+        }
+    }
+}
+
+/// Error diffusion dithering, the difference between each pixel and the
+/// chosen black or white is spread to the following pixels
+///
+/// ## Arguments
+/// * image - The grayscale image
+/// * spread - The x and y offsets of the neighbouring pixels with their share of the error
+/// * divisor - The divisor for the shares of the error
+fn diffuse(image: &GrayImage, spread: &[(i64, i64, i32)], divisor: i32) -> Vec<bool> {
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    let mut values: Vec<i32> = image.pixels().map(|pixel| pixel.0[0] as i32).collect();
+    let mut black = Vec::with_capacity(values.len());
+
+    for y in 0..height {
+        for x in 0..width {
+            let value = values[(y * width + x) as usize];
+            let is_black = value < MonochromeOptions::DEFAULT_THRESHOLD as i32;
+            let error = value - if is_black { 0 } else { 255 };
+            black.push(is_black);
+
+            for &(dx, dy, share) in spread {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || nx >= width || ny >= height {
+                    continue;
+                }
+
+                values[(ny * width + nx) as usize] += error * share / divisor;
+            }
+        }
+    }
+
+    black
+}
+
+/// Renders a specific page from the provided PDF file as black and white
+/// pixels. The page is rendered using [RenderColor::Monochrome] for
+/// [Binarization::Renderer] otherwise it is rendered using
+/// [RenderColor::Grayscale] and converted. The render color of the
+/// provided args is replaced
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * page - The page to render
+/// * args - Optional args to pdftocairo
+/// * options - How to convert the page to black and white
+pub async fn render_single_page_monochrome(
+    data: &[u8],
+    info: &PdfInfo,
+    page: u32,
+    args: &RenderArgs,
+    options: &MonochromeOptions,
+) -> Result<MonochromePixels, PdfRenderError> {
+    let render_color = match options.binarization {
+        Binarization::Renderer => RenderColor::Monochrome,
+        _ => RenderColor::Grayscale,
+    };
+    let args = args.clone().set_render_color(render_color);
+
+    let image = render_single_page_luma8(data, info, OutputFormat::Png, page, &args).await?;
+    Ok(binarize(&image, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{binarize, Binarization, MonochromeDepth, MonochromeOptions};
+    use crate::image_crate::{GrayImage, Luma};
+
+    /// Fraction of the pixels that are black
+    fn black_fraction(image: &GrayImage, options: &MonochromeOptions) -> f32 {
+        let pixels = binarize(image, options);
+        let black = (0..image.height())
+            .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| pixels.is_black(x, y).unwrap())
+            .count();
+        black as f32 / (image.width() * image.height()) as f32
+    }
+
+    /// Tests thresholds split the pixels at the threshold value
+    #[test]
+    fn test_threshold() {
+        let image = GrayImage::from_fn(4, 1, |x, _| Luma([x as u8 * 80]));
+
+        let pixels = binarize(&image, &MonochromeOptions::default());
+        assert_eq!(pixels.pixels, [0, 0, 255, 255]);
+
+        let options = MonochromeOptions::default().set_binarization(Binarization::Threshold(200));
+        let pixels = binarize(&image, &options);
+        assert_eq!(pixels.pixels, [0, 0, 0, 255]);
+    }
+
+    /// Tests 1 bit pixels are packed most significant bit first with padded rows
+    #[test]
+    fn test_packed_bits() {
+        let image = GrayImage::from_fn(10, 2, |x, y| Luma([if x == y { 0 } else { 255 }]));
+        let options = MonochromeOptions::default().set_bit_depth(MonochromeDepth::One);
+
+        let pixels = binarize(&image, &options);
+        assert_eq!(pixels.stride(), 2);
+        assert_eq!(pixels.pixels, [0b1000_0000, 0, 0b0100_0000, 0]);
+        assert_eq!(pixels.is_black(1, 1), Some(true));
+        assert_eq!(pixels.is_black(9, 1), Some(false));
+        assert_eq!(pixels.is_black(10, 1), None);
+    }
+
+    /// Tests dithering keeps the tone of a mid gray that a threshold loses
+    #[test]
+    fn test_dithering() {
+        let gray = GrayImage::from_pixel(32, 32, Luma([128]));
+
+        let threshold = MonochromeOptions::default();
+        assert_eq!(black_fraction(&gray, &threshold), 0.0);
+
+        for binarization in [
+            Binarization::FloydSteinberg,
+            Binarization::Ordered,
+            Binarization::Atkinson,
+        ] {
+            let options = MonochromeOptions::default().set_binarization(binarization);
+            let fraction = black_fraction(&gray, &options);
+            assert!(
+                (0.35..=0.65).contains(&fraction),
+                "{binarization:?} gave {fraction}"
+            );
+        }
+
+        // Solid colors stay solid
+        let white = GrayImage::from_pixel(8, 8, Luma([255]));
+        let options = MonochromeOptions::default().set_binarization(Binarization::FloydSteinberg);
+        assert_eq!(black_fraction(&white, &options), 0.0);
+    }
+}
@@ -0,0 +1,282 @@
+//! Helpers for generating previews of PDF files
+//!
+//! * [generate_preview] - Gets the info, page thumbnails and a text excerpt in one call
+
+use futures_util::future::{try_join, try_join_all};
+use thiserror::Error;
+
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+use crate::image_crate::DynamicImage;
+use crate::{
+    degrade::{render_single_page_raw_degrading, Degradation, DegradePolicy},
+    image::{render_pages_raw, OutputFormat, PdfRenderError, RenderArgs},
+    info::{info_page_count, pdf_info, PdfInfo, PdfInfoArgs, PdfInfoError},
+    shared::Password,
+    text::{info_text_excerpt, PdfTextArgs, PdfTextError},
+    version::{render_provenance, PopplerVersionError, RenderProvenance},
+};
+
+/// Options for generating a preview
+pub struct PreviewOptions {
+    /// Args for reading the PDF info
+    pub info_args: PdfInfoArgs,
+    /// Args for rendering the thumbnails
+    pub render_args: RenderArgs,
+    /// Args for extracting the text
+    pub text_args: PdfTextArgs,
+    /// Format to render the thumbnails as
+    pub format: OutputFormat,
+    /// Maximum number of pages to render thumbnails for, starting
+    /// from the first page
+    pub max_thumbnails: u32,
+    /// Maximum number of characters of text to include
+    pub max_text_chars: usize,
+    /// Whether to decode the thumbnails into images
+    #[cfg(any(feature = "image", feature = "image-0-24"))]
+    pub decode: bool,
+    /// Whether to record the poppler version and render parameters
+    /// used for the thumbnails, see [Preview::provenance]
+    pub record_provenance: bool,
+    /// Optional policy for retrying thumbnails at lower resolutions when
+    /// they exceed the output size or memory limits, see [Preview::degraded]
+    pub degrade_policy: Option<DegradePolicy>,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> Self {
+        Self {
+            info_args: Default::default(),
+            render_args: Default::default(),
+            text_args: Default::default(),
+            format: Default::default(),
+            max_thumbnails: Self::DEFAULT_MAX_THUMBNAILS,
+            max_text_chars: Self::DEFAULT_MAX_TEXT_CHARS,
+            #[cfg(any(feature = "image", feature = "image-0-24"))]
+            decode: true,
+            record_provenance: false,
+            degrade_policy: None,
+        }
+    }
+}
+
+impl PreviewOptions {
+    pub const DEFAULT_MAX_THUMBNAILS: u32 = 1;
+    pub const DEFAULT_MAX_TEXT_CHARS: usize = 1000;
+
+    pub fn set_info_args(mut self, info_args: PdfInfoArgs) -> Self {
+        self.info_args = info_args;
+        self
+    }
+
+    pub fn set_render_args(mut self, render_args: RenderArgs) -> Self {
+        self.render_args = render_args;
+        self
+    }
+
+    pub fn set_text_args(mut self, text_args: PdfTextArgs) -> Self {
+        self.text_args = text_args;
+        self
+    }
+
+    /// Sets the password used by all the stages of the preview
+    pub fn set_password(mut self, password: Password) -> Self {
+        self.info_args = self.info_args.set_password(password.clone());
+        self.render_args = self.render_args.set_password(password.clone());
+        self.text_args = self.text_args.set_password(password);
+        self
+    }
+
+    pub fn set_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn set_max_thumbnails(mut self, max_thumbnails: u32) -> Self {
+        self.max_thumbnails = max_thumbnails;
+        self
+    }
+
+    pub fn set_max_text_chars(mut self, max_text_chars: usize) -> Self {
+        self.max_text_chars = max_text_chars;
+        self
+    }
+
+    #[cfg(any(feature = "image", feature = "image-0-24"))]
+    pub fn set_decode(mut self, decode: bool) -> Self {
+        self.decode = decode;
+        self
+    }
+
+    pub fn set_record_provenance(mut self, record_provenance: bool) -> Self {
+        self.record_provenance = record_provenance;
+        self
+    }
+
+    pub fn set_degrade_policy(mut self, degrade_policy: DegradePolicy) -> Self {
+        self.degrade_policy = Some(degrade_policy);
+        self
+    }
+
+    /// Format the thumbnails are rendered as, decoded thumbnails skip
+    /// re-encoding formats that pdftocairo cannot produce
+    fn thumbnail_format(&self) -> OutputFormat {
+        #[cfg(any(feature = "image", feature = "image-0-24"))]
+        if self.decode {
+            return self.format.render_format();
+        }
+
+        self.format
+    }
+}
+
+/// Rendered thumbnail, decoded when [PreviewOptions::decode] is
+/// enabled otherwise the encoded output from pdftocairo
+#[derive(Debug, Clone)]
+pub enum ImageOrBytes {
+    #[cfg(any(feature = "image", feature = "image-0-24"))]
+    Image(DynamicImage),
+    Bytes(Vec<u8>),
+}
+
+/// Preview of a PDF file
+#[derive(Debug)]
+pub struct Preview {
+    /// Info for the PDF
+    pub info: PdfInfo,
+    /// Thumbnails for the first pages
+    pub thumbnails: Vec<ImageOrBytes>,
+    /// Text from the start of the PDF
+    pub text_excerpt: String,
+    /// Poppler version and render parameters used for the thumbnails
+    /// when [PreviewOptions::record_provenance] is enabled
+    pub provenance: Option<RenderProvenance>,
+    /// Thumbnails rendered at a lower resolution than requested when
+    /// [PreviewOptions::degrade_policy] is set
+    pub degraded: Vec<Degradation>,
+}
+
+#[derive(Debug, Error)]
+pub enum PreviewError {
+    #[error(transparent)]
+    Info(#[from] PdfInfoError),
+
+    #[error(transparent)]
+    Render(#[from] PdfRenderError),
+
+    #[error(transparent)]
+    Text(#[from] PdfTextError),
+
+    #[error(transparent)]
+    Version(#[from] PopplerVersionError),
+}
+
+/// Generates a preview of the provided PDF, reads the info and then
+/// renders the thumbnails and extracts the text concurrently
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * options - Options for the preview
+pub async fn generate_preview(
+    data: &[u8],
+    options: &PreviewOptions,
+) -> Result<Preview, PreviewError> {
+    let info = pdf_info(data, &options.info_args).await?;
+
+    let page_count = info_page_count(&info)
+        .and_then(|page_count| options.render_args.empty_document.check(page_count))
+        .map_err(PdfRenderError::from)?;
+    let pages: Vec<u32> = (1..=page_count.min(options.max_thumbnails)).collect();
+
+    let (thumbnails, text_excerpt) = try_join(
+        async {
+            render_thumbnails(data, &info, pages, options)
+                .await
+                .map_err(PreviewError::from)
+        },
+        async {
+            info_text_excerpt(data, &info, options.max_text_chars, &options.text_args)
+                .await
+                .map_err(PreviewError::from)
+        },
+    )
+    .await?;
+
+    let (thumbnails, degraded) = thumbnails;
+    let thumbnails = thumbnails
+        .into_iter()
+        .map(|bytes| thumbnail(bytes, options))
+        .collect::<Result<_, _>>()?;
+
+    let provenance = if options.record_provenance {
+        Some(render_provenance(options.format, &options.render_args).await?)
+    } else {
+        None
+    };
+
+    Ok(Preview {
+        info,
+        thumbnails,
+        text_excerpt,
+        provenance,
+        degraded,
+    })
+}
+
+/// Renders the thumbnails, retrying each page at lower resolutions
+/// when a [PreviewOptions::degrade_policy] is set
+async fn render_thumbnails(
+    data: &[u8],
+    info: &PdfInfo,
+    pages: Vec<u32>,
+    options: &PreviewOptions,
+) -> Result<(Vec<Vec<u8>>, Vec<Degradation>), PdfRenderError> {
+    let Some(policy) = options.degrade_policy.as_ref() else {
+        let thumbnails = render_pages_raw(
+            data,
+            info,
+            options.thumbnail_format(),
+            pages,
+            &options.render_args,
+        )
+        .await?;
+        return Ok((thumbnails, Vec::new()));
+    };
+
+    let renders = try_join_all(pages.into_iter().map(|page| {
+        render_single_page_raw_degrading(
+            data,
+            info,
+            options.thumbnail_format(),
+            page,
+            &options.render_args,
+            policy,
+        )
+    }))
+    .await?;
+
+    let (thumbnails, degraded): (Vec<_>, Vec<_>) = renders
+        .into_iter()
+        .map(|render| (render.output, render.degradation))
+        .unzip();
+
+    Ok((thumbnails, degraded.into_iter().flatten().collect()))
+}
+
+/// Decodes the thumbnail bytes if decoding is enabled
+fn thumbnail(bytes: Vec<u8>, options: &PreviewOptions) -> Result<ImageOrBytes, PreviewError> {
+    #[cfg(any(feature = "image", feature = "image-0-24"))]
+    if options.decode {
+        let image = crate::image_crate::load_from_memory_with_format(
+            &bytes,
+            options.thumbnail_format().image_format(),
+        )
+        .map_err(PdfRenderError::Image)?;
+
+        return Ok(ImageOrBytes::Image(image));
+    }
+
+    #[cfg(not(any(feature = "image", feature = "image-0-24")))]
+    let _ = options;
+
+    Ok(ImageOrBytes::Bytes(bytes))
+}
@@ -0,0 +1,314 @@
+//! Helpers for probing the start of a PDF file without spawning any processes
+//!
+//! * [probe_pdf_header] - Reads the version and structure hints from the first bytes of a file
+//! * [quick_page_count] - Reads the page count from the page tree of well-formed files
+//!
+//! Only the first [PDF_PROBE_LENGTH] bytes are needed, allowing uploads to be
+//! checked or routed before the whole body has been received. The results
+//! are hints, use [pdf_info](crate::info::pdf_info) once the whole file is
+//! available for accurate details
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::shared::{has_encrypt_entry, has_pdf_header, PDF_HEADER_SEARCH_LENGTH};
+
+/// Recommended number of bytes from the start of the file to probe
+pub const PDF_PROBE_LENGTH: usize = 4 * 1024;
+
+/// Details found in the start of a PDF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PdfProbe {
+    /// Version from the PDF header (e.g. "1.7")
+    pub version: String,
+    /// Offset of the PDF header from the start of the file, non zero
+    /// when the file has leading junk bytes
+    pub header_offset: usize,
+    /// Whether a linearization dictionary was found, linearized files
+    /// are optimized for viewing before they are fully downloaded
+    pub linearized: bool,
+    /// Whether an encryption dictionary reference was found, for files
+    /// that are not linearized this is usually only found at the end of
+    /// the file so a false value does not mean the file is unencrypted
+    pub encrypted: bool,
+}
+
+/// Probes the first bytes of a PDF file for the version, linearization
+/// and encryption without spawning any processes. Returns [None] when
+/// the bytes do not start with a PDF header.
+///
+/// ## Arguments
+/// * prefix_bytes - The first bytes of the file, ideally at least [PDF_PROBE_LENGTH]
+pub fn probe_pdf_header(prefix_bytes: &[u8]) -> Option<PdfProbe> {
+    let prefix = &prefix_bytes[..prefix_bytes.len().min(PDF_PROBE_LENGTH)];
+    let header = &prefix[..prefix.len().min(PDF_HEADER_SEARCH_LENGTH)];
+
+    let header_offset = header.windows(5).position(|value| value == b"%PDF-")?;
+
+    let version: String = prefix[header_offset + 5..]
+        .iter()
+        .take_while(|value| value.is_ascii_digit() || **value == b'.')
+        .map(|value| *value as char)
+        .collect();
+
+    if version.is_empty() {
+        return None;
+    }
+
+    Some(PdfProbe {
+        version,
+        header_offset,
+        linearized: prefix.windows(11).any(|value| value == b"/Linearized"),
+        encrypted: has_encrypt_entry(prefix),
+    })
+}
+
+/// Experimental fast path reading the page count directly from the page
+/// tree (`/Root` -> `/Pages` -> `/Count`) without spawning pdfinfo.
+///
+/// Only works for well-formed files where the trailer, catalog and page
+/// tree objects are stored uncompressed, [None] is returned when any of
+/// these cannot be found. Files with object streams or cross reference
+/// streams always return [None] as an update stored in a compressed
+/// object would not be seen, as do files where the count is an indirect
+/// reference. Encrypted files always return [None] so their password
+/// requirements are checked by pdfinfo.
+/// [pdf_page_count](crate::info::pdf_page_count) falls back to pdfinfo
+/// when this fails
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+pub fn quick_page_count(data: &[u8]) -> Option<u32> {
+    if !has_pdf_header(data) || has_encrypt_entry(data) {
+        return None;
+    }
+
+    // Newer revisions of the catalog or page tree may be stored in object
+    // streams which cannot be read without decompressing them, matches
+    // both `/XRef` streams and the `/XRefStm` entry of hybrid files
+    if contains(data, b"/ObjStm") || contains(data, b"/XRef") {
+        return None;
+    }
+
+    // The last trailer is the most recent revision of the file when it
+    // has incremental updates
+    let root_start = rfind(data, b"/Root")?;
+    let root = parse_reference(&data[root_start + 5..])?;
+
+    let catalog = find_object(data, root)?;
+    let pages = parse_reference(dict_entry(catalog, b"/Pages")?)?;
+
+    let page_tree = find_object(data, pages)?;
+    let count = dict_entry(page_tree, b"/Count")?;
+
+    // The count is stored in another object (e.g. `/Count 5 0 R`)
+    if parse_reference(count).is_some() {
+        return None;
+    }
+
+    let (count, _) = take_integer(count)?;
+
+    Some(count)
+}
+
+/// Checks whether the data contains the needle
+fn contains(data: &[u8], needle: &[u8]) -> bool {
+    data.windows(needle.len()).any(|value| value == needle)
+}
+
+/// Finds the last position of the needle within the data
+fn rfind(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .rposition(|value| value == needle)
+}
+
+/// Skips the leading PDF whitespace characters
+fn skip_whitespace(data: &[u8]) -> &[u8] {
+    let start = data
+        .iter()
+        .position(|value| !is_whitespace(*value))
+        .unwrap_or(data.len());
+    &data[start..]
+}
+
+fn is_whitespace(value: u8) -> bool {
+    matches!(value, b' ' | b'\t' | b'\n' | b'\r' | b'\x0C' | b'\0')
+}
+
+/// Parses an unsigned integer from the start of the data after any
+/// whitespace, provides the remaining data after the integer
+fn take_integer(data: &[u8]) -> Option<(u32, &[u8])> {
+    let data = skip_whitespace(data);
+    let length = data
+        .iter()
+        .position(|value| !value.is_ascii_digit())
+        .unwrap_or(data.len());
+
+    let value = std::str::from_utf8(&data[..length]).ok()?.parse().ok()?;
+    Some((value, &data[length..]))
+}
+
+/// Parses an indirect reference in the format `12 0 R` from the start of the data
+fn parse_reference(data: &[u8]) -> Option<(u32, u32)> {
+    let (number, data) = take_integer(data)?;
+    let (generation, data) = take_integer(data)?;
+
+    if skip_whitespace(data).first() != Some(&b'R') {
+        return None;
+    }
+
+    Some((number, generation))
+}
+
+/// Finds the body of the last definition of the object, between
+/// `12 0 obj` and `endobj`
+fn find_object(data: &[u8], (number, generation): (u32, u32)) -> Option<&[u8]> {
+    let marker = format!("{number} {generation} obj");
+    let marker = marker.as_bytes();
+
+    // Find the last definition that is not the end of a longer object number
+    let start = data
+        .windows(marker.len())
+        .enumerate()
+        .filter(|(index, value)| {
+            *value == marker && (*index == 0 || !data[index - 1].is_ascii_digit())
+        })
+        .map(|(index, _)| index + marker.len())
+        .next_back()?;
+
+    let body = &data[start..];
+    let end = body
+        .windows(6)
+        .position(|value| value == b"endobj")
+        .unwrap_or(body.len());
+
+    Some(&body[..end])
+}
+
+/// Finds the value following the provided key in a dictionary, the key
+/// must not be the start of a longer name (e.g. `/Pages` and `/PageMode`)
+fn dict_entry<'a>(dict: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    dict.windows(key.len())
+        .enumerate()
+        .filter(|(index, value)| {
+            *value == key
+                && dict
+                    .get(index + key.len())
+                    .is_some_and(|next| !next.is_ascii_alphanumeric())
+        })
+        .map(|(index, _)| &dict[index + key.len()..])
+        .next()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_object, parse_reference, probe_pdf_header, quick_page_count, PdfProbe};
+
+    /// Tests probing the header of a linearized encrypted file
+    #[test]
+    fn test_probe_linearized() {
+        let data = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n1 0 obj\n<</Linearized 1/L 1234/O 3/E 100/N 2/T 1000/H [ 500 120]>>\nendobj\n\
+            trailer\n<</Size 10/Encrypt 9 0 R/Root 2 0 R>>\n";
+
+        assert_eq!(
+            probe_pdf_header(data),
+            Some(PdfProbe {
+                version: "1.7".to_string(),
+                header_offset: 0,
+                linearized: true,
+                encrypted: true,
+            })
+        );
+    }
+
+    /// Tests probing a file with leading bytes before the header
+    #[test]
+    fn test_probe_leading_bytes() {
+        let probe = probe_pdf_header(b"junk\n%PDF-2.0\n1 0 obj\n<</Type/Catalog>>").unwrap();
+
+        assert_eq!(probe.version, "2.0");
+        assert_eq!(probe.header_offset, 5);
+        assert!(!probe.linearized);
+        assert!(!probe.encrypted);
+    }
+
+    /// Tests files without a header are rejected
+    #[test]
+    fn test_probe_not_pdf() {
+        assert_eq!(probe_pdf_header(b"Not a pdf file"), None);
+        assert_eq!(probe_pdf_header(b"%PDF-"), None);
+        assert_eq!(probe_pdf_header(b""), None);
+    }
+
+    /// Tests reading the page count from the page tree, using the most
+    /// recent definition of the objects
+    #[test]
+    fn test_quick_page_count() {
+        let data =
+            b"%PDF-1.4\n1 0 obj\n<</Type /Catalog /PageMode /UseNone /Pages 2 0 R>>\nendobj\n\
+            2 0 obj\n<</Type /Pages /Kids [3 0 R] /Count 1>>\nendobj\n\
+            12 0 obj\n<</Type /Pages /Count 7>>\nendobj\n\
+            trailer\n<</Size 4 /Root 1 0 R>>\n\
+            2 0 obj\n<</Type /Pages /Kids [3 0 R 4 0 R] /Count 2>>\nendobj\n\
+            trailer\n<</Size 5 /Root 1 0 R /Prev 9>>\n%%EOF";
+
+        assert_eq!(quick_page_count(data), Some(2));
+        assert_eq!(
+            find_object(data, (12, 0)),
+            Some(&b"\n<</Type /Pages /Count 7>>\n"[..])
+        );
+        assert_eq!(parse_reference(b" 12 0 R>>"), Some((12, 0)));
+        assert_eq!(parse_reference(b" 12 0 obj"), None);
+    }
+
+    /// Tests files that cannot be read fall back
+    #[test]
+    fn test_quick_page_count_unavailable() {
+        // Catalog in a compressed object stream
+        assert_eq!(
+            quick_page_count(b"%PDF-1.5\n5 0 obj\n<</Type /XRef /Root 1 0 R>>\nstream\n"),
+            None
+        );
+        // Encrypted file
+        assert_eq!(
+            quick_page_count(b"%PDF-1.4\ntrailer\n<</Root 1 0 R /Encrypt 2 0 R>>"),
+            None
+        );
+        assert_eq!(quick_page_count(b"Not a pdf file"), None);
+    }
+
+    /// Tests incremental updates stored in object streams fall back
+    /// instead of using the outdated uncompressed page tree
+    #[test]
+    fn test_quick_page_count_object_stream_update() {
+        let data = b"%PDF-1.5\n1 0 obj\n<</Type /Catalog /Pages 2 0 R>>\nendobj\n\
+            2 0 obj\n<</Type /Pages /Kids [3 0 R] /Count 1>>\nendobj\n\
+            trailer\n<</Size 4 /Root 1 0 R>>\n\
+            6 0 obj\n<</Type /ObjStm /N 2 /First 10 /Length 80>>\nstream\n\
+            endstream\nendobj\n\
+            7 0 obj\n<</Type /XRef /Size 8 /Root 1 0 R /Prev 9>>\nstream\n\
+            endstream\nendobj\n%%EOF";
+
+        assert_eq!(quick_page_count(data), None);
+
+        // Hybrid file with a cross reference stream for the update
+        let data = b"%PDF-1.5\n1 0 obj\n<</Type /Catalog /Pages 2 0 R>>\nendobj\n\
+            2 0 obj\n<</Type /Pages /Kids [3 0 R] /Count 1>>\nendobj\n\
+            trailer\n<</Size 4 /Root 1 0 R /XRefStm 120>>\n%%EOF";
+
+        assert_eq!(quick_page_count(data), None);
+    }
+
+    /// Tests a count stored in another object falls back instead of
+    /// reading the object number as the count
+    #[test]
+    fn test_quick_page_count_indirect_count() {
+        let data = b"%PDF-1.4\n1 0 obj\n<</Type /Catalog /Pages 2 0 R>>\nendobj\n\
+            2 0 obj\n<</Type /Pages /Kids [3 0 R] /Count 5 0 R>>\nendobj\n\
+            5 0 obj\n1\nendobj\n\
+            trailer\n<</Size 6 /Root 1 0 R>>\n%%EOF";
+
+        assert_eq!(quick_page_count(data), None);
+    }
+}
@@ -0,0 +1,1147 @@
+//! Processor carrying default args and tracking the operations that are currently running
+//!
+//! * [PdfProcessor] - Runs operations with a [ProcessorConfig] while keeping a handle to each in-flight job
+//!
+//! Every operation started through a [PdfProcessor] is registered as a job
+//! until it completes or is dropped, the active jobs can be listed with
+//! [PdfProcessor::active_jobs] and aborted with [PdfProcessor::abort]
+//!
+//! Jobs that outlive a deadline (e.g. a tool stuck on a malformed file
+//! without a timeout) can be reaped with [PdfProcessor::reap_stragglers]
+//! or periodically using an opt-in [PdfProcessor::spawn_janitor]. The
+//! temporary directories left behind by crashed runs are removed with
+//! [PdfProcessor::sweep_temp_dirs]
+//!
+//! Pages that are likely to be viewed next can be rendered ahead of time
+//! using [PdfProcessor::prefetch], the output is kept in a bounded cache
+//! used by [PdfProcessor::render_single_page_raw]
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Display, Formatter},
+    fs,
+    future::Future,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use futures_util::future::{AbortHandle, Abortable};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use tokio::{sync::Semaphore, task::JoinHandle};
+
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+use crate::{image::render_single_page, image_crate::DynamicImage};
+use crate::{
+    image::{render_pages_raw, render_single_page_raw, OutputFormat, PdfRenderError, RenderArgs},
+    info::{pdf_info, PdfInfo, PdfInfoArgs, PdfInfoError},
+    shared::{ProcessPriority, ToolError, TEMP_DIR_PREFIX},
+    text::{text_all_pages, text_pages, text_single_page, PdfTextArgs, PdfTextError},
+};
+
+/// Unique identifier for a job within a [PdfProcessor]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct JobId(u64);
+
+impl Display for JobId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Details about an in-flight job
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct JobInfo {
+    /// Identifier for the job, used to abort the job
+    pub id: JobId,
+    /// Identifier for the document provided by the caller
+    pub document_id: String,
+    /// Poppler tool used by the job
+    pub tool: &'static str,
+    /// Page the job is working on, [None] for jobs covering
+    /// multiple pages or the whole document
+    pub page: Option<u32>,
+    /// When the job was started
+    pub started_at: SystemTime,
+}
+
+/// Registered job along with the handle used to abort it
+struct ActiveJob {
+    info: JobInfo,
+    abort: AbortHandle,
+}
+
+/// Counters for the jobs reaped by [PdfProcessor::reap_stragglers] and
+/// the directories removed by [PdfProcessor::sweep_temp_dirs]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct JanitorStats {
+    /// Number of times the stragglers were reaped
+    pub runs: u64,
+    /// Total number of jobs aborted for running past their deadline,
+    /// the child process of each is killed
+    pub reaped_jobs: u64,
+    /// Total number of stale temporary directories removed
+    pub removed_dirs: u64,
+    /// Total size in bytes of the files within the removed directories
+    pub reclaimed_bytes: u64,
+}
+
+#[derive(Default)]
+struct JobRegistry {
+    /// Identifier for the next job
+    next_id: u64,
+    /// Jobs that are currently running
+    jobs: HashMap<JobId, ActiveJob>,
+    /// Counters for the reaped jobs
+    janitor: JanitorStats,
+}
+
+impl JobRegistry {
+    /// Aborts the jobs that were started at least `max_age` ago
+    fn reap(&mut self, max_age: Duration) -> usize {
+        let now = SystemTime::now();
+
+        let reaped = self
+            .jobs
+            .values()
+            .filter(|job| {
+                now.duration_since(job.info.started_at)
+                    .is_ok_and(|age| age >= max_age)
+            })
+            .inspect(|job| job.abort.abort())
+            .count();
+
+        self.janitor.runs += 1;
+        self.janitor.reaped_jobs += reaped as u64;
+        reaped
+    }
+
+    /// Adds the directories removed by a sweep to the counters
+    fn record_sweep(&mut self, sweep: TempSweep) {
+        self.janitor.removed_dirs += sweep.removed_dirs;
+        self.janitor.reclaimed_bytes += sweep.reclaimed_bytes;
+    }
+}
+
+/// Directories removed by a single sweep of the temporary directory
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct TempSweep {
+    removed_dirs: u64,
+    reclaimed_bytes: u64,
+}
+
+/// Removes the directories within `directory` starting with the
+/// [TEMP_DIR_PREFIX] that were last modified at least `max_age` ago
+///
+/// ## Arguments
+/// * directory - The directory to search, usually [std::env::temp_dir]
+/// * max_age - How long since a directory was modified before it is removed
+fn sweep_temp_dirs(directory: &Path, max_age: Duration) -> TempSweep {
+    let mut sweep = TempSweep::default();
+    let Ok(entries) = fs::read_dir(directory) else {
+        return sweep;
+    };
+
+    let now = SystemTime::now();
+
+    for entry in entries.flatten() {
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(TEMP_DIR_PREFIX)
+        {
+            continue;
+        }
+
+        // Symlinks are not followed so only directories created by
+        // the tools are removed
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        let stale = metadata.is_dir()
+            && metadata
+                .modified()
+                .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() >= max_age);
+
+        if !stale {
+            continue;
+        }
+
+        let path = entry.path();
+        let size = directory_size(&path);
+
+        if fs::remove_dir_all(&path).is_ok() {
+            sweep.removed_dirs += 1;
+            sweep.reclaimed_bytes += size;
+        }
+    }
+
+    sweep
+}
+
+/// Total size of the files within the directory and its subdirectories
+fn directory_size(directory: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some(if metadata.is_dir() {
+                directory_size(&entry.path())
+            } else {
+                metadata.len()
+            })
+        })
+        .sum()
+}
+
+/// Rendered page kept in the [RenderCache]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    document_id: String,
+    page: u32,
+    format: OutputFormat,
+    /// Program and arguments the page was rendered with
+    program: &'static str,
+    args: Vec<String>,
+}
+
+impl CacheKey {
+    fn new(document_id: &str, page: u32, format: OutputFormat, args: &RenderArgs) -> Self {
+        Self {
+            document_id: document_id.to_string(),
+            page,
+            format,
+            program: args.backend.program(),
+            args: args.build_args(),
+        }
+    }
+}
+
+/// Pages rendered by [PdfProcessor::prefetch], the least recently used
+/// pages are evicted once the capacity is reached
+#[derive(Default)]
+struct RenderCache {
+    pages: HashMap<CacheKey, Vec<u8>>,
+    /// Keys from the least to the most recently used
+    order: VecDeque<CacheKey>,
+}
+
+impl RenderCache {
+    fn get(&mut self, key: &CacheKey) -> Option<Vec<u8>> {
+        let output = self.pages.get(key)?.clone();
+        self.touch(key);
+        Some(output)
+    }
+
+    fn insert(&mut self, key: CacheKey, output: Vec<u8>, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+
+        if self.pages.insert(key.clone(), output).is_some() {
+            self.touch(&key);
+            return;
+        }
+
+        self.order.push_back(key);
+
+        while self.order.len() > capacity {
+            if let Some(key) = self.order.pop_front() {
+                self.pages.remove(&key);
+            }
+        }
+    }
+
+    /// Moves the key to the most recently used position
+    fn touch(&mut self, key: &CacheKey) {
+        if let Some(index) = self.order.iter().position(|value| value == key) {
+            if let Some(key) = self.order.remove(index) {
+                self.order.push_back(key);
+            }
+        }
+    }
+
+    /// Removes the pages for the document, returns the number of pages removed
+    fn remove_document(&mut self, document_id: &str) -> usize {
+        let before = self.pages.len();
+        self.pages.retain(|key, _| key.document_id != document_id);
+        self.order.retain(|key| key.document_id != document_id);
+        before - self.pages.len()
+    }
+}
+
+/// Default number of rendered pages kept by [PdfProcessor::prefetch]
+pub const DEFAULT_RENDER_CACHE_SIZE: usize = 16;
+
+/// Defaults used by the operations of a [PdfProcessor]
+#[derive(Debug, Clone, Default)]
+pub struct ProcessorConfig {
+    /// Args used for rendering
+    pub render_args: RenderArgs,
+    /// Args used for extracting text
+    pub text_args: PdfTextArgs,
+    /// Args used for reading the PDF info
+    pub info_args: PdfInfoArgs,
+    /// Timeout applied to any of the args that do not set their own
+    pub timeout: Option<Duration>,
+    /// Maximum number of operations that can run at the same time,
+    /// further operations wait for a running operation to finish
+    pub max_concurrency: Option<usize>,
+    /// Priority applied to any of the args that do not set their own, use
+    /// [ProcessPriority::background] for processors running batch jobs
+    pub priority: Option<ProcessPriority>,
+    /// Maximum number of rendered pages kept by [PdfProcessor::prefetch],
+    /// defaults to [DEFAULT_RENDER_CACHE_SIZE]
+    pub render_cache_size: Option<usize>,
+    /// How long since a temporary directory was modified before the
+    /// janitor removes it, the janitor leaves the temporary directories
+    /// alone when not set. Should be longer than any render takes
+    pub temp_dir_max_age: Option<Duration>,
+}
+
+impl ProcessorConfig {
+    pub fn set_render_args(mut self, render_args: RenderArgs) -> Self {
+        self.render_args = render_args;
+        self
+    }
+
+    pub fn set_text_args(mut self, text_args: PdfTextArgs) -> Self {
+        self.text_args = text_args;
+        self
+    }
+
+    pub fn set_info_args(mut self, info_args: PdfInfoArgs) -> Self {
+        self.info_args = info_args;
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    pub fn set_priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn set_render_cache_size(mut self, render_cache_size: usize) -> Self {
+        self.render_cache_size = Some(render_cache_size);
+        self
+    }
+
+    pub fn set_temp_dir_max_age(mut self, temp_dir_max_age: Duration) -> Self {
+        self.temp_dir_max_age = Some(temp_dir_max_age);
+        self
+    }
+}
+
+/// Runs operations on PDF files using a shared set of default args while
+/// tracking each of the in-flight jobs, cloning the processor shares the
+/// same set of jobs and concurrency limit
+#[derive(Clone, Default)]
+pub struct PdfProcessor {
+    registry: Arc<Mutex<JobRegistry>>,
+    /// Defaults for the operations
+    config: Arc<ProcessorConfig>,
+    /// Limits the number of operations running at once
+    limiter: Option<Arc<Semaphore>>,
+    /// Pages rendered ahead of time
+    cache: Arc<Mutex<RenderCache>>,
+}
+
+impl std::fmt::Debug for PdfProcessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PdfProcessor")
+            .field("config", &self.config)
+            .field("active_jobs", &self.active_jobs().len())
+            .finish()
+    }
+}
+
+/// Removes the job from the registry once the operation completes
+/// or is dropped
+struct JobGuard<'a> {
+    registry: &'a Mutex<JobRegistry>,
+    id: JobId,
+}
+
+impl Drop for JobGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.jobs.remove(&self.id);
+        }
+    }
+}
+
+impl PdfProcessor {
+    /// Creates a new processor with no active jobs using the default config
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new processor with no active jobs using the provided config
+    pub fn from_config(config: ProcessorConfig) -> Self {
+        let limiter = config
+            .max_concurrency
+            .map(|max_concurrency| Arc::new(Semaphore::new(max_concurrency.max(1))));
+
+        Self {
+            registry: Default::default(),
+            config: Arc::new(config),
+            limiter,
+            cache: Default::default(),
+        }
+    }
+
+    /// Creates a processor using a different config that shares the active
+    /// jobs, concurrency limit and render cache of this processor, for
+    /// operations that need different args
+    pub fn with_config(&self, config: ProcessorConfig) -> Self {
+        Self {
+            registry: self.registry.clone(),
+            config: Arc::new(config),
+            limiter: self.limiter.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+
+    pub fn config(&self) -> &ProcessorConfig {
+        &self.config
+    }
+
+    /// Gets the configured render args with the default timeout and priority applied
+    pub fn render_args(&self) -> RenderArgs {
+        let mut args = self.config.render_args.clone();
+        args.timeout = args.timeout.or(self.config.timeout);
+        args.priority = args.priority.or(self.config.priority);
+        args
+    }
+
+    /// Gets the configured text args with the default timeout and priority applied
+    pub fn text_args(&self) -> PdfTextArgs {
+        let mut args = self.config.text_args.clone();
+        args.timeout = args.timeout.or(self.config.timeout);
+        args.priority = args.priority.or(self.config.priority);
+        args
+    }
+
+    /// Gets the configured info args with the default timeout and priority applied
+    pub fn info_args(&self) -> PdfInfoArgs {
+        let mut args = self.config.info_args.clone();
+        args.timeout = args.timeout.or(self.config.timeout);
+        args.priority = args.priority.or(self.config.priority);
+        args
+    }
+
+    /// Gets the details of all the jobs that are currently running,
+    /// ordered by the time they were started
+    pub fn active_jobs(&self) -> Vec<JobInfo> {
+        let registry = match self.registry.lock() {
+            Ok(value) => value,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut jobs: Vec<JobInfo> = registry.jobs.values().map(|job| job.info.clone()).collect();
+        jobs.sort_by_key(|job| job.id);
+        jobs
+    }
+
+    /// Aborts the job with the provided ID, the operation fails with an
+    /// aborted error and its child process is killed. Returns false if
+    /// the job is no longer running
+    pub fn abort(&self, id: JobId) -> bool {
+        let registry = match self.registry.lock() {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+
+        match registry.jobs.get(&id) {
+            Some(job) => {
+                job.abort.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Aborts all the jobs for the provided document, returns the
+    /// number of jobs that were aborted
+    pub fn abort_document(&self, document_id: &str) -> usize {
+        let registry = match self.registry.lock() {
+            Ok(value) => value,
+            Err(_) => return 0,
+        };
+
+        registry
+            .jobs
+            .values()
+            .filter(|job| job.info.document_id == document_id)
+            .inspect(|job| job.abort.abort())
+            .count()
+    }
+
+    /// Aborts all the jobs that have been running for at least `max_age`,
+    /// returns the number of jobs that were aborted
+    ///
+    /// ## Arguments
+    /// * max_age - How long a job may run before it is aborted
+    pub fn reap_stragglers(&self, max_age: Duration) -> usize {
+        match self.registry.lock() {
+            Ok(mut registry) => registry.reap(max_age),
+            Err(_) => 0,
+        }
+    }
+
+    /// Removes the temporary directories starting with the [TEMP_DIR_PREFIX]
+    /// that were last modified at least `max_age` ago, these are left behind
+    /// when a process is killed while rendering. Returns the number of
+    /// directories that were removed
+    ///
+    /// Blocks while the directories are removed, the janitor runs this on
+    /// the blocking thread pool when [ProcessorConfig::temp_dir_max_age] is set
+    ///
+    /// ## Arguments
+    /// * max_age - How long since a directory was modified before it is removed
+    pub fn sweep_temp_dirs(&self, max_age: Duration) -> usize {
+        let sweep = sweep_temp_dirs(&std::env::temp_dir(), max_age);
+
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.record_sweep(sweep);
+        }
+
+        sweep.removed_dirs as usize
+    }
+
+    /// Counters for the jobs aborted by [PdfProcessor::reap_stragglers],
+    /// the directories removed by [PdfProcessor::sweep_temp_dirs] and the janitor
+    pub fn janitor_stats(&self) -> JanitorStats {
+        match self.registry.lock() {
+            Ok(registry) => registry.janitor,
+            Err(_) => JanitorStats::default(),
+        }
+    }
+
+    /// Spawns a background task on the tokio runtime that reaps jobs
+    /// running for at least `max_age` every `interval`, also removes the
+    /// stale temporary directories when [ProcessorConfig::temp_dir_max_age]
+    /// is set. The task stops once all the clones of the processor are
+    /// dropped, or when the returned handle is aborted
+    ///
+    /// ## Arguments
+    /// * interval - How often to check for stragglers
+    /// * max_age - How long a job may run before it is aborted
+    pub fn spawn_janitor(&self, interval: Duration, max_age: Duration) -> JoinHandle<()> {
+        let registry = Arc::downgrade(&self.registry);
+        let temp_dir_max_age = self.config.temp_dir_max_age;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+
+            loop {
+                interval.tick().await;
+
+                let Some(registry) = registry.upgrade() else {
+                    break;
+                };
+
+                if let Ok(mut registry) = registry.lock() {
+                    registry.reap(max_age);
+                };
+
+                if let Some(temp_dir_max_age) = temp_dir_max_age {
+                    let sweep = tokio::task::spawn_blocking(move || {
+                        sweep_temp_dirs(&std::env::temp_dir(), temp_dir_max_age)
+                    })
+                    .await
+                    .unwrap_or_default();
+
+                    if let Ok(mut registry) = registry.lock() {
+                        registry.record_sweep(sweep);
+                    };
+                }
+            }
+        })
+    }
+
+    /// Runs the provided operation as a tracked job, waits for the
+    /// concurrency limit before the job is started
+    ///
+    /// ## Arguments
+    /// * document_id - Identifier for the document
+    /// * tool - Poppler tool used by the operation
+    /// * page - Page the operation is working on
+    /// * operation - The operation to run
+    pub(crate) async fn track<T, E, F>(
+        &self,
+        document_id: &str,
+        tool: &'static str,
+        page: Option<u32>,
+        operation: F,
+    ) -> Result<T, E>
+    where
+        E: From<ToolError>,
+        F: Future<Output = Result<T, E>>,
+    {
+        let _permit = match self.limiter.as_ref() {
+            Some(limiter) => Some(
+                limiter
+                    .acquire()
+                    .await
+                    .map_err(|_| ToolError::Task("processor is closed".to_string()))?,
+            ),
+            None => None,
+        };
+
+        let (abort, registration) = AbortHandle::new_pair();
+
+        let id = {
+            let mut registry = self
+                .registry
+                .lock()
+                .map_err(|_| ToolError::Task("job registry is poisoned".to_string()))?;
+
+            let id = JobId(registry.next_id);
+            registry.next_id += 1;
+            registry.jobs.insert(
+                id,
+                ActiveJob {
+                    info: JobInfo {
+                        id,
+                        document_id: document_id.to_string(),
+                        tool,
+                        page,
+                        started_at: SystemTime::now(),
+                    },
+                    abort,
+                },
+            );
+            id
+        };
+
+        let _guard = JobGuard {
+            registry: &self.registry,
+            id,
+        };
+
+        Abortable::new(operation, registration)
+            .await
+            .map_err(|_| ToolError::Aborted)?
+    }
+
+    /// Tracked version of [pdf_info] using the configured args
+    pub async fn pdf_info(&self, document_id: &str, data: &[u8]) -> Result<PdfInfo, PdfInfoError> {
+        let args = self.info_args();
+        self.track(document_id, "pdfinfo", None, pdf_info(data, &args))
+            .await
+    }
+
+    /// Tracked version of [render_single_page] using the configured args
+    #[cfg(any(feature = "image", feature = "image-0-24"))]
+    pub async fn render_single_page(
+        &self,
+        document_id: &str,
+        data: &[u8],
+        info: &PdfInfo,
+        format: OutputFormat,
+        page: u32,
+    ) -> Result<DynamicImage, PdfRenderError> {
+        let args = self.render_args();
+        self.track(
+            document_id,
+            args.backend.program(),
+            Some(page),
+            render_single_page(data, info, format, page, &args),
+        )
+        .await
+    }
+
+    /// Renders the pages in the background keeping the output in a bounded
+    /// cache, used to render the pages that are likely to be viewed next.
+    /// The pages are rendered one at a time at [ProcessPriority::background]
+    /// as tracked jobs, pages that are already cached are skipped and pages
+    /// that fail to render are not cached
+    ///
+    /// Use [PdfProcessor::cached_page] to get a rendered page, or
+    /// [PdfProcessor::render_single_page_raw] which uses the cache when
+    /// the configured render args were used
+    ///
+    /// ## Arguments
+    /// * document_id - Identifier for the document
+    /// * data - The raw PDF file bytes
+    /// * info - The PDF info
+    /// * pages - The pages to render
+    /// * format - The output format to render
+    /// * args - Args to render with, the priority is replaced
+    pub fn prefetch(
+        &self,
+        document_id: &str,
+        data: Arc<[u8]>,
+        info: &PdfInfo,
+        pages: Vec<u32>,
+        format: OutputFormat,
+        args: &RenderArgs,
+    ) -> JoinHandle<()> {
+        let processor = self.clone();
+        let document_id = document_id.to_string();
+        let info = info.clone();
+        let args = args.clone().set_priority(ProcessPriority::background());
+
+        tokio::spawn(async move {
+            for page in pages {
+                let key = CacheKey::new(&document_id, page, format, &args);
+                if processor.cache_get(&key).is_some() {
+                    continue;
+                }
+
+                let output = processor
+                    .track(
+                        &document_id,
+                        args.backend.program(),
+                        Some(page),
+                        render_single_page_raw(&data, &info, format, page, &args),
+                    )
+                    .await;
+
+                if let Ok(output) = output {
+                    processor.cache_insert(key, output);
+                }
+            }
+        })
+    }
+
+    /// Gets a page rendered by [PdfProcessor::prefetch] with the same
+    /// format and args, the priority of the args is ignored
+    ///
+    /// ## Arguments
+    /// * document_id - Identifier for the document
+    /// * page - The rendered page
+    /// * format - The format the page was rendered as
+    /// * args - The args the page was rendered with
+    pub fn cached_page(
+        &self,
+        document_id: &str,
+        page: u32,
+        format: OutputFormat,
+        args: &RenderArgs,
+    ) -> Option<Vec<u8>> {
+        self.cache_get(&CacheKey::new(document_id, page, format, args))
+    }
+
+    /// Removes the cached pages for the provided document, returns
+    /// the number of pages that were removed
+    pub fn clear_cached_pages(&self, document_id: &str) -> usize {
+        match self.cache.lock() {
+            Ok(mut cache) => cache.remove_document(document_id),
+            Err(_) => 0,
+        }
+    }
+
+    fn cache_get(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        self.cache.lock().ok()?.get(key)
+    }
+
+    fn cache_insert(&self, key: CacheKey, output: Vec<u8>) {
+        let capacity = self
+            .config
+            .render_cache_size
+            .unwrap_or(DEFAULT_RENDER_CACHE_SIZE);
+
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(key, output, capacity);
+        }
+    }
+
+    /// Tracked version of [render_single_page_raw] using the configured args,
+    /// uses the page from [PdfProcessor::prefetch] when it was rendered with
+    /// the configured args
+    pub async fn render_single_page_raw(
+        &self,
+        document_id: &str,
+        data: &[u8],
+        info: &PdfInfo,
+        format: OutputFormat,
+        page: u32,
+    ) -> Result<Vec<u8>, PdfRenderError> {
+        let args = self.render_args();
+        if let Some(output) = self.cached_page(document_id, page, format, &args) {
+            return Ok(output);
+        }
+
+        self.track(
+            document_id,
+            args.backend.program(),
+            Some(page),
+            render_single_page_raw(data, info, format, page, &args),
+        )
+        .await
+    }
+
+    /// Tracked version of [render_pages_raw] using the configured args
+    pub async fn render_pages_raw(
+        &self,
+        document_id: &str,
+        data: &[u8],
+        info: &PdfInfo,
+        format: OutputFormat,
+        pages: Vec<u32>,
+    ) -> Result<Vec<Vec<u8>>, PdfRenderError> {
+        let args = self.render_args();
+        self.track(
+            document_id,
+            args.backend.program(),
+            None,
+            render_pages_raw(data, info, format, pages, &args),
+        )
+        .await
+    }
+
+    /// Tracked version of [text_single_page] using the configured args
+    pub async fn text_single_page(
+        &self,
+        document_id: &str,
+        data: &[u8],
+        info: &PdfInfo,
+        page: u32,
+    ) -> Result<String, PdfTextError> {
+        let args = self.text_args();
+        self.track(
+            document_id,
+            "pdftotext",
+            Some(page),
+            text_single_page(data, info, page, &args),
+        )
+        .await
+    }
+
+    /// Tracked version of [text_pages] using the configured args
+    pub async fn text_pages(
+        &self,
+        document_id: &str,
+        data: &[u8],
+        info: &PdfInfo,
+        pages: Vec<u32>,
+    ) -> Result<Vec<String>, PdfTextError> {
+        let args = self.text_args();
+        self.track(
+            document_id,
+            "pdftotext",
+            None,
+            text_pages(data, info, pages, &args),
+        )
+        .await
+    }
+
+    /// Tracked version of [text_all_pages] using the configured args
+    pub async fn text_all_pages(
+        &self,
+        document_id: &str,
+        data: &[u8],
+    ) -> Result<String, PdfTextError> {
+        let args = self.text_args();
+        self.track(document_id, "pdftotext", None, text_all_pages(data, &args))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{future::pending, time::Duration};
+
+    use tokio::task::{yield_now, JoinHandle};
+
+    use super::{
+        sweep_temp_dirs, CacheKey, JanitorStats, JobRegistry, PdfProcessor, ProcessorConfig,
+        TempSweep,
+    };
+    use crate::shared::TEMP_DIR_PREFIX;
+    use crate::{
+        image::{OutputFormat, RenderArgs, Resolution},
+        info::PdfInfoArgs,
+        shared::ProcessPriority,
+        text::{PdfTextArgs, PdfTextError},
+    };
+
+    /// Spawns an operation that never completes unless aborted
+    async fn spawn_pending(
+        processor: &PdfProcessor,
+        document_id: &'static str,
+    ) -> JoinHandle<Result<(), PdfTextError>> {
+        let processor_clone = processor.clone();
+        let jobs = processor.active_jobs().len();
+
+        let task = tokio::spawn(async move {
+            processor_clone
+                .track(document_id, "pdftotext", Some(2), pending())
+                .await
+        });
+
+        // Wait for the job to be registered
+        while processor.active_jobs().len() == jobs {
+            yield_now().await;
+        }
+
+        task
+    }
+
+    /// Tests jobs are listed while running and removed once aborted
+    #[tokio::test]
+    async fn test_abort_job() {
+        let processor = PdfProcessor::new();
+        let task = spawn_pending(&processor, "doc-1").await;
+
+        let jobs = processor.active_jobs();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].document_id, "doc-1");
+        assert_eq!(jobs[0].tool, "pdftotext");
+        assert_eq!(jobs[0].page, Some(2));
+
+        assert!(processor.abort(jobs[0].id));
+
+        let err = task.await.unwrap().unwrap_err();
+        assert!(matches!(err, PdfTextError::Aborted));
+        assert!(processor.active_jobs().is_empty());
+        assert!(!processor.abort(jobs[0].id));
+    }
+
+    /// Tests completed jobs are removed and aborting by document
+    #[tokio::test]
+    async fn test_abort_document() {
+        let processor = PdfProcessor::new();
+
+        processor
+            .track("doc-1", "pdfinfo", None, async {
+                Ok::<_, PdfTextError>(())
+            })
+            .await
+            .unwrap();
+        assert!(processor.active_jobs().is_empty());
+
+        let first = spawn_pending(&processor, "doc-1").await;
+        let _second = spawn_pending(&processor, "doc-2").await;
+
+        assert_eq!(processor.abort_document("doc-1"), 1);
+        assert!(matches!(first.await.unwrap(), Err(PdfTextError::Aborted)));
+
+        let jobs = processor.active_jobs();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].document_id, "doc-2");
+    }
+
+    /// Tests only jobs past the deadline are reaped and counted
+    #[tokio::test]
+    async fn test_reap_stragglers() {
+        let processor = PdfProcessor::new();
+        let task = spawn_pending(&processor, "doc-1").await;
+
+        assert_eq!(processor.reap_stragglers(Duration::from_secs(3600)), 0);
+        assert_eq!(processor.active_jobs().len(), 1);
+
+        assert_eq!(processor.reap_stragglers(Duration::ZERO), 1);
+        assert!(matches!(task.await.unwrap(), Err(PdfTextError::Aborted)));
+        assert!(processor.active_jobs().is_empty());
+
+        assert_eq!(
+            processor.janitor_stats(),
+            JanitorStats {
+                runs: 2,
+                reaped_jobs: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    /// Tests only the stale directories starting with the prefix are removed
+    #[test]
+    fn test_sweep_temp_dirs() {
+        let directory = tempfile::tempdir().unwrap();
+
+        let stale = directory.path().join(format!("{TEMP_DIR_PREFIX}abc123"));
+        std::fs::create_dir_all(stale.join("nested")).unwrap();
+        std::fs::write(stale.join("page-1.png"), [0; 100]).unwrap();
+        std::fs::write(stale.join("nested").join("page-2.png"), [0; 50]).unwrap();
+
+        let other = directory.path().join("other");
+        std::fs::create_dir(&other).unwrap();
+        let file = directory.path().join(format!("{TEMP_DIR_PREFIX}.txt"));
+        std::fs::write(&file, [0; 10]).unwrap();
+
+        // Recently modified directories are kept
+        let sweep = sweep_temp_dirs(directory.path(), Duration::from_secs(3600));
+        assert_eq!(sweep, TempSweep::default());
+        assert!(stale.exists());
+
+        let sweep = sweep_temp_dirs(directory.path(), Duration::ZERO);
+        assert_eq!(
+            sweep,
+            TempSweep {
+                removed_dirs: 1,
+                reclaimed_bytes: 150
+            }
+        );
+        assert!(!stale.exists());
+        assert!(other.exists());
+        assert!(file.exists());
+
+        let mut registry = JobRegistry::default();
+        registry.record_sweep(sweep);
+        registry.record_sweep(sweep);
+        assert_eq!(registry.janitor.removed_dirs, 2);
+        assert_eq!(registry.janitor.reclaimed_bytes, 300);
+    }
+
+    /// Tests the janitor reaps jobs in the background
+    #[tokio::test]
+    async fn test_janitor() {
+        let processor = PdfProcessor::new();
+        let task = spawn_pending(&processor, "doc-1").await;
+
+        let janitor = processor.spawn_janitor(Duration::from_millis(1), Duration::ZERO);
+        assert!(matches!(task.await.unwrap(), Err(PdfTextError::Aborted)));
+        assert!(processor.janitor_stats().reaped_jobs >= 1);
+
+        // Stops once the processor is dropped
+        drop(processor);
+        janitor.await.unwrap();
+    }
+
+    /// Tests the default timeout is applied to args without a timeout
+    #[test]
+    fn test_config_timeout() {
+        let config = ProcessorConfig::default()
+            .set_text_args(PdfTextArgs::default().set_timeout(Duration::from_secs(1)))
+            .set_timeout(Duration::from_secs(30));
+        let processor = PdfProcessor::from_config(config);
+
+        assert_eq!(
+            processor.render_args().timeout,
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(processor.info_args().timeout, Some(Duration::from_secs(30)));
+        assert_eq!(processor.text_args().timeout, Some(Duration::from_secs(1)));
+    }
+
+    /// Tests the default priority is applied to args without a priority
+    #[test]
+    fn test_config_priority() {
+        let config = ProcessorConfig::default()
+            .set_info_args(PdfInfoArgs::default().set_priority(ProcessPriority::default()))
+            .set_priority(ProcessPriority::background());
+        let processor = PdfProcessor::from_config(config);
+
+        assert_eq!(
+            processor.render_args().priority,
+            Some(ProcessPriority::background())
+        );
+        assert_eq!(
+            processor.info_args().priority,
+            Some(ProcessPriority::default())
+        );
+    }
+
+    /// Tests operations wait for the concurrency limit
+    #[tokio::test]
+    async fn test_max_concurrency() {
+        let processor =
+            PdfProcessor::from_config(ProcessorConfig::default().set_max_concurrency(1));
+        let _first = spawn_pending(&processor, "doc-1").await;
+
+        let other = processor.clone();
+        let second = tokio::spawn(async move {
+            other
+                .track("doc-2", "pdftotext", None, async {
+                    Ok::<_, PdfTextError>(())
+                })
+                .await
+        });
+
+        // Second operation cannot start while the first is running
+        for _ in 0..10 {
+            yield_now().await;
+        }
+        assert!(!second.is_finished());
+
+        processor.abort_document("doc-1");
+        second.await.unwrap().unwrap();
+    }
+
+    /// Tests the render cache keeps the most recently used pages
+    #[test]
+    fn test_render_cache() {
+        let processor =
+            PdfProcessor::from_config(ProcessorConfig::default().set_render_cache_size(2));
+        let args = RenderArgs::default();
+        let key = |page| CacheKey::new("doc-1", page, OutputFormat::Png, &args);
+
+        processor.cache_insert(key(1), vec![1]);
+        processor.cache_insert(key(2), vec![2]);
+
+        // Using the first page makes the second the least recently used
+        assert_eq!(
+            processor.cached_page("doc-1", 1, OutputFormat::Png, &args),
+            Some(vec![1])
+        );
+
+        processor.cache_insert(key(3), vec![3]);
+        assert_eq!(
+            processor.cached_page("doc-1", 2, OutputFormat::Png, &args),
+            None
+        );
+        assert_eq!(
+            processor.cached_page("doc-1", 3, OutputFormat::Png, &args),
+            Some(vec![3])
+        );
+
+        // Pages rendered with other args or formats are not used
+        let other = args.clone().set_resolution(Resolution::uniform(300));
+        assert_eq!(
+            processor.cached_page("doc-1", 1, OutputFormat::Png, &other),
+            None
+        );
+        assert_eq!(
+            processor.cached_page("doc-1", 1, OutputFormat::Jpeg, &args),
+            None
+        );
+
+        // Priority does not change the output
+        let background = args.clone().set_priority(ProcessPriority::background());
+        assert_eq!(
+            processor.cached_page("doc-1", 1, OutputFormat::Png, &background),
+            Some(vec![1])
+        );
+
+        assert_eq!(processor.clear_cached_pages("doc-1"), 2);
+        assert_eq!(
+            processor.cached_page("doc-1", 1, OutputFormat::Png, &args),
+            None
+        );
+    }
+
+    /// Tests nothing is kept when the cache size is zero
+    #[test]
+    fn test_render_cache_disabled() {
+        let processor =
+            PdfProcessor::from_config(ProcessorConfig::default().set_render_cache_size(0));
+        let args = RenderArgs::default();
+
+        processor.cache_insert(CacheKey::new("doc-1", 1, OutputFormat::Png, &args), vec![1]);
+        assert_eq!(
+            processor.cached_page("doc-1", 1, OutputFormat::Png, &args),
+            None
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Helpers for rewriting PDF files using qpdf, requires the `qpdf`
+//! feature and the qpdf binary
+//!
+//! * [reencrypt_pdf] - Change the passwords and permissions of a PDF file
+//! * [strip_metadata] - Remove the document info and XMP metadata from a PDF file
+
+use std::{
+    ffi::{OsStr, OsString},
+    time::Duration,
+};
+
+use thiserror::Error;
+
+use crate::{
+    info::PdfPermission,
+    shared::{run_tool, ChildError, Password, RunOptions, Secret, ToolError},
+};
+
+#[derive(Debug, Error)]
+pub enum QpdfError {
+    #[error("failed to spawn qpdf: {0}")]
+    SpawnProcess(std::io::Error),
+
+    #[error("failed to write pdf bytes: {0}")]
+    WritePdf(std::io::Error),
+
+    #[error("failed to get output: {0}")]
+    WaitOutput(std::io::Error),
+
+    #[error("output exceeded the maximum allowed size of {0} bytes")]
+    OutputLimitExceeded(usize),
+
+    #[error("qpdf did not finish within {0:?}")]
+    Timeout(Duration),
+
+    #[error("qpdf was aborted")]
+    Aborted,
+
+    #[error("failed to get qpdf exit code: {0}")]
+    QpdfFailure(String),
+
+    #[error("pdf is encrypted and no password was provided")]
+    PdfEncrypted,
+
+    #[error("incorrect password was provided")]
+    IncorrectPassword,
+
+    #[error("file is not a pdf")]
+    NotPdfFile,
+}
+
+impl From<ChildError> for QpdfError {
+    fn from(value: ChildError) -> Self {
+        match value {
+            ChildError::WriteInput(err) => Self::WritePdf(err),
+            ChildError::ReadOutput(err) | ChildError::WriteOutput(err) => Self::WaitOutput(err),
+            ChildError::OutputLimitExceeded(limit) => Self::OutputLimitExceeded(limit),
+            ChildError::Timeout(timeout) => Self::Timeout(timeout),
+        }
+    }
+}
+
+impl From<ToolError> for QpdfError {
+    fn from(value: ToolError) -> Self {
+        match value {
+            ToolError::Spawn(err) => Self::SpawnProcess(err),
+            ToolError::Child(err) => err.into(),
+            ToolError::NotPdfFile => Self::NotPdfFile,
+            ToolError::PdfEncrypted => Self::PdfEncrypted,
+            ToolError::IncorrectPassword => Self::IncorrectPassword,
+            // qpdf does not enforce permissions, exit code 3 is disabled
+            ToolError::PermissionError(err) => Self::QpdfFailure(err.to_string()),
+            ToolError::PageOutOfBounds(page, page_count) => Self::QpdfFailure(format!(
+                "page {page} is outside the number of available pages {page_count}"
+            )),
+            ToolError::PageCountUnknown => {
+                Self::QpdfFailure("page count is missing or invalid".to_string())
+            }
+            ToolError::EmptyDocument => {
+                Self::QpdfFailure("pdf does not contain any pages".to_string())
+            }
+            ToolError::Failure(err) | ToolError::Task(err) => Self::QpdfFailure(err),
+            ToolError::Aborted => Self::Aborted,
+            ToolError::BudgetExceeded(err) => Self::QpdfFailure(err.to_string()),
+        }
+    }
+}
+
+/// New security settings for a PDF file, the file is encrypted using
+/// 256 bit AES
+#[derive(Debug, Clone)]
+pub struct PdfSecurity {
+    /// Password required to open the file, empty to allow opening
+    /// the file without a password
+    pub user_password: Secret<OsString>,
+    /// Password that bypasses all the permission restrictions
+    pub owner_password: Secret<OsString>,
+    /// Whether printing is allowed
+    pub print: bool,
+    /// Whether copying text and graphics is allowed
+    pub copy: bool,
+    /// Whether changing the document is allowed
+    pub change: bool,
+    /// Whether adding and changing annotations is allowed
+    pub add_notes: bool,
+    /// Optional maximum time re-encrypting may take
+    pub timeout: Option<Duration>,
+}
+
+impl PdfSecurity {
+    /// Creates new security settings with all permissions allowed
+    ///
+    /// ## Arguments
+    /// * user_password - Password required to open the file, empty for none
+    /// * owner_password - Password that bypasses the permission restrictions
+    pub fn new(user_password: impl Into<OsString>, owner_password: impl Into<OsString>) -> Self {
+        Self {
+            user_password: Secret(user_password.into()),
+            owner_password: Secret(owner_password.into()),
+            print: true,
+            copy: true,
+            change: true,
+            add_notes: true,
+            timeout: None,
+        }
+    }
+
+    pub fn set_permission(mut self, permission: PdfPermission, allowed: bool) -> Self {
+        match permission {
+            PdfPermission::Print => self.print = allowed,
+            PdfPermission::Copy => self.copy = allowed,
+            PdfPermission::Change => self.change = allowed,
+            PdfPermission::AddNotes => self.add_notes = allowed,
+        }
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Builds the `--encrypt` argument list for qpdf
+    pub fn build_args(&self) -> Vec<OsString> {
+        let flag = |allowed: bool, yes: &str, no: &str| -> OsString {
+            if allowed { yes } else { no }.into()
+        };
+
+        vec![
+            "--encrypt".into(),
+            self.user_password.0.clone(),
+            self.owner_password.0.clone(),
+            "256".into(),
+            flag(self.print, "--print=full", "--print=none"),
+            flag(self.copy, "--extract=y", "--extract=n"),
+            flag(self.change, "--modify=all", "--modify=none"),
+            flag(self.add_notes, "--annotate=y", "--annotate=n"),
+            "--".into(),
+        ]
+    }
+}
+
+/// Decrypts the provided PDF file and encrypts it again using the new
+/// passwords and permissions, providing the new file bytes
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * old_password - The current user or owner password, [None] for
+///   unencrypted files or files with an empty user password
+/// * new_security - The new passwords and permissions
+pub async fn reencrypt_pdf(
+    data: &[u8],
+    old_password: Option<&Password>,
+    new_security: &PdfSecurity,
+) -> Result<Vec<u8>, QpdfError> {
+    let mut cli_args: Vec<OsString> = vec!["--warning-exit-0".into()];
+    cli_args.extend(new_security.build_args());
+    // Read from stdin and write to stdout
+    cli_args.push("-".into());
+    cli_args.push("-".into());
+
+    let options = RunOptions {
+        max_output_size: None,
+        timeout: new_security.timeout,
+        password: old_password.cloned(),
+        ..Default::default()
+    };
+
+    run_qpdf(&cli_args, data, &options).await
+}
+
+/// Removes the document info dictionary (Title, Author, Producer, etc) and
+/// the XMP metadata stream from the provided PDF file, providing the cleaned
+/// file bytes. The file is fully rewritten so earlier revisions of the
+/// metadata left behind by incremental updates are also dropped.
+///
+/// Requires a qpdf release with the `--remove-info` and `--remove-metadata`
+/// options, encryption of the file is kept so files that require a password
+/// to open are reported as [QpdfError::PdfEncrypted]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+pub async fn strip_metadata(data: &[u8]) -> Result<Vec<u8>, QpdfError> {
+    let cli_args = [
+        "--warning-exit-0",
+        "--remove-info",
+        "--remove-metadata",
+        // Read from stdin and write to stdout
+        "-",
+        "-",
+    ];
+
+    run_qpdf(&cli_args, data, &RunOptions::default()).await
+}
+
+/// Messages qpdf reports when the password is missing or wrong
+const PASSWORD_MESSAGES: &[&str] = &["invalid password"];
+
+/// Runs qpdf providing the output file bytes, qpdf reports password
+/// failures with its own messages and exit code so they are classified
+/// here rather than with the poppler failures
+///
+/// ## Arguments
+/// * args - The qpdf arguments
+/// * data - The raw PDF file bytes
+/// * options - Options for running qpdf
+async fn run_qpdf<S>(args: &[S], data: &[u8], options: &RunOptions) -> Result<Vec<u8>, QpdfError>
+where
+    S: AsRef<OsStr>,
+{
+    match run_tool("qpdf", args, data, options).await {
+        Ok(output) => Ok(output.stdout),
+        Err(ToolError::Failure(err)) if is_password_failure(&err) => {
+            if options.password.is_some() {
+                Err(QpdfError::IncorrectPassword)
+            } else {
+                Err(QpdfError::PdfEncrypted)
+            }
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Checks whether the qpdf stderr output is reporting a password failure
+fn is_password_failure(stderr: &str) -> bool {
+    PASSWORD_MESSAGES
+        .iter()
+        .any(|message| stderr.contains(message))
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::OsString;
+
+    use super::{is_password_failure, PdfSecurity};
+    use crate::info::PdfPermission;
+
+    /// Tests building the encryption arguments
+    #[test]
+    fn test_security_args() {
+        let security = PdfSecurity::new("user", "owner")
+            .set_permission(PdfPermission::Copy, false)
+            .set_permission(PdfPermission::Change, false);
+
+        let expected: Vec<OsString> = [
+            "--encrypt",
+            "user",
+            "owner",
+            "256",
+            "--print=full",
+            "--extract=n",
+            "--modify=none",
+            "--annotate=y",
+            "--",
+        ]
+        .into_iter()
+        .map(OsString::from)
+        .collect();
+
+        assert_eq!(security.build_args(), expected);
+    }
+
+    /// Tests the qpdf password failures are recognized
+    #[test]
+    fn test_password_failure() {
+        assert!(is_password_failure("qpdf: -: invalid password\n"));
+        assert!(!is_password_failure(
+            "qpdf: -: unable to find trailer dictionary while recovering damaged file\n"
+        ));
+    }
+}
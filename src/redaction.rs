@@ -0,0 +1,229 @@
+//! Helpers for verifying that redacted text was removed from PDF files
+//!
+//! * [verify_redaction] - Finds banned terms that are still present in the text layer
+//!
+//! Redactions drawn as boxes over the text leave the text in place, these
+//! helpers check the text layer using [text_with_boxes] so the location of
+//! any remaining text can be reported.
+//!
+//! Only the text layer is checked, text that only exists within images
+//! requires OCR which is not provided by this crate
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+    shared::{Point, Rect},
+    text::{PdfTextArgs, PdfTextError, TextNormalization},
+    words::{text_with_boxes, Word},
+};
+
+/// Bounding box in PDF points with the origin at the top left of the page
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BoundingBox {
+    pub x_min: f32,
+    pub y_min: f32,
+    pub x_max: f32,
+    pub y_max: f32,
+}
+
+impl BoundingBox {
+    /// Bounding box as a rectangle in PDF points with the origin
+    /// at the top left of the page
+    pub fn rect(&self) -> Rect {
+        Rect::from_corners(
+            Point::new(self.x_min, self.y_min),
+            Point::new(self.x_max, self.y_max),
+        )
+    }
+
+    /// Creates the smallest box containing both boxes
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            x_min: self.x_min.min(other.x_min),
+            y_min: self.y_min.min(other.y_min),
+            x_max: self.x_max.max(other.x_max),
+            y_max: self.y_max.max(other.y_max),
+        }
+    }
+}
+
+impl From<Rect> for BoundingBox {
+    fn from(value: Rect) -> Self {
+        let (min, max) = (value.min(), value.max());
+        Self {
+            x_min: min.x,
+            y_min: min.y,
+            x_max: max.x,
+            y_max: max.y,
+        }
+    }
+}
+
+/// Banned term that was found in the text layer
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RedactionHit {
+    /// The banned term that was found
+    pub term: String,
+    /// Page the term was found on
+    pub page: u32,
+    /// Area of the page containing the term
+    pub bbox: BoundingBox,
+}
+
+/// Checks the text layer of the provided PDF for any of the banned terms,
+/// terms are matched ignoring case and may span multiple words.
+///
+/// The words and terms are normalized before matching so ligatures and
+/// soft hyphens do not hide a term, [TextNormalization::standard] is
+/// used unless the args provide a normalization
+///
+/// Returns a hit for every occurrence of a banned term, an empty list
+/// means none of the terms were found
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * banned_terms - Terms that should have been redacted
+/// * args - Optional args for the pdf to text
+pub async fn verify_redaction<S: AsRef<str>>(
+    data: &[u8],
+    banned_terms: &[S],
+    args: &PdfTextArgs,
+) -> Result<Vec<RedactionHit>, PdfTextError> {
+    let normalization = args
+        .normalization
+        .clone()
+        .unwrap_or_else(TextNormalization::standard);
+    let args = args.clone().set_normalization(normalization.clone());
+
+    let pages = text_with_boxes(data, &args).await?;
+
+    let mut hits = Vec::new();
+
+    for (index, page) in pages.iter().enumerate() {
+        let page_number = index as u32 + 1;
+
+        for term in banned_terms {
+            find_term(
+                term.as_ref(),
+                &normalization,
+                page_number,
+                &page.words,
+                &mut hits,
+            );
+        }
+    }
+
+    Ok(hits)
+}
+
+/// Finds all the occurrences of a term within the normalized words of a page
+fn find_term(
+    term: &str,
+    normalization: &TextNormalization,
+    page: u32,
+    words: &[Word],
+    hits: &mut Vec<RedactionHit>,
+) {
+    let needle = normalization.apply(term);
+    let needle = needle.split_whitespace().collect::<Vec<_>>().join(" ");
+    if needle.is_empty() {
+        return;
+    }
+
+    let needle = needle.to_lowercase();
+    let word_count = needle.split(' ').count();
+
+    for window in words.windows(word_count) {
+        let haystack = window
+            .iter()
+            .map(|word| word.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+
+        if !haystack.contains(&needle) {
+            continue;
+        }
+
+        let bbox = window
+            .iter()
+            .skip(1)
+            .fold(window[0].bbox(), |bbox, word| bbox.union(&word.bbox()));
+
+        hits.push(RedactionHit {
+            term: term.to_string(),
+            page,
+            bbox,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_term, BoundingBox};
+    use crate::{text::TextNormalization, words::parse_bbox_pages};
+
+    const BBOX_OUTPUT: &str = r#"<doc>
+  <page width="612.000000" height="792.000000">
+    <word xMin="56.800000" yMin="57.208000" xMax="80.248000" yMax="70.492000">Secret</word>
+    <word xMin="83.800000" yMin="57.208000" xMax="120.500000" yMax="70.492000">A&amp;B</word>
+  </page>
+</doc>"#;
+
+    /// Tests finding single and multiple word terms
+    #[test]
+    fn test_find_term() {
+        let pages = parse_bbox_pages(BBOX_OUTPUT);
+        let normalization = TextNormalization::new();
+        let mut hits = Vec::new();
+
+        find_term("secret a&b", &normalization, 1, &pages[0].words, &mut hits);
+        find_term("a&", &normalization, 1, &pages[0].words, &mut hits);
+        find_term("private", &normalization, 1, &pages[0].words, &mut hits);
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(
+            hits[0].bbox,
+            BoundingBox {
+                x_min: 56.8,
+                y_min: 57.208,
+                x_max: 120.5,
+                y_max: 70.492
+            }
+        );
+        assert_eq!(hits[1].bbox.x_min, 83.8);
+    }
+
+    /// Tests ligatures and soft hyphens do not hide a term
+    #[test]
+    fn test_find_term_normalized() {
+        let mut pages = parse_bbox_pages(BBOX_OUTPUT);
+        let normalization = TextNormalization::standard();
+        for word in &mut pages[0].words {
+            word.text =
+                normalization.apply(&word.text.replace("Secret", "Con\u{FB01}den\u{AD}tial"));
+        }
+
+        let mut hits = Vec::new();
+        find_term(
+            "confidential",
+            &normalization,
+            1,
+            &pages[0].words,
+            &mut hits,
+        );
+        find_term(
+            "con\u{FB01}dential",
+            &normalization,
+            1,
+            &pages[0].words,
+            &mut hits,
+        );
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[1].term, "con\u{FB01}dential");
+    }
+}
@@ -0,0 +1,548 @@
+//! Helpers for searching the text of PDF files
+//!
+//! * [find_first] - Finds the first page matching a [TextMatcher], stopping as soon as a match is found
+//! * [TextMatcher] - Finds a match within the text of a page, implemented for strings and closures
+//! * [SearchPattern] - Literal or regex pattern with case folding, diacritic folding and fuzzy matching
+//!
+//! Pages are extracted in page order with at most [FindOptions::concurrency]
+//! pages extracted at once, any extractions still running when a match is
+//! found are killed so a match near the start of a large document only
+//! extracts the first few pages
+
+use std::ops::Range;
+
+use futures_util::{stream, StreamExt};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use unicode_normalization::char::{decompose_compatible, is_combining_mark};
+
+use crate::{
+    info::PdfInfo,
+    shared::PageSelection,
+    text::{page_text, verify_pages, PdfTextArgs, PdfTextError},
+};
+
+/// Finds a match within the text of a page
+pub trait TextMatcher {
+    /// Byte range of the first match within the text, [None] when
+    /// the text does not match
+    fn find_match(&self, text: &str) -> Option<Range<usize>>;
+}
+
+/// Exact substring match
+impl TextMatcher for str {
+    fn find_match(&self, text: &str) -> Option<Range<usize>> {
+        text.find(self).map(|start| start..start + self.len())
+    }
+}
+
+/// Exact substring match
+impl TextMatcher for String {
+    fn find_match(&self, text: &str) -> Option<Range<usize>> {
+        self.as_str().find_match(text)
+    }
+}
+
+/// Custom predicate providing the byte range of the match
+impl<F> TextMatcher for F
+where
+    F: Fn(&str) -> Option<Range<usize>>,
+{
+    fn find_match(&self, text: &str) -> Option<Range<usize>> {
+        self(text)
+    }
+}
+
+/// Options for how a [SearchPattern] matches the text
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MatchOptions {
+    /// Whether to ignore the case of letters
+    pub case_insensitive: bool,
+    /// Whether to ignore accents and other combining marks (e.g. "cafe"
+    /// matches "café"), uses the compatibility decomposition so ligatures
+    /// such as "ﬁ" also match their separate letters
+    pub ignore_diacritics: bool,
+    /// Maximum number of inserted, removed or replaced characters for a
+    /// literal pattern to still match, allows matching text mangled by
+    /// OCR. Regex patterns always match exactly
+    pub max_edits: usize,
+}
+
+impl MatchOptions {
+    pub fn set_case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub fn set_ignore_diacritics(mut self, ignore_diacritics: bool) -> Self {
+        self.ignore_diacritics = ignore_diacritics;
+        self
+    }
+
+    pub fn set_max_edits(mut self, max_edits: usize) -> Self {
+        self.max_edits = max_edits;
+        self
+    }
+
+    /// Folds the provided text for matching
+    fn fold(&self, text: &str) -> FoldedText {
+        FoldedText::new(text, self)
+    }
+}
+
+/// Pattern for searching the text, matches are reported as the byte
+/// range of the original text even when the text is folded
+#[derive(Debug, Clone)]
+pub struct SearchPattern {
+    kind: PatternKind,
+    options: MatchOptions,
+}
+
+#[derive(Debug, Clone)]
+enum PatternKind {
+    /// Literal text already folded using the options
+    Literal(String),
+    /// Regular expression matched against the folded text
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl SearchPattern {
+    /// Creates a pattern matching the literal text
+    ///
+    /// ## Arguments
+    /// * pattern - The text to find
+    /// * options - How the text is matched
+    pub fn literal(pattern: &str, options: MatchOptions) -> Self {
+        Self {
+            kind: PatternKind::Literal(options.fold(pattern).text),
+            options,
+        }
+    }
+
+    /// Creates a pattern matching the regular expression (Requires the
+    /// `regex` feature). Diacritics are removed from the expression when
+    /// [MatchOptions::ignore_diacritics] is enabled
+    ///
+    /// ## Arguments
+    /// * pattern - The regular expression to find
+    /// * options - How the text is matched, [MatchOptions::max_edits] is ignored
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: &str, options: MatchOptions) -> Result<Self, regex::Error> {
+        let pattern = match options.ignore_diacritics {
+            true => {
+                MatchOptions::default()
+                    .set_ignore_diacritics(true)
+                    .fold(pattern)
+                    .text
+            }
+            false => pattern.to_string(),
+        };
+
+        let regex = regex::RegexBuilder::new(&pattern)
+            .case_insensitive(options.case_insensitive)
+            .build()?;
+
+        Ok(Self {
+            kind: PatternKind::Regex(regex),
+            options,
+        })
+    }
+
+    /// Options the pattern matches with
+    pub fn options(&self) -> &MatchOptions {
+        &self.options
+    }
+}
+
+impl TextMatcher for SearchPattern {
+    fn find_match(&self, text: &str) -> Option<Range<usize>> {
+        let folded = self.options.fold(text);
+
+        let range = match &self.kind {
+            PatternKind::Literal(pattern) if self.options.max_edits == 0 => {
+                pattern.as_str().find_match(&folded.text)?
+            }
+            PatternKind::Literal(pattern) => {
+                fuzzy_find(&folded.text, pattern, self.options.max_edits)?
+            }
+            #[cfg(feature = "regex")]
+            PatternKind::Regex(regex) => regex.find(&folded.text)?.range(),
+        };
+
+        Some(folded.original_range(range))
+    }
+}
+
+/// Text folded for matching along with the position of each folded
+/// character within the original text
+struct FoldedText {
+    /// The folded text
+    text: String,
+    /// Byte offset of each folded character paired with the byte
+    /// range of the original character it came from
+    chars: Vec<(usize, Range<usize>)>,
+    /// Length of the original text
+    original_len: usize,
+}
+
+impl FoldedText {
+    fn new(value: &str, options: &MatchOptions) -> Self {
+        let mut text = String::with_capacity(value.len());
+        let mut chars = Vec::with_capacity(value.len());
+
+        for (index, char) in value.char_indices() {
+            let source = index..index + char.len_utf8();
+            let mut push = |char: char| {
+                if options.case_insensitive {
+                    for char in char.to_lowercase() {
+                        chars.push((text.len(), source.clone()));
+                        text.push(char);
+                    }
+                } else {
+                    chars.push((text.len(), source.clone()));
+                    text.push(char);
+                }
+            };
+
+            if options.ignore_diacritics {
+                decompose_compatible(char, |char| {
+                    if !is_combining_mark(char) {
+                        push(char)
+                    }
+                });
+            } else {
+                push(char);
+            }
+        }
+
+        Self {
+            text,
+            chars,
+            original_len: value.len(),
+        }
+    }
+
+    /// Converts a byte range of the folded text into the byte range of
+    /// the original characters it was folded from
+    fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self
+            .chars
+            .partition_point(|(offset, _)| *offset < range.start);
+        let end = self
+            .chars
+            .partition_point(|(offset, _)| *offset < range.end);
+
+        let original_start = self
+            .chars
+            .get(start)
+            .map_or(self.original_len, |(_, source)| source.start);
+
+        match end.checked_sub(1).and_then(|last| self.chars.get(last)) {
+            Some((_, source)) if end > start => original_start..source.end,
+            _ => original_start..original_start,
+        }
+    }
+}
+
+/// Finds the earliest substring of the text within `max_edits` of the
+/// pattern (Levenshtein distance), provides its byte range. When the
+/// first match can be improved by extending it the closer match is used
+fn fuzzy_find(text: &str, pattern: &str, max_edits: usize) -> Option<Range<usize>> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let offsets: Vec<(usize, char)> = text.char_indices().collect();
+    let byte_offset = |index: usize| offsets.get(index).map_or(text.len(), |(offset, _)| *offset);
+
+    if pattern.len() <= max_edits {
+        return Some(0..0);
+    }
+
+    // Edit distance and start of the best alignment of the pattern prefix
+    // ending at the current character of the text
+    let mut costs: Vec<usize> = (0..=pattern.len()).collect();
+    let mut starts: Vec<usize> = vec![0; pattern.len() + 1];
+
+    let mut best: Option<(usize, Range<usize>)> = None;
+
+    for (column, (_, char)) in offsets.iter().enumerate() {
+        let (mut diagonal_cost, mut diagonal_start) = (costs[0], starts[0]);
+        costs[0] = 0;
+        starts[0] = column + 1;
+
+        for row in 1..=pattern.len() {
+            let substitution = diagonal_cost + usize::from(pattern[row - 1] != *char);
+            let insertion = costs[row] + 1;
+            let deletion = costs[row - 1] + 1;
+
+            let (cost, start) = if substitution <= insertion && substitution <= deletion {
+                (substitution, diagonal_start)
+            } else if insertion <= deletion {
+                (insertion, starts[row])
+            } else {
+                (deletion, starts[row - 1])
+            };
+
+            diagonal_cost = costs[row];
+            diagonal_start = starts[row];
+            costs[row] = cost;
+            starts[row] = start;
+        }
+
+        let cost = costs[pattern.len()];
+        match &best {
+            // Keep extending the match while it gets closer
+            Some((best_cost, _)) if cost < *best_cost => {}
+            Some(_) => break,
+            None if cost <= max_edits => {}
+            None => continue,
+        }
+
+        best = Some((
+            cost,
+            byte_offset(starts[pattern.len()])..byte_offset(column + 1),
+        ));
+    }
+
+    best.map(|(_, range)| range)
+}
+
+/// Options for searching the text
+#[derive(Debug, Clone)]
+pub struct FindOptions {
+    /// Args for extracting the text
+    pub text_args: PdfTextArgs,
+    /// Pages to search in the order they are searched, defaults to all pages
+    pub pages: PageSelection,
+    /// Maximum number of pages extracted at the same time, pages are
+    /// still checked in order so the first match is always the earliest
+    pub concurrency: usize,
+    /// Number of characters to include either side of the match in
+    /// the [TextMatch::context]
+    pub context_chars: usize,
+}
+
+impl Default for FindOptions {
+    fn default() -> Self {
+        Self {
+            text_args: Default::default(),
+            pages: Default::default(),
+            concurrency: Self::DEFAULT_CONCURRENCY,
+            context_chars: Self::DEFAULT_CONTEXT_CHARS,
+        }
+    }
+}
+
+impl FindOptions {
+    pub const DEFAULT_CONCURRENCY: usize = 1;
+    pub const DEFAULT_CONTEXT_CHARS: usize = 40;
+
+    pub fn set_text_args(mut self, text_args: PdfTextArgs) -> Self {
+        self.text_args = text_args;
+        self
+    }
+
+    pub fn set_pages(mut self, pages: impl Into<PageSelection>) -> Self {
+        self.pages = pages.into();
+        self
+    }
+
+    pub fn set_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn set_context_chars(mut self, context_chars: usize) -> Self {
+        self.context_chars = context_chars;
+        self
+    }
+}
+
+/// Match found within the text of a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TextMatch {
+    /// The page number
+    pub page: u32,
+    /// Byte range of the match within the text of the page
+    pub range: Range<usize>,
+    /// The matched text
+    pub text: String,
+    /// The matched text along with the surrounding text
+    pub context: String,
+}
+
+impl TextMatch {
+    /// Creates a match from the text of the page, the range is clamped
+    /// to the text and widened to the nearest character boundaries
+    ///
+    /// ## Arguments
+    /// * page - The page number
+    /// * text - The text of the page
+    /// * range - Byte range of the match within the text
+    /// * context_chars - Number of characters to include either side of the match
+    pub fn new(page: u32, text: &str, range: Range<usize>, context_chars: usize) -> Self {
+        let mut start = range.start.min(text.len());
+        while !text.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        let mut end = range.end.clamp(start, text.len());
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+
+        let context_start = text[..start]
+            .char_indices()
+            .rev()
+            .take(context_chars)
+            .last()
+            .map_or(start, |(index, _)| index);
+        let context_end = end
+            + text[end..]
+                .chars()
+                .take(context_chars)
+                .map(char::len_utf8)
+                .sum::<usize>();
+
+        Self {
+            page,
+            range: start..end,
+            text: text[start..end].to_string(),
+            context: text[context_start..context_end].to_string(),
+        }
+    }
+}
+
+/// Finds the first page of the provided PDF with text matching the
+/// `matcher`. Pages are searched in the order of [FindOptions::pages]
+/// and the search stops as soon as a match is found, killing any
+/// extractions that are still running
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * matcher - Finds the match within the text of each page
+/// * options - Options for the search
+pub async fn find_first<M>(
+    data: &[u8],
+    info: &PdfInfo,
+    matcher: &M,
+    options: &FindOptions,
+) -> Result<Option<TextMatch>, PdfTextError>
+where
+    M: TextMatcher + ?Sized,
+{
+    let args = &options.text_args;
+    let pages = options.pages.resolve(info, args.empty_document)?;
+    verify_pages(data, info, args, &pages).await?;
+
+    let mut texts = stream::iter(pages)
+        .map(
+            |page| async move { Ok::<_, PdfTextError>((page, page_text(data, page, args).await?)) },
+        )
+        .buffered(options.concurrency.max(1));
+
+    while let Some(result) = texts.next().await {
+        let (page, text) = result?;
+
+        if let Some(range) = matcher.find_match(&text) {
+            return Ok(Some(TextMatch::new(
+                page,
+                &text,
+                range,
+                options.context_chars,
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MatchOptions, SearchPattern, TextMatch, TextMatcher};
+
+    /// Tests substring and closure matchers
+    #[test]
+    fn test_matchers() {
+        let text = "Invoice 1234\nTotal due: $56.00";
+
+        assert_eq!("Total".find_match(text), Some(13..18));
+        assert_eq!("Missing".to_string().find_match(text), None);
+
+        let digits = |text: &str| {
+            let start = text.find(|c: char| c.is_ascii_digit())?;
+            let len = text[start..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(text.len() - start);
+            Some(start..start + len)
+        };
+        assert_eq!(digits.find_match(text), Some(8..12));
+    }
+
+    /// Tests case and diacritic folding report ranges of the original text
+    #[test]
+    fn test_folded_pattern() {
+        let options = MatchOptions::default().set_case_insensitive(true);
+        let pattern = SearchPattern::literal("INVOICE", options);
+        assert_eq!(pattern.find_match("Final Invoice"), Some(6..13));
+
+        let options = options.set_ignore_diacritics(true);
+        let pattern = SearchPattern::literal("cafe", options);
+        assert_eq!(pattern.find_match("Le Café"), Some(3..8));
+
+        // Ligatures are split into their letters
+        let pattern = SearchPattern::literal("final", options);
+        assert_eq!(pattern.find_match("The \u{FB01}nal total"), Some(4..10));
+
+        let pattern = SearchPattern::literal("cafe", MatchOptions::default());
+        assert_eq!(pattern.find_match("Le Café"), None);
+    }
+
+    /// Tests fuzzy matching finds text with OCR mistakes
+    #[test]
+    fn test_fuzzy_pattern() {
+        let options = MatchOptions::default().set_max_edits(1);
+        let pattern = SearchPattern::literal("invoice", options);
+        assert_eq!(pattern.find_match("Total lnvoice amount"), Some(6..13));
+        assert_eq!(pattern.find_match("Total invoce amount"), Some(6..12));
+        assert_eq!(pattern.find_match("Total lnvoce amount"), None);
+
+        let pattern = SearchPattern::literal("invoice", options.set_max_edits(2));
+        assert_eq!(pattern.find_match("Total lnvoce amount"), Some(6..12));
+    }
+
+    /// Tests regex patterns match the folded text
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_regex_pattern() {
+        let options = MatchOptions::default()
+            .set_case_insensitive(true)
+            .set_ignore_diacritics(true);
+        let pattern = SearchPattern::regex(r"total: \$\d+", options).unwrap();
+        assert_eq!(pattern.find_match("Café TOTAL: $56"), Some(6..16));
+
+        assert!(SearchPattern::regex("(", options).is_err());
+    }
+
+    /// Tests the context surrounding the match
+    #[test]
+    fn test_match_context() {
+        let text = "Café au lait costs €4 today";
+
+        let start = text.find("costs").unwrap();
+        let found = TextMatch::new(2, text, start..start + 5, 6);
+        assert_eq!(found.page, 2);
+        assert_eq!(found.text, "costs");
+        assert_eq!(found.context, " lait costs €4 to");
+
+        // Ranges inside a character are widened to its boundaries
+        let euro = text.find('€').unwrap();
+        let found = TextMatch::new(1, text, euro + 1..euro + 2, 0);
+        assert_eq!(found.text, "€");
+        assert_eq!(found.range, euro..euro + '€'.len_utf8());
+
+        let found = TextMatch::new(1, text, 0..4, 100);
+        assert_eq!(found.context, text);
+    }
+}
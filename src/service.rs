@@ -0,0 +1,410 @@
+//! Framework agnostic helpers for services that accept uploaded PDF files
+//!
+//! * [PreviewService] - Checks uploads against a [ServicePolicy] and produces a [PreviewBundle]
+
+use std::{sync::Arc, time::Duration};
+
+use futures_util::future::try_join;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::Semaphore;
+
+use crate::{
+    image::{render_single_page_raw, OutputFormat, PdfRenderError, RenderArgs, ScaleTo},
+    info::{pdf_info, PdfInfoArgs, PdfInfoError, PdfSummary},
+    shared::{has_pdf_header, Password},
+    text::{text_single_page, PdfTextArgs, PdfTextError},
+};
+
+/// Checks applied to uploaded files before and after reading their info
+#[derive(Debug, Clone)]
+pub struct ServicePolicy {
+    /// Maximum size of an uploaded file in bytes
+    pub max_upload_size: usize,
+    /// Optional maximum number of pages
+    pub max_pages: Option<u32>,
+    /// Whether encrypted files are allowed, files that require a password
+    /// to open can only be previewed using [PreviewService::preview_with_password]
+    pub allow_encrypted: bool,
+    /// Whether files containing JavaScript are allowed
+    pub allow_javascript: bool,
+    /// Optional maximum time each of the tools may run for
+    pub timeout: Option<Duration>,
+    /// Optional maximum number of bytes the thumbnail and text
+    /// tools may output
+    pub max_output_size: Option<usize>,
+}
+
+impl Default for ServicePolicy {
+    fn default() -> Self {
+        Self {
+            max_upload_size: Self::DEFAULT_MAX_UPLOAD_SIZE,
+            max_pages: None,
+            allow_encrypted: false,
+            allow_javascript: true,
+            timeout: Some(Self::DEFAULT_TIMEOUT),
+            max_output_size: Some(Self::DEFAULT_MAX_OUTPUT_SIZE),
+        }
+    }
+}
+
+impl ServicePolicy {
+    /// Default maximum upload size of 50MB
+    pub const DEFAULT_MAX_UPLOAD_SIZE: usize = 50 * 1024 * 1024;
+    /// Default maximum time each tool may run for of 30 seconds
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+    /// Default maximum tool output size of 32MB
+    pub const DEFAULT_MAX_OUTPUT_SIZE: usize = 32 * 1024 * 1024;
+
+    pub fn set_max_upload_size(mut self, max_upload_size: usize) -> Self {
+        self.max_upload_size = max_upload_size;
+        self
+    }
+
+    pub fn set_max_pages(mut self, max_pages: u32) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    pub fn set_allow_encrypted(mut self, allow_encrypted: bool) -> Self {
+        self.allow_encrypted = allow_encrypted;
+        self
+    }
+
+    pub fn set_allow_javascript(mut self, allow_javascript: bool) -> Self {
+        self.allow_javascript = allow_javascript;
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_max_output_size(mut self, max_output_size: usize) -> Self {
+        self.max_output_size = Some(max_output_size);
+        self
+    }
+
+    /// Args for reading the info of an upload
+    fn info_args(&self, password: Option<&Password>) -> PdfInfoArgs {
+        let mut args = PdfInfoArgs::default().set_raw_dates(true);
+        args.timeout = self.timeout;
+        args.password = password.cloned();
+        args
+    }
+
+    /// Args for rendering the thumbnail of an upload
+    fn render_args(&self, password: Option<&Password>, thumbnail_width: u32) -> RenderArgs {
+        let mut args = RenderArgs::default().set_scale_to(ScaleTo::x(thumbnail_width as i32));
+        args.timeout = self.timeout;
+        args.max_output_size = self.max_output_size;
+        args.password = password.cloned();
+        args
+    }
+
+    /// Args for extracting the text of an upload
+    fn text_args(&self, password: Option<&Password>) -> PdfTextArgs {
+        PdfTextArgs {
+            timeout: self.timeout,
+            max_output_size: self.max_output_size,
+            password: password.cloned(),
+            ..Default::default()
+        }
+    }
+
+    /// Checks the raw upload before any processes are spawned
+    pub fn check_upload(&self, data: &[u8]) -> Result<(), PolicyViolation> {
+        if data.len() > self.max_upload_size {
+            return Err(PolicyViolation::TooLarge(data.len(), self.max_upload_size));
+        }
+
+        if !has_pdf_header(data) {
+            return Err(PolicyViolation::NotPdf);
+        }
+
+        Ok(())
+    }
+
+    /// Checks the details of the file
+    pub fn check_summary(&self, summary: &PdfSummary) -> Result<(), PolicyViolation> {
+        if let (Some(max_pages), Some(pages)) = (self.max_pages, summary.pages) {
+            if pages > max_pages {
+                return Err(PolicyViolation::TooManyPages(pages, max_pages));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reasons an upload was rejected by a [ServicePolicy]
+#[derive(Debug, Error)]
+pub enum PolicyViolation {
+    #[error("file size {0} exceeds the maximum of {1} bytes")]
+    TooLarge(usize, usize),
+
+    #[error("file is not a pdf")]
+    NotPdf,
+
+    #[error("file has {0} pages which exceeds the maximum of {1}")]
+    TooManyPages(u32, u32),
+
+    #[error("file is encrypted")]
+    Encrypted,
+
+    #[error("file contains javascript")]
+    JavaScript,
+}
+
+#[derive(Debug, Error)]
+pub enum ServiceError {
+    #[error(transparent)]
+    Policy(#[from] PolicyViolation),
+
+    #[error(transparent)]
+    Info(#[from] PdfInfoError),
+
+    #[error(transparent)]
+    Render(#[from] PdfRenderError),
+
+    #[error(transparent)]
+    Text(#[from] PdfTextError),
+
+    #[error("service is shutting down")]
+    Closed,
+}
+
+/// Preview of an uploaded file
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PreviewBundle {
+    /// Summary of the file info
+    pub info: PdfSummary,
+    /// Encoded thumbnail of the first page
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub thumbnail: Vec<u8>,
+    /// Format of the thumbnail
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub thumbnail_format: OutputFormat,
+    /// Text from the first page
+    pub text: String,
+}
+
+/// Produces previews for uploaded files, limiting the number of
+/// previews that are processed at the same time
+#[derive(Debug, Clone)]
+pub struct PreviewService {
+    /// Policy uploads are checked against
+    policy: ServicePolicy,
+    /// Limits the number of previews processed at once
+    limiter: Arc<Semaphore>,
+    /// Width of the thumbnail in pixels
+    thumbnail_width: u32,
+    /// Format to render the thumbnail as
+    thumbnail_format: OutputFormat,
+    /// Maximum number of characters of text to include
+    max_text_chars: usize,
+}
+
+impl PreviewService {
+    pub const DEFAULT_THUMBNAIL_WIDTH: u32 = 256;
+    pub const DEFAULT_MAX_TEXT_CHARS: usize = 1000;
+
+    /// Creates a new service
+    ///
+    /// ## Arguments
+    /// * policy - Policy to check uploads against
+    /// * max_concurrent - Maximum number of previews to process at once,
+    ///   at least one preview is always allowed
+    pub fn new(policy: ServicePolicy, max_concurrent: usize) -> Self {
+        Self {
+            policy,
+            limiter: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            thumbnail_width: Self::DEFAULT_THUMBNAIL_WIDTH,
+            thumbnail_format: OutputFormat::Jpeg,
+            max_text_chars: Self::DEFAULT_MAX_TEXT_CHARS,
+        }
+    }
+
+    pub fn set_thumbnail_width(mut self, thumbnail_width: u32) -> Self {
+        self.thumbnail_width = thumbnail_width;
+        self
+    }
+
+    pub fn set_thumbnail_format(mut self, thumbnail_format: OutputFormat) -> Self {
+        self.thumbnail_format = thumbnail_format;
+        self
+    }
+
+    pub fn set_max_text_chars(mut self, max_text_chars: usize) -> Self {
+        self.max_text_chars = max_text_chars;
+        self
+    }
+
+    pub fn policy(&self) -> &ServicePolicy {
+        &self.policy
+    }
+
+    /// Checks the uploaded file against the policy and produces a preview,
+    /// waits for a free slot when the maximum number of previews are already
+    /// being processed
+    ///
+    /// ## Arguments
+    /// * data - The raw PDF file bytes
+    pub async fn preview(&self, data: &[u8]) -> Result<PreviewBundle, ServiceError> {
+        self.preview_inner(data, None).await
+    }
+
+    /// Checks the uploaded file against the policy and produces a preview,
+    /// opening the file with the provided password. Encrypted files are
+    /// rejected unless [ServicePolicy::allow_encrypted] is set
+    ///
+    /// ## Arguments
+    /// * data - The raw PDF file bytes
+    /// * password - The password to open the file with
+    pub async fn preview_with_password(
+        &self,
+        data: &[u8],
+        password: &Password,
+    ) -> Result<PreviewBundle, ServiceError> {
+        self.preview_inner(data, Some(password)).await
+    }
+
+    async fn preview_inner(
+        &self,
+        data: &[u8],
+        password: Option<&Password>,
+    ) -> Result<PreviewBundle, ServiceError> {
+        self.policy.check_upload(data)?;
+
+        let _permit = self
+            .limiter
+            .acquire()
+            .await
+            .map_err(|_| ServiceError::Closed)?;
+
+        let info = match pdf_info(data, &self.policy.info_args(password)).await {
+            Ok(value) => value,
+            Err(PdfInfoError::PdfEncrypted | PdfInfoError::IncorrectPassword)
+                if !self.policy.allow_encrypted =>
+            {
+                return Err(PolicyViolation::Encrypted.into())
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        // Files opened with the password are still encrypted
+        if !self.policy.allow_encrypted && info.encrypted().unwrap_or_default() {
+            return Err(PolicyViolation::Encrypted.into());
+        }
+
+        if !self.policy.allow_javascript && info.javascript().unwrap_or_default() {
+            return Err(PolicyViolation::JavaScript.into());
+        }
+
+        let summary = info.summary();
+        self.policy.check_summary(&summary)?;
+
+        let render_args = self.policy.render_args(password, self.thumbnail_width);
+        let text_args = self.policy.text_args(password);
+
+        let (thumbnail, text) = try_join(
+            async {
+                render_single_page_raw(data, &info, self.thumbnail_format, 1, &render_args)
+                    .await
+                    .map_err(ServiceError::from)
+            },
+            async {
+                text_single_page(data, &info, 1, &text_args)
+                    .await
+                    .map_err(ServiceError::from)
+            },
+        )
+        .await?;
+
+        let text = text.chars().take(self.max_text_chars).collect();
+
+        Ok(PreviewBundle {
+            info: summary,
+            thumbnail,
+            thumbnail_format: self.thumbnail_format,
+            text,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{PolicyViolation, PreviewService, ServicePolicy};
+    use crate::{info::PdfSummary, shared::Password};
+
+    /// Tests the policy limits and password are applied to the args
+    #[test]
+    fn test_policy_args() {
+        let policy = ServicePolicy::default()
+            .set_timeout(Duration::from_secs(5))
+            .set_max_output_size(1024);
+        let password = Password::user("secret");
+
+        let args = policy.info_args(Some(&password));
+        assert_eq!(args.timeout, Some(Duration::from_secs(5)));
+        assert!(args.password.is_some());
+
+        let args = policy.render_args(None, 128);
+        assert_eq!(args.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(args.max_output_size, Some(1024));
+        assert!(args.password.is_none());
+
+        let args = policy.text_args(Some(&password));
+        assert_eq!(args.max_output_size, Some(1024));
+        assert!(args.password.is_some());
+    }
+
+    /// Tests a concurrency of zero still allows previews to run
+    #[test]
+    fn test_zero_concurrency() {
+        let service = PreviewService::new(ServicePolicy::default(), 0);
+        assert_eq!(service.limiter.available_permits(), 1);
+    }
+
+    /// Tests uploads are checked before processing
+    #[test]
+    fn test_check_upload() {
+        let policy = ServicePolicy::default().set_max_upload_size(16);
+
+        policy.check_upload(b"%PDF-1.7\n").unwrap();
+
+        let err = policy.check_upload(b"Not a pdf file").unwrap_err();
+        assert!(matches!(err, PolicyViolation::NotPdf));
+
+        let err = policy.check_upload(&[b'A'; 17]).unwrap_err();
+        assert!(matches!(err, PolicyViolation::TooLarge(17, 16)));
+    }
+
+    /// Tests the page count is checked against the policy
+    #[test]
+    fn test_check_summary() {
+        let policy = ServicePolicy::default().set_max_pages(2);
+        let summary = PdfSummary {
+            pages: Some(3),
+            title: None,
+            subject: None,
+            keywords: None,
+            author: None,
+            creator: None,
+            producer: None,
+            creation_date: None,
+            mod_date: None,
+            encrypted: Some(false),
+            page_size: None,
+            pdf_version: None,
+        };
+
+        let err = policy.check_summary(&summary).unwrap_err();
+        assert!(matches!(err, PolicyViolation::TooManyPages(3, 2)));
+    }
+}
@@ -1,12 +1,74 @@
-use std::fmt::{Debug, Display};
+use std::{
+    collections::HashMap,
+    ffi::{OsStr, OsString},
+    fmt::{Debug, Display},
+    fs::OpenOptions,
+    io::{ErrorKind, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    process::{ExitStatus, Stdio},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use futures_util::{
+    future::try_join3,
+    stream::{self, FuturesOrdered},
+    StreamExt, TryStreamExt,
+};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    process::{Child, Command},
+    task::JoinSet,
+};
+
+use crate::{
+    budget::{Budget, BudgetExceeded, BudgetLimit},
+    info::{info_page_count, PdfInfo, PermissionDenied},
+};
 
 /// Password for a DPF
+///
+/// Passwords are not required to be UTF-8, the standard security handlers
+/// allow Latin-1 and raw byte passwords. Use [Password::owner_bytes] or
+/// [Password::user_bytes] for these, they are passed through to the tools
+/// unchanged
 #[derive(Debug, Clone)]
 pub enum Password {
     /// Specify the owner password for the PDF file.  Providing this will bypass all security re‐strictions.
     Owner(Secret<String>),
     /// Specify the user password for the PDF file.
     User(Secret<String>),
+    /// Owner password that is not valid UTF-8, created with [Password::owner_bytes]
+    OwnerBytes(Secret<OsString>),
+    /// User password that is not valid UTF-8, created with [Password::user_bytes]
+    UserBytes(Secret<OsString>),
+}
+
+/// Errors that can occur when creating a [Password]
+#[derive(Debug, Error)]
+pub enum PasswordError {
+    /// The platform only accepts UTF-8 arguments so the password
+    /// cannot be passed to the tools without changing it
+    #[error("password is not valid UTF-8, byte passwords are only supported on unix platforms")]
+    NotUtf8,
+}
+
+impl From<String> for Password {
+    /// Creates a user password
+    fn from(value: String) -> Self {
+        Self::user(value)
+    }
+}
+
+impl From<&str> for Password {
+    /// Creates a user password
+    fn from(value: &str) -> Self {
+        Self::user(value)
+    }
 }
 
 impl Password {
@@ -18,24 +80,100 @@ impl Password {
         Self::User(Secret(value.into()))
     }
 
+    /// Creates an owner password from raw bytes (e.g. a Latin-1 password),
+    /// fails with [PasswordError::NotUtf8] when the bytes are not UTF-8 on
+    /// platforms that cannot pass them to the tools unchanged
+    ///
+    /// ## Arguments
+    /// * value - The password bytes
+    pub fn owner_bytes(value: impl Into<Vec<u8>>) -> Result<Self, PasswordError> {
+        match String::from_utf8(value.into()) {
+            Ok(value) => Ok(Self::Owner(Secret(value))),
+            Err(err) => {
+                bytes_to_os_string(err.into_bytes()).map(|value| Self::OwnerBytes(Secret(value)))
+            }
+        }
+    }
+
+    /// Creates a user password from raw bytes (e.g. a Latin-1 password),
+    /// fails with [PasswordError::NotUtf8] when the bytes are not UTF-8 on
+    /// platforms that cannot pass them to the tools unchanged
+    ///
+    /// ## Arguments
+    /// * value - The password bytes
+    pub fn user_bytes(value: impl Into<Vec<u8>>) -> Result<Self, PasswordError> {
+        match String::from_utf8(value.into()) {
+            Ok(value) => Ok(Self::User(Secret(value))),
+            Err(err) => {
+                bytes_to_os_string(err.into_bytes()).map(|value| Self::UserBytes(Secret(value)))
+            }
+        }
+    }
+
+    /// Pushes the password argument for the poppler tools. Byte passwords
+    /// that are not UTF-8 are converted lossily, the tools run by this crate
+    /// are given the password separately so it is kept intact
     pub fn push_arg(&self, args: &mut Vec<String>) {
+        let (flag, password) = self.flag_value();
+
+        if !password.is_empty() {
+            args.push(flag.to_string());
+            args.push(password.to_string_lossy().into_owned());
+        }
+    }
+
+    /// Flag used by the poppler tools for the password along with its value
+    fn flag_value(&self) -> (&'static str, &OsStr) {
         match self {
-            Password::Owner(Secret(password)) => {
-                if !password.is_empty() {
-                    args.push("-opw".to_string());
-                    args.push(password.to_string())
-                }
+            Password::Owner(Secret(password)) => ("-opw", password.as_ref()),
+            Password::User(Secret(password)) => ("-upw", password.as_ref()),
+            Password::OwnerBytes(Secret(password)) => ("-opw", password),
+            Password::UserBytes(Secret(password)) => ("-upw", password),
+        }
+    }
+
+    /// Pushes the password argument in the form expected by the `program`
+    /// without converting the password, qpdf takes either password
+    /// through `--password`
+    pub(crate) fn push_tool_arg(&self, program: &str, args: &mut Vec<OsString>) {
+        let (flag, password) = self.flag_value();
+
+        if password.is_empty() {
+            return;
+        }
+
+        match program {
+            "qpdf" => {
+                let mut arg = OsString::from("--password=");
+                arg.push(password);
+                args.push(arg);
             }
-            Password::User(Secret(password)) => {
-                if !password.is_empty() {
-                    args.push("-upw".to_string());
-                    args.push(password.to_string())
-                }
+            _ => {
+                args.push(flag.into());
+                args.push(password.to_os_string());
             }
         }
     }
 }
 
+/// Converts the password bytes into an argument, unix arguments are
+/// arbitrary bytes so any password can be passed losslessly, other
+/// platforms require the password to be valid UTF-8
+fn bytes_to_os_string(value: Vec<u8>) -> Result<OsString, PasswordError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Ok(OsString::from_vec(value))
+    }
+
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(value)
+            .map(OsString::from)
+            .map_err(|_| PasswordError::NotUtf8)
+    }
+}
+
 /// Wrapper around some value to hide the [Debug] and [Display] for
 /// values that shouldn't be printed
 #[derive(Clone)]
@@ -58,3 +196,1737 @@ impl<T> Display for Secret<T> {
         f.write_str("******")
     }
 }
+
+/// Destination for the stderr output of the tools, used instead of keeping
+/// all of stderr in memory. Useful for debugging documents whose warnings
+/// run to megabytes.
+///
+/// The output is written as it arrives, when multiple processes share the
+/// same sink (e.g. rendering multiple pages) their output can be interleaved.
+/// Only the first [STDERR_RETAIN_LIMIT] bytes are kept in memory for
+/// classifying failures and for error values
+#[derive(Clone)]
+pub struct StderrSink(Arc<Mutex<Box<dyn Write + Send>>>);
+
+/// Number of bytes of stderr kept in memory when a [StderrSink] is used
+pub const STDERR_RETAIN_LIMIT: usize = 64 * 1024;
+
+impl StderrSink {
+    /// Creates a sink writing to the provided writer
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(Box::new(writer))))
+    }
+
+    /// Creates a sink appending to the file at the provided path,
+    /// the file is created if it does not exist
+    pub fn file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(file))
+    }
+
+    /// Writes a chunk of stderr output to the sink
+    fn write_all(&self, data: &[u8]) -> std::io::Result<()> {
+        let mut writer = self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        writer.write_all(data)?;
+        writer.flush()
+    }
+}
+
+impl Debug for StderrSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StderrSink")
+    }
+}
+
+/// Lowered CPU and IO priority for the spawned tools, used for batch work
+/// (e.g. re-indexing) that should not compete with interactive requests
+///
+/// On Unix the tool is started through `nice` and on Linux through `ionice`,
+/// both must be available on the `PATH`. On Windows the niceness is mapped
+/// to the below normal (1 to 9) or idle (10 and above) priority class
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessPriority {
+    /// Niceness added to the tool process from 0 to 19, higher values
+    /// get less CPU time
+    pub nice: u8,
+    /// Whether to use the idle IO scheduling class, the tool only gets
+    /// disk time when no other process needs it. Only used on Linux
+    pub idle_io: bool,
+}
+
+impl ProcessPriority {
+    /// Niceness of the [ProcessPriority::background] priority
+    pub const BACKGROUND_NICE: u8 = 10;
+    pub const MAX_NICE: u8 = 19;
+
+    /// Priority for background jobs, lowers the CPU priority and uses
+    /// the idle IO scheduling class
+    pub fn background() -> Self {
+        Self {
+            nice: Self::BACKGROUND_NICE,
+            idle_io: true,
+        }
+    }
+
+    /// Sets the niceness, clamped to the range 0 to 19
+    pub fn set_nice(mut self, nice: u8) -> Self {
+        self.nice = nice.min(Self::MAX_NICE);
+        self
+    }
+
+    pub fn set_idle_io(mut self, idle_io: bool) -> Self {
+        self.idle_io = idle_io;
+        self
+    }
+
+    /// Creates the command for running the program at this priority
+    #[cfg(unix)]
+    fn command(&self, program: PathBuf) -> Command {
+        let mut wrapper: Vec<OsString> = Vec::new();
+
+        if cfg!(target_os = "linux") && self.idle_io {
+            wrapper.extend(["ionice", "-c", "3"].map(OsString::from));
+        }
+
+        if self.nice > 0 {
+            let nice = self.nice.min(Self::MAX_NICE).to_string();
+            wrapper.extend(["nice".into(), "-n".into(), nice.into()]);
+        }
+
+        let Some((wrapper, wrapper_args)) = wrapper.split_first() else {
+            return Command::new(program);
+        };
+
+        let mut command = Command::new(wrapper);
+        command.args(wrapper_args).arg(program);
+        command
+    }
+
+    /// Creates the command for running the program at this priority
+    #[cfg(windows)]
+    fn command(&self, program: PathBuf) -> Command {
+        const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+        const IDLE_PRIORITY_CLASS: u32 = 0x0000_0040;
+
+        let mut command = Command::new(program);
+        match self.nice {
+            0 => {}
+            1..=9 => _ = command.creation_flags(BELOW_NORMAL_PRIORITY_CLASS),
+            _ => _ = command.creation_flags(IDLE_PRIORITY_CLASS),
+        }
+        command
+    }
+
+    /// Creates the command for running the program at this priority
+    #[cfg(not(any(unix, windows)))]
+    fn command(&self, program: PathBuf) -> Command {
+        Command::new(program)
+    }
+}
+
+/// Output collected from a finished child process
+#[derive(Debug)]
+pub(crate) struct ChildOutput<T = Vec<u8>> {
+    /// Exit status of the process
+    pub status: ExitStatus,
+    /// Bytes written to stdout, or the number of bytes when stdout
+    /// was streamed to a writer
+    pub stdout: T,
+    /// Bytes written to stderr
+    pub stderr: Vec<u8>,
+}
+
+/// Errors that can occur while feeding and reading a child process
+#[derive(Debug)]
+pub(crate) enum ChildError {
+    /// Failed to write the input to stdin
+    WriteInput(std::io::Error),
+    /// Failed to read the output or exit status
+    ReadOutput(std::io::Error),
+    /// Failed to write the output to the provided writer
+    WriteOutput(std::io::Error),
+    /// Stdout exceeded the provided limit
+    OutputLimitExceeded(usize),
+    /// Process did not finish within the provided timeout
+    Timeout(Duration),
+}
+
+/// Size of the chunks stdout is read in
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Writes the provided input to the child stdin while streaming its stdout
+/// into the `writer` and reading stderr as they arrive, rather than waiting
+/// for the process to exit before reading anything.
+///
+/// When a `limit` is provided and stdout grows beyond it the read is aborted
+/// early, the child is killed when it is dropped (Commands should be created
+/// with `kill_on_drop(true)`)
+///
+/// ## Arguments
+/// * program - Name of the program, used for logging
+/// * child - The child process, must have piped stdin, stdout and stderr
+/// * input - The bytes to write to stdin
+/// * limit - Optional maximum number of bytes allowed on stdout
+/// * stderr_sink - Optional destination for stderr, only the start of
+///   stderr is kept in the output when provided
+/// * writer - Destination for stdout
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) async fn run_child<W>(
+    program: &str,
+    mut child: Child,
+    input: &[u8],
+    limit: Option<usize>,
+    stderr_sink: Option<&StderrSink>,
+    writer: &mut W,
+) -> Result<ChildOutput<usize>, ChildError>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    // Should always have these when using Stdio::piped()
+    let mut stdin = child.stdin.take().expect("process missing piped stdin");
+    let mut stdout = child.stdout.take().expect("process missing piped stdout");
+    let mut stderr = child.stderr.take().expect("process missing piped stderr");
+
+    let write = async move {
+        let closed_early = match stdin.write_all(input).await {
+            Ok(()) => false,
+            // Child exited before reading all the input, its exit status and
+            // stderr describe the actual failure so continue reading those
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => true,
+            Err(err) => return Err(ChildError::WriteInput(err)),
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "pdf_process",
+            tool = program,
+            input_len = input.len(),
+            closed_early,
+            "stdin written"
+        );
+        #[cfg(not(feature = "tracing"))]
+        let _ = closed_early;
+
+        // Dropping stdin closes it so the child knows the input has ended
+        drop(stdin);
+        Ok(())
+    };
+
+    let read_stderr = async move {
+        let Some(sink) = stderr_sink else {
+            let mut output = Vec::new();
+            stderr
+                .read_to_end(&mut output)
+                .await
+                .map_err(ChildError::ReadOutput)?;
+            return Ok(output);
+        };
+
+        let mut output = Vec::new();
+        let mut chunk = vec![0; READ_CHUNK_SIZE];
+
+        loop {
+            let count = stderr
+                .read(&mut chunk)
+                .await
+                .map_err(ChildError::ReadOutput)?;
+
+            if count == 0 {
+                break;
+            }
+
+            let chunk = &chunk[..count];
+            sink.write_all(chunk).map_err(ChildError::ReadOutput)?;
+
+            let retain = STDERR_RETAIN_LIMIT.saturating_sub(output.len());
+            output.extend_from_slice(&chunk[..retain.min(count)]);
+        }
+
+        Ok(output)
+    };
+
+    let ((), stdout, stderr) =
+        try_join3(write, copy_limited(&mut stdout, writer, limit), read_stderr).await?;
+
+    let status = child.wait().await.map_err(ChildError::ReadOutput)?;
+
+    Ok(ChildOutput {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Options for running one of the poppler tools
+#[derive(Debug, Default, Clone)]
+pub(crate) struct RunOptions {
+    /// Optional maximum number of bytes allowed on stdout
+    pub max_output_size: Option<usize>,
+    /// Optional maximum time the process is allowed to run for
+    pub timeout: Option<Duration>,
+    /// Password to open the file with, passed separately from the other
+    /// arguments so non UTF-8 passwords are kept intact. Also decides whether
+    /// password failures are reported as encrypted or incorrect password
+    pub password: Option<Password>,
+    /// Optional destination for stderr instead of keeping it in memory
+    pub stderr: Option<StderrSink>,
+    /// Optional lowered priority to run the process at
+    pub priority: Option<ProcessPriority>,
+    /// Optional limits shared with the other processes of the operation
+    pub budget: Option<Budget>,
+}
+
+/// Errors from running one of the poppler tools, each module maps
+/// these into its own error type
+#[derive(Debug)]
+pub(crate) enum ToolError {
+    /// Failed to spawn the process
+    Spawn(std::io::Error),
+    /// Failed while feeding or reading the process
+    Child(ChildError),
+    /// Input was not a PDF file
+    NotPdfFile,
+    /// PDF is encrypted and no password was provided
+    PdfEncrypted,
+    /// Provided password was incorrect
+    IncorrectPassword,
+    /// Requested page and the page count of the PDF
+    PageOutOfBounds(u32, u32),
+    /// Page count is missing from the PDF info
+    PageCountUnknown,
+    /// PDF does not contain any pages and [EmptyDocumentPolicy::Error] was used
+    EmptyDocument,
+    /// Operation is not permitted by the PDF permissions
+    PermissionError(PermissionDenied),
+    /// Any other failure, contains the stderr output
+    Failure(String),
+    /// Spawned task failed to complete
+    Task(String),
+    /// Operation was aborted through its job handle
+    Aborted,
+    /// Operation reached a limit of its [Budget]
+    BudgetExceeded(BudgetExceeded),
+}
+
+impl From<ChildError> for ToolError {
+    fn from(value: ChildError) -> Self {
+        Self::Child(value)
+    }
+}
+
+impl From<BudgetExceeded> for ToolError {
+    fn from(value: BudgetExceeded) -> Self {
+        Self::BudgetExceeded(value)
+    }
+}
+
+/// Locations of the poppler tools, by default the tools are found
+/// using the `PATH`
+#[derive(Debug, Clone, Default)]
+pub struct ToolPaths {
+    /// Directory containing all the tools
+    pub directory: Option<PathBuf>,
+    /// Paths for specific tools, takes priority over the directory
+    pub tools: HashMap<String, PathBuf>,
+}
+
+impl ToolPaths {
+    pub fn set_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Sets the path for a specific tool (e.g. "pdftocairo")
+    pub fn set_tool(mut self, tool: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        self.tools.insert(tool.into(), path.into());
+        self
+    }
+
+    /// Resolves the path to run the provided tool with
+    pub fn resolve(&self, tool: &str) -> PathBuf {
+        if let Some(path) = self.tools.get(tool) {
+            return path.clone();
+        }
+
+        match self.directory.as_ref() {
+            Some(directory) => directory.join(tool),
+            None => PathBuf::from(tool),
+        }
+    }
+}
+
+/// Tool paths used by all operations
+static TOOL_PATHS: RwLock<Option<ToolPaths>> = RwLock::new(None);
+
+/// Sets the locations of the poppler tools used by all operations.
+///
+/// This is process wide rather than per args as some operations run
+/// other tools internally (e.g. pdfinfo is used to check encryption
+/// before rendering)
+pub fn configure_tool_paths(tool_paths: ToolPaths) {
+    if let Ok(mut value) = TOOL_PATHS.write() {
+        *value = Some(tool_paths);
+    }
+}
+
+/// Resolves the path to run the provided tool with
+fn tool_path(tool: &str) -> PathBuf {
+    TOOL_PATHS
+        .read()
+        .ok()
+        .and_then(|value| value.as_ref().map(|paths| paths.resolve(tool)))
+        .unwrap_or_else(|| PathBuf::from(tool))
+}
+
+/// Exit code used by the poppler tools for permission errors, qpdf uses
+/// this for warnings so it is always run with `--warning-exit-0`
+const PERMISSION_ERROR_EXIT_CODE: i32 = 3;
+
+/// Runs one of the poppler tools with the provided input on stdin,
+/// failures are classified from the exit code and stderr output
+///
+/// With the `tracing` feature enabled debug events are logged with the
+/// `pdf_process` target for each stage of the process, every event has a
+/// `tool` field along with:
+/// * `spawned` - `pid`, `input_len`
+/// * `spawn failed` - `error`
+/// * `stdin written` - `input_len`, `closed_early`
+/// * `exited` - `code` (Missing when killed by a signal), `success`,
+///   `duration_ms`, `stdout_len`, `stderr_len`
+/// * `timed out` - `duration_ms`
+///
+/// ## Arguments
+/// * program - The poppler tool to run
+/// * args - Arguments for the tool
+/// * input - The bytes to write to stdin
+/// * options - Options for running the tool
+pub(crate) async fn run_tool<S>(
+    program: &str,
+    args: &[S],
+    input: &[u8],
+    options: &RunOptions,
+) -> Result<ChildOutput, ToolError>
+where
+    S: AsRef<OsStr>,
+{
+    let mut stdout = Vec::new();
+    let output = run_tool_to(program, args, input, options, &mut stdout).await?;
+
+    Ok(ChildOutput {
+        status: output.status,
+        stdout,
+        stderr: output.stderr,
+    })
+}
+
+/// Runs one of the poppler tools like [run_tool] streaming its stdout into
+/// the provided `writer` as it is produced, providing the number of bytes
+/// written. Output written before a failure is not removed from the writer
+///
+/// ## Arguments
+/// * program - The poppler tool to run
+/// * args - Arguments for the tool
+/// * input - The bytes to write to stdin
+/// * options - Options for running the tool
+/// * writer - Destination for stdout
+pub(crate) async fn run_tool_to<S, W>(
+    program: &str,
+    args: &[S],
+    input: &[u8],
+    options: &RunOptions,
+    writer: &mut W,
+) -> Result<ChildOutput<usize>, ToolError>
+where
+    S: AsRef<OsStr>,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let budget = options.budget.as_ref();
+    if let Some(budget) = budget {
+        budget.start_process()?;
+    }
+
+    let started = Instant::now();
+
+    let mut password_args = Vec::new();
+    if let Some(password) = options.password.as_ref() {
+        password.push_tool_arg(program, &mut password_args);
+    }
+
+    let mut command = match options.priority {
+        Some(priority) => priority.command(tool_path(program)),
+        None => Command::new(tool_path(program)),
+    };
+
+    let child = command
+        .args(password_args)
+        .args(args)
+        // Use untranslated messages so failures can be classified
+        .env("LC_ALL", "C")
+        .env("LANG", "C")
+        // Pipe input and output for use
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // Kill the process if the operation is aborted early
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(target: "pdf_process", tool = program, error = %err, "spawn failed");
+
+            ToolError::Spawn(err)
+        })?;
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        target: "pdf_process",
+        tool = program,
+        pid = child.id(),
+        input_len = input.len(),
+        "spawned"
+    );
+
+    // The budget may lower the limits of this process
+    let budget_output = budget.and_then(Budget::remaining_output);
+    let budget_time = budget.and_then(Budget::remaining_time);
+    let limit = lowest(options.max_output_size, budget_output);
+    let timeout = lowest(options.timeout, budget_time.map(|(time, _)| time));
+
+    let run = run_child(
+        program,
+        child,
+        input,
+        limit,
+        options.stderr.as_ref(),
+        writer,
+    );
+
+    let output = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, run).await {
+            Ok(output) => output,
+            Err(_) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    target: "pdf_process",
+                    tool = program,
+                    duration_ms = started.elapsed().as_millis() as u64,
+                    "timed out"
+                );
+
+                Err(ChildError::Timeout(timeout))
+            }
+        },
+        None => run.await,
+    };
+
+    let output = match budget {
+        Some(budget) => {
+            let success = output.as_ref().is_ok_and(|output| output.status.success());
+            let written = output.as_ref().map(|output| output.stdout).unwrap_or(0);
+            let finished = budget.finish_process(started.elapsed(), written, success);
+
+            // Report limits lowered by the budget as the budget being exceeded
+            match output {
+                Err(ChildError::Timeout(timeout))
+                    if budget_time.is_some_and(|(time, _)| time == timeout)
+                        && options.timeout != Some(timeout) =>
+                {
+                    let (_, limit) = budget_time.expect("budget time checked above");
+                    return Err(budget.exceeded(limit).into());
+                }
+                Err(ChildError::OutputLimitExceeded(limit))
+                    if budget_output == Some(limit) && options.max_output_size != Some(limit) =>
+                {
+                    return Err(budget.exceeded(BudgetLimit::OutputBytes).into());
+                }
+                output => {
+                    let output = output?;
+                    finished?;
+                    output
+                }
+            }
+        }
+        None => output?,
+    };
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        target: "pdf_process",
+        tool = program,
+        code = output.status.code(),
+        success = output.status.success(),
+        duration_ms = started.elapsed().as_millis() as u64,
+        stdout_len = output.stdout,
+        stderr_len = output.stderr.len(),
+        "exited"
+    );
+
+    if !output.status.success() {
+        return Err(classify_failure(
+            program,
+            &output,
+            input,
+            options.password.is_some(),
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Lowest of two optional limits
+fn lowest<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Exit code used by the poppler tools when the PDF could not be opened
+const OPEN_ERROR_EXIT_CODE: i32 = 1;
+
+/// Hashes the data using 64 bit FNV-1a, unlike the std hashers the
+/// value is stable across Rust versions so it can be stored
+pub(crate) fn fnv1a_64(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, value| {
+        (hash ^ *value as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Messages poppler reports when the input does not look like a PDF
+const NOT_PDF_MESSAGES: &[&str] = &["May not be a PDF file", "may not be a PDF file"];
+
+/// Messages poppler reports when the password is missing or wrong
+const PASSWORD_MESSAGES: &[&str] = &["Incorrect password", "incorrect password"];
+
+/// Number of bytes from the start of the file the PDF header must appear within
+pub(crate) const PDF_HEADER_SEARCH_LENGTH: usize = 1024;
+
+/// Checks whether the PDF header appears near the start of the data
+pub(crate) fn has_pdf_header(data: &[u8]) -> bool {
+    let header = &data[..data.len().min(PDF_HEADER_SEARCH_LENGTH)];
+    header.windows(5).any(|value| value == b"%PDF-")
+}
+
+/// Checks whether the data contains an encryption dictionary reference
+pub(crate) fn has_encrypt_entry(data: &[u8]) -> bool {
+    data.windows(8).any(|value| value == b"/Encrypt")
+}
+
+/// Classifies the failure of a poppler tool from its output. Known messages
+/// are checked first (The tools are run with the C locale so messages are
+/// not translated), when the message is not recognized the exit code is
+/// combined with the input to classify failures to open the file. The raw
+/// stderr is kept for any failure that cannot be classified
+fn classify_failure<T>(
+    program: &str,
+    output: &ChildOutput<T>,
+    input: &[u8],
+    has_password: bool,
+) -> ToolError {
+    let value = String::from_utf8_lossy(&output.stderr);
+
+    let password_error = || {
+        if has_password {
+            ToolError::IncorrectPassword
+        } else {
+            ToolError::PdfEncrypted
+        }
+    };
+
+    if NOT_PDF_MESSAGES
+        .iter()
+        .any(|message| value.contains(message))
+    {
+        return ToolError::NotPdfFile;
+    }
+
+    if PASSWORD_MESSAGES
+        .iter()
+        .any(|message| value.contains(message))
+    {
+        return password_error();
+    }
+
+    if let Some((page, page_count)) = parse_wrong_page_range(&value) {
+        return ToolError::PageOutOfBounds(page, page_count);
+    }
+
+    match output.status.code() {
+        Some(PERMISSION_ERROR_EXIT_CODE) => {
+            ToolError::PermissionError(PermissionDenied::from_output(program, &value))
+        }
+        Some(OPEN_ERROR_EXIT_CODE) if !has_pdf_header(input) => ToolError::NotPdfFile,
+        Some(OPEN_ERROR_EXIT_CODE) if has_encrypt_entry(input) => password_error(),
+        _ => ToolError::Failure(value.to_string()),
+    }
+}
+
+/// Copies all of the provided reader into the writer chunk by chunk,
+/// failing as soon as the total read exceeds the `limit`. Provides the
+/// number of bytes written
+async fn copy_limited<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    limit: Option<usize>,
+) -> Result<usize, ChildError>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut written = 0;
+    let mut chunk = vec![0; READ_CHUNK_SIZE];
+
+    loop {
+        let count = reader
+            .read(&mut chunk)
+            .await
+            .map_err(ChildError::ReadOutput)?;
+
+        if count == 0 {
+            break;
+        }
+
+        if let Some(limit) = limit {
+            if written + count > limit {
+                return Err(ChildError::OutputLimitExceeded(limit));
+            }
+        }
+
+        writer
+            .write_all(&chunk[..count])
+            .await
+            .map_err(ChildError::WriteOutput)?;
+        written += count;
+    }
+
+    writer.flush().await.map_err(ChildError::WriteOutput)?;
+
+    Ok(written)
+}
+
+/// How functions that work on every page handle documents reporting a
+/// page count of zero, common with malformed PDFs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum EmptyDocumentPolicy {
+    /// Succeed with an empty result (Default)
+    #[default]
+    Empty,
+    /// Fail with an `EmptyDocument` error
+    Error,
+}
+
+impl EmptyDocumentPolicy {
+    /// Applies the policy to the page count of a document
+    pub(crate) fn check(self, page_count: u32) -> Result<u32, ToolError> {
+        match self {
+            Self::Error if page_count == 0 => Err(ToolError::EmptyDocument),
+            _ => Ok(page_count),
+        }
+    }
+}
+
+/// Selection of pages within a PDF, resolved into the list of page
+/// numbers using the page count of the PDF
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum PageSelection {
+    /// Every page in the PDF (Default)
+    #[default]
+    All,
+    /// Inclusive range of pages (e.g. `2..=5`)
+    Range(RangeInclusive<u32>),
+    /// Specific pages in the provided order
+    List(Vec<u32>),
+    /// Odd numbered pages (1, 3, 5, ...)
+    Odd,
+    /// Even numbered pages (2, 4, 6, ...)
+    Even,
+    /// Another selection in reverse order
+    Reversed(Box<PageSelection>),
+}
+
+impl PageSelection {
+    /// Reverses the order of the selected pages
+    pub fn reversed(self) -> Self {
+        match self {
+            Self::Reversed(selection) => *selection,
+            selection => Self::Reversed(Box::new(selection)),
+        }
+    }
+
+    /// Page numbers of the selection for a PDF with the provided page
+    /// count. Ranges and lists are kept as provided so pages outside
+    /// the page count are reported when verifying the pages
+    ///
+    /// ## Arguments
+    /// * page_count - The number of pages in the PDF
+    pub fn pages(&self, page_count: u32) -> Vec<u32> {
+        match self {
+            Self::All => (1..=page_count).collect(),
+            Self::Range(range) => range.clone().collect(),
+            Self::List(pages) => pages.clone(),
+            Self::Odd => (1..=page_count).step_by(2).collect(),
+            Self::Even => (2..=page_count).step_by(2).collect(),
+            Self::Reversed(selection) => {
+                let mut pages = selection.pages(page_count);
+                pages.reverse();
+                pages
+            }
+        }
+    }
+
+    /// Whether the page count is needed to resolve the selection
+    fn needs_page_count(&self) -> bool {
+        match self {
+            Self::All | Self::Odd | Self::Even => true,
+            Self::Range(_) | Self::List(_) => false,
+            Self::Reversed(selection) => selection.needs_page_count(),
+        }
+    }
+
+    /// Page numbers of the selection using the page count from the info,
+    /// the page count is only required for selections that depend on it
+    /// and is checked against the `empty_document` policy
+    pub(crate) fn resolve(
+        &self,
+        info: &PdfInfo,
+        empty_document: EmptyDocumentPolicy,
+    ) -> Result<Vec<u32>, ToolError> {
+        let page_count = if self.needs_page_count() {
+            empty_document.check(info_page_count(info)?)?
+        } else {
+            0
+        };
+
+        Ok(self.pages(page_count))
+    }
+}
+
+impl From<Vec<u32>> for PageSelection {
+    fn from(value: Vec<u32>) -> Self {
+        Self::List(value)
+    }
+}
+
+impl From<RangeInclusive<u32>> for PageSelection {
+    fn from(value: RangeInclusive<u32>) -> Self {
+        Self::Range(value)
+    }
+}
+
+/// Strategy for running the work for multiple pages
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FanOut {
+    /// Run all the pages at once on the current task, (Default)
+    #[default]
+    Ordered,
+    /// Run at most this many pages at once on the current task
+    Buffered(usize),
+    /// Run each page as a separate task on the tokio runtime so decoding
+    /// can use multiple threads, the PDF bytes are copied once to share
+    /// them between the tasks
+    Spawned,
+}
+
+/// Single invocation of a poppler tool for a page
+#[derive(Debug, Clone)]
+pub(crate) struct ToolJob {
+    /// The poppler tool to run
+    pub program: &'static str,
+    /// Arguments for the tool
+    pub args: Vec<String>,
+    /// Options for running the tool
+    pub options: RunOptions,
+}
+
+impl ToolJob {
+    /// Runs the job providing its stdout
+    pub async fn run(&self, data: &[u8]) -> Result<Vec<u8>, ToolError> {
+        let output = run_tool(self.program, &self.args, data, &self.options).await?;
+        Ok(output.stdout)
+    }
+
+    /// Runs the job streaming its stdout into the writer, provides
+    /// the number of bytes written
+    pub async fn run_to<W>(&self, data: &[u8], writer: &mut W) -> Result<usize, ToolError>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let output = run_tool_to(self.program, &self.args, data, &self.options, writer).await?;
+        Ok(output.stdout)
+    }
+}
+
+/// Runs a job for each of the pages using the `fan_out` strategy, `finish`
+/// converts the output of each job. Provides the results in the same order
+/// as the pages.
+///
+/// When `dedup` is enabled repeated page numbers only run the job once
+/// and the result is cloned for each occurrence
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * pages - The page numbers to run the job for
+/// * dedup - Whether to only run the job once for repeated pages
+/// * fan_out - Strategy for running the jobs
+/// * job - Creates the job for a page
+/// * finish - Converts the output of a job
+pub(crate) async fn for_each_page<T, E, J, F>(
+    data: &[u8],
+    pages: Vec<u32>,
+    dedup: bool,
+    fan_out: FanOut,
+    job: J,
+    finish: F,
+) -> Result<Vec<T>, E>
+where
+    T: Clone + Send + 'static,
+    E: From<ToolError> + Send + 'static,
+    J: Fn(u32) -> ToolJob,
+    F: Fn(Vec<u8>) -> Result<T, E> + Clone + Send + 'static,
+{
+    if !dedup {
+        let jobs = pages.into_iter().map(job).collect();
+        return run_jobs(data, jobs, fan_out, finish).await;
+    }
+
+    let mut unique = pages.clone();
+    unique.sort_unstable();
+    unique.dedup();
+
+    let jobs = unique.iter().copied().map(job).collect();
+    let results = run_jobs(data, jobs, fan_out, finish).await?;
+
+    // Fan the results back out in the requested order
+    Ok(pages
+        .iter()
+        .map(|page| {
+            let index = unique
+                .binary_search(page)
+                .expect("page missing from unique pages");
+            results[index].clone()
+        })
+        .collect())
+}
+
+/// Runs all the jobs using the `fan_out` strategy providing the
+/// results in the same order as the jobs
+pub(crate) async fn run_jobs<T, E, F>(
+    data: &[u8],
+    jobs: Vec<ToolJob>,
+    fan_out: FanOut,
+    finish: F,
+) -> Result<Vec<T>, E>
+where
+    T: Send + 'static,
+    E: From<ToolError> + Send + 'static,
+    F: Fn(Vec<u8>) -> Result<T, E> + Clone + Send + 'static,
+{
+    match fan_out {
+        FanOut::Ordered => {
+            jobs.iter()
+                .map(|job| async {
+                    let output = job.run(data).await?;
+                    finish(output)
+                })
+                .collect::<FuturesOrdered<_>>()
+                .try_collect()
+                .await
+        }
+
+        FanOut::Buffered(limit) => {
+            let mut results: Vec<(usize, T)> = stream::iter(jobs.iter().enumerate())
+                .map(|(index, job)| {
+                    let finish = finish.clone();
+                    async move {
+                        let output = job.run(data).await?;
+                        Ok::<_, E>((index, finish(output)?))
+                    }
+                })
+                .buffer_unordered(limit.max(1))
+                .try_collect()
+                .await?;
+
+            results.sort_unstable_by_key(|(index, _)| *index);
+            Ok(results.into_iter().map(|(_, value)| value).collect())
+        }
+
+        FanOut::Spawned => {
+            let data: Arc<[u8]> = Arc::from(data);
+
+            // Dropping the set aborts any remaining tasks on failure
+            let mut tasks = JoinSet::new();
+
+            for (index, job) in jobs.into_iter().enumerate() {
+                let data = data.clone();
+                let finish = finish.clone();
+
+                tasks.spawn(async move {
+                    let output = job.run(&data).await?;
+                    Ok::<_, E>((index, finish(output)?))
+                });
+            }
+
+            let mut results = Vec::with_capacity(tasks.len());
+
+            while let Some(result) = tasks.join_next().await {
+                let result = result.map_err(|err| ToolError::Task(err.to_string()))?;
+                results.push(result?);
+            }
+
+            results.sort_unstable_by_key(|(index, _)| *index);
+            Ok(results.into_iter().map(|(_, value)| value).collect())
+        }
+    }
+}
+
+/// Results of a multi-page operation that may have stopped early, keeps
+/// the pages that completed before the operation failed (e.g. a timeout,
+/// an exceeded [Budget] or being aborted) along with the error
+#[derive(Debug)]
+pub struct PartialResult<T, E> {
+    /// Completed pages paired with their page number in the requested order
+    pub completed: Vec<(u32, T)>,
+    /// Error that stopped the operation, [None] when every page completed
+    pub error: Option<E>,
+}
+
+impl<T, E> PartialResult<T, E> {
+    /// Result for an operation that failed before any page completed
+    pub fn failed(error: E) -> Self {
+        Self {
+            completed: Vec::new(),
+            error: Some(error),
+        }
+    }
+
+    /// Whether every requested page completed
+    pub fn is_complete(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Converts into a regular result, the completed pages are
+    /// discarded when there was an error
+    pub fn into_result(self) -> Result<Vec<(u32, T)>, E> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.completed),
+        }
+    }
+}
+
+/// Runs a job for each of the pages like [for_each_page] but stops at the
+/// first error keeping the results of the pages that already completed,
+/// jobs still running when the error occurs are aborted
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * pages - The page numbers to run the job for
+/// * dedup - Whether to only run the job once for repeated pages
+/// * fan_out - Strategy for running the jobs
+/// * job - Creates the job for a page
+/// * finish - Converts the output of a job
+pub(crate) async fn for_each_page_partial<T, E, J, F>(
+    data: &[u8],
+    pages: Vec<u32>,
+    dedup: bool,
+    fan_out: FanOut,
+    job: J,
+    finish: F,
+) -> PartialResult<T, E>
+where
+    T: Clone + Send + 'static,
+    E: From<ToolError> + Send + 'static,
+    J: Fn(u32) -> ToolJob,
+    F: Fn(Vec<u8>) -> Result<T, E> + Clone + Send + 'static,
+{
+    let mut unique = pages.clone();
+    if dedup {
+        unique.sort_unstable();
+        unique.dedup();
+    }
+
+    let jobs: Vec<ToolJob> = unique.iter().copied().map(job).collect();
+    let mut results: Vec<(usize, T)> = Vec::with_capacity(jobs.len());
+    let mut error = None;
+
+    match fan_out {
+        FanOut::Ordered | FanOut::Buffered(_) => {
+            let limit = match fan_out {
+                FanOut::Buffered(limit) => limit.max(1),
+                _ => jobs.len().max(1),
+            };
+
+            let mut outputs = stream::iter(jobs.iter().enumerate())
+                .map(|(index, job)| {
+                    let finish = finish.clone();
+                    async move {
+                        let output = job.run(data).await?;
+                        Ok::<_, E>((index, finish(output)?))
+                    }
+                })
+                .buffer_unordered(limit);
+
+            while let Some(result) = outputs.next().await {
+                match result {
+                    Ok(value) => results.push(value),
+                    Err(err) => {
+                        error = Some(err);
+                        break;
+                    }
+                }
+            }
+        }
+
+        FanOut::Spawned => {
+            let data: Arc<[u8]> = Arc::from(data);
+
+            // Dropping the set aborts any remaining tasks on failure
+            let mut tasks = JoinSet::new();
+
+            for (index, job) in jobs.into_iter().enumerate() {
+                let data = data.clone();
+                let finish = finish.clone();
+
+                tasks.spawn(async move {
+                    let output = job.run(&data).await?;
+                    Ok::<_, E>((index, finish(output)?))
+                });
+            }
+
+            while let Some(result) = tasks.join_next().await {
+                let result = result
+                    .map_err(|err| E::from(ToolError::Task(err.to_string())))
+                    .and_then(|result| result);
+
+                match result {
+                    Ok(value) => results.push(value),
+                    Err(err) => {
+                        error = Some(err);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut values: Vec<Option<T>> = (0..unique.len()).map(|_| None).collect();
+    for (index, value) in results {
+        values[index] = Some(value);
+    }
+
+    // Requested order, repeated pages are cloned when deduplicated
+    let completed = if dedup {
+        pages
+            .iter()
+            .filter_map(|page| {
+                let index = unique
+                    .binary_search(page)
+                    .expect("page missing from unique pages");
+                Some((*page, values[index].clone()?))
+            })
+            .collect()
+    } else {
+        pages
+            .into_iter()
+            .zip(values)
+            .filter_map(|(page, value)| Some((page, value?)))
+            .collect()
+    };
+
+    PartialResult { completed, error }
+}
+
+/// Parses the page range error reported by poppler when a page beyond
+/// the end of the document is requested:
+///
+/// "Wrong page range given: the first page (3) can not be after the last page (2)."
+///
+/// Poppler clamps the last page to the page count so this provides the
+/// requested page and the page count
+fn parse_wrong_page_range(stderr: &str) -> Option<(u32, u32)> {
+    let (_, message) = stderr.split_once("Wrong page range given")?;
+
+    let mut numbers = message
+        .split(['(', ')'])
+        .skip(1)
+        .step_by(2)
+        .filter_map(|value| value.parse::<u32>().ok());
+
+    let requested = numbers.next()?;
+    let page_count = numbers.next()?;
+
+    Some((requested, page_count))
+}
+
+/// Prefix of the temporary directories created while running the tools,
+/// directories left behind by crashed processes can be removed using
+/// [PdfProcessor::sweep_temp_dirs](crate::processor::PdfProcessor::sweep_temp_dirs)
+pub const TEMP_DIR_PREFIX: &str = "pdf_process";
+
+/// Number of PDF points in an inch, resolutions are in pixels per inch
+pub const POINTS_PER_INCH: f32 = 72.0;
+
+/// Converts a length in PDF points to pixels at the provided resolution
+fn points_to_pixels(value: f32, ppi: f32) -> f32 {
+    value * ppi / POINTS_PER_INCH
+}
+
+/// Converts a length in pixels at the provided resolution to PDF points
+fn pixels_to_points(value: f32, ppi: f32) -> f32 {
+    value * POINTS_PER_INCH / ppi
+}
+
+/// Point in either PDF points or pixels depending on where it is used
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Converts from PDF points to pixels at the provided resolution
+    pub fn to_pixels(&self, ppi: f32) -> Self {
+        Self::new(points_to_pixels(self.x, ppi), points_to_pixels(self.y, ppi))
+    }
+
+    /// Converts from pixels at the provided resolution to PDF points
+    pub fn to_points(&self, ppi: f32) -> Self {
+        Self::new(pixels_to_points(self.x, ppi), pixels_to_points(self.y, ppi))
+    }
+}
+
+/// Size in either PDF points or pixels depending on where it is used
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Size {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+
+    /// Size with the width and height swapped
+    pub fn transposed(&self) -> Self {
+        Self::new(self.height, self.width)
+    }
+
+    /// Converts from PDF points to pixels at the provided resolution
+    pub fn to_pixels(&self, ppi: f32) -> Self {
+        Self::new(
+            points_to_pixels(self.width, ppi),
+            points_to_pixels(self.height, ppi),
+        )
+    }
+
+    /// Converts from pixels at the provided resolution to PDF points
+    pub fn to_points(&self, ppi: f32) -> Self {
+        Self::new(
+            pixels_to_points(self.width, ppi),
+            pixels_to_points(self.height, ppi),
+        )
+    }
+}
+
+/// Rectangle in either PDF points or pixels depending on where it is used,
+/// `origin` is the corner with the smallest coordinates
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Rect {
+    pub origin: Point,
+    pub size: Size,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            origin: Point::new(x, y),
+            size: Size::new(width, height),
+        }
+    }
+
+    /// Creates the rectangle between two corners, the corners
+    /// may be provided in any order
+    pub fn from_corners(a: Point, b: Point) -> Self {
+        let (x_min, x_max) = (a.x.min(b.x), a.x.max(b.x));
+        let (y_min, y_max) = (a.y.min(b.y), a.y.max(b.y));
+        Self::new(x_min, y_min, x_max - x_min, y_max - y_min)
+    }
+
+    /// Corner with the smallest coordinates
+    pub fn min(&self) -> Point {
+        self.origin
+    }
+
+    /// Corner with the largest coordinates
+    pub fn max(&self) -> Point {
+        Point::new(
+            self.origin.x + self.size.width,
+            self.origin.y + self.size.height,
+        )
+    }
+
+    /// Creates the smallest rectangle containing both rectangles
+    pub fn union(&self, other: &Rect) -> Rect {
+        let (min, max) = (self.min(), self.max());
+        let (other_min, other_max) = (other.min(), other.max());
+
+        Rect::from_corners(
+            Point::new(min.x.min(other_min.x), min.y.min(other_min.y)),
+            Point::new(max.x.max(other_max.x), max.y.max(other_max.y)),
+        )
+    }
+
+    /// Area shared by both rectangles, [None] when they do not overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let (min, max) = (self.min(), self.max());
+        let (other_min, other_max) = (other.min(), other.max());
+
+        let min = Point::new(min.x.max(other_min.x), min.y.max(other_min.y));
+        let max = Point::new(max.x.min(other_max.x), max.y.min(other_max.y));
+
+        (min.x < max.x && min.y < max.y).then(|| Rect::from_corners(min, max))
+    }
+
+    /// Checks whether the point is within the rectangle
+    pub fn contains(&self, point: Point) -> bool {
+        let (min, max) = (self.min(), self.max());
+        point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y
+    }
+
+    /// Converts from PDF points to pixels at the provided resolution
+    pub fn to_pixels(&self, ppi: f32) -> Self {
+        Self {
+            origin: self.origin.to_pixels(ppi),
+            size: self.size.to_pixels(ppi),
+        }
+    }
+
+    /// Converts from pixels at the provided resolution to PDF points
+    pub fn to_points(&self, ppi: f32) -> Self {
+        Self {
+            origin: self.origin.to_points(ppi),
+            size: self.size.to_points(ppi),
+        }
+    }
+}
+
+/// Replaces the XML entities escaped by the poppler tools
+pub(crate) fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(unix)]
+    use std::{os::unix::process::ExitStatusExt, process::ExitStatus};
+
+    #[cfg(unix)]
+    use super::{classify_failure, ChildOutput};
+    use super::{
+        copy_limited, for_each_page, for_each_page_partial, parse_wrong_page_range, run_tool,
+        ChildError, EmptyDocumentPolicy, FanOut, PageSelection, Password, Point, ProcessPriority,
+        Rect, RunOptions, Secret, Size, StderrSink, ToolError, ToolJob, ToolPaths,
+        STDERR_RETAIN_LIMIT,
+    };
+    use crate::budget::{Budget, BudgetLimit};
+    #[cfg(unix)]
+    use crate::info::PdfPermission;
+
+    /// Tests reading output that fits within the limit
+    #[tokio::test]
+    async fn test_read_within_limit() {
+        let mut reader: &[u8] = b"Test output";
+        let mut output = Vec::new();
+        let written = copy_limited(&mut reader, &mut output, Some(11))
+            .await
+            .unwrap();
+        assert_eq!(written, 11);
+        assert_eq!(output, b"Test output");
+    }
+
+    /// Tests reading is aborted when the limit is exceeded
+    #[tokio::test]
+    async fn test_read_exceeds_limit() {
+        let mut reader: &[u8] = b"Test output";
+        let err = copy_limited(&mut reader, &mut Vec::new(), Some(4))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ChildError::OutputLimitExceeded(4)));
+    }
+
+    /// Tests parsing the page range error from poppler
+    #[test]
+    fn test_parse_wrong_page_range() {
+        let value =
+            "Wrong page range given: the first page (3) can not be after the last page (2).\n";
+        assert_eq!(parse_wrong_page_range(value), Some((3, 2)));
+
+        assert_eq!(parse_wrong_page_range("Syntax Error: Invalid page"), None);
+    }
+
+    /// Tests each fan out strategy provides the results in the page order
+    /// both with and without deduplication
+    #[tokio::test]
+    async fn test_for_each_page() {
+        let job = |page: u32| ToolJob {
+            program: "sh",
+            args: vec![
+                "-c".to_string(),
+                // Later pages finish first to check the results are reordered
+                format!("sleep 0.0{}; echo -n {page}", 5 - page),
+            ],
+            options: RunOptions::default(),
+        };
+        let finish = |output: Vec<u8>| Ok::<_, ToolError>(String::from_utf8(output).unwrap());
+
+        for fan_out in [FanOut::Ordered, FanOut::Buffered(2), FanOut::Spawned] {
+            let output = for_each_page(&[], vec![3, 1, 3, 2, 1], true, fan_out, job, finish)
+                .await
+                .unwrap();
+            assert_eq!(output, vec!["3", "1", "3", "2", "1"]);
+
+            let output = for_each_page(&[], vec![3, 1, 4], false, fan_out, job, finish)
+                .await
+                .unwrap();
+            assert_eq!(output, vec!["3", "1", "4"]);
+        }
+    }
+
+    /// Tests the pages completed before a failure are kept
+    #[tokio::test]
+    async fn test_for_each_page_partial() {
+        let job = |page: u32| ToolJob {
+            program: "sh",
+            args: vec![
+                "-c".to_string(),
+                match page {
+                    3 => "sleep 0.1; echo failed >&2; exit 2".to_string(),
+                    4 => "sleep 5".to_string(),
+                    page => format!("echo -n {page}"),
+                },
+            ],
+            options: RunOptions::default(),
+        };
+        let finish = |output: Vec<u8>| Ok::<_, ToolError>(String::from_utf8(output).unwrap());
+
+        for fan_out in [FanOut::Ordered, FanOut::Buffered(4), FanOut::Spawned] {
+            let result =
+                for_each_page_partial(&[], vec![2, 4, 1, 3, 2], true, fan_out, job, finish).await;
+            assert_eq!(
+                result.completed,
+                [
+                    (2, "2".to_string()),
+                    (1, "1".to_string()),
+                    (2, "2".to_string())
+                ]
+            );
+            assert!(matches!(result.error, Some(ToolError::Failure(_))));
+        }
+
+        // Pages after the budget is used are not started
+        let options = RunOptions {
+            budget: Some(Budget::default().set_max_processes(2)),
+            ..Default::default()
+        };
+        let job = |page: u32| ToolJob {
+            program: "echo",
+            args: vec!["-n".to_string(), page.to_string()],
+            options: options.clone(),
+        };
+        let result =
+            for_each_page_partial(&[], vec![1, 2, 5], false, FanOut::Buffered(1), job, finish)
+                .await;
+        assert_eq!(result.completed.len(), 2);
+        let Some(ToolError::BudgetExceeded(err)) = result.error else {
+            panic!("expected budget exceeded");
+        };
+        assert_eq!(err.limit, BudgetLimit::Processes);
+        assert_eq!(err.usage.completed, 2);
+    }
+
+    /// Tests classifying failures from the exit code and stderr
+    #[cfg(unix)]
+    #[test]
+    fn test_classify_failure() {
+        let output = |code: i32, stderr: &str| ChildOutput {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: Vec::<u8>::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+
+        let value = output(
+            1,
+            "Syntax Warning: May not be a PDF file (continuing anyway)",
+        );
+        assert!(matches!(
+            classify_failure("pdftotext", &value, b"%PDF-1.7", false),
+            ToolError::NotPdfFile
+        ));
+
+        let value = output(1, "Command Line Error: Incorrect password");
+        assert!(matches!(
+            classify_failure("pdftotext", &value, b"%PDF-1.7", false),
+            ToolError::PdfEncrypted
+        ));
+        assert!(matches!(
+            classify_failure("pdftotext", &value, b"%PDF-1.7", true),
+            ToolError::IncorrectPassword
+        ));
+
+        let value = output(
+            3,
+            "Permission Error: Copying of text from this document is not allowed.",
+        );
+        assert!(matches!(
+            classify_failure("pdftotext", &value, b"%PDF-1.7", false),
+            ToolError::PermissionError(denied) if denied.permission() == Some(PdfPermission::Copy)
+        ));
+
+        // Localized messages fall back to the exit code and input
+        let value = output(1, "Erreur de syntaxe");
+        assert!(matches!(
+            classify_failure("pdftotext", &value, b"Not a pdf", false),
+            ToolError::NotPdfFile
+        ));
+        assert!(matches!(
+            classify_failure(
+                "pdftotext",
+                &value,
+                b"%PDF-1.7 trailer << /Encrypt 5 0 R >>",
+                false
+            ),
+            ToolError::PdfEncrypted
+        ));
+        assert!(matches!(
+            classify_failure("pdftotext", &value, b"%PDF-1.7", false),
+            ToolError::Failure(_)
+        ));
+
+        let value = output(99, "Unknown failure");
+        assert!(matches!(
+            classify_failure("pdftotext", &value, b"%PDF-1.7", false),
+            ToolError::Failure(_)
+        ));
+    }
+
+    /// Tests a child exiting before reading its input reports its own failure
+    /// rather than the broken pipe
+    #[tokio::test]
+    async fn test_child_exits_early() {
+        let args = [
+            "-c".to_string(),
+            "echo 'Early failure' >&2; exit 99".to_string(),
+        ];
+        let input = vec![0; 4 * 1024 * 1024];
+
+        let err = run_tool("sh", &args, &input, &RunOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::Failure(value) if value.contains("Early failure")));
+    }
+
+    /// Tests non UTF-8 passwords are passed to the tool unchanged
+    #[tokio::test]
+    async fn test_byte_password() {
+        // Latin-1 "pässword"
+        let password = Password::user_bytes(b"p\xe4ssword".to_vec()).unwrap();
+        let options = RunOptions {
+            password: Some(password),
+            ..Default::default()
+        };
+
+        let output = run_tool::<String>("echo", &[], &[], &options)
+            .await
+            .unwrap();
+        assert_eq!(output.stdout, b"-upw p\xe4ssword\n");
+
+        // Empty passwords are not passed
+        let options = RunOptions {
+            password: Some(Password::owner("")),
+            ..Default::default()
+        };
+        let output = run_tool::<String>("echo", &[], &[], &options)
+            .await
+            .unwrap();
+        assert_eq!(output.stdout, b"\n");
+    }
+
+    /// Writer collecting the output into a shared buffer
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Tests stderr is written to the sink with only the start kept in memory
+    #[tokio::test]
+    async fn test_stderr_sink() {
+        let args = [
+            "-c".to_string(),
+            "head -c 200000 /dev/zero | tr '\\0' 'a' >&2; exit 99".to_string(),
+        ];
+
+        let buffer = SharedBuffer::default();
+        let options = RunOptions {
+            stderr: Some(StderrSink::new(buffer.clone())),
+            ..Default::default()
+        };
+
+        let err = run_tool("sh", &args, &[], &options).await.unwrap_err();
+        assert!(matches!(err, ToolError::Failure(value) if value.len() == STDERR_RETAIN_LIMIT));
+
+        let output = buffer.0.lock().unwrap();
+        assert_eq!(output.len(), 200000);
+        assert!(output.iter().all(|value| *value == b'a'));
+    }
+
+    /// Tests the tools are started with the lowered priority
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_priority() {
+        let args = ["-c", "echo $(nice) $(ionice)"];
+
+        let output = run_tool("sh", &args, &[], &RunOptions::default())
+            .await
+            .unwrap();
+        let output = String::from_utf8(output.stdout).unwrap();
+        let (base, _) = output.split_once(' ').unwrap();
+        let base: u8 = base.parse().unwrap();
+
+        let options = RunOptions {
+            priority: Some(ProcessPriority::default().set_nice(5).set_idle_io(true)),
+            ..Default::default()
+        };
+        let output = run_tool("sh", &args, &[], &options).await.unwrap();
+        let expected = format!("{} idle\n", (base + 5).min(ProcessPriority::MAX_NICE));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+    }
+
+    /// Tests qpdf takes either password through the same argument
+    #[test]
+    fn test_qpdf_password_arg() {
+        let mut args = Vec::new();
+        Password::owner("secret").push_tool_arg("qpdf", &mut args);
+        Password::user("secret").push_tool_arg("pdfinfo", &mut args);
+        assert_eq!(args, ["--password=secret", "-upw", "secret"]);
+    }
+
+    /// Tests UTF-8 byte passwords use the string variants and the
+    /// public argument list still contains non UTF-8 passwords
+    #[test]
+    fn test_password_bytes() {
+        let password = Password::owner_bytes(b"secret".to_vec()).unwrap();
+        assert!(matches!(password, Password::Owner(Secret(value)) if value == "secret"));
+
+        let password = Password::from("secret");
+        assert!(matches!(password, Password::User(Secret(value)) if value == "secret"));
+
+        let mut args = Vec::new();
+        Password::user_bytes(b"p\xe4ssword".to_vec())
+            .unwrap()
+            .push_arg(&mut args);
+        assert_eq!(args, ["-upw", "p\u{FFFD}ssword"]);
+    }
+
+    /// Tests each page selection resolves into the page numbers
+    #[test]
+    fn test_page_selection() {
+        assert_eq!(PageSelection::All.pages(4), [1, 2, 3, 4]);
+        assert_eq!(PageSelection::from(2..=3).pages(4), [2, 3]);
+        assert_eq!(PageSelection::from(vec![4, 1]).pages(4), [4, 1]);
+        assert_eq!(PageSelection::Odd.pages(5), [1, 3, 5]);
+        assert_eq!(PageSelection::Even.pages(5), [2, 4]);
+        assert_eq!(PageSelection::Even.reversed().pages(5), [4, 2]);
+        assert_eq!(PageSelection::Odd.reversed().reversed(), PageSelection::Odd);
+
+        // Out of range pages are kept for verification to report
+        assert_eq!(PageSelection::from(3..=6).pages(4), [3, 4, 5, 6]);
+        assert!(PageSelection::All.pages(0).is_empty());
+    }
+
+    /// Tests zero page documents follow the policy
+    #[test]
+    fn test_empty_document_policy() {
+        assert!(matches!(EmptyDocumentPolicy::Empty.check(0), Ok(0)));
+        assert!(matches!(
+            EmptyDocumentPolicy::Error.check(0),
+            Err(ToolError::EmptyDocument)
+        ));
+        assert!(matches!(EmptyDocumentPolicy::Error.check(3), Ok(3)));
+    }
+
+    /// Tests the rectangle helpers and unit conversions
+    #[test]
+    fn test_rect() {
+        let rect = Rect::from_corners(Point::new(100.0, 50.0), Point::new(10.0, 20.0));
+        assert_eq!(rect, Rect::new(10.0, 20.0, 90.0, 30.0));
+        assert_eq!(rect.max(), Point::new(100.0, 50.0));
+        assert!(rect.contains(Point::new(50.0, 30.0)));
+        assert!(!rect.contains(Point::new(5.0, 30.0)));
+
+        let other = Rect::new(50.0, 40.0, 100.0, 100.0);
+        assert_eq!(rect.union(&other), Rect::new(10.0, 20.0, 140.0, 120.0));
+        assert_eq!(
+            rect.intersection(&other),
+            Some(Rect::new(50.0, 40.0, 50.0, 10.0))
+        );
+        assert_eq!(rect.intersection(&Rect::new(0.0, 0.0, 5.0, 5.0)), None);
+
+        // Letter page at 150 ppi
+        let page = Size::new(612.0, 792.0);
+        assert_eq!(page.to_pixels(150.0), Size::new(1275.0, 1650.0));
+        assert_eq!(page.to_pixels(150.0).to_points(150.0), page);
+        assert_eq!(page.transposed(), Size::new(792.0, 612.0));
+        assert_eq!(
+            Rect::new(72.0, 144.0, 36.0, 36.0).to_pixels(300.0),
+            Rect::new(300.0, 600.0, 150.0, 150.0)
+        );
+    }
+
+    /// Tests resolving the tool paths
+    #[test]
+    fn test_tool_paths() {
+        let paths = ToolPaths::default();
+        assert_eq!(paths.resolve("pdfinfo").to_str(), Some("pdfinfo"));
+
+        let paths = paths
+            .set_directory("/opt/poppler/bin")
+            .set_tool("pdftocairo", "/usr/local/bin/pdftocairo");
+        assert_eq!(
+            paths.resolve("pdfinfo").to_str(),
+            Some("/opt/poppler/bin/pdfinfo")
+        );
+        assert_eq!(
+            paths.resolve("pdftocairo").to_str(),
+            Some("/usr/local/bin/pdftocairo")
+        );
+    }
+}
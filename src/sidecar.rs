@@ -0,0 +1,251 @@
+//! Helpers for exporting per page metadata for document processing pipelines
+//!
+//! * [page_records] - Gets the dimensions, text statistics, image count and render hash of each page
+//! * [page_records_json_lines] - Gets the records as JSON Lines (Requires the `json` feature)
+
+use futures_util::future::try_join4;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    image::{OutputFormat, PageArgs, PdfRenderError, RenderArgs},
+    images::{pdf_image_list, PdfImageKind, PdfImagesArgs, PdfImagesError},
+    info::{pdf_info, pdf_page_count, PageDimensions, PdfInfoArgs, PdfInfoError},
+    shared::{fnv1a_64, for_each_page, FanOut, Password},
+    stats::document_stats,
+    text::{PdfTextArgs, PdfTextError},
+};
+
+/// Options for exporting the page records
+pub struct SidecarOptions {
+    /// Args for reading the PDF info and listing the images
+    pub info_args: PdfInfoArgs,
+    /// Args for rendering the pages that are hashed
+    pub render_args: RenderArgs,
+    /// Args for extracting the text
+    pub text_args: PdfTextArgs,
+    /// Format to render the pages as for hashing
+    pub format: OutputFormat,
+    /// Maximum number of pages rendered at the same time
+    pub concurrency: usize,
+}
+
+impl Default for SidecarOptions {
+    fn default() -> Self {
+        Self {
+            info_args: Default::default(),
+            render_args: Default::default(),
+            text_args: Default::default(),
+            format: OutputFormat::Png,
+            concurrency: Self::DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+impl SidecarOptions {
+    pub const DEFAULT_CONCURRENCY: usize = 4;
+
+    pub fn set_info_args(mut self, info_args: PdfInfoArgs) -> Self {
+        self.info_args = info_args;
+        self
+    }
+
+    pub fn set_render_args(mut self, render_args: RenderArgs) -> Self {
+        self.render_args = render_args;
+        self
+    }
+
+    pub fn set_text_args(mut self, text_args: PdfTextArgs) -> Self {
+        self.text_args = text_args;
+        self
+    }
+
+    /// Sets the password used by all the stages of the export
+    pub fn set_password(mut self, password: Password) -> Self {
+        self.info_args = self.info_args.set_password(password.clone());
+        self.render_args = self.render_args.set_password(password.clone());
+        self.text_args = self.text_args.set_password(password);
+        self
+    }
+
+    pub fn set_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn set_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+}
+
+/// Metadata for a single page
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PageRecord {
+    /// Page number
+    pub page: u32,
+    /// Dimensions and rotation of the page when reported by pdfinfo
+    pub dimensions: Option<PageDimensions>,
+    /// Number of whitespace separated words
+    pub words: usize,
+    /// Number of non-whitespace characters
+    pub characters: usize,
+    /// Number of images drawn on the page, masks are not included
+    pub image_count: usize,
+    /// Hash of the rendered page (64 bit FNV-1a as hex), stable across
+    /// runs with the same poppler version and render args
+    pub render_hash: String,
+}
+
+#[derive(Debug, Error)]
+pub enum SidecarError {
+    #[error(transparent)]
+    Info(#[from] PdfInfoError),
+
+    #[error(transparent)]
+    Render(#[from] PdfRenderError),
+
+    #[error(transparent)]
+    Text(#[from] PdfTextError),
+
+    #[error(transparent)]
+    Images(#[from] PdfImagesError),
+
+    #[cfg(feature = "json")]
+    #[error("failed to serialize record: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Gets the metadata record for each page of the provided PDF, the info,
+/// text, image list and page renders are all gathered concurrently with
+/// at most [SidecarOptions::concurrency] pages rendered at once. Only the
+/// hash of each render is kept
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * options - Options for the export
+pub async fn page_records(
+    data: &[u8],
+    options: &SidecarOptions,
+) -> Result<Vec<PageRecord>, SidecarError> {
+    options
+        .render_args
+        .validate(options.format)
+        .map_err(PdfRenderError::from)?;
+
+    let page_count = pdf_page_count(data, &options.info_args).await?;
+    let page_count = options
+        .render_args
+        .empty_document
+        .check(page_count)
+        .map_err(PdfRenderError::from)?;
+    if page_count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let pages: Vec<u32> = (1..=page_count).collect();
+    let page_args = PageArgs::new(data, None, &options.render_args, &pages).await?;
+
+    let info_args = options
+        .info_args
+        .clone()
+        .set_first_page(1)
+        .set_last_page(page_count);
+
+    let mut images_args = PdfImagesArgs::default();
+    if let Some(password) = options.info_args.password.clone() {
+        images_args = images_args.set_password(password);
+    }
+    if let Some(timeout) = options.info_args.timeout {
+        images_args = images_args.set_timeout(timeout);
+    }
+
+    let (info, stats, images, hashes) = try_join4(
+        async { pdf_info(data, &info_args).await.map_err(SidecarError::from) },
+        async {
+            document_stats(data, &options.text_args)
+                .await
+                .map_err(SidecarError::from)
+        },
+        async {
+            pdf_image_list(data, &images_args)
+                .await
+                .map_err(SidecarError::from)
+        },
+        async {
+            for_each_page(
+                data,
+                pages,
+                false,
+                FanOut::Buffered(options.concurrency),
+                |page| page_args.job(options.format, page),
+                |output| Ok::<_, PdfRenderError>(render_hash(&output)),
+            )
+            .await
+            .map_err(SidecarError::from)
+        },
+    )
+    .await?;
+
+    Ok(hashes
+        .into_iter()
+        .zip(1..)
+        .map(|(render_hash, page)| {
+            let stats = stats.pages.get(page as usize - 1);
+
+            PageRecord {
+                page,
+                dimensions: info.page_dimensions(page).and_then(Result::ok),
+                words: stats.map(|stats| stats.words).unwrap_or_default(),
+                characters: stats.map(|stats| stats.characters).unwrap_or_default(),
+                image_count: images
+                    .iter()
+                    .filter(|image| image.page == page && image.kind == PdfImageKind::Image)
+                    .count(),
+                render_hash,
+            }
+        })
+        .collect())
+}
+
+/// Gets the metadata records for the provided PDF as JSON Lines, one
+/// JSON object per page. See [page_records]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * options - Options for the export
+#[cfg(feature = "json")]
+pub async fn page_records_json_lines(
+    data: &[u8],
+    options: &SidecarOptions,
+) -> Result<String, SidecarError> {
+    let records = page_records(data, options).await?;
+
+    let mut output = String::new();
+    for record in records {
+        output.push_str(&serde_json::to_string(&record)?);
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+/// Hashes the rendered page using 64 bit FNV-1a
+fn render_hash(data: &[u8]) -> String {
+    format!("{:016x}", fnv1a_64(data))
+}
+
+#[cfg(test)]
+mod test {
+    use super::render_hash;
+
+    /// Tests the hash matches the FNV-1a reference values
+    #[test]
+    fn test_render_hash() {
+        assert_eq!(render_hash(b""), "cbf29ce484222325");
+        assert_eq!(render_hash(b"a"), "af63dc4c8601ec8c");
+        assert_eq!(render_hash(b"foobar"), "85944171f73967e8");
+    }
+}
@@ -0,0 +1,109 @@
+//! Helpers for detecting which pages changed between versions of a PDF file
+//!
+//! * [page_signatures] - Gets a stable signature for each page
+//! * [changed_pages] - Compares the signatures of two versions
+//!
+//! Signatures are hashes of a normalized low resolution render of each page,
+//! they stay the same between versions of a file as long as the page looks
+//! the same and the same poppler version is used
+
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+    image::{
+        page_job, verify_all_pages, OutputFormat, PdfRenderError, RenderArgs, RenderColor,
+        Resolution,
+    },
+    info::PdfInfo,
+    shared::{fnv1a_64, for_each_page},
+};
+
+/// Resolution the pages are rendered at for their signature
+const SIGNATURE_RESOLUTION: u32 = 36;
+
+/// Stable signature of a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PageSignature(pub u64);
+
+impl Display for PageSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Gets the signature of every page in the provided PDF, signatures are
+/// provided in page order.
+///
+/// The pages are rendered with normalized settings so signatures are always
+/// comparable, only the password, timeout, fan out and verification from
+/// the provided args are used
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info
+/// * args - Optional args for pdftocairo
+pub async fn page_signatures(
+    data: &[u8],
+    info: &PdfInfo,
+    args: &RenderArgs,
+) -> Result<Vec<PageSignature>, PdfRenderError> {
+    let page_count = verify_all_pages(data, info, args).await?;
+
+    let mut normalized = RenderArgs::default()
+        .set_resolution(Resolution::uniform(SIGNATURE_RESOLUTION))
+        .set_render_color(RenderColor::Grayscale)
+        .set_fan_out(args.fan_out);
+    normalized.password = args.password.clone();
+    normalized.timeout = args.timeout;
+
+    for_each_page(
+        data,
+        (1..=page_count).collect(),
+        false,
+        normalized.fan_out,
+        |page| page_job(OutputFormat::Png, page, &normalized),
+        |output| Ok(PageSignature(fnv1a_64(&output))),
+    )
+    .await
+}
+
+/// Compares the signatures of two versions of a PDF, provides the page
+/// numbers in the new version that differ from the same page in the
+/// old version, including pages that were added
+///
+/// ## Arguments
+/// * old - Signatures of the previous version
+/// * new - Signatures of the new version
+pub fn changed_pages(old: &[PageSignature], new: &[PageSignature]) -> Vec<u32> {
+    new.iter()
+        .zip(1..)
+        .filter(|(signature, page)| old.get(*page as usize - 1) != Some(*signature))
+        .map(|(_, page)| page)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{changed_pages, PageSignature};
+
+    /// Tests finding the changed and added pages
+    #[test]
+    fn test_changed_pages() {
+        let old = [PageSignature(1), PageSignature(2), PageSignature(3)];
+        let new = [
+            PageSignature(1),
+            PageSignature(5),
+            PageSignature(3),
+            PageSignature(4),
+        ];
+
+        assert_eq!(changed_pages(&old, &new), vec![2, 4]);
+        assert_eq!(changed_pages(&new, &old), vec![2]);
+        assert!(changed_pages(&old, &old).is_empty());
+        assert_eq!(PageSignature(255).to_string(), "00000000000000ff");
+    }
+}
@@ -0,0 +1,125 @@
+//! Helpers for computing statistics about the text in PDF files
+//!
+//! * [document_stats] - Gets the word and character counts along with a reading time estimate
+
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::text::{pages_text, PdfTextArgs, PdfTextError, PAGE_END_CHARACTER};
+
+/// Average number of words read per minute used for reading time estimates
+pub const WORDS_PER_MINUTE: u32 = 200;
+
+/// Statistics for the text of a single page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PageStats {
+    /// Page number
+    pub page: u32,
+    /// Number of whitespace separated words
+    pub words: usize,
+    /// Number of non-whitespace characters
+    pub characters: usize,
+}
+
+impl PageStats {
+    /// Computes the statistics for the text of a page
+    pub fn from_text(page: u32, text: &str) -> Self {
+        Self {
+            page,
+            words: text.split_whitespace().count(),
+            characters: text.chars().filter(|value| !value.is_whitespace()).count(),
+        }
+    }
+}
+
+/// Statistics for the text of a whole PDF
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DocumentStats {
+    /// Total number of whitespace separated words
+    pub words: usize,
+    /// Total number of non-whitespace characters
+    pub characters: usize,
+    /// Estimated time to read the text at [WORDS_PER_MINUTE]
+    pub reading_time: Duration,
+    /// Statistics for each page
+    pub pages: Vec<PageStats>,
+}
+
+impl DocumentStats {
+    /// Computes the statistics from the text of each page
+    pub fn from_pages<S: AsRef<str>>(pages: &[S]) -> Self {
+        let pages: Vec<PageStats> = pages
+            .iter()
+            .enumerate()
+            .map(|(index, text)| PageStats::from_text(index as u32 + 1, text.as_ref()))
+            .collect();
+
+        let words = pages.iter().map(|page| page.words).sum();
+        let characters = pages.iter().map(|page| page.characters).sum();
+        let reading_time = Duration::from_secs_f64(words as f64 * 60.0 / WORDS_PER_MINUTE as f64);
+
+        Self {
+            words,
+            characters,
+            reading_time,
+            pages,
+        }
+    }
+}
+
+/// Computes the word and character counts for the provided PDF along with
+/// an estimated reading time, the text is extracted in a single pass
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Optional args for the pdf to text
+pub async fn document_stats(
+    data: &[u8],
+    args: &PdfTextArgs,
+) -> Result<DocumentStats, PdfTextError> {
+    let text = pages_text(data, &args.with_page_breaks()).await?;
+
+    // Every page ends with a page end character, ignore the empty trailing value
+    let text = text.strip_suffix(PAGE_END_CHARACTER).unwrap_or(&text);
+    let pages: Vec<&str> = text.split(PAGE_END_CHARACTER).collect();
+
+    Ok(DocumentStats::from_pages(&pages))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{DocumentStats, PageStats};
+
+    /// Tests counting the words and characters on a page
+    #[test]
+    fn test_page_stats() {
+        let stats = PageStats::from_text(1, "Test pdf with\ntext in it\n\n");
+        assert_eq!(
+            stats,
+            PageStats {
+                page: 1,
+                words: 6,
+                characters: 19
+            }
+        );
+    }
+
+    /// Tests combining the statistics for multiple pages
+    #[test]
+    fn test_document_stats() {
+        let text = "word ".repeat(200);
+        let stats = DocumentStats::from_pages(&[text.as_str(), "Test page 2"]);
+
+        assert_eq!(stats.words, 203);
+        assert_eq!(stats.characters, 809);
+        assert_eq!(stats.reading_time, Duration::from_secs_f64(60.9));
+        assert_eq!(stats.pages.len(), 2);
+        assert_eq!(stats.pages[1].page, 2);
+    }
+}
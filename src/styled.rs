@@ -0,0 +1,477 @@
+//! Helpers for extracting text along with its font and size
+//!
+//! * [text_styled_pages] - Gets the lines of each page as runs of styled text
+//!
+//! pdftotext does not report the fonts of the text so the XML output
+//! of `pdftohtml` is used instead, each line is made of runs that share
+//! the same font allowing headings to be detected from their size
+
+use std::{collections::HashMap, time::Duration};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    info::PermissionDenied,
+    redaction::BoundingBox,
+    shared::{
+        run_tool, unescape_xml, ChildError, Password, ProcessPriority, RunOptions, StderrSink,
+        ToolError,
+    },
+};
+
+/// Maximum difference in the top of two runs for them to be
+/// considered part of the same line
+const LINE_TOLERANCE: f32 = 2.0;
+
+/// Run of text on a line that uses the same font
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct StyledRun {
+    /// Text of the run
+    pub text: String,
+    /// Name of the font, [None] when the font was not declared
+    pub font: Option<String>,
+    /// Font size in PDF points
+    pub size: f32,
+    /// Color of the text (e.g. "#000000")
+    pub color: Option<String>,
+    /// Whether the font is bold, derived from the font name
+    pub bold: bool,
+    /// Whether the font is italic, derived from the font name
+    pub italic: bool,
+    /// Area of the page containing the run
+    pub bbox: BoundingBox,
+}
+
+/// Line of text made of styled runs in reading order
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct StyledLine {
+    /// Runs within the line ordered from left to right
+    pub runs: Vec<StyledRun>,
+    /// Area of the page containing the line
+    pub bbox: BoundingBox,
+}
+
+impl StyledLine {
+    /// Text of the line with the runs separated by spaces
+    pub fn text(&self) -> String {
+        self.runs
+            .iter()
+            .map(|run| run.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Largest font size used within the line
+    pub fn max_size(&self) -> f32 {
+        self.runs.iter().map(|run| run.size).fold(0.0, f32::max)
+    }
+}
+
+/// Styled lines of a single page
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct StyledPage {
+    /// Page number
+    pub page: u32,
+    /// Width of the page in PDF points
+    pub width: f32,
+    /// Height of the page in PDF points
+    pub height: f32,
+    /// Lines of the page from top to bottom
+    pub lines: Vec<StyledLine>,
+}
+
+#[derive(Debug, Error)]
+pub enum PdfStyledTextError {
+    #[error("failed to spawn pdftohtml: {0}")]
+    SpawnProcess(std::io::Error),
+
+    #[error("failed to write pdf bytes: {0}")]
+    WritePdf(std::io::Error),
+
+    #[error("failed to get output: {0}")]
+    WaitOutput(std::io::Error),
+
+    #[error("output exceeded the maximum allowed size of {0} bytes")]
+    OutputLimitExceeded(usize),
+
+    #[error("pdftohtml did not finish within {0:?}")]
+    Timeout(Duration),
+
+    #[error("pdftohtml was aborted")]
+    Aborted,
+
+    #[error("failed to get pdftohtml exit code: {0}")]
+    PdfToHtmlFailure(String),
+
+    #[error("pdftohtml reported permission error: {0}")]
+    PermissionError(PermissionDenied),
+
+    #[error("page {0} is outside the number of available pages {1}")]
+    PageOutOfBounds(u32, u32),
+
+    #[error("pdf is encrypted and no password was provided")]
+    PdfEncrypted,
+
+    #[error("incorrect password was provided")]
+    IncorrectPassword,
+
+    #[error("file is not a pdf")]
+    NotPdfFile,
+}
+
+impl From<ChildError> for PdfStyledTextError {
+    fn from(value: ChildError) -> Self {
+        match value {
+            ChildError::WriteInput(err) => Self::WritePdf(err),
+            ChildError::ReadOutput(err) | ChildError::WriteOutput(err) => Self::WaitOutput(err),
+            ChildError::OutputLimitExceeded(limit) => Self::OutputLimitExceeded(limit),
+            ChildError::Timeout(timeout) => Self::Timeout(timeout),
+        }
+    }
+}
+
+impl From<ToolError> for PdfStyledTextError {
+    fn from(value: ToolError) -> Self {
+        match value {
+            ToolError::Spawn(err) => Self::SpawnProcess(err),
+            ToolError::Child(err) => err.into(),
+            ToolError::NotPdfFile => Self::NotPdfFile,
+            ToolError::PdfEncrypted => Self::PdfEncrypted,
+            ToolError::IncorrectPassword => Self::IncorrectPassword,
+            ToolError::PermissionError(err) => Self::PermissionError(err),
+            ToolError::PageOutOfBounds(page, page_count) => Self::PageOutOfBounds(page, page_count),
+            ToolError::PageCountUnknown => {
+                Self::PdfToHtmlFailure("page count is missing or invalid".to_string())
+            }
+            ToolError::EmptyDocument => {
+                Self::PdfToHtmlFailure("pdf does not contain any pages".to_string())
+            }
+            ToolError::Failure(err) | ToolError::Task(err) => Self::PdfToHtmlFailure(err),
+            ToolError::Aborted => Self::Aborted,
+            ToolError::BudgetExceeded(err) => Self::PdfToHtmlFailure(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PdfStyledTextArgs {
+    /// Password for the PDF
+    pub password: Option<Password>,
+    /// First page to extract
+    pub first_page: Option<u32>,
+    /// Last page to extract
+    pub last_page: Option<u32>,
+    /// Optional maximum size in bytes for the pdftohtml output,
+    /// extraction is aborted once this is exceeded
+    pub max_output_size: Option<usize>,
+    /// Optional maximum time extracting the text may take
+    pub timeout: Option<Duration>,
+    /// Optional destination for the pdftohtml stderr instead of keeping it in memory
+    pub stderr: Option<StderrSink>,
+    /// Optional lowered CPU and IO priority to run pdftohtml at
+    pub priority: Option<ProcessPriority>,
+}
+
+impl PdfStyledTextArgs {
+    pub fn set_password(mut self, password: Password) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    pub fn set_first_page(mut self, first_page: u32) -> Self {
+        self.first_page = Some(first_page);
+        self
+    }
+
+    pub fn set_last_page(mut self, last_page: u32) -> Self {
+        self.last_page = Some(last_page);
+        self
+    }
+
+    pub fn set_max_output_size(mut self, max_output_size: usize) -> Self {
+        self.max_output_size = Some(max_output_size);
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_stderr(mut self, stderr: StderrSink) -> Self {
+        self.stderr = Some(stderr);
+        self
+    }
+
+    pub fn set_priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Options for running pdftohtml
+    pub(crate) fn run_options(&self) -> RunOptions {
+        RunOptions {
+            max_output_size: self.max_output_size,
+            timeout: self.timeout,
+            password: self.password.clone(),
+            stderr: self.stderr.clone(),
+            priority: self.priority,
+            budget: None,
+        }
+    }
+
+    /// Builds an argument list from all the options, the password is passed
+    /// to the tool separately so it is not included
+    pub fn build_args(&self) -> Vec<String> {
+        let mut out = Vec::new();
+
+        if let Some(first_page) = self.first_page {
+            out.push("-f".to_string());
+            out.push(first_page.to_string());
+        }
+
+        if let Some(last_page) = self.last_page {
+            out.push("-l".to_string());
+            out.push(last_page.to_string());
+        }
+
+        out
+    }
+}
+
+/// Arguments for XML output without images using PDF points with the
+/// full font names
+pub(crate) const XML_ARGS: [&str; 7] =
+    ["-xml", "-i", "-zoom", "1", "-fontfullname", "-q", "-stdout"];
+
+/// Extracts the text of the provided PDF as lines of styled runs
+/// using the XML output of `pdftohtml`
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Extra args to provide to pdftohtml
+pub async fn text_styled_pages(
+    data: &[u8],
+    args: &PdfStyledTextArgs,
+) -> Result<Vec<StyledPage>, PdfStyledTextError> {
+    let mut cli_args: Vec<String> = XML_ARGS.iter().map(|arg| arg.to_string()).collect();
+    cli_args.extend(args.build_args());
+
+    // Take input from stdin
+    cli_args.push("-".to_string());
+
+    let output = run_tool("pdftohtml", &cli_args, data, &args.run_options()).await?;
+
+    let value = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_styled_pages(&value))
+}
+
+/// Font declared by a `fontspec` element
+struct FontSpec {
+    name: String,
+    size: f32,
+    color: String,
+}
+
+/// Parses the pages from the XML output of `pdftohtml`
+fn parse_styled_pages(value: &str) -> Vec<StyledPage> {
+    // Fonts are declared once and shared by the following pages
+    let mut fonts: HashMap<String, FontSpec> = HashMap::new();
+    let mut pages: Vec<StyledPage> = Vec::new();
+
+    for line in value.lines().map(str::trim) {
+        if let Some(attributes) = line.strip_prefix("<page ") {
+            let number = attribute(attributes, "number").and_then(|value| value.parse().ok());
+            let number = number.unwrap_or(pages.len() as u32 + 1);
+
+            pages.push(StyledPage {
+                page: number,
+                width: float_attribute(attributes, "width").unwrap_or_default(),
+                height: float_attribute(attributes, "height").unwrap_or_default(),
+                lines: Vec::new(),
+            });
+        } else if let Some(attributes) = line.strip_prefix("<fontspec ") {
+            let Some(id) = attribute(attributes, "id") else {
+                continue;
+            };
+
+            fonts.insert(
+                id.to_string(),
+                FontSpec {
+                    name: attribute(attributes, "family")
+                        .unwrap_or_default()
+                        .to_string(),
+                    size: float_attribute(attributes, "size").unwrap_or_default(),
+                    color: attribute(attributes, "color")
+                        .unwrap_or_default()
+                        .to_string(),
+                },
+            );
+        } else if line.starts_with("<text ") {
+            let (Some(page), Some(run)) = (pages.last_mut(), parse_run(line, &fonts)) else {
+                continue;
+            };
+
+            push_run(&mut page.lines, run);
+        }
+    }
+
+    pages
+}
+
+/// Adds the run to the line it shares the top of, otherwise starts a new line
+fn push_run(lines: &mut Vec<StyledLine>, run: StyledRun) {
+    let line = lines
+        .iter_mut()
+        .rev()
+        .find(|line| (line.bbox.y_min - run.bbox.y_min).abs() <= LINE_TOLERANCE);
+
+    match line {
+        Some(line) => {
+            line.bbox = line.bbox.union(&run.bbox);
+
+            let index = line
+                .runs
+                .iter()
+                .position(|other| other.bbox.x_min > run.bbox.x_min)
+                .unwrap_or(line.runs.len());
+            line.runs.insert(index, run);
+        }
+        None => lines.push(StyledLine {
+            bbox: run.bbox,
+            runs: vec![run],
+        }),
+    }
+}
+
+/// Parses a single text element in the format:
+/// `<text top="1" left="2" width="3" height="4" font="0"><b>Text</b></text>`
+fn parse_run(line: &str, fonts: &HashMap<String, FontSpec>) -> Option<StyledRun> {
+    let (attributes, rest) = line.strip_prefix("<text ")?.split_once('>')?;
+    let content = rest.strip_suffix("</text>")?;
+
+    let top = float_attribute(attributes, "top")?;
+    let left = float_attribute(attributes, "left")?;
+    let width = float_attribute(attributes, "width")?;
+    let height = float_attribute(attributes, "height")?;
+
+    let text = unescape_xml(&strip_tags(content));
+    if text.trim().is_empty() {
+        return None;
+    }
+
+    let font = attribute(attributes, "font").and_then(|id| fonts.get(id));
+    let name = font
+        .map(|font| font.name.to_lowercase())
+        .unwrap_or_default();
+
+    Some(StyledRun {
+        text,
+        font: font.map(|font| font.name.clone()),
+        size: font.map(|font| font.size).unwrap_or(height),
+        color: font.map(|font| font.color.clone()),
+        bold: content.contains("<b>") || name.contains("bold") || name.contains("black"),
+        italic: content.contains("<i>") || name.contains("italic") || name.contains("oblique"),
+        bbox: BoundingBox {
+            x_min: left,
+            y_min: top,
+            x_max: left + width,
+            y_max: top + height,
+        },
+    })
+}
+
+/// Removes the inline formatting and link elements from text content
+fn strip_tags(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut in_tag = false;
+
+    for char in value.chars() {
+        match char {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            char if !in_tag => out.push(char),
+            _ => {}
+        }
+    }
+
+    out
+}
+
+/// Gets the value of an attribute from the attributes of an element
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let key = format!("{name}=\"");
+
+    // Match whole attribute names so "top" does not match "stop"
+    let start = if attributes.starts_with(&key) {
+        0
+    } else {
+        attributes.find(&format!(" {key}"))? + 1
+    };
+    let start = start + key.len();
+    let length = attributes[start..].find('"')?;
+    Some(&attributes[start..start + length])
+}
+
+fn float_attribute(attributes: &str, name: &str) -> Option<f32> {
+    attribute(attributes, name)?.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_styled_pages;
+
+    const XML_OUTPUT: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE pdf2xml SYSTEM "pdf2xml.dtd">
+
+<pdf2xml producer="poppler" version="22.02.0">
+<page number="1" position="absolute" top="0" left="0" height="792" width="612">
+	<fontspec id="0" size="24" family="Arial-BoldMT" color="#000000"/>
+	<fontspec id="1" size="12" family="TimesNewRomanPSMT" color="#333333"/>
+<text top="72" left="72" width="200" height="27" font="0"><b>Annual Report</b></text>
+<text top="120" left="150" width="100" height="14" font="1">of the year</text>
+<text top="121" left="72" width="70" height="14" font="1"><i>The results</i></text>
+<text top="140" left="72" width="70" height="14" font="1">A &amp; B</text>
+</page>
+<page number="2" position="absolute" top="0" left="0" height="792" width="612">
+<text top="72" left="72" width="50" height="14" font="1"><a href="https://example.com">Link</a></text>
+</page>
+</pdf2xml>
+"##;
+
+    /// Tests parsing the styled lines from the pdftohtml output
+    #[test]
+    fn test_parse_styled_pages() {
+        let pages = parse_styled_pages(XML_OUTPUT);
+        assert_eq!(pages.len(), 2);
+
+        let page = &pages[0];
+        assert_eq!((page.page, page.width, page.height), (1, 612.0, 792.0));
+        assert_eq!(page.lines.len(), 3);
+
+        let heading = &page.lines[0];
+        assert_eq!(heading.text(), "Annual Report");
+        assert_eq!(heading.max_size(), 24.0);
+        assert!(heading.runs[0].bold && !heading.runs[0].italic);
+        assert_eq!(heading.runs[0].font.as_deref(), Some("Arial-BoldMT"));
+
+        // Runs on the same line are ordered from left to right
+        let line = &page.lines[1];
+        assert_eq!(line.text(), "The results of the year");
+        assert!(line.runs[0].italic && !line.runs[1].italic);
+        assert_eq!(line.runs[1].color.as_deref(), Some("#333333"));
+        assert_eq!(line.bbox.x_min, 72.0);
+        assert_eq!(line.bbox.x_max, 250.0);
+
+        assert_eq!(page.lines[2].text(), "A & B");
+
+        // Fonts declared on earlier pages are shared
+        assert_eq!(pages[1].lines[0].text(), "Link");
+        assert_eq!(pages[1].lines[0].runs[0].size, 12.0);
+    }
+}
@@ -0,0 +1,265 @@
+//! Helpers for previewing pages in a terminal
+//!
+//! * [render_ascii_preview] - Renders a page as ASCII art
+//! * [render_sixel_preview] - Renders a page as a sixel image
+//! * [ascii_preview], [sixel_preview] - Converts an already rendered page
+//!
+//! Intended for quick checks over SSH where the rendered images cannot be
+//! opened, sixel output needs a terminal with sixel support (e.g. xterm
+//! with `-ti vt340`, mlterm, WezTerm or foot)
+
+use std::fmt::Write;
+
+use crate::{
+    image::{render_single_page, OutputFormat, PdfRenderError, RenderArgs, RenderColor, ScaleTo},
+    image_crate::{
+        imageops::{self, FilterType},
+        DynamicImage, GrayImage,
+    },
+    info::PdfInfo,
+};
+
+/// Characters used for the ASCII preview from lightest to darkest
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Terminal cells are roughly twice as tall as they are wide
+const CELL_ASPECT: f32 = 2.0;
+
+/// Number of pixels rendered for each column of the ASCII preview,
+/// rendering larger than needed gives a smoother downsample
+const ASCII_OVERSAMPLE: u32 = 4;
+
+/// Number of gray levels in the sixel palette
+const SIXEL_LEVELS: u32 = 16;
+
+/// Number of pixel rows in each sixel band
+const SIXEL_BAND: u32 = 6;
+
+/// Converts a rendered page into ASCII art using darker characters for
+/// darker areas, each line ends with a newline. Rows are halved to account
+/// for terminal cells being taller than they are wide
+///
+/// ## Arguments
+/// * image - The rendered page
+/// * cols - Width of the preview in characters
+pub fn ascii_preview(image: &DynamicImage, cols: u32) -> String {
+    let (width, height) = (image.width(), image.height());
+    if cols == 0 || width == 0 || height == 0 {
+        return String::new();
+    }
+
+    let rows = ((height as f32 * cols as f32 / width as f32 / CELL_ASPECT).round() as u32).max(1);
+    let gray = imageops::resize(&image.to_luma8(), cols, rows, FilterType::Triangle);
+
+    let mut output = String::with_capacity(((cols + 1) * rows) as usize);
+    for row in gray.rows() {
+        for pixel in row {
+            let darkness = (255 - pixel.0[0]) as usize;
+            output.push(ASCII_RAMP[darkness * (ASCII_RAMP.len() - 1) / 255] as char);
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Converts a rendered page into a grayscale sixel image, the output
+/// includes the escape sequences to start and end the image
+///
+/// ## Arguments
+/// * image - The rendered page
+/// * width - Width of the preview in pixels
+pub fn sixel_preview(image: &DynamicImage, width: u32) -> String {
+    let (image_width, image_height) = (image.width(), image.height());
+    if width == 0 || image_width == 0 || image_height == 0 {
+        return String::new();
+    }
+
+    let height = ((image_height as f32 * width as f32 / image_width as f32).round() as u32).max(1);
+    let gray = imageops::resize(&image.to_luma8(), width, height, FilterType::Triangle);
+
+    let mut output = String::new();
+
+    // Start the image with square pixels and the size of the image
+    let _ = write!(output, "\x1bPq\"1;1;{width};{height}");
+
+    // Palette of gray levels with the RGB values as percentages
+    for level in 0..SIXEL_LEVELS {
+        let percent = level * 100 / (SIXEL_LEVELS - 1);
+        let _ = write!(output, "#{level};2;{percent};{percent};{percent}");
+    }
+
+    for band in (0..height).step_by(SIXEL_BAND as usize) {
+        let mut levels: Vec<u32> = (band..(band + SIXEL_BAND).min(height))
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| gray_level(&gray, x, y))
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+
+        for (index, level) in levels.iter().enumerate() {
+            // Return to the start of the band for each color after the first
+            if index > 0 {
+                output.push('$');
+            }
+
+            let _ = write!(output, "#{level}");
+
+            let sixels = (0..width).map(|x| {
+                let bits = (0..SIXEL_BAND)
+                    .filter(|dy| {
+                        let y = band + dy;
+                        y < height && gray_level(&gray, x, y) == *level
+                    })
+                    .fold(0u8, |bits, dy| bits | (1 << dy));
+
+                (63 + bits) as char
+            });
+
+            push_run_length(&mut output, sixels);
+        }
+
+        output.push('-');
+    }
+
+    output.push_str("\x1b\\");
+    output
+}
+
+/// Palette index of the gray level for the pixel
+fn gray_level(gray: &GrayImage, x: u32, y: u32) -> u32 {
+    (gray.get_pixel(x, y).0[0] as u32 * (SIXEL_LEVELS - 1) + 127) / 255
+}
+
+/// Writes the sixel characters using the repeat introducer for runs
+/// of the same character
+fn push_run_length(output: &mut String, sixels: impl Iterator<Item = char>) {
+    let mut run: Option<(char, usize)> = None;
+
+    let flush = |output: &mut String, value: char, count: usize| {
+        if count > 3 {
+            let _ = write!(output, "!{count}{value}");
+        } else {
+            output.extend(std::iter::repeat_n(value, count));
+        }
+    };
+
+    for value in sixels {
+        run = match run {
+            Some((current, count)) if current == value => Some((current, count + 1)),
+            Some((current, count)) => {
+                flush(output, current, count);
+                Some((value, 1))
+            }
+            None => Some((value, 1)),
+        };
+    }
+
+    if let Some((value, count)) = run {
+        flush(output, value, count);
+    }
+}
+
+/// Args for rendering a preview of the provided width in pixels, the
+/// resolution of the provided args is replaced by the scale
+fn preview_args(args: &RenderArgs, width: u32) -> RenderArgs {
+    let mut args = args
+        .clone()
+        .set_scale_to(ScaleTo::x(width.clamp(1, i32::MAX as u32) as i32))
+        .set_render_color(RenderColor::Grayscale);
+    args.resolution = None;
+    args.auto_dpi = None;
+    args
+}
+
+/// Renders a specific page from the provided PDF file as ASCII art
+///
+/// See [ascii_preview]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * page - The page to render
+/// * cols - Width of the preview in characters
+/// * args - Optional args to pdftocairo, the resolution is replaced
+pub async fn render_ascii_preview(
+    data: &[u8],
+    info: &PdfInfo,
+    page: u32,
+    cols: u32,
+    args: &RenderArgs,
+) -> Result<String, PdfRenderError> {
+    let args = preview_args(args, cols.saturating_mul(ASCII_OVERSAMPLE));
+    let image = render_single_page(data, info, OutputFormat::Png, page, &args).await?;
+    Ok(ascii_preview(&image, cols))
+}
+
+/// Renders a specific page from the provided PDF file as a sixel image
+///
+/// See [sixel_preview]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * page - The page to render
+/// * width - Width of the preview in pixels
+/// * args - Optional args to pdftocairo, the resolution is replaced
+pub async fn render_sixel_preview(
+    data: &[u8],
+    info: &PdfInfo,
+    page: u32,
+    width: u32,
+    args: &RenderArgs,
+) -> Result<String, PdfRenderError> {
+    let args = preview_args(args, width);
+    let image = render_single_page(data, info, OutputFormat::Png, page, &args).await?;
+    Ok(sixel_preview(&image, width))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ascii_preview, push_run_length, sixel_preview};
+    use crate::image_crate::{DynamicImage, GrayImage, Luma};
+
+    /// Page with a black top half and white bottom half
+    fn half_page() -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(40, 80, |_, y| {
+            Luma([if y < 40 { 0 } else { 255 }])
+        }))
+    }
+
+    /// Tests the preview size and characters follow the page
+    #[test]
+    fn test_ascii_preview() {
+        let preview = ascii_preview(&half_page(), 10);
+        let lines: Vec<&str> = preview.lines().collect();
+
+        // 10 columns of a 1:2 page with cells twice as tall
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], "@@@@@@@@@@");
+        assert_eq!(lines[9], "          ");
+
+        assert_eq!(ascii_preview(&half_page(), 0), "");
+    }
+
+    /// Tests the sixel image is wrapped in its escape sequences
+    #[test]
+    fn test_sixel_preview() {
+        let preview = sixel_preview(&half_page(), 12);
+
+        assert!(preview.starts_with("\x1bPq\"1;1;12;24#0;2;0;0;0"));
+        assert!(preview.ends_with("-\x1b\\"));
+        // 24 rows is 4 bands of 6 rows
+        assert_eq!(preview.matches('-').count(), 4);
+        // First band is solid black
+        assert!(preview.contains("#0!12~"));
+    }
+
+    /// Tests runs of characters use the repeat introducer
+    #[test]
+    fn test_run_length() {
+        let mut output = String::new();
+        push_run_length(&mut output, "~~~~~??@@@@@@".chars());
+        assert_eq!(output, "!5~??!6@");
+    }
+}
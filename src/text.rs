@@ -3,18 +3,43 @@
 //! * [text_all_pages] - Gets the text from all pages as a single string
 //! * [text_all_pages_split] - Gets the text from all pages as separate strings
 //! * [text_pages] - Gets the text from a specific set of pages as separate strings
+//! * [text_pages_numbered] - Gets the text from a specific set of pages paired with their page numbers
+//! * [text_pages_partial] - Gets the text from a specific set of pages keeping the completed pages on failure
+//! * [text_page_selection] - Gets the text from a [PageSelection] of pages paired with their page numbers
+//! * [text_pages_with_provenance] - Gets the text from a specific set of pages tagged with how it was extracted
 //! * [text_single_page] - Gets the text from a specific page
+//! * [text_all_pages_bytes] - Gets the text from all pages in the [TextEncoding] without decoding it
+//! * [text_single_page_bytes] - Gets the text from a specific page in the [TextEncoding] without decoding it
+//! * [text_excerpt] - Gets the text from the start of the PDF up to a maximum length
+//! * [text_pager] - Lazily gets the text one page at a time
+//! * [text_encodings] - Lists the output encodings supported by pdftotext
 
-use futures_util::{stream::FuturesOrdered, TryStreamExt};
-use std::process::Stdio;
+use futures_util::{future::try_join, stream, Stream};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::{borrow::Cow, collections::HashMap, time::Duration};
 use thiserror::Error;
-use tokio::{io::AsyncWriteExt, process::Command};
 
-use crate::{info::PdfInfo, shared::Password};
+use crate::{
+    budget::{Budget, BudgetExceeded},
+    info::{
+        info_page_count, pdf_info, verify_document, PdfInfo, PdfInfoArgs, PdfInfoError,
+        PermissionDenied,
+    },
+    shared::{
+        for_each_page, for_each_page_partial, run_tool, ChildError, EmptyDocumentPolicy, FanOut,
+        PageSelection, PartialResult, Password, ProcessPriority, RunOptions, StderrSink, ToolError,
+        ToolJob,
+    },
+    version::{text_provenance, PopplerVersionError, TextProvenance},
+};
 
 /// Character that indicates the end of a page in a PDF file
 pub const PAGE_END_CHARACTER: char = '\u{c}';
 
+/// Maximum time listing the encodings may take
+const ENCODINGS_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Debug, Error)]
 pub enum PdfTextError {
     #[error("failed to spawn pdftotext: {0}")]
@@ -26,15 +51,33 @@ pub enum PdfTextError {
     #[error("failed to get output: {0}")]
     WaitOutput(std::io::Error),
 
+    #[error("output exceeded the maximum allowed size of {0} bytes")]
+    OutputLimitExceeded(usize),
+
+    #[error("pdftotext did not finish within {0:?}")]
+    Timeout(Duration),
+
+    #[error("pdftotext was aborted")]
+    Aborted,
+
+    #[error(transparent)]
+    BudgetExceeded(BudgetExceeded),
+
     #[error("failed to get pdfinfo exit code: {0}")]
     PdfTextFailure(String),
 
+    #[error("pdftotext reported permission error: {0}")]
+    PermissionError(PermissionDenied),
+
     #[error("page {0} is outside the number of available pages {1}")]
     PageOutOfBounds(u32, u32),
 
     #[error("page info page count is missing or invalid, pdf likely invalid")]
     PageCountUnknown,
 
+    #[error("pdf does not contain any pages")]
+    EmptyDocument,
+
     #[error("pdf is encrypted and no password was provided")]
     PdfEncrypted,
 
@@ -43,12 +86,425 @@ pub enum PdfTextError {
 
     #[error("file is not a pdf")]
     NotPdfFile,
+
+    #[error(transparent)]
+    Info(PdfInfoError),
+
+    #[error(transparent)]
+    Version(#[from] PopplerVersionError),
+}
+
+impl From<ChildError> for PdfTextError {
+    fn from(value: ChildError) -> Self {
+        match value {
+            ChildError::WriteInput(err) => Self::WritePdf(err),
+            ChildError::ReadOutput(err) | ChildError::WriteOutput(err) => Self::WaitOutput(err),
+            ChildError::OutputLimitExceeded(limit) => Self::OutputLimitExceeded(limit),
+            ChildError::Timeout(timeout) => Self::Timeout(timeout),
+        }
+    }
+}
+
+impl From<PdfInfoError> for PdfTextError {
+    fn from(value: PdfInfoError) -> Self {
+        match value {
+            PdfInfoError::PdfEncrypted => Self::PdfEncrypted,
+            PdfInfoError::IncorrectPassword => Self::IncorrectPassword,
+            PdfInfoError::NotPdfFile => Self::NotPdfFile,
+            PdfInfoError::EmptyDocument => Self::EmptyDocument,
+            err => Self::Info(err),
+        }
+    }
+}
+
+impl From<ToolError> for PdfTextError {
+    fn from(value: ToolError) -> Self {
+        match value {
+            ToolError::Spawn(err) => Self::SpawnProcess(err),
+            ToolError::Child(err) => err.into(),
+            ToolError::NotPdfFile => Self::NotPdfFile,
+            ToolError::PdfEncrypted => Self::PdfEncrypted,
+            ToolError::IncorrectPassword => Self::IncorrectPassword,
+            ToolError::PageOutOfBounds(page, page_count) => Self::PageOutOfBounds(page, page_count),
+            ToolError::PageCountUnknown => Self::PageCountUnknown,
+            ToolError::EmptyDocument => Self::EmptyDocument,
+            ToolError::PermissionError(err) => Self::PermissionError(err),
+            ToolError::Failure(err) | ToolError::Task(err) => Self::PdfTextFailure(err),
+            ToolError::Aborted => Self::Aborted,
+            ToolError::BudgetExceeded(err) => Self::BudgetExceeded(err),
+        }
+    }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct PdfTextArgs {
     /// Password for the PDF
     pub password: Option<Password>,
+
+    /// Optional maximum size in bytes for the extracted text,
+    /// extraction is aborted once this is exceeded
+    pub max_output_size: Option<usize>,
+
+    /// Whether to only extract repeated page numbers once
+    pub dedup_pages: bool,
+
+    /// Optional maximum time extracting the text may take
+    pub timeout: Option<Duration>,
+
+    /// Optional destination for the pdftotext stderr instead of keeping it in memory
+    pub stderr: Option<StderrSink>,
+
+    /// Optional lowered CPU and IO priority to run pdftotext at
+    pub priority: Option<ProcessPriority>,
+    /// Optional limits on all the pdftotext processes of the operation,
+    /// shared with any other args the budget is set on
+    pub budget: Option<Budget>,
+
+    /// Whether to keep the original physical layout of the text
+    pub layout: bool,
+
+    /// Whether to keep the text in the order it is stored in the content
+    /// stream instead of the reading order, useful for vertical CJK text
+    /// and for documents where the reading order is detected incorrectly
+    pub raw: bool,
+
+    /// How right-to-left text (Hebrew, Arabic) is post-processed
+    pub bidi: BidiMode,
+
+    /// Whether to join runs of lines that each contain a single CJK
+    /// character into a single line, vertical CJK text is often
+    /// extracted one character per line
+    pub join_vertical_cjk: bool,
+
+    /// Character normalization applied to the extracted text, poppler
+    /// passes through ligatures, soft hyphens and smart quotes as they
+    /// are stored in the PDF
+    pub normalization: Option<TextNormalization>,
+
+    /// Strategy for extracting multiple pages
+    pub fan_out: FanOut,
+
+    /// Whether to check the encryption state and page bounds using the
+    /// [PdfInfo] before extracting specific pages, when disabled the errors
+    /// reported by pdftotext are relied on instead. Defaults to true
+    pub verify: bool,
+
+    /// How extracting every page handles documents with zero pages
+    pub empty_document: EmptyDocumentPolicy,
+
+    /// How page breaks are represented by [text_all_pages]
+    pub page_break: PageBreak,
+
+    /// Whether [text_all_pages_split] drops the empty value after the
+    /// final page break, kept by default
+    pub trim_trailing_page: bool,
+
+    /// Optional encoding pdftotext outputs the text in, uses the pdftotext
+    /// default (UTF-8) when not set. Functions providing a [String] decode
+    /// the output back into UTF-8 so the encoding only limits the characters
+    /// in the text, use [text_all_pages_bytes] or [text_single_page_bytes]
+    /// for the output in the encoding itself
+    pub encoding: Option<TextEncoding>,
+
+    /// Optional line ending pdftotext ends each line with, uses the
+    /// pdftotext default for the platform when not set
+    pub eol: Option<Eol>,
+
+    /// Whether pdftotext leaves out the [PAGE_END_CHARACTER] after each
+    /// page, unlike [PageBreak::None] the text is not rewritten after
+    /// extraction. Ignored by the functions that split the text into
+    /// pages such as [text_all_pages_split]
+    pub no_page_breaks: bool,
+}
+
+/// How right-to-left text is post-processed after extraction
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BidiMode {
+    /// Keep the text in the order provided by pdftotext (Default)
+    #[default]
+    Keep,
+    /// Converts lines containing right-to-left text from visual order into
+    /// logical order. Documents that store right-to-left text in the order
+    /// it is displayed (Common in older Hebrew documents) otherwise extract
+    /// with the words and letters reversed. Left-to-right runs such as
+    /// numbers and Latin words within the line keep their order
+    VisualToLogical,
+}
+
+impl BidiMode {
+    /// Applies the bidi handling to each line of the text
+    pub fn apply(&self, value: String) -> String {
+        match self {
+            BidiMode::Keep => value,
+            BidiMode::VisualToLogical => map_lines(&value, visual_to_logical),
+        }
+    }
+}
+
+/// Applies the provided mapping to each line keeping the line endings
+/// and page end characters in place
+fn map_lines(value: &str, map: impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut line = String::new();
+
+    for char in value.chars() {
+        if matches!(char, '\n' | '\r' | PAGE_END_CHARACTER) {
+            out.push_str(&map(&line));
+            out.push(char);
+            line.clear();
+        } else {
+            line.push(char);
+        }
+    }
+
+    out.push_str(&map(&line));
+    out
+}
+
+/// Whether the character is from a right-to-left script
+fn is_rtl(char: char) -> bool {
+    matches!(char,
+        // Hebrew, Arabic, Syriac, Arabic supplement, Thaana and NKo
+        '\u{0590}'..='\u{07FF}'
+        // Hebrew and Arabic presentation forms
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFF}')
+}
+
+/// Whether the character is a CJK ideograph, kana, hangul or CJK punctuation
+fn is_cjk(char: char) -> bool {
+    matches!(char,
+        '\u{3000}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FFEF}')
+}
+
+/// Mirrored form of paired punctuation, right-to-left text displays
+/// the logical opening bracket using the closing glyph
+fn mirror(char: char) -> char {
+    match char {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        char => char,
+    }
+}
+
+/// Converts a single line from visual order into logical order, lines
+/// without any right-to-left characters are left unchanged
+fn visual_to_logical(line: &str) -> String {
+    if !line.chars().any(is_rtl) {
+        return line.to_string();
+    }
+
+    let reversed: Vec<char> = line.chars().rev().collect();
+    let is_ltr = |char: char| char.is_alphanumeric() && !is_rtl(char);
+
+    let mut out = String::with_capacity(line.len());
+    let mut index = 0;
+
+    while index < reversed.len() {
+        if !is_ltr(reversed[index]) {
+            out.push(mirror(reversed[index]));
+            index += 1;
+            continue;
+        }
+
+        // Left-to-right runs include the punctuation between their
+        // characters (e.g. "150.00" or "2024-01-31")
+        let mut end = index;
+        let mut next = index;
+        while next < reversed.len() && !is_rtl(reversed[next]) && !reversed[next].is_whitespace() {
+            if is_ltr(reversed[next]) {
+                end = next;
+            }
+            next += 1;
+        }
+
+        out.extend(reversed[index..=end].iter().rev());
+        index = end + 1;
+    }
+
+    out
+}
+
+/// Joins runs of lines that each contain a single CJK character
+fn join_vertical_cjk(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut column = String::new();
+
+    let is_vertical = |line: &str| {
+        let mut chars = line.trim().chars();
+        matches!((chars.next(), chars.next()), (Some(char), None) if is_cjk(char))
+    };
+
+    for line in value.split_inclusive('\n') {
+        if is_vertical(line) {
+            column.push_str(line.trim());
+            continue;
+        }
+
+        if !column.is_empty() {
+            out.push_str(&column);
+            out.push('\n');
+            column.clear();
+        }
+
+        out.push_str(line);
+    }
+
+    if !column.is_empty() {
+        out.push_str(&column);
+        out.push('\n');
+    }
+
+    out
+}
+
+/// How the page breaks between pages are represented when the text
+/// for all the pages is provided as a single string
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum PageBreak {
+    /// Replace each page break with a new line (Default)
+    #[default]
+    Newline,
+    /// Keep the [PAGE_END_CHARACTER]
+    Keep,
+    /// Replace each page break with the provided separator
+    Separator(String),
+    /// Remove the page breaks
+    None,
+}
+
+impl PageBreak {
+    /// Applies the page break handling to the text
+    pub fn apply(&self, value: String) -> String {
+        match self {
+            PageBreak::Newline => value.replace(PAGE_END_CHARACTER, "\n"),
+            PageBreak::Keep => value,
+            PageBreak::Separator(separator) => value.replace(PAGE_END_CHARACTER, separator),
+            PageBreak::None => value.replace(PAGE_END_CHARACTER, ""),
+        }
+    }
+}
+
+/// Output encoding for pdftotext (`-enc`), see [text_encodings] for
+/// the encodings supported by the installed pdftotext
+///
+/// Text provided as a [String] is always UTF-8, it is decoded from this
+/// encoding so only characters that can be represented are kept. The
+/// `_bytes` functions provide the text in the encoding unchanged
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum TextEncoding {
+    /// UTF-8 (Default)
+    #[default]
+    Utf8,
+    /// ISO-8859-1, characters outside of Latin-1 are dropped
+    Latin1,
+    /// 7-bit ASCII, characters outside of ASCII are approximated
+    /// or dropped
+    Ascii7,
+    /// UCS-2 big endian, characters outside of the basic multilingual
+    /// plane are dropped
+    Ucs2,
+}
+
+impl TextEncoding {
+    /// Name of the encoding as used by pdftotext
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Latin1 => "Latin1",
+            Self::Ascii7 => "ASCII7",
+            Self::Ucs2 => "UCS-2",
+        }
+    }
+
+    /// Decodes the output of pdftotext in this encoding, invalid
+    /// sequences are replaced
+    pub fn decode(&self, output: &[u8]) -> String {
+        match self {
+            Self::Utf8 | Self::Ascii7 => String::from_utf8_lossy(output).into_owned(),
+            Self::Latin1 => output.iter().map(|value| *value as char).collect(),
+            Self::Ucs2 => {
+                let units = output
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]));
+
+                char::decode_utf16(units)
+                    .map(|value| value.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Line ending for pdftotext (`-eol`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Eol {
+    /// Line feed "\n"
+    Unix,
+    /// Carriage return and line feed "\r\n"
+    Dos,
+    /// Carriage return "\r"
+    Mac,
+}
+
+impl Eol {
+    /// Name of the line ending as used by pdftotext
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Unix => "unix",
+            Self::Dos => "dos",
+            Self::Mac => "mac",
+        }
+    }
+
+    /// Characters the line ending is made of
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unix => "\n",
+            Self::Dos => "\r\n",
+            Self::Mac => "\r",
+        }
+    }
+}
+
+impl Default for PdfTextArgs {
+    fn default() -> Self {
+        Self {
+            password: None,
+            max_output_size: None,
+            dedup_pages: false,
+            timeout: None,
+            stderr: None,
+            priority: None,
+            budget: None,
+            layout: false,
+            raw: false,
+            bidi: BidiMode::default(),
+            join_vertical_cjk: false,
+            normalization: None,
+            fan_out: FanOut::default(),
+            verify: true,
+            empty_document: EmptyDocumentPolicy::default(),
+            page_break: PageBreak::default(),
+            trim_trailing_page: false,
+            encoding: None,
+            eol: None,
+            no_page_breaks: false,
+        }
+    }
 }
 
 impl PdfTextArgs {
@@ -57,12 +513,331 @@ impl PdfTextArgs {
         self
     }
 
-    /// Builds an argument list from all the options
+    pub fn set_max_output_size(mut self, max_output_size: usize) -> Self {
+        self.max_output_size = Some(max_output_size);
+        self
+    }
+
+    pub fn set_dedup_pages(mut self, dedup_pages: bool) -> Self {
+        self.dedup_pages = dedup_pages;
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_stderr(mut self, stderr: StderrSink) -> Self {
+        self.stderr = Some(stderr);
+        self
+    }
+
+    pub fn set_priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn set_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn set_layout(mut self, layout: bool) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn set_raw(mut self, raw: bool) -> Self {
+        self.raw = raw;
+        self
+    }
+
+    pub fn set_bidi(mut self, bidi: BidiMode) -> Self {
+        self.bidi = bidi;
+        self
+    }
+
+    pub fn set_join_vertical_cjk(mut self, join_vertical_cjk: bool) -> Self {
+        self.join_vertical_cjk = join_vertical_cjk;
+        self
+    }
+
+    pub fn set_normalization(mut self, normalization: TextNormalization) -> Self {
+        self.normalization = Some(normalization);
+        self
+    }
+
+    pub fn set_fan_out(mut self, fan_out: FanOut) -> Self {
+        self.fan_out = fan_out;
+        self
+    }
+
+    pub fn set_page_break(mut self, page_break: PageBreak) -> Self {
+        self.page_break = page_break;
+        self
+    }
+
+    pub fn set_trim_trailing_page(mut self, trim_trailing_page: bool) -> Self {
+        self.trim_trailing_page = trim_trailing_page;
+        self
+    }
+
+    pub fn set_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    pub fn set_empty_document(mut self, empty_document: EmptyDocumentPolicy) -> Self {
+        self.empty_document = empty_document;
+        self
+    }
+
+    pub fn set_encoding(mut self, encoding: TextEncoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    pub fn set_eol(mut self, eol: Eol) -> Self {
+        self.eol = Some(eol);
+        self
+    }
+
+    pub fn set_no_page_breaks(mut self, no_page_breaks: bool) -> Self {
+        self.no_page_breaks = no_page_breaks;
+        self
+    }
+
+    /// Options for running pdftotext
+    pub(crate) fn run_options(&self) -> RunOptions {
+        RunOptions {
+            max_output_size: self.max_output_size,
+            timeout: self.timeout,
+            password: self.password.clone(),
+            stderr: self.stderr.clone(),
+            priority: self.priority,
+            budget: self.budget.clone(),
+        }
+    }
+
+    /// Builds an argument list from all the options, the password is passed
+    /// to the tool separately so it is not included
     pub fn build_args(&self) -> Vec<String> {
         let mut out = Vec::new();
 
-        if let Some(password) = self.password.as_ref() {
-            password.push_arg(&mut out);
+        if self.layout {
+            out.push("-layout".to_string());
+        }
+
+        if self.raw {
+            out.push("-raw".to_string());
+        }
+
+        if let Some(encoding) = self.encoding {
+            out.push("-enc".to_string());
+            out.push(encoding.name().to_string());
+        }
+
+        if let Some(eol) = self.eol {
+            out.push("-eol".to_string());
+            out.push(eol.name().to_string());
+        }
+
+        if self.no_page_breaks {
+            out.push("-nopgbrk".to_string());
+        }
+
+        out
+    }
+
+    /// Args for reading the info of the PDF the text is extracted from
+    pub(crate) fn info_args(&self) -> PdfInfoArgs {
+        PdfInfoArgs {
+            password: self.password.clone(),
+            timeout: self.timeout,
+            stderr: self.stderr.clone(),
+            priority: self.priority,
+            budget: self.budget.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Args for the functions that split the text on the page
+    /// breaks, the page breaks are always kept for these
+    pub(crate) fn with_page_breaks(&self) -> Cow<'_, PdfTextArgs> {
+        if self.no_page_breaks {
+            Cow::Owned(self.clone().set_no_page_breaks(false))
+        } else {
+            Cow::Borrowed(self)
+        }
+    }
+
+    /// Handling applied to the text after extraction
+    pub(crate) fn post_process(&self) -> PostProcess {
+        PostProcess {
+            encoding: self.encoding.unwrap_or_default(),
+            eol: self.eol,
+            normalization: self.normalization.clone(),
+            bidi: self.bidi,
+            join_vertical_cjk: self.join_vertical_cjk,
+        }
+    }
+}
+
+/// Handling applied to the text after extraction, kept separate from
+/// the [PdfTextArgs] so it can be moved into the page jobs
+#[derive(Debug, Clone)]
+pub(crate) struct PostProcess {
+    encoding: TextEncoding,
+    eol: Option<Eol>,
+    normalization: Option<TextNormalization>,
+    bidi: BidiMode,
+    join_vertical_cjk: bool,
+}
+
+impl PostProcess {
+    /// Decodes the output of pdftotext using the output encoding
+    pub(crate) fn decode(&self, output: &[u8]) -> String {
+        self.encoding.decode(output)
+    }
+
+    /// Applies the normalization, bidi and vertical text handling
+    pub(crate) fn apply(&self, value: String) -> String {
+        let value = match self.normalization.as_ref() {
+            Some(normalization) => normalization.apply(&value),
+            None => value,
+        };
+
+        if self.bidi == BidiMode::Keep && !self.join_vertical_cjk {
+            return value;
+        }
+
+        // The line handling works on "\n" so other line endings are
+        // converted for the handling and restored after
+        let eol = self.eol.filter(|eol| *eol != Eol::Unix);
+        let value = match eol {
+            Some(eol) => value.replace(eol.as_str(), "\n"),
+            None => value,
+        };
+
+        let value = self.bidi.apply(value);
+
+        let value = if self.join_vertical_cjk {
+            // Page end characters start their own line so columns do not
+            // continue across pages
+            value
+                .split(PAGE_END_CHARACTER)
+                .map(join_vertical_cjk)
+                .collect::<Vec<_>>()
+                .join(&PAGE_END_CHARACTER.to_string())
+        } else {
+            value
+        };
+
+        match eol {
+            Some(eol) => value.replace('\n', eol.as_str()),
+            None => value,
+        }
+    }
+}
+
+/// Table of characters replaced in the extracted text
+///
+/// [TextNormalization::standard] covers the characters that commonly get
+/// in the way of searching, additional characters can be mapped using
+/// [TextNormalization::set] and standard ones kept using
+/// [TextNormalization::remove]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TextNormalization {
+    /// Replacement for each character, an empty replacement
+    /// removes the character
+    table: HashMap<char, String>,
+}
+
+impl TextNormalization {
+    /// Soft hyphens are only displayed when a word is broken across lines
+    pub const SOFT_HYPHENS: &[(char, &str)] = &[('\u{AD}', "")];
+
+    /// Latin ligatures from the alphabetic presentation forms block
+    pub const LIGATURES: &[(char, &str)] = &[
+        ('\u{FB00}', "ff"),
+        ('\u{FB01}', "fi"),
+        ('\u{FB02}', "fl"),
+        ('\u{FB03}', "ffi"),
+        ('\u{FB04}', "ffl"),
+        ('\u{FB05}', "st"),
+        ('\u{FB06}', "st"),
+        ('\u{0132}', "IJ"),
+        ('\u{0133}', "ij"),
+        ('\u{0152}', "OE"),
+        ('\u{0153}', "oe"),
+    ];
+
+    /// Curly quotes and primes replaced with their ASCII forms
+    pub const SMART_QUOTES: &[(char, &str)] = &[
+        ('\u{2018}', "'"),
+        ('\u{2019}', "'"),
+        ('\u{201A}', "'"),
+        ('\u{201B}', "'"),
+        ('\u{2032}', "'"),
+        ('\u{201C}', "\""),
+        ('\u{201D}', "\""),
+        ('\u{201E}', "\""),
+        ('\u{201F}', "\""),
+        ('\u{2033}', "\""),
+    ];
+
+    /// Creates an empty table that leaves the text unchanged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a table with the [TextNormalization::SOFT_HYPHENS],
+    /// [TextNormalization::LIGATURES] and [TextNormalization::SMART_QUOTES]
+    pub fn standard() -> Self {
+        Self::new()
+            .extend(Self::SOFT_HYPHENS)
+            .extend(Self::LIGATURES)
+            .extend(Self::SMART_QUOTES)
+    }
+
+    /// Replaces the character with the provided replacement, an
+    /// empty replacement removes the character
+    pub fn set(mut self, char: char, replacement: impl Into<String>) -> Self {
+        self.table.insert(char, replacement.into());
+        self
+    }
+
+    /// Adds all the provided replacements
+    pub fn extend(mut self, replacements: &[(char, &str)]) -> Self {
+        self.table.extend(
+            replacements
+                .iter()
+                .map(|(char, replacement)| (*char, replacement.to_string())),
+        );
+        self
+    }
+
+    /// Keeps the character unchanged
+    pub fn remove(mut self, char: char) -> Self {
+        self.table.remove(&char);
+        self
+    }
+
+    /// Replacement for the provided character if it is normalized
+    pub fn get(&self, char: char) -> Option<&str> {
+        self.table.get(&char).map(String::as_str)
+    }
+
+    /// Applies the replacements to the provided text
+    pub fn apply(&self, value: &str) -> String {
+        let mut out = String::with_capacity(value.len());
+
+        for char in value.chars() {
+            match self.table.get(&char) {
+                Some(replacement) => out.push_str(replacement),
+                None => out.push(char),
+            }
         }
 
         out
@@ -71,7 +846,8 @@ impl PdfTextArgs {
 
 /// Extracts the text from all the pages in the provided PDF.
 /// Replaces the page break characters with a single new line
-/// provides all pages as a single string.
+/// (See [PdfTextArgs::page_break]) provides all pages as a single string.
+/// The new line uses the [PdfTextArgs::eol] line ending when set
 ///
 /// Use [text_all_pages_split] to get a separate string for
 /// each page as a list
@@ -85,15 +861,18 @@ impl PdfTextArgs {
 pub async fn text_all_pages(data: &[u8], args: &PdfTextArgs) -> Result<String, PdfTextError> {
     let value = pages_text(data, args).await?;
 
-    // Strip page end characters
-    let value = value.replace(PAGE_END_CHARACTER, "\n");
+    let page_break = match (&args.page_break, args.eol) {
+        (PageBreak::Newline, Some(eol)) => PageBreak::Separator(eol.as_str().to_string()),
+        (page_break, _) => page_break.clone(),
+    };
 
-    Ok(value)
+    Ok(page_break.apply(value))
 }
 
 /// Extracts the text from all the pages in the provided PDF.
 /// Provides a list of strings one string per page. Pages are
-/// split on the [PAGE_END_CHARACTER]
+/// split on the [PAGE_END_CHARACTER], the final page break leaves an
+/// empty value at the end unless [PdfTextArgs::trim_trailing_page] is set
 ///
 /// If you only want a specific page use [text_single_page]
 ///
@@ -106,7 +885,13 @@ pub async fn text_all_pages_split(
     data: &[u8],
     args: &PdfTextArgs,
 ) -> Result<Vec<String>, PdfTextError> {
-    let out = pages_text(data, args).await?;
+    let out = pages_text(data, &args.with_page_breaks()).await?;
+
+    let out = if args.trim_trailing_page {
+        out.strip_suffix(PAGE_END_CHARACTER).unwrap_or(&out)
+    } else {
+        &out
+    };
 
     // Split on page ends
     Ok(out
@@ -132,25 +917,141 @@ pub async fn text_pages(
     pages: Vec<u32>,
     args: &PdfTextArgs,
 ) -> Result<Vec<String>, PdfTextError> {
-    // Get the page count
-    let page_count = info
-        .pages()
-        .ok_or(PdfTextError::PageCountUnknown)?
-        .map_err(|_| PdfTextError::PageCountUnknown)?;
-
-    // Validate requested pages
-    for page in &pages {
-        if *page > page_count {
-            return Err(PdfTextError::PageOutOfBounds(*page, page_count));
-        }
+    verify_pages(data, info, args, &pages).await?;
+
+    // Extract all the pages individually
+    for_each_page(
+        data,
+        pages,
+        args.dedup_pages,
+        args.fan_out,
+        |page| page_job(page, args),
+        {
+            let post_process = args.post_process();
+            move |output| finish_page_text(output, &post_process)
+        },
+    )
+    .await
+}
+
+/// Extracts the text from the provided pages in the provided PDF, keeping
+/// the text of the pages that completed before any failure. Once a page
+/// fails no more pages are extracted and the pages still running are stopped
+///
+/// See [text_pages]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * pages - The page numbers to get text from
+/// * args - Optional args for the pdf to text
+pub async fn text_pages_partial(
+    data: &[u8],
+    info: &PdfInfo,
+    pages: Vec<u32>,
+    args: &PdfTextArgs,
+) -> PartialResult<String, PdfTextError> {
+    if let Err(err) = verify_pages(data, info, args, &pages).await {
+        return PartialResult::failed(err);
     }
-    // Render all the pages individually
-    pages
+
+    for_each_page_partial(
+        data,
+        pages,
+        args.dedup_pages,
+        args.fan_out,
+        |page| page_job(page, args),
+        {
+            let post_process = args.post_process();
+            move |output| finish_page_text(output, &post_process)
+        },
+    )
+    .await
+}
+
+/// Extracts the text from the provided pages in the provided PDF,
+/// providing the text for each page paired with its page number in
+/// the requested order
+///
+/// See [text_pages]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * pages - The page numbers to get text from
+/// * args - Optional args for the pdf to text
+pub async fn text_pages_numbered(
+    data: &[u8],
+    info: &PdfInfo,
+    pages: Vec<u32>,
+    args: &PdfTextArgs,
+) -> Result<Vec<(u32, String)>, PdfTextError> {
+    let text = text_pages(data, info, pages.clone(), args).await?;
+    Ok(pages.into_iter().zip(text).collect())
+}
+
+/// Extracts the text from the selected pages in the provided PDF,
+/// providing the text for each page paired with its page number in
+/// the selected order
+///
+/// See [text_pages]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * selection - The pages to get text from
+/// * args - Optional args for the pdf to text
+pub async fn text_page_selection(
+    data: &[u8],
+    info: &PdfInfo,
+    selection: &PageSelection,
+    args: &PdfTextArgs,
+) -> Result<Vec<(u32, String)>, PdfTextError> {
+    let pages = selection.resolve(info, args.empty_document)?;
+    text_pages_numbered(data, info, pages, args).await
+}
+
+/// Text of a single page tagged with how it was extracted
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ExtractedText {
+    /// The page number
+    pub page: u32,
+    /// The text of the page
+    pub text: String,
+    /// Extraction method and poppler version used for the text
+    pub provenance: TextProvenance,
+}
+
+/// Extracts the text from the specific pages in the provided PDF, tagging
+/// the text of each page with the extraction method and poppler version
+///
+/// See [text_pages_numbered]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * pages - The page numbers to get text from
+/// * args - Optional args for the pdf to text
+pub async fn text_pages_with_provenance(
+    data: &[u8],
+    info: &PdfInfo,
+    pages: Vec<u32>,
+    args: &PdfTextArgs,
+) -> Result<Vec<ExtractedText>, PdfTextError> {
+    let (pages, provenance) = try_join(text_pages_numbered(data, info, pages, args), async {
+        Ok(text_provenance(args).await?)
+    })
+    .await?;
+
+    Ok(pages
         .into_iter()
-        .map(|page| page_text(data, page, args))
-        .collect::<FuturesOrdered<_>>()
-        .try_collect()
-        .await
+        .map(|(page, text)| ExtractedText {
+            page,
+            text,
+            provenance: provenance.clone(),
+        })
+        .collect())
 }
 
 /// Extracts the text from the specific pages in the provided PDF.
@@ -166,18 +1067,243 @@ pub async fn text_single_page(
     page: u32,
     args: &PdfTextArgs,
 ) -> Result<String, PdfTextError> {
-    // Get the page count
-    let page_count = info
-        .pages()
-        .ok_or(PdfTextError::PageCountUnknown)?
-        .map_err(|_| PdfTextError::PageCountUnknown)?;
+    verify_pages(data, info, args, &[page]).await?;
+
+    page_text(data, page, args).await
+}
+
+/// Extracts the text from all the pages in the provided PDF in the
+/// [PdfTextArgs::encoding] without decoding it, provides the pdftotext
+/// output unchanged. Pages are separated by the [PAGE_END_CHARACTER] in
+/// the encoding and none of the text post processing options are applied
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Optional args for the pdf to text
+pub async fn text_all_pages_bytes(
+    data: &[u8],
+    args: &PdfTextArgs,
+) -> Result<Vec<u8>, PdfTextError> {
+    // Take input from stdin and provide to stdout
+    let mut cli_args = vec!["-".to_string(), "-".to_string()];
+    cli_args.extend(args.build_args());
+
+    let output = run_tool("pdftotext", &cli_args, data, &args.run_options()).await?;
+    Ok(output.stdout)
+}
+
+/// Extracts the text from a specific page in the provided PDF in the
+/// [PdfTextArgs::encoding] without decoding it, provides the pdftotext
+/// output unchanged including the trailing [PAGE_END_CHARACTER]. None
+/// of the text post processing options are applied
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * page - The page number to get text from
+/// * args - Optional args for the pdf to text
+pub async fn text_single_page_bytes(
+    data: &[u8],
+    info: &PdfInfo,
+    page: u32,
+    args: &PdfTextArgs,
+) -> Result<Vec<u8>, PdfTextError> {
+    verify_pages(data, info, args, &[page]).await?;
+
+    Ok(page_job(page, args).run(data).await?)
+}
+
+/// Extracts the text from the start of the provided PDF up to the
+/// provided number of characters. Pages are extracted one at a time
+/// and extraction stops as soon as enough text has been collected,
+/// pages are separated by a single new line
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * max_chars - The maximum number of characters to extract
+/// * args - Optional args for the pdf to text
+pub async fn text_excerpt(
+    data: &[u8],
+    max_chars: usize,
+    args: &PdfTextArgs,
+) -> Result<String, PdfTextError> {
+    let info = pdf_info(data, &args.info_args()).await?;
+
+    info_text_excerpt(data, &info, max_chars, args).await
+}
+
+/// Extracts the text from the start of the provided PDF up to the
+/// provided number of characters using an existing [PdfInfo]
+///
+/// See [text_excerpt]
+pub(crate) async fn info_text_excerpt(
+    data: &[u8],
+    info: &PdfInfo,
+    max_chars: usize,
+    args: &PdfTextArgs,
+) -> Result<String, PdfTextError> {
+    let page_count = verify_all_pages(data, info, args).await?;
+
+    let mut excerpt = String::new();
+    let mut length = 0;
+
+    for page in 1..=page_count {
+        if length >= max_chars {
+            break;
+        }
 
-    // Validate chosen page
-    if page > page_count {
-        return Err(PdfTextError::PageOutOfBounds(page, page_count));
+        // Pages after the first need room for the separator and some text
+        if page > 1 {
+            if length + 1 >= max_chars {
+                break;
+            }
+
+            excerpt.push('\n');
+            length += 1;
+        }
+
+        let text = page_text(data, page, args).await?;
+        for value in text.chars().take(max_chars - length) {
+            excerpt.push(value);
+            length += 1;
+        }
     }
 
-    page_text(data, page, args).await
+    Ok(excerpt)
+}
+
+/// Creates a [TextPager] for lazily extracting the text from the
+/// provided PDF one page at a time
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * args - Optional args for the pdf to text
+pub async fn text_pager<'a>(
+    data: &'a [u8],
+    info: &PdfInfo,
+    args: &'a PdfTextArgs,
+) -> Result<TextPager<'a>, PdfTextError> {
+    let page_count = verify_all_pages(data, info, args).await?;
+
+    Ok(TextPager {
+        data,
+        args,
+        next_page: 1,
+        page_count,
+    })
+}
+
+/// Extracts the text from a PDF one page at a time, `pdftotext` is
+/// only spawned for the next page when it is requested so callers can
+/// stop early without extracting the whole document
+///
+/// Created using [text_pager]
+pub struct TextPager<'a> {
+    /// The raw PDF file bytes
+    data: &'a [u8],
+    /// Args for the pdf to text
+    args: &'a PdfTextArgs,
+    /// The next page to extract
+    next_page: u32,
+    /// Total number of pages in the PDF
+    page_count: u32,
+}
+
+impl<'a> TextPager<'a> {
+    /// Extracts the text for the next page, provides the page number
+    /// along with the text. Returns [None] once all pages are extracted
+    pub async fn next(&mut self) -> Option<Result<(u32, String), PdfTextError>> {
+        if self.next_page > self.page_count {
+            return None;
+        }
+
+        let page = self.next_page;
+        self.next_page += 1;
+
+        Some(
+            page_text(self.data, page, self.args)
+                .await
+                .map(|text| (page, text)),
+        )
+    }
+
+    /// Number of pages that have not been extracted yet
+    pub fn remaining(&self) -> u32 {
+        (self.page_count + 1).saturating_sub(self.next_page)
+    }
+
+    /// Total number of pages in the PDF
+    pub fn page_count(&self) -> u32 {
+        self.page_count
+    }
+
+    /// Converts the pager into a [Stream] of the page numbers and text
+    pub fn into_stream(self) -> impl Stream<Item = Result<(u32, String), PdfTextError>> + 'a {
+        stream::unfold(self, |mut pager| async move {
+            let item = pager.next().await?;
+            Some((item, pager))
+        })
+    }
+}
+
+/// Checks the encryption state and that the pages are within the page
+/// count of the PDF, skipped when [PdfTextArgs::verify] is disabled
+pub(crate) async fn verify_pages(
+    data: &[u8],
+    info: &PdfInfo,
+    args: &PdfTextArgs,
+    pages: &[u32],
+) -> Result<(), PdfTextError> {
+    if !args.verify {
+        return Ok(());
+    }
+
+    verify_document(data, info, args.password.as_ref(), pages).await?;
+    Ok(())
+}
+
+/// Checks the encryption state providing the page count of the PDF, only
+/// the page count is read when [PdfTextArgs::verify] is disabled. The page
+/// count is checked against the [PdfTextArgs::empty_document] policy
+async fn verify_all_pages(
+    data: &[u8],
+    info: &PdfInfo,
+    args: &PdfTextArgs,
+) -> Result<u32, PdfTextError> {
+    let page_count = if args.verify {
+        verify_document(data, info, args.password.as_ref(), &[]).await?
+    } else {
+        info_page_count(info)?
+    };
+
+    Ok(args.empty_document.check(page_count)?)
+}
+
+/// Lists the names of the output encodings supported by the installed
+/// pdftotext using `pdftotext -listenc`, includes encodings not covered
+/// by [TextEncoding]
+pub async fn text_encodings() -> Result<Vec<String>, PdfTextError> {
+    let options = RunOptions {
+        timeout: Some(ENCODINGS_TIMEOUT),
+        ..Default::default()
+    };
+
+    let output = run_tool("pdftotext", &["-listenc"], &[], &options).await?;
+
+    let value = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_encodings(&value))
+}
+
+/// Parses the encoding names from the `pdftotext -listenc` output,
+/// the names are listed one per line after a header line
+fn parse_encodings(value: &str) -> Vec<String> {
+    value
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.ends_with(':'))
+        .map(str::to_string)
+        .collect()
 }
 
 /// Extracts the text contents from the provided pdf file data
@@ -192,54 +1318,38 @@ pub async fn text_single_page(
 /// ## Arguments
 /// * data - The raw PDF file bytes
 /// * args - Extra args to provide to pdftotext
-async fn pages_text(data: &[u8], args: &PdfTextArgs) -> Result<String, PdfTextError> {
-    let cli_args = args.build_args();
-    let mut child = Command::new("pdftotext")
-        // Take input from stdin and provide to stdout
-        .args(["-", "-"])
-        .args(cli_args)
-        // Pipe input and output for use
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(PdfTextError::SpawnProcess)?;
-
-    child
-        .stdin
-        .as_mut()
-        // Should always have stdin when using .stdin(Stdio::piped())
-        .expect("progress missing stdin after being piped")
-        .write_all(data)
-        .await
-        .map_err(PdfTextError::WritePdf)?;
-
-    let output = child
-        .wait_with_output()
-        .await
-        .map_err(PdfTextError::WaitOutput)?;
-
-    // Handle info failure
-    if !output.status.success() {
-        let value = String::from_utf8_lossy(&output.stderr);
-
-        if value.contains("May not be a PDF file") {
-            return Err(PdfTextError::NotPdfFile);
-        }
+pub(crate) async fn pages_text(data: &[u8], args: &PdfTextArgs) -> Result<String, PdfTextError> {
+    let output = text_all_pages_bytes(data, args).await?;
 
-        if value.contains("Incorrect password") {
-            return Err(if args.password.is_none() {
-                PdfTextError::PdfEncrypted
-            } else {
-                PdfTextError::IncorrectPassword
-            });
-        }
+    let post_process = args.post_process();
+    let value = post_process.decode(&output);
+    Ok(post_process.apply(value))
+}
 
-        return Err(PdfTextError::PdfTextFailure(value.to_string()));
-    }
+/// Extracts the text contents from the provided pdf file data
+/// using the `pdftotext` program with one of the options that include
+/// the bounding box of each word
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Extra args to provide to pdftotext
+/// * format - The output option, one of `-bbox`, `-bbox-layout` or `-tsv`
+pub(crate) async fn bbox_text(
+    data: &[u8],
+    args: &PdfTextArgs,
+    format: &str,
+) -> Result<String, PdfTextError> {
+    // Take input from stdin and provide to stdout
+    let mut cli_args = vec!["-".to_string(), "-".to_string()];
 
-    let value = String::from_utf8_lossy(&output.stdout);
-    Ok(value.into_owned())
+    // Include the word bounding boxes
+    cli_args.push(format.to_string());
+
+    cli_args.extend(args.build_args());
+
+    let output = run_tool("pdftotext", &cli_args, data, &args.run_options()).await?;
+
+    Ok(args.post_process().decode(&output.stdout))
 }
 
 /// Extracts the text contents from the provided pdf file data
@@ -252,61 +1362,41 @@ async fn pages_text(data: &[u8], args: &PdfTextArgs) -> Result<String, PdfTextEr
 /// * data - The raw PDF file
 /// * page - The page to extract text from
 /// * args - Extra args to provide to pdftotext
-async fn page_text(data: &[u8], page: u32, args: &PdfTextArgs) -> Result<String, PdfTextError> {
-    let cli_args = args.build_args();
-    let mut child = Command::new("pdftotext")
-        // Take input from stdin and provide to stdout
-        .args(["-", "-"])
-        // Add the page args
-        .args([
-            "-f".to_string(),
-            format!("{page}"),
-            "-l".to_string(),
-            format!("{page}"),
-        ])
-        .args(cli_args)
-        // Pipe input and output for use
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(PdfTextError::SpawnProcess)?;
-
-    child
-        .stdin
-        .as_mut()
-        // Should always have stdin when using .stdin(Stdio::piped())
-        .expect("progress missing stdin after being piped")
-        .write_all(data)
-        .await
-        .map_err(PdfTextError::WritePdf)?;
-
-    let output = child
-        .wait_with_output()
-        .await
-        .map_err(PdfTextError::WaitOutput)?;
-
-    // Handle info failure
-    if !output.status.success() {
-        let value = String::from_utf8_lossy(&output.stderr);
-
-        if value.contains("May not be a PDF file") {
-            return Err(PdfTextError::NotPdfFile);
-        }
+pub(crate) async fn page_text(
+    data: &[u8],
+    page: u32,
+    args: &PdfTextArgs,
+) -> Result<String, PdfTextError> {
+    let output = page_job(page, args).run(data).await?;
+    finish_page_text(output, &args.post_process())
+}
 
-        if value.contains("Incorrect password") {
-            return Err(if args.password.is_none() {
-                PdfTextError::PdfEncrypted
-            } else {
-                PdfTextError::IncorrectPassword
-            });
-        }
+/// Creates the `pdftotext` job for extracting the text of the provided page
+fn page_job(page: u32, args: &PdfTextArgs) -> ToolJob {
+    // Take input from stdin and provide to stdout
+    let mut cli_args = vec!["-".to_string(), "-".to_string()];
+
+    // Add the page args
+    cli_args.extend([
+        "-f".to_string(),
+        page.to_string(),
+        "-l".to_string(),
+        page.to_string(),
+    ]);
+
+    cli_args.extend(args.build_args());
 
-        return Err(PdfTextError::PdfTextFailure(value.to_string()));
+    ToolJob {
+        program: "pdftotext",
+        args: cli_args,
+        options: args.run_options(),
     }
+}
 
-    let value = String::from_utf8_lossy(&output.stdout);
-    let mut value = value.to_string();
+/// Converts the output of `pdftotext` for a single page into text
+fn finish_page_text(output: Vec<u8>, post_process: &PostProcess) -> Result<String, PdfTextError> {
+    let value = post_process.decode(&output);
+    let mut value = post_process.apply(value);
 
     // Strip the page end char
     if value.ends_with(PAGE_END_CHARACTER) {
@@ -318,15 +1408,173 @@ async fn page_text(data: &[u8], page: u32, args: &PdfTextArgs) -> Result<String,
 
 #[cfg(test)]
 mod test {
-    use crate::text::{page_text, pages_text, PdfTextArgs, PdfTextError};
+    use std::time::Duration;
+
+    use crate::{
+        budget::Budget,
+        shared::{Password, ProcessPriority},
+        text::{
+            page_text, pages_text, parse_encodings, BidiMode, Eol, PageBreak, PdfTextArgs,
+            PdfTextError, TextEncoding, TextNormalization, PAGE_END_CHARACTER,
+        },
+    };
     use tokio::fs::read;
 
+    /// Tests applying the page break handling
+    #[test]
+    fn test_page_break() {
+        let value = || "Page 1\u{c}Page 2\u{c}".to_string();
+
+        assert_eq!(PageBreak::Newline.apply(value()), "Page 1\nPage 2\n");
+        assert_eq!(PageBreak::Keep.apply(value()), value());
+        assert_eq!(
+            PageBreak::Separator("\n---\n".to_string()).apply(value()),
+            "Page 1\n---\nPage 2\n---\n"
+        );
+        assert_eq!(PageBreak::None.apply(value()), "Page 1Page 2");
+    }
+
+    /// Tests converting visual order right-to-left text into logical order
+    #[test]
+    fn test_bidi_visual_to_logical() {
+        let mode = BidiMode::VisualToLogical;
+
+        // "Total: 150.00" in Hebrew stored in visual order
+        assert_eq!(mode.apply("150.00 :כ\"הס".to_string()), "סה\"כ: 150.00");
+
+        // Dates and Latin words keep their order, brackets are mirrored
+        assert_eq!(
+            mode.apply("(PDF) 2024-01-31 ךיראת\nםולש".to_string()),
+            "תאריך 2024-01-31 (PDF)\nשלום"
+        );
+
+        // Lines without right-to-left text are unchanged
+        let value = "Invoice 42\u{c}請求書\n".to_string();
+        assert_eq!(mode.apply(value.clone()), value);
+        assert_eq!(BidiMode::Keep.apply("םולש".to_string()), "םולש");
+    }
+
+    /// Tests joining vertical CJK text extracted one character per line
+    #[test]
+    fn test_join_vertical_cjk() {
+        let args = PdfTextArgs::default().set_join_vertical_cjk(true);
+
+        let value = format!("請\n求\n書\nTotal 100\n日\n本{PAGE_END_CHARACTER}語\n");
+        assert_eq!(
+            args.post_process().apply(value),
+            format!("請求書\nTotal 100\n日本\n{PAGE_END_CHARACTER}語\n")
+        );
+
+        // Horizontal lines are unchanged
+        let value = "請求書\n合計\n".to_string();
+        assert_eq!(args.post_process().apply(value.clone()), value);
+    }
+
+    /// Tests the info args keep the run options of the text args
+    #[test]
+    fn test_info_args() {
+        let budget = Budget::default().set_max_processes(4);
+        let args = PdfTextArgs::default()
+            .set_password(Password::user("secret"))
+            .set_timeout(Duration::from_secs(5))
+            .set_priority(ProcessPriority::background())
+            .set_budget(budget)
+            .set_layout(true);
+        let info_args = args.info_args();
+
+        assert!(info_args.password.is_some());
+        assert_eq!(info_args.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(info_args.priority, Some(ProcessPriority::background()));
+        assert!(info_args.budget.is_some());
+        assert!(info_args.build_args().is_empty());
+    }
+
+    /// Tests ligatures, soft hyphens and smart quotes are normalized
+    #[test]
+    fn test_normalization() {
+        let normalization = TextNormalization::standard();
+        assert_eq!(
+            normalization.apply("The \u{FB01}nal \u{201C}o\u{FB03}ce\u{201D} is\u{AD}n\u{2019}t"),
+            "The final \"office\" isn't"
+        );
+
+        // Characters can be kept or added to the table
+        let normalization = normalization.remove('\u{201C}').set('\u{2013}', "-");
+        assert_eq!(normalization.get('\u{201C}'), None);
+        assert_eq!(normalization.apply("\u{201C}1\u{2013}2"), "\u{201C}1-2");
+
+        assert_eq!(TextNormalization::new().apply("\u{FB01}"), "\u{FB01}");
+
+        // Normalization runs with the other post-processing
+        let args = PdfTextArgs::default().set_normalization(TextNormalization::standard());
+        assert_eq!(args.post_process().apply("\u{FB02}ow".to_string()), "flow");
+    }
+
+    /// Tests the raw order argument
+    #[test]
+    fn test_raw_args() {
+        let args = PdfTextArgs::default().set_raw(true);
+        assert_eq!(args.build_args(), ["-raw"]);
+    }
+
+    /// Tests the encoding argument and decoding the output
+    #[test]
+    fn test_encoding() {
+        let args = PdfTextArgs::default().set_encoding(TextEncoding::Latin1);
+        assert_eq!(args.build_args(), ["-enc", "Latin1"]);
+        assert_eq!(args.post_process().decode(b"caf\xe9\x0c"), "café\u{c}");
+
+        let ucs2 = [0x00, 0x63, 0x00, 0xe9, 0x4e, 0x2d, 0x00, 0x0c];
+        assert_eq!(TextEncoding::Ucs2.decode(&ucs2), "cé中\u{c}");
+        assert_eq!(TextEncoding::Utf8.decode("café".as_bytes()), "café");
+
+        assert!(PdfTextArgs::default().build_args().is_empty());
+    }
+
+    /// Tests the line ending argument and the line handling with other line endings
+    #[test]
+    fn test_eol() {
+        let args = PdfTextArgs::default().set_eol(Eol::Dos);
+        assert_eq!(args.build_args(), ["-eol", "dos"]);
+
+        let args = args.set_join_vertical_cjk(true);
+        assert_eq!(
+            args.post_process()
+                .apply("請\r\n求\r\nTotal\r\n".to_string()),
+            "請求\r\nTotal\r\n"
+        );
+
+        let args = PdfTextArgs::default()
+            .set_eol(Eol::Mac)
+            .set_bidi(BidiMode::VisualToLogical);
+        assert_eq!(
+            args.post_process().apply("םולש\rTotal\r".to_string()),
+            "שלום\rTotal\r"
+        );
+    }
+
+    /// Tests the page break argument is removed for splitting pages
+    #[test]
+    fn test_no_page_breaks() {
+        let args = PdfTextArgs::default().set_no_page_breaks(true);
+        assert_eq!(args.build_args(), ["-nopgbrk"]);
+        assert!(args.with_page_breaks().build_args().is_empty());
+    }
+
+    /// Tests parsing the encoding list
+    #[test]
+    fn test_parse_encodings() {
+        let output = "Available encodings are:\nUTF-16\nUTF-8\nLatin1\nASCII7\nUCS-2\n";
+        assert_eq!(
+            parse_encodings(output),
+            ["UTF-16", "UTF-8", "Latin1", "ASCII7", "UCS-2"]
+        );
+    }
+
     /// Tests invalid files are handled
     #[tokio::test]
     async fn test_invalid_file() {
-        let err = pages_text(&[b'A'], &PdfTextArgs::default())
-            .await
-            .unwrap_err();
+        let err = pages_text(b"A", &PdfTextArgs::default()).await.unwrap_err();
         assert!(matches!(err, PdfTextError::NotPdfFile));
     }
 
@@ -0,0 +1,113 @@
+//! Helpers for rendering thumbnails of PDF files
+//!
+//! * [render_thumbnail_strip] - Renders the page thumbnails side by side into a single image
+
+use crate::{
+    image::{render_pages, verify_all_pages, OutputFormat, PdfRenderError, RenderArgs, ScaleTo},
+    image_crate::{
+        imageops::{self, FilterType},
+        RgbaImage,
+    },
+    info::PdfInfo,
+};
+
+/// Number of pages rendered at once, only the scaled down thumbnails
+/// are kept between batches
+const THUMBNAIL_BATCH_SIZE: usize = 8;
+
+/// Renders thumbnails of the first pages side by side into a single
+/// horizontal strip, for use in page scrubbers.
+///
+/// Each page is scaled to `thumb_height` while maintaining its aspect ratio,
+/// the pages are rendered in batches so only a few full pages are decoded
+/// at once. The scale of the provided args is replaced
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info
+/// * thumb_height - Height of the thumbnails in pixels
+/// * max_pages - Maximum number of pages to include, starting from the first page
+/// * args - Optional args for pdftocairo
+pub async fn render_thumbnail_strip(
+    data: &[u8],
+    info: &PdfInfo,
+    thumb_height: u32,
+    max_pages: u32,
+    args: &RenderArgs,
+) -> Result<RgbaImage, PdfRenderError> {
+    let page_count = verify_all_pages(data, info, args).await?;
+    let pages: Vec<u32> = (1..=page_count.min(max_pages)).collect();
+
+    // Pages were already verified above
+    let args = args
+        .clone()
+        .set_scale_to(ScaleTo::y(thumb_height as i32))
+        .set_verify(false);
+
+    let mut thumbnails = Vec::with_capacity(pages.len());
+
+    for batch in pages.chunks(THUMBNAIL_BATCH_SIZE) {
+        let images = render_pages(data, info, OutputFormat::Png, batch.to_vec(), &args).await?;
+
+        thumbnails.extend(
+            images
+                .into_iter()
+                .map(|image| scale_thumbnail(image.to_rgba8(), thumb_height)),
+        );
+    }
+
+    Ok(join_strip(&thumbnails, thumb_height))
+}
+
+/// Ensures the thumbnail is exactly the provided height, pdftocairo
+/// can be off by a pixel due to rounding
+fn scale_thumbnail(image: RgbaImage, height: u32) -> RgbaImage {
+    if image.height() == height || image.height() == 0 {
+        return image;
+    }
+
+    let width = (image.width() as u64 * height as u64 / image.height() as u64).max(1) as u32;
+    imageops::resize(&image, width, height, FilterType::Triangle)
+}
+
+/// Places the thumbnails side by side from left to right
+fn join_strip(thumbnails: &[RgbaImage], height: u32) -> RgbaImage {
+    let width = thumbnails.iter().map(|image| image.width()).sum();
+    let mut strip = RgbaImage::new(width, height);
+
+    let mut x = 0;
+    for thumbnail in thumbnails {
+        imageops::replace(&mut strip, thumbnail, x as i64, 0);
+        x += thumbnail.width();
+    }
+
+    strip
+}
+
+#[cfg(test)]
+mod test {
+    use super::{join_strip, scale_thumbnail};
+    use crate::image_crate::{Rgba, RgbaImage};
+
+    /// Tests thumbnails are placed side by side
+    #[test]
+    fn test_join_strip() {
+        let red = RgbaImage::from_pixel(2, 4, Rgba([255, 0, 0, 255]));
+        let blue = RgbaImage::from_pixel(3, 4, Rgba([0, 0, 255, 255]));
+
+        let strip = join_strip(&[red, blue], 4);
+        assert_eq!(strip.dimensions(), (5, 4));
+        assert_eq!(strip.get_pixel(1, 3), &Rgba([255, 0, 0, 255]));
+        assert_eq!(strip.get_pixel(2, 0), &Rgba([0, 0, 255, 255]));
+    }
+
+    /// Tests thumbnails are scaled to the exact height
+    #[test]
+    fn test_scale_thumbnail() {
+        let image = RgbaImage::new(10, 21);
+        assert_eq!(scale_thumbnail(image, 20).dimensions(), (9, 20));
+
+        let image = RgbaImage::new(10, 20);
+        assert_eq!(scale_thumbnail(image, 20).dimensions(), (10, 20));
+    }
+}
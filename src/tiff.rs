@@ -0,0 +1,292 @@
+//! Combining the single page TIFF files rendered by `pdftocairo` into
+//! one multi-page TIFF file
+//!
+//! pdftocairo writes each page to its own file, the files are appended
+//! one after another and the offsets within each file are moved by the
+//! position of the file so the image directories can be chained together
+
+/// Tag holding the offsets of the image strips
+const TAG_STRIP_OFFSETS: u16 = 273;
+/// Tag holding the offsets of unused space in the file
+const TAG_FREE_OFFSETS: u16 = 288;
+/// Tag holding the offsets of the image tiles
+const TAG_TILE_OFFSETS: u16 = 324;
+/// Tag holding the offset of the embedded JPEG stream
+const TAG_JPEG_INTERCHANGE_FORMAT: u16 = 513;
+/// Tags pointing to nested image directories, these are not produced by
+/// pdftocairo and would need their own directories moved
+const TAG_SUB_IFDS: [u16; 3] = [330, 34665, 34853];
+
+/// Field type for 16bit unsigned values
+const TYPE_SHORT: u16 = 3;
+
+/// Upper bound on the directories within a single file, protects
+/// against directory chains that loop back on themselves
+const MAX_DIRECTORIES: usize = 1024;
+
+/// Byte order of a TIFF file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteOrder {
+    Little,
+    Big,
+}
+
+/// Buffer of the combined file being written
+struct TiffBuffer {
+    data: Vec<u8>,
+    order: ByteOrder,
+}
+
+impl TiffBuffer {
+    fn slice<const N: usize>(&self, pos: usize) -> Result<[u8; N], String> {
+        self.data
+            .get(pos..pos + N)
+            .and_then(|value| value.try_into().ok())
+            .ok_or_else(|| format!("offset {pos} is outside the file"))
+    }
+
+    fn read_u16(&self, pos: usize) -> Result<u16, String> {
+        let value = self.slice(pos)?;
+        Ok(match self.order {
+            ByteOrder::Little => u16::from_le_bytes(value),
+            ByteOrder::Big => u16::from_be_bytes(value),
+        })
+    }
+
+    fn read_u32(&self, pos: usize) -> Result<u32, String> {
+        let value = self.slice(pos)?;
+        Ok(match self.order {
+            ByteOrder::Little => u32::from_le_bytes(value),
+            ByteOrder::Big => u32::from_be_bytes(value),
+        })
+    }
+
+    fn write_u16(&mut self, pos: usize, value: u16) {
+        let value = match self.order {
+            ByteOrder::Little => value.to_le_bytes(),
+            ByteOrder::Big => value.to_be_bytes(),
+        };
+        self.data[pos..pos + 2].copy_from_slice(&value);
+    }
+
+    fn write_u32(&mut self, pos: usize, value: u32) {
+        let value = match self.order {
+            ByteOrder::Little => value.to_le_bytes(),
+            ByteOrder::Big => value.to_be_bytes(),
+        };
+        self.data[pos..pos + 4].copy_from_slice(&value);
+    }
+
+    /// Moves the offset stored at the provided position by the base
+    fn move_u32(&mut self, pos: usize, base: u32) -> Result<u32, String> {
+        let value = moved(self.read_u32(pos)?, base)?;
+        self.write_u32(pos, value);
+        Ok(value)
+    }
+}
+
+/// Moves an offset by the base position of its file
+fn moved(offset: u32, base: u32) -> Result<u32, String> {
+    offset
+        .checked_add(base)
+        .ok_or_else(|| "combined file exceeds the maximum tiff size".to_string())
+}
+
+/// Size in bytes of a single value of the provided field type
+fn type_size(field_type: u16) -> Result<u32, String> {
+    Ok(match field_type {
+        // BYTE, ASCII, SBYTE, UNDEFINED
+        1 | 2 | 6 | 7 => 1,
+        // SHORT, SSHORT
+        3 | 8 => 2,
+        // LONG, SLONG, FLOAT, IFD
+        4 | 9 | 11 | 13 => 4,
+        // RATIONAL, SRATIONAL, DOUBLE
+        5 | 10 | 12 => 8,
+        _ => return Err(format!("unknown field type {field_type}")),
+    })
+}
+
+/// Reads the byte order from the header of a TIFF file
+fn byte_order(page: &[u8]) -> Result<ByteOrder, String> {
+    let order = match page.get(0..2) {
+        Some(b"II") => ByteOrder::Little,
+        Some(b"MM") => ByteOrder::Big,
+        _ => return Err("file is not a tiff".to_string()),
+    };
+
+    let magic = match (order, page.get(2..4)) {
+        (ByteOrder::Little, Some(&[low, high])) => u16::from_le_bytes([low, high]),
+        (ByteOrder::Big, Some(&[high, low])) => u16::from_be_bytes([high, low]),
+        _ => return Err("file is not a tiff".to_string()),
+    };
+
+    match magic {
+        42 => Ok(order),
+        43 => Err("bigtiff files are not supported".to_string()),
+        _ => Err("file is not a tiff".to_string()),
+    }
+}
+
+/// Combines the provided single page TIFF files into one multi-page
+/// TIFF file, pages are kept in the order provided
+///
+/// ## Arguments
+/// * pages - The encoded TIFF file for each page
+pub(crate) fn merge_pages(pages: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+    let first = pages.first().ok_or("no pages to combine")?;
+
+    let mut buffer = TiffBuffer {
+        data: Vec::with_capacity(pages.iter().map(Vec::len).sum()),
+        order: byte_order(first)?,
+    };
+
+    // Position of the pointer to the next directory, starts
+    // as the pointer to the first directory in the header
+    let mut next_pointer = 4;
+
+    for page in pages {
+        if byte_order(page)? != buffer.order {
+            return Err("pages use different byte orders".to_string());
+        }
+
+        // Directories must start on a word boundary
+        if buffer.data.len() % 2 == 1 {
+            buffer.data.push(0);
+        }
+
+        let base = u32::try_from(buffer.data.len())
+            .map_err(|_| "combined file exceeds the maximum tiff size".to_string())?;
+        buffer.data.extend_from_slice(page);
+
+        let mut directory = moved(buffer.read_u32(base as usize + 4)?, base)?;
+        buffer.write_u32(next_pointer, directory);
+
+        for _ in 0..MAX_DIRECTORIES {
+            next_pointer = move_directory(&mut buffer, directory as usize, base)?;
+
+            match buffer.read_u32(next_pointer)? {
+                0 => break,
+                next => {
+                    directory = moved(next, base)?;
+                    buffer.write_u32(next_pointer, directory);
+                }
+            }
+        }
+
+        // Last directory of the page until another page follows
+        buffer.write_u32(next_pointer, 0);
+    }
+
+    Ok(buffer.data)
+}
+
+/// Moves the offsets within the directory at the provided position,
+/// providing the position of the pointer to the next directory
+fn move_directory(buffer: &mut TiffBuffer, pos: usize, base: u32) -> Result<usize, String> {
+    let count = buffer.read_u16(pos)? as usize;
+
+    for index in 0..count {
+        let entry = pos + 2 + index * 12;
+        let tag = buffer.read_u16(entry)?;
+        let field_type = buffer.read_u16(entry + 2)?;
+        let value_count = buffer.read_u32(entry + 4)?;
+
+        if TAG_SUB_IFDS.contains(&tag) {
+            return Err(format!(
+                "nested image directories (tag {tag}) are not supported"
+            ));
+        }
+
+        let size = type_size(field_type)?
+            .checked_mul(value_count)
+            .ok_or_else(|| format!("field {tag} is too large"))?;
+
+        // Values that do not fit in the entry are stored at an offset
+        let values = if size > 4 {
+            buffer.move_u32(entry + 8, base)? as usize
+        } else {
+            entry + 8
+        };
+
+        if matches!(
+            tag,
+            TAG_STRIP_OFFSETS | TAG_FREE_OFFSETS | TAG_TILE_OFFSETS | TAG_JPEG_INTERCHANGE_FORMAT
+        ) {
+            for value in 0..value_count as usize {
+                if field_type == TYPE_SHORT {
+                    let pos = values + value * 2;
+                    let offset = moved(buffer.read_u16(pos)? as u32, base)?;
+                    let offset = u16::try_from(offset)
+                        .map_err(|_| format!("offset in field {tag} no longer fits"))?;
+                    buffer.write_u16(pos, offset);
+                } else {
+                    buffer.move_u32(values + value * 4, base)?;
+                }
+            }
+        }
+    }
+
+    Ok(pos + 2 + count * 12)
+}
+
+#[cfg(test)]
+mod test {
+    use super::merge_pages;
+
+    /// Creates a minimal little endian TIFF with a single directory
+    /// holding the strip offset of one byte of image data
+    fn single_page(pixel: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"II");
+        data.extend_from_slice(&42u16.to_le_bytes());
+        data.extend_from_slice(&10u32.to_le_bytes());
+        // Image data then padding to a word boundary
+        data.push(pixel);
+        data.push(0);
+        // Directory with the strip offset and byte count
+        data.extend_from_slice(&2u16.to_le_bytes());
+        for (tag, value) in [(273u16, 8u32), (279, 1)] {
+            data.extend_from_slice(&tag.to_le_bytes());
+            data.extend_from_slice(&4u16.to_le_bytes());
+            data.extend_from_slice(&1u32.to_le_bytes());
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data
+    }
+
+    fn read_u32(data: &[u8], pos: usize) -> usize {
+        u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()) as usize
+    }
+
+    /// Tests the directories of each page are chained together and the
+    /// strip offsets point to the image data of each page
+    #[test]
+    fn test_merge_pages() {
+        let pages = [single_page(1), single_page(2), single_page(3)];
+        let merged = merge_pages(&pages).unwrap();
+
+        let mut directory = read_u32(&merged, 4);
+        let mut pixels = Vec::new();
+
+        while directory != 0 {
+            let strip = read_u32(&merged, directory + 2 + 8);
+            pixels.push(merged[strip]);
+            directory = read_u32(&merged, directory + 2 + 2 * 12);
+        }
+
+        assert_eq!(pixels, [1, 2, 3]);
+    }
+
+    /// Tests invalid files are rejected
+    #[test]
+    fn test_merge_invalid() {
+        assert!(merge_pages(&[]).is_err());
+        assert!(merge_pages(&[b"not a tiff".to_vec()]).is_err());
+
+        let mut big_endian = single_page(1);
+        big_endian[0..2].copy_from_slice(b"MM");
+        assert!(merge_pages(&[single_page(1), big_endian]).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+//! Running the poppler tools directly for options this crate does not cover
+//!
+//! * [run_poppler_tool] - Runs one of the poppler tools with the provided arguments
+//!
+//! The tools are run the same way as the rest of the crate, passwords are
+//! passed separately from the other arguments, the timeouts, output limits
+//! and budgets apply and failures are classified from the exit code and
+//! stderr output (e.g. encrypted files and incorrect passwords)
+
+use std::{ffi::OsStr, fmt::Display, time::Duration};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    budget::{Budget, BudgetExceeded},
+    info::PermissionDenied,
+    shared::{run_tool, ChildError, Password, ProcessPriority, RunOptions, StderrSink, ToolError},
+};
+
+/// Poppler command line tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum PopplerTool {
+    PdfAttach,
+    PdfDetach,
+    PdfFonts,
+    PdfImages,
+    PdfInfo,
+    PdfSeparate,
+    PdfSig,
+    PdfToCairo,
+    PdfToHtml,
+    PdfToPpm,
+    PdfToPs,
+    PdfToText,
+    PdfUnite,
+}
+
+impl PopplerTool {
+    /// Name of the program for the tool, resolved using the
+    /// [ToolPaths](crate::shared::ToolPaths) when configured
+    pub fn program(&self) -> &'static str {
+        match self {
+            Self::PdfAttach => "pdfattach",
+            Self::PdfDetach => "pdfdetach",
+            Self::PdfFonts => "pdffonts",
+            Self::PdfImages => "pdfimages",
+            Self::PdfInfo => "pdfinfo",
+            Self::PdfSeparate => "pdfseparate",
+            Self::PdfSig => "pdfsig",
+            Self::PdfToCairo => "pdftocairo",
+            Self::PdfToHtml => "pdftohtml",
+            Self::PdfToPpm => "pdftoppm",
+            Self::PdfToPs => "pdftops",
+            Self::PdfToText => "pdftotext",
+            Self::PdfUnite => "pdfunite",
+        }
+    }
+}
+
+impl Display for PopplerTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.program())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PopplerToolError {
+    #[error("failed to spawn poppler tool: {0}")]
+    SpawnProcess(std::io::Error),
+
+    #[error("failed to write input bytes: {0}")]
+    WriteInput(std::io::Error),
+
+    #[error("failed to get output: {0}")]
+    WaitOutput(std::io::Error),
+
+    #[error("output exceeded the maximum allowed size of {0} bytes")]
+    OutputLimitExceeded(usize),
+
+    #[error("poppler tool did not finish within {0:?}")]
+    Timeout(Duration),
+
+    #[error("poppler tool was aborted")]
+    Aborted,
+
+    #[error(transparent)]
+    BudgetExceeded(BudgetExceeded),
+
+    #[error("poppler tool failed: {0}")]
+    ToolFailure(String),
+
+    #[error("poppler tool reported permission error: {0}")]
+    PermissionError(PermissionDenied),
+
+    #[error("pdf is encrypted and no password was provided")]
+    PdfEncrypted,
+
+    #[error("incorrect password was provided")]
+    IncorrectPassword,
+
+    #[error("file is not a pdf")]
+    NotPdfFile,
+}
+
+impl From<ChildError> for PopplerToolError {
+    fn from(value: ChildError) -> Self {
+        match value {
+            ChildError::WriteInput(err) => Self::WriteInput(err),
+            ChildError::ReadOutput(err) | ChildError::WriteOutput(err) => Self::WaitOutput(err),
+            ChildError::OutputLimitExceeded(limit) => Self::OutputLimitExceeded(limit),
+            ChildError::Timeout(timeout) => Self::Timeout(timeout),
+        }
+    }
+}
+
+impl From<ToolError> for PopplerToolError {
+    fn from(value: ToolError) -> Self {
+        match value {
+            ToolError::Spawn(err) => Self::SpawnProcess(err),
+            ToolError::Child(err) => err.into(),
+            ToolError::NotPdfFile => Self::NotPdfFile,
+            ToolError::PdfEncrypted => Self::PdfEncrypted,
+            ToolError::IncorrectPassword => Self::IncorrectPassword,
+            ToolError::PermissionError(err) => Self::PermissionError(err),
+            ToolError::PageOutOfBounds(page, page_count) => Self::ToolFailure(format!(
+                "page {page} is outside the number of available pages {page_count}"
+            )),
+            ToolError::PageCountUnknown => {
+                Self::ToolFailure("page count is missing or invalid".to_string())
+            }
+            ToolError::EmptyDocument => {
+                Self::ToolFailure("pdf does not contain any pages".to_string())
+            }
+            ToolError::Failure(err) | ToolError::Task(err) => Self::ToolFailure(err),
+            ToolError::Aborted => Self::Aborted,
+            ToolError::BudgetExceeded(err) => Self::BudgetExceeded(err),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PopplerToolArgs {
+    /// Password for the PDF, passed to the tool using `-opw` or `-upw`
+    pub password: Option<Password>,
+    /// Optional maximum size in bytes for stdout, the tool is
+    /// stopped once this is exceeded
+    pub max_output_size: Option<usize>,
+    /// Optional maximum time the tool may run for
+    pub timeout: Option<Duration>,
+    /// Optional destination for the stderr instead of keeping it in memory
+    pub stderr: Option<StderrSink>,
+    /// Optional lowered CPU and IO priority to run the tool at
+    pub priority: Option<ProcessPriority>,
+    /// Optional limits shared with any other args the budget is set on
+    pub budget: Option<Budget>,
+}
+
+impl PopplerToolArgs {
+    pub fn set_password(mut self, password: Password) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    pub fn set_max_output_size(mut self, max_output_size: usize) -> Self {
+        self.max_output_size = Some(max_output_size);
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_stderr(mut self, stderr: StderrSink) -> Self {
+        self.stderr = Some(stderr);
+        self
+    }
+
+    pub fn set_priority(mut self, priority: ProcessPriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn set_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Options for running the tool
+    pub(crate) fn run_options(&self) -> RunOptions {
+        RunOptions {
+            max_output_size: self.max_output_size,
+            timeout: self.timeout,
+            password: self.password.clone(),
+            stderr: self.stderr.clone(),
+            priority: self.priority,
+            budget: self.budget.clone(),
+        }
+    }
+}
+
+/// Output of a poppler tool that finished successfully
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolOutput {
+    /// Bytes written to stdout
+    pub stdout: Vec<u8>,
+    /// Bytes written to stderr, only the start of stderr is kept
+    /// when [PopplerToolArgs::stderr] is provided
+    pub stderr: Vec<u8>,
+}
+
+/// Runs a poppler tool with the provided arguments writing the input to
+/// its stdin, use "-" as the file argument for tools that read the PDF
+/// from stdin. Tools that exit unsuccessfully are reported as errors
+///
+/// ## Arguments
+/// * tool - The poppler tool to run
+/// * args - Arguments for the tool, not including the password
+/// * stdin - The bytes to write to stdin
+/// * options - Options for running the tool
+pub async fn run_poppler_tool<S>(
+    tool: PopplerTool,
+    args: &[S],
+    stdin: &[u8],
+    options: &PopplerToolArgs,
+) -> Result<ToolOutput, PopplerToolError>
+where
+    S: AsRef<OsStr>,
+{
+    let output = run_tool(tool.program(), args, stdin, &options.run_options()).await?;
+
+    Ok(ToolOutput {
+        stdout: output.stdout,
+        stderr: output.stderr,
+    })
+}
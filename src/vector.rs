@@ -0,0 +1,485 @@
+//! Helpers for rendering PDF pages to vector formats using `pdftocairo`
+//!
+//! * [render_page_svg] - Render a single page as SVG
+//! * [render_pages_svg] - Render multiple pages as SVG
+//! * [render_to_pdf] - Render a range of pages to a new PDF
+//! * [render_to_ps] - Render a range of pages to PostScript
+//! * [render_to_eps] - Render a single page to Encapsulated PostScript
+//!
+//! Printing related options for PostScript and PDF output are set using
+//! [PrintOptions] and the paper size and fitting using [PaperOptions]
+//! on the [RenderArgs]
+
+use std::ops::RangeInclusive;
+
+use crate::{
+    image::{
+        verify_all_pages, verify_pages, PageArgs, PageColor, PdfRenderError, RenderArgs,
+        RenderArgsError, RenderBackend, RenderColor,
+    },
+    info::PdfInfo,
+    shared::{for_each_page, ToolJob},
+};
+
+/// Print options for PostScript and PDF output
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PrintOptions {
+    /// Whether to request duplex printing, PostScript output only
+    pub duplex: bool,
+}
+
+impl PrintOptions {
+    pub fn set_duplex(mut self, duplex: bool) -> Self {
+        self.duplex = duplex;
+        self
+    }
+
+    /// Checks the options can be used with the provided pdftocairo
+    /// output format argument
+    fn validate(&self, format_arg: &str) -> Result<(), RenderArgsError> {
+        if !matches!(format_arg, "-ps" | "-pdf") {
+            return Err(unsupported("print options", format_arg));
+        }
+
+        if self.duplex && format_arg != "-ps" {
+            return Err(RenderArgsError::DuplexFormat);
+        }
+
+        Ok(())
+    }
+
+    pub fn push_arg(&self, args: &mut Vec<String>) {
+        if self.duplex {
+            args.push("-duplex".to_string());
+        }
+    }
+}
+
+/// Paper size to place the pages on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaperSize {
+    /// US Letter (612x792 points)
+    Letter,
+    /// US Legal (612x1008 points)
+    Legal,
+    /// ISO A4 (595x842 points)
+    A4,
+    /// ISO A3 (842x1190 points)
+    A3,
+    /// Use the size of the first page
+    Match,
+    /// Custom width and height in PDF points
+    Custom { width: u32, height: u32 },
+}
+
+impl PaperSize {
+    pub fn push_arg(&self, args: &mut Vec<String>) {
+        let name = match self {
+            Self::Letter => "letter",
+            Self::Legal => "legal",
+            Self::A4 => "A4",
+            Self::A3 => "A3",
+            Self::Match => "match",
+            Self::Custom { width, height } => {
+                args.push("-paperw".to_string());
+                args.push(width.to_string());
+
+                args.push("-paperh".to_string());
+                args.push(height.to_string());
+                return;
+            }
+        };
+
+        args.push("-paper".to_string());
+        args.push(name.to_string());
+    }
+}
+
+/// Paper size and fitting options for PostScript, PDF and SVG output
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PaperOptions {
+    /// Paper size to use, defaults to the size of the first page when [None]
+    pub paper: Option<PaperSize>,
+    /// Whether to use the size of each page as the paper size instead of
+    /// a single paper size for the whole document
+    pub original_page_sizes: bool,
+    /// Whether to expand pages smaller than the paper to fill the paper
+    pub expand: bool,
+    /// Whether to keep pages larger than the paper at their size instead
+    /// of shrinking them to fit the paper
+    pub no_shrink: bool,
+    /// Whether to place pages at the origin of the paper instead of
+    /// centering pages smaller than the paper
+    pub no_center: bool,
+}
+
+impl PaperOptions {
+    pub fn set_paper(mut self, paper: PaperSize) -> Self {
+        self.paper = Some(paper);
+        self
+    }
+
+    pub fn set_original_page_sizes(mut self, original_page_sizes: bool) -> Self {
+        self.original_page_sizes = original_page_sizes;
+        self
+    }
+
+    pub fn set_expand(mut self, expand: bool) -> Self {
+        self.expand = expand;
+        self
+    }
+
+    pub fn set_no_shrink(mut self, no_shrink: bool) -> Self {
+        self.no_shrink = no_shrink;
+        self
+    }
+
+    pub fn set_no_center(mut self, no_center: bool) -> Self {
+        self.no_center = no_center;
+        self
+    }
+
+    /// Checks the options can be used with the provided pdftocairo
+    /// output format argument
+    fn validate(&self, format_arg: &str) -> Result<(), RenderArgsError> {
+        if !matches!(format_arg, "-ps" | "-pdf" | "-svg") {
+            return Err(unsupported("paper options", format_arg));
+        }
+
+        match self.paper {
+            Some(PaperSize::Custom { width, height }) if width == 0 || height == 0 => {
+                Err(RenderArgsError::PaperSize)
+            }
+            Some(_) if self.original_page_sizes => Err(RenderArgsError::PaperSizeWithOriginal),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn push_arg(&self, args: &mut Vec<String>) {
+        if let Some(paper) = self.paper.as_ref() {
+            paper.push_arg(args);
+        }
+
+        if self.original_page_sizes {
+            args.push("-origpagesizes".to_string());
+        }
+
+        if self.expand {
+            args.push("-expand".to_string());
+        }
+
+        if self.no_shrink {
+            args.push("-noshrink".to_string());
+        }
+
+        if self.no_center {
+            args.push("-nocenter".to_string());
+        }
+    }
+}
+
+/// Renders a single page from a PDF file as SVG
+///
+/// The resolution only applies to content that cairo has to rasterize
+/// and the JPEG options are not supported
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * page - The page to render
+/// * args - Optional args to pdftocairo
+pub async fn render_page_svg(
+    data: &[u8],
+    info: &PdfInfo,
+    page: u32,
+    args: &RenderArgs,
+) -> Result<String, PdfRenderError> {
+    let mut pages = render_pages_svg(data, info, vec![page], args).await?;
+    Ok(pages.remove(0))
+}
+
+/// Renders all the provided pages as SVG in parallel
+///
+/// See [render_page_svg]
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * pages - The list of page numbers to render
+/// * args - Optional args to pdftocairo
+pub async fn render_pages_svg(
+    data: &[u8],
+    info: &PdfInfo,
+    pages: Vec<u32>,
+    args: &RenderArgs,
+) -> Result<Vec<String>, PdfRenderError> {
+    validate_vector_args(args, "-svg")?;
+    verify_pages(data, info, args, &pages).await?;
+    let page_args = PageArgs::new(data, Some(info), args, &pages).await?;
+
+    // Render all the pages individually
+    for_each_page(
+        data,
+        pages,
+        args.dedup_pages,
+        args.fan_out,
+        |page| vector_job("-svg", page, page, &page_args.args(page)),
+        |output| {
+            String::from_utf8(output).map_err(|err| {
+                PdfRenderError::PdfRenderFailure(format!("svg output is not valid utf8: {err}"))
+            })
+        },
+    )
+    .await
+}
+
+/// Renders a range of pages to a new PDF file providing the file bytes,
+/// the output is not encrypted so this can be used to re-export a
+/// decrypted copy or a subset of the pages
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * pages - The range of pages to include, [None] for all pages
+/// * args - Optional args to pdftocairo
+pub async fn render_to_pdf(
+    data: &[u8],
+    info: &PdfInfo,
+    pages: Option<RangeInclusive<u32>>,
+    args: &RenderArgs,
+) -> Result<Vec<u8>, PdfRenderError> {
+    render_document(data, info, "-pdf", pages, args).await
+}
+
+/// Renders a range of pages to a PostScript file providing the file bytes
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * pages - The range of pages to include, [None] for all pages
+/// * args - Optional args to pdftocairo
+pub async fn render_to_ps(
+    data: &[u8],
+    info: &PdfInfo,
+    pages: Option<RangeInclusive<u32>>,
+    args: &RenderArgs,
+) -> Result<Vec<u8>, PdfRenderError> {
+    render_document(data, info, "-ps", pages, args).await
+}
+
+/// Renders a single page to an Encapsulated PostScript file providing
+/// the file bytes, EPS files can only contain one page
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * info - The PDF info to use for the page count and encryption state
+/// * page - The page to render
+/// * args - Optional args to pdftocairo
+pub async fn render_to_eps(
+    data: &[u8],
+    info: &PdfInfo,
+    page: u32,
+    args: &RenderArgs,
+) -> Result<Vec<u8>, PdfRenderError> {
+    render_document(data, info, "-eps", Some(page..=page), args).await
+}
+
+/// Renders a range of pages to a single output file
+async fn render_document(
+    data: &[u8],
+    info: &PdfInfo,
+    format_arg: &str,
+    pages: Option<RangeInclusive<u32>>,
+    args: &RenderArgs,
+) -> Result<Vec<u8>, PdfRenderError> {
+    validate_vector_args(args, format_arg)?;
+
+    let (first_page, last_page) = match pages {
+        Some(pages) => {
+            let (first_page, last_page) = pages.into_inner();
+            if first_page > last_page {
+                return Err(RenderArgsError::PageRange(first_page, last_page).into());
+            }
+
+            verify_pages(data, info, args, &[first_page, last_page]).await?;
+            (first_page, last_page)
+        }
+        None => (1, verify_all_pages(data, info, args).await?),
+    };
+
+    // Relative args can only be resolved against a single page size
+    if args.is_relative() && first_page != last_page {
+        return Err(RenderArgsError::RelativeMultiplePages.into());
+    }
+
+    let page_args = PageArgs::new(data, Some(info), args, &[first_page]).await?;
+    let job = vector_job(
+        format_arg,
+        first_page,
+        last_page,
+        &page_args.args(first_page),
+    );
+
+    Ok(job.run(data).await?)
+}
+
+/// Checks the args can be used with the provided pdftocairo
+/// vector output format argument
+pub(crate) fn validate_vector_args(
+    args: &RenderArgs,
+    format_arg: &str,
+) -> Result<(), RenderArgsError> {
+    if matches!(args.page_color, Some(PageColor::Transparent)) {
+        return Err(unsupported("transparent page color", format_arg));
+    }
+
+    if matches!(args.render_color, Some(RenderColor::Monochrome)) {
+        return Err(unsupported("monochrome render color", format_arg));
+    }
+
+    if args.jpeg_options.is_some() {
+        return Err(unsupported("jpeg options", format_arg));
+    }
+
+    if args.tiff_compression.is_some() {
+        return Err(unsupported("tiff compression", format_arg));
+    }
+
+    #[cfg(feature = "avif")]
+    if args.avif_options.is_some() {
+        return Err(unsupported("avif options", format_arg));
+    }
+
+    if args.backend != RenderBackend::Cairo {
+        return Err(RenderArgsError::UnsupportedBackend {
+            option: "vector output",
+            backend: args.backend.program(),
+        });
+    }
+
+    if args.splash_options.is_some() {
+        return Err(unsupported("splash options", format_arg));
+    }
+
+    if let Some(print_options) = args.print_options.as_ref() {
+        print_options.validate(format_arg)?;
+    }
+
+    if let Some(paper_options) = args.paper_options.as_ref() {
+        paper_options.validate(format_arg)?;
+    }
+
+    args.validate_scale()
+}
+
+/// Error for an option that cannot be used with the provided pdftocairo
+/// vector output format argument
+fn unsupported(option: &'static str, format_arg: &str) -> RenderArgsError {
+    RenderArgsError::UnsupportedFormat {
+        option,
+        format: format_arg.trim_start_matches('-').to_string(),
+    }
+}
+
+/// Creates the `pdftocairo` job for rendering the provided range
+/// of pages to a vector format
+pub(crate) fn vector_job(
+    format_arg: &str,
+    first_page: u32,
+    last_page: u32,
+    args: &RenderArgs,
+) -> ToolJob {
+    // Take input from stdin and provide to stdout
+    let mut cli_args = vec!["-".to_string(), "-".to_string()];
+
+    // Specify first and last pages
+    cli_args.extend([
+        "-f".to_string(),
+        first_page.to_string(),
+        "-l".to_string(),
+        last_page.to_string(),
+    ]);
+
+    // Add optional args and output format
+    cli_args.extend(args.build_args());
+    cli_args.push(format_arg.to_string());
+
+    ToolJob {
+        program: "pdftocairo",
+        args: cli_args,
+        options: args.run_options(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{validate_vector_args, vector_job, PaperOptions, PaperSize, PrintOptions};
+    use crate::image::{JpegOptions, RenderArgs, RenderBackend};
+
+    /// Tests the vector job arguments
+    #[test]
+    fn test_vector_job() {
+        let job = vector_job("-svg", 3, 3, &RenderArgs::default());
+        assert_eq!(job.program, "pdftocairo");
+        assert_eq!(job.args, ["-", "-", "-f", "3", "-l", "3", "-svg"]);
+
+        let job = vector_job("-pdf", 2, 5, &RenderArgs::default());
+        assert_eq!(job.args, ["-", "-", "-f", "2", "-l", "5", "-pdf"]);
+
+        let args = RenderArgs::default().set_jpeg_options(JpegOptions::default());
+        assert!(validate_vector_args(&args, "-svg").is_err());
+
+        let args = RenderArgs::default().set_backend(RenderBackend::Splash);
+        assert!(validate_vector_args(&args, "-pdf").is_err());
+    }
+
+    /// Tests the print options arguments and validation
+    #[test]
+    fn test_print_options() {
+        let args =
+            RenderArgs::default().set_print_options(PrintOptions::default().set_duplex(true));
+
+        assert_eq!(args.build_args(), ["-duplex"]);
+        assert!(validate_vector_args(&args, "-ps").is_ok());
+        assert!(validate_vector_args(&args, "-pdf").is_err());
+        assert!(validate_vector_args(&args, "-svg").is_err());
+        assert!(args.validate(crate::image::OutputFormat::Png).is_err());
+    }
+
+    /// Tests the paper options arguments and validation
+    #[test]
+    fn test_paper_options() {
+        let options = PaperOptions::default()
+            .set_paper(PaperSize::A4)
+            .set_expand(true)
+            .set_no_center(true);
+        let args = RenderArgs::default().set_paper_options(options);
+
+        assert_eq!(args.build_args(), ["-paper", "A4", "-expand", "-nocenter"]);
+        assert!(validate_vector_args(&args, "-svg").is_ok());
+        assert!(validate_vector_args(&args, "-eps").is_err());
+        assert!(args.validate(crate::image::OutputFormat::Png).is_err());
+
+        let options = PaperOptions::default()
+            .set_paper(PaperSize::Custom {
+                width: 595,
+                height: 842,
+            })
+            .set_no_shrink(true);
+        let args = RenderArgs::default().set_paper_options(options);
+        assert_eq!(
+            args.build_args(),
+            ["-paperw", "595", "-paperh", "842", "-noshrink"]
+        );
+
+        let args = RenderArgs::default().set_paper_options(options.set_original_page_sizes(true));
+        assert!(validate_vector_args(&args, "-pdf").is_err());
+
+        let options = PaperOptions::default().set_paper(PaperSize::Custom {
+            width: 0,
+            height: 842,
+        });
+        let args = RenderArgs::default().set_paper_options(options);
+        assert!(validate_vector_args(&args, "-pdf").is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! Helpers for the version of the installed poppler tools
+//!
+//! * [poppler_version] - Get the version of the installed poppler tools
+//! * [render_provenance] - Record the poppler version and render parameters
+//! * [text_provenance] - Record the poppler version and text extraction method
+
+use std::{fmt::Display, time::Duration};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::OnceCell;
+
+use crate::{
+    image::{OutputFormat, RenderArgs},
+    shared::{fnv1a_64, run_tool, ChildError, RunOptions, ToolError},
+    styled::XML_ARGS,
+    text::PdfTextArgs,
+};
+
+/// Maximum time reading the version may take
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Version of the installed poppler tools
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PopplerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl PopplerVersion {
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses the version from the output of `pdftocairo -v`
+    /// e.g "pdftocairo version 22.02.0"
+    fn parse(output: &str) -> Option<Self> {
+        let version = output
+            .lines()
+            .find_map(|line| line.split_once(" version "))?
+            .1
+            .trim();
+
+        let mut parts = version.split('.').map(|value| value.parse::<u32>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl Display for PopplerVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:02}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PopplerVersionError {
+    #[error("failed to spawn pdftocairo: {0}")]
+    SpawnProcess(std::io::Error),
+
+    #[error("failed to get output: {0}")]
+    WaitOutput(std::io::Error),
+
+    #[error("pdftocairo did not finish within {0:?}")]
+    Timeout(Duration),
+
+    #[error("failed to get pdftocairo version: {0}")]
+    PopplerFailure(String),
+
+    #[error("version is malformed: {0}")]
+    MalformedVersion(String),
+}
+
+impl From<ToolError> for PopplerVersionError {
+    fn from(value: ToolError) -> Self {
+        match value {
+            ToolError::Spawn(err) => Self::SpawnProcess(err),
+            ToolError::Child(
+                ChildError::WriteInput(err)
+                | ChildError::ReadOutput(err)
+                | ChildError::WriteOutput(err),
+            ) => Self::WaitOutput(err),
+            ToolError::Child(ChildError::Timeout(timeout)) => Self::Timeout(timeout),
+            ToolError::Child(ChildError::OutputLimitExceeded(limit)) => Self::PopplerFailure(
+                format!("output exceeded the maximum allowed size of {limit} bytes"),
+            ),
+            ToolError::PermissionError(err) => Self::PopplerFailure(err.to_string()),
+            ToolError::Failure(err) | ToolError::Task(err) => Self::PopplerFailure(err),
+            err => Self::PopplerFailure(format!("{err:?}")),
+        }
+    }
+}
+
+/// Version of the installed poppler, read once per process
+static POPPLER_VERSION: OnceCell<PopplerVersion> = OnceCell::const_new();
+
+/// Gets the version of the installed poppler tools using `pdftocairo -v`,
+/// the version is read once and reused for the rest of the process.
+/// Failures are not cached
+pub async fn poppler_version() -> Result<PopplerVersion, PopplerVersionError> {
+    POPPLER_VERSION
+        .get_or_try_init(|| async {
+            let options = RunOptions {
+                timeout: Some(VERSION_TIMEOUT),
+                ..Default::default()
+            };
+
+            let output = run_tool("pdftocairo", &["-v"], &[], &options).await?;
+
+            // Older versions print the version to stderr
+            let value = String::from_utf8_lossy(&output.stderr);
+            let value = format!("{value}\n{}", String::from_utf8_lossy(&output.stdout));
+
+            PopplerVersion::parse(&value)
+                .ok_or_else(|| PopplerVersionError::MalformedVersion(value.trim().to_string()))
+        })
+        .await
+        .copied()
+}
+
+/// Poppler version and render parameters used for a render. Renders differ
+/// subtly between poppler versions so this can be stored alongside cached
+/// renders and compared to invalidate them after poppler is upgraded
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RenderProvenance {
+    /// Name of the poppler tool used for the render
+    pub tool: &'static str,
+    /// Version of poppler used for the render
+    pub poppler_version: PopplerVersion,
+    /// Arguments provided to pdftocairo, excluding the page
+    /// selection and password
+    pub args: Vec<String>,
+}
+
+impl RenderProvenance {
+    pub fn new(poppler_version: PopplerVersion, format: OutputFormat, args: &RenderArgs) -> Self {
+        let mut cli_args = Vec::new();
+        format.push_arg(&mut cli_args);
+        cli_args.extend(args.build_args());
+
+        Self {
+            tool: args.backend.program(),
+            poppler_version,
+            args: cli_args,
+        }
+    }
+
+    /// Stable key for the version and parameters, renders with the same
+    /// key are expected to produce the same output
+    pub fn cache_key(&self) -> String {
+        let value = format!(
+            "{}\0{}\0{}",
+            self.tool,
+            self.poppler_version,
+            self.args.join("\0")
+        );
+        format!("{:016x}", fnv1a_64(value.as_bytes()))
+    }
+}
+
+/// Records the installed poppler version along with the render parameters
+///
+/// ## Arguments
+/// * format - The output format used for the render
+/// * args - The args used for the render
+pub async fn render_provenance(
+    format: OutputFormat,
+    args: &RenderArgs,
+) -> Result<RenderProvenance, PopplerVersionError> {
+    let poppler_version = poppler_version().await?;
+    Ok(RenderProvenance::new(poppler_version, format, args))
+}
+
+/// Method used to extract text from a PDF, the reading order and
+/// reliability of the text differs between methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum ExtractionMethod {
+    /// pdftotext in its default reading order
+    ReadingOrder,
+    /// pdftotext keeping the physical layout of the text (`-layout`)
+    Layout,
+    /// pdftotext in the order the text appears in the content stream (`-raw`)
+    ContentStream,
+    /// pdftohtml XML output of positioned and styled runs
+    Styled,
+}
+
+impl ExtractionMethod {
+    /// Name of the poppler tool used for the method
+    pub fn tool(&self) -> &'static str {
+        match self {
+            Self::ReadingOrder | Self::Layout | Self::ContentStream => "pdftotext",
+            Self::Styled => "pdftohtml",
+        }
+    }
+
+    /// Method used by pdftotext with the provided args, `-raw`
+    /// takes priority when both `-layout` and `-raw` are set
+    pub fn for_text_args(args: &PdfTextArgs) -> Self {
+        if args.raw {
+            Self::ContentStream
+        } else if args.layout {
+            Self::Layout
+        } else {
+            Self::ReadingOrder
+        }
+    }
+}
+
+/// Extraction method and poppler version used for extracted text. Stored
+/// alongside the text so consumers combining several sources (e.g. OCR)
+/// can weigh the text by how it was produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TextProvenance {
+    /// Method used to extract the text
+    pub method: ExtractionMethod,
+    /// Name of the poppler tool that extracted the text
+    pub tool: &'static str,
+    /// Version of poppler used to extract the text
+    pub poppler_version: PopplerVersion,
+    /// Arguments provided to the tool, excluding the page
+    /// selection and password
+    pub args: Vec<String>,
+}
+
+impl TextProvenance {
+    /// Provenance of text extracted by pdftotext
+    pub fn new(poppler_version: PopplerVersion, args: &PdfTextArgs) -> Self {
+        let method = ExtractionMethod::for_text_args(args);
+
+        Self {
+            method,
+            tool: method.tool(),
+            poppler_version,
+            args: args.build_args(),
+        }
+    }
+
+    /// Provenance of styled text extracted by pdftohtml
+    pub fn styled(poppler_version: PopplerVersion) -> Self {
+        let method = ExtractionMethod::Styled;
+
+        Self {
+            method,
+            tool: method.tool(),
+            poppler_version,
+            args: XML_ARGS.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+}
+
+/// Records the installed poppler version along with the method pdftotext
+/// extracts text with for the provided args
+///
+/// ## Arguments
+/// * args - The args used to extract the text
+pub async fn text_provenance(args: &PdfTextArgs) -> Result<TextProvenance, PopplerVersionError> {
+    let poppler_version = poppler_version().await?;
+    Ok(TextProvenance::new(poppler_version, args))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ExtractionMethod, PopplerVersion, RenderProvenance, TextProvenance};
+    use crate::{
+        image::{OutputFormat, RenderArgs, RenderBackend, Resolution},
+        text::PdfTextArgs,
+    };
+
+    /// Tests parsing the version output
+    #[test]
+    fn test_parse_version() {
+        let output = "pdftocairo version 22.02.0\nCopyright 2005-2022 The Poppler Developers";
+        assert_eq!(
+            PopplerVersion::parse(output),
+            Some(PopplerVersion::new(22, 2, 0))
+        );
+        assert_eq!(PopplerVersion::new(22, 2, 0).to_string(), "22.02.0");
+
+        assert_eq!(
+            PopplerVersion::parse("pdftocairo version 0.86"),
+            Some(PopplerVersion::new(0, 86, 0))
+        );
+        assert_eq!(PopplerVersion::parse("pdftocairo version x"), None);
+        assert_eq!(PopplerVersion::parse(""), None);
+
+        assert!(PopplerVersion::new(24, 1, 0) > PopplerVersion::new(22, 12, 0));
+    }
+
+    /// Tests the cache key changes with the version and parameters
+    #[test]
+    fn test_provenance_cache_key() {
+        let args = RenderArgs::default();
+        let old = RenderProvenance::new(PopplerVersion::new(22, 2, 0), OutputFormat::Png, &args);
+        let new = RenderProvenance::new(PopplerVersion::new(24, 2, 0), OutputFormat::Png, &args);
+        assert_eq!(old.args[0], "-png");
+        assert_ne!(old.cache_key(), new.cache_key());
+        assert_eq!(old.cache_key(), old.clone().cache_key());
+
+        let args = args.set_resolution(Resolution::uniform(150));
+        let changed =
+            RenderProvenance::new(PopplerVersion::new(22, 2, 0), OutputFormat::Png, &args);
+        assert_ne!(old.cache_key(), changed.cache_key());
+
+        let args = args.set_backend(RenderBackend::Splash);
+        let splash = RenderProvenance::new(PopplerVersion::new(22, 2, 0), OutputFormat::Png, &args);
+        assert_eq!(splash.tool, "pdftoppm");
+        assert_ne!(changed.cache_key(), splash.cache_key());
+    }
+
+    /// Tests the extraction method follows the text args
+    #[test]
+    fn test_text_provenance() {
+        let version = PopplerVersion::new(22, 2, 0);
+
+        let plain = TextProvenance::new(version, &PdfTextArgs::default());
+        assert_eq!(plain.method, ExtractionMethod::ReadingOrder);
+        assert_eq!(plain.tool, "pdftotext");
+        assert!(plain.args.is_empty());
+
+        let raw = TextProvenance::new(version, &PdfTextArgs::default().set_raw(true));
+        assert_eq!(raw.method, ExtractionMethod::ContentStream);
+        assert_eq!(raw.args, ["-raw"]);
+
+        let layout = TextProvenance::new(version, &PdfTextArgs::default().set_layout(true));
+        assert_eq!(layout.method, ExtractionMethod::Layout);
+
+        let both = PdfTextArgs::default().set_layout(true).set_raw(true);
+        let both = TextProvenance::new(version, &both);
+        assert_eq!(both.method, ExtractionMethod::ContentStream);
+
+        let styled = TextProvenance::styled(version);
+        assert_eq!(styled.method, ExtractionMethod::Styled);
+        assert_eq!(styled.tool, "pdftohtml");
+        assert_eq!(styled.args[0], "-xml");
+    }
+}
@@ -0,0 +1,560 @@
+//! Helpers for extracting the words of the text layer along with their location
+//!
+//! * [text_with_boxes] - Gets the words of each page with their bounding boxes
+//! * [text_with_layout] - Gets the words of each page grouped into flows, blocks and lines
+//! * [text_tsv] - Gets the page, block, line and word records from the TSV output
+//!
+//! The bounding boxes are in PDF points with the origin at the top left of
+//! the page, multiply by `resolution / 72` to get the pixel coordinates on a
+//! page rendered at that resolution (e.g. for highlighting search hits)
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+    redaction::BoundingBox,
+    shared::unescape_xml,
+    text::{bbox_text, PdfTextArgs, PdfTextError},
+};
+
+/// Page from the text layer along with its words
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WordPage {
+    /// Width of the page in PDF points
+    pub width: f32,
+    /// Height of the page in PDF points
+    pub height: f32,
+    /// Words on the page in reading order
+    pub words: Vec<Word>,
+}
+
+/// Word from the text layer along with its location on the page
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Word {
+    pub text: String,
+    pub x_min: f32,
+    pub y_min: f32,
+    pub x_max: f32,
+    pub y_max: f32,
+}
+
+impl Word {
+    /// Bounding box of the word
+    pub fn bbox(&self) -> BoundingBox {
+        BoundingBox {
+            x_min: self.x_min,
+            y_min: self.y_min,
+            x_max: self.x_max,
+            y_max: self.y_max,
+        }
+    }
+}
+
+/// Extracts the words from all the pages in the provided PDF along with
+/// their bounding boxes using `pdftotext -bbox`. Provides one [WordPage]
+/// for each page in order.
+///
+/// The [PdfTextArgs::normalization] is applied to the text of each word,
+/// the layout and bidi options do not apply to the words
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Optional args for the pdf to text
+pub async fn text_with_boxes(
+    data: &[u8],
+    args: &PdfTextArgs,
+) -> Result<Vec<WordPage>, PdfTextError> {
+    let output = bbox_text(data, args, "-bbox").await?;
+    let mut pages = parse_bbox_pages(&output);
+
+    if let Some(normalization) = args.normalization.as_ref() {
+        for word in pages.iter_mut().flat_map(|page| page.words.iter_mut()) {
+            word.text = normalization.apply(&word.text);
+        }
+    }
+
+    Ok(pages)
+}
+
+/// Extracts the words from all the pages in the provided PDF grouped
+/// into flows, blocks and lines using `pdftotext -bbox-layout`. Provides
+/// one [LayoutPage] for each page in order.
+///
+/// The [PdfTextArgs::normalization] is applied to the text of each word,
+/// the layout and bidi options do not apply to the words
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Optional args for the pdf to text
+pub async fn text_with_layout(
+    data: &[u8],
+    args: &PdfTextArgs,
+) -> Result<Vec<LayoutPage>, PdfTextError> {
+    let output = bbox_text(data, args, "-bbox-layout").await?;
+    let mut pages = parse_layout_pages(&output);
+
+    if let Some(normalization) = args.normalization.as_ref() {
+        for word in pages
+            .iter_mut()
+            .flat_map(|page| page.flows.iter_mut())
+            .flat_map(|flow| flow.blocks.iter_mut())
+            .flat_map(|block| block.lines.iter_mut())
+            .flat_map(|line| line.words.iter_mut())
+        {
+            word.text = normalization.apply(&word.text);
+        }
+    }
+
+    Ok(pages)
+}
+
+/// Extracts the records for every page, block, line and word in the
+/// provided PDF using `pdftotext -tsv`. Records are provided in the order
+/// they are output, each page record is followed by the records on the page
+///
+/// The [PdfTextArgs::normalization] is applied to the text of each word
+///
+/// ## Arguments
+/// * data - The raw PDF file bytes
+/// * args - Optional args for the pdf to text
+pub async fn text_tsv(data: &[u8], args: &PdfTextArgs) -> Result<Vec<TsvRecord>, PdfTextError> {
+    let output = bbox_text(data, args, "-tsv").await?;
+    let mut records = parse_tsv_records(&output);
+
+    if let Some(normalization) = args.normalization.as_ref() {
+        for record in records
+            .iter_mut()
+            .filter(|record| record.level == TsvLevel::Word)
+        {
+            record.text = normalization.apply(&record.text);
+        }
+    }
+
+    Ok(records)
+}
+
+/// Level of a [TsvRecord], uses the same numbering as the Tesseract TSV format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum TsvLevel {
+    /// Whole page (1)
+    Page,
+    /// Block of text (2)
+    Block,
+    /// Paragraph or flow of text (3)
+    Paragraph,
+    /// Line of text (4)
+    Line,
+    /// Single word (5)
+    Word,
+    /// Level that is not known
+    Unknown(u8),
+}
+
+impl TsvLevel {
+    fn from_number(value: u8) -> Self {
+        match value {
+            1 => Self::Page,
+            2 => Self::Block,
+            3 => Self::Paragraph,
+            4 => Self::Line,
+            5 => Self::Word,
+            value => Self::Unknown(value),
+        }
+    }
+}
+
+/// Row of the `pdftotext -tsv` output, the position is in PDF points
+/// with the origin at the top left of the page
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TsvRecord {
+    pub level: TsvLevel,
+    /// Page number starting at 1
+    pub page: u32,
+    pub paragraph: u32,
+    pub block: u32,
+    pub line: u32,
+    pub word: u32,
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Confidence of the word from 0 to 100, [None] for the records
+    /// that are not words
+    pub confidence: Option<f32>,
+    /// Text of the word, the records that are not words contain
+    /// markers such as `###PAGE###` and `###LINE###`
+    pub text: String,
+}
+
+impl TsvRecord {
+    /// Bounding box of the record
+    pub fn bbox(&self) -> BoundingBox {
+        BoundingBox {
+            x_min: self.left,
+            y_min: self.top,
+            x_max: self.left + self.width,
+            y_max: self.top + self.height,
+        }
+    }
+}
+
+/// Page from the text layer with its words grouped into flows of text
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LayoutPage {
+    /// Width of the page in PDF points
+    pub width: f32,
+    /// Height of the page in PDF points
+    pub height: f32,
+    /// Flows of text on the page in reading order
+    pub flows: Vec<LayoutFlow>,
+}
+
+/// Run of blocks that are read one after another, such as a column
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LayoutFlow {
+    pub blocks: Vec<LayoutBlock>,
+}
+
+/// Block of lines, usually a paragraph
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LayoutBlock {
+    pub bbox: BoundingBox,
+    pub lines: Vec<LayoutLine>,
+}
+
+/// Line of words within a block
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LayoutLine {
+    pub bbox: BoundingBox,
+    pub words: Vec<Word>,
+}
+
+impl LayoutPage {
+    /// Text of the page with a blank line between each block
+    pub fn text(&self) -> String {
+        self.flows
+            .iter()
+            .flat_map(|flow| &flow.blocks)
+            .map(LayoutBlock::text)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+impl LayoutBlock {
+    /// Text of the block with each line on its own line
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(LayoutLine::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl LayoutLine {
+    /// Text of the line with the words separated by spaces
+    pub fn text(&self) -> String {
+        self.words
+            .iter()
+            .map(|word| word.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Parses the pages and their words from the XHTML output of `pdftotext -bbox`
+pub(crate) fn parse_bbox_pages(value: &str) -> Vec<WordPage> {
+    let mut pages: Vec<WordPage> = Vec::new();
+
+    for line in value.lines().map(str::trim) {
+        if let Some(attributes) = line.strip_prefix("<page ") {
+            pages.push(WordPage {
+                width: float_attribute(attributes, "width").unwrap_or_default(),
+                height: float_attribute(attributes, "height").unwrap_or_default(),
+                words: Vec::new(),
+            });
+            continue;
+        }
+
+        if !line.starts_with("<word ") {
+            continue;
+        }
+
+        let (Some(page), Some(word)) = (pages.last_mut(), parse_word(line)) else {
+            continue;
+        };
+
+        page.words.push(word);
+    }
+
+    pages
+}
+
+/// Parses the pages from the XHTML output of `pdftotext -bbox-layout`,
+/// elements outside of their expected parent are skipped
+pub(crate) fn parse_layout_pages(value: &str) -> Vec<LayoutPage> {
+    let mut pages: Vec<LayoutPage> = Vec::new();
+
+    for line in value.lines().map(str::trim) {
+        if let Some(attributes) = line.strip_prefix("<page ") {
+            pages.push(LayoutPage {
+                width: float_attribute(attributes, "width").unwrap_or_default(),
+                height: float_attribute(attributes, "height").unwrap_or_default(),
+                flows: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(page) = pages.last_mut() else {
+            continue;
+        };
+
+        if line.starts_with("<flow") && !line.starts_with("<flow/") {
+            page.flows.push(LayoutFlow::default());
+        } else if let Some(attributes) = line.strip_prefix("<block ") {
+            let (Some(flow), Some(bbox)) = (page.flows.last_mut(), parse_bbox(attributes)) else {
+                continue;
+            };
+
+            flow.blocks.push(LayoutBlock {
+                bbox,
+                lines: Vec::new(),
+            });
+        } else if let Some(attributes) = line.strip_prefix("<line ") {
+            let block = page
+                .flows
+                .last_mut()
+                .and_then(|flow| flow.blocks.last_mut());
+            let (Some(block), Some(bbox)) = (block, parse_bbox(attributes)) else {
+                continue;
+            };
+
+            block.lines.push(LayoutLine {
+                bbox,
+                words: Vec::new(),
+            });
+        } else if line.starts_with("<word ") {
+            let layout_line = page
+                .flows
+                .last_mut()
+                .and_then(|flow| flow.blocks.last_mut())
+                .and_then(|block| block.lines.last_mut());
+            let (Some(layout_line), Some(word)) = (layout_line, parse_word(line)) else {
+                continue;
+            };
+
+            layout_line.words.push(word);
+        }
+    }
+
+    pages
+}
+
+/// Parses the records from the output of `pdftotext -tsv`, the header
+/// and malformed rows are skipped
+pub(crate) fn parse_tsv_records(value: &str) -> Vec<TsvRecord> {
+    value.lines().filter_map(parse_tsv_record).collect()
+}
+
+/// Parses a single row in the format:
+/// `level page_num par_num block_num line_num word_num left top width height conf text`
+fn parse_tsv_record(line: &str) -> Option<TsvRecord> {
+    let columns: Vec<&str> = line.splitn(12, '\t').collect();
+    if columns.len() < 12 {
+        return None;
+    }
+
+    let number = |index: usize| columns[index].trim().parse::<u32>().ok();
+    let float = |index: usize| columns[index].trim().parse::<f32>().ok();
+
+    let level = columns[0].trim().parse::<u8>().ok()?;
+    let confidence = float(10)?;
+
+    Some(TsvRecord {
+        level: TsvLevel::from_number(level),
+        page: number(1)?,
+        paragraph: number(2)?,
+        block: number(3)?,
+        line: number(4)?,
+        word: number(5)?,
+        left: float(6)?,
+        top: float(7)?,
+        width: float(8)?,
+        height: float(9)?,
+        confidence: (confidence >= 0.0).then_some(confidence),
+        text: columns[11].trim_end_matches('\r').to_string(),
+    })
+}
+
+/// Parses the `xMin`, `yMin`, `xMax` and `yMax` attributes
+fn parse_bbox(attributes: &str) -> Option<BoundingBox> {
+    Some(BoundingBox {
+        x_min: float_attribute(attributes, "xMin")?,
+        y_min: float_attribute(attributes, "yMin")?,
+        x_max: float_attribute(attributes, "xMax")?,
+        y_max: float_attribute(attributes, "yMax")?,
+    })
+}
+
+/// Parses a single word element in the format:
+/// `<word xMin="1.0" yMin="2.0" xMax="3.0" yMax="4.0">Text</word>`
+fn parse_word(line: &str) -> Option<Word> {
+    let (attributes, rest) = line.strip_prefix("<word ")?.split_once('>')?;
+    let text = rest.strip_suffix("</word>")?;
+
+    let bbox = parse_bbox(attributes)?;
+
+    Some(Word {
+        text: unescape_xml(text),
+        x_min: bbox.x_min,
+        y_min: bbox.y_min,
+        x_max: bbox.x_max,
+        y_max: bbox.y_max,
+    })
+}
+
+/// Parses the value of a `name="value"` attribute as a float
+fn float_attribute(attributes: &str, name: &str) -> Option<f32> {
+    let start = attributes.find(&format!("{name}=\""))? + name.len() + 2;
+    let length = attributes[start..].find('"')?;
+    attributes[start..start + length].parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_bbox_pages, parse_layout_pages, parse_tsv_records, TsvLevel, Word};
+
+    const BBOX_OUTPUT: &str = r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Transitional//EN" "http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head>
+<title></title>
+</head>
+<body>
+<doc>
+  <page width="612.000000" height="792.000000">
+    <word xMin="56.800000" yMin="57.208000" xMax="80.248000" yMax="70.492000">Secret</word>
+    <word xMin="83.800000" yMin="57.208000" xMax="120.500000" yMax="70.492000">A&amp;B</word>
+  </page>
+  <page width="595.000000" height="842.000000">
+    <word xMin="10.000000" yMin="20.000000" xMax="30.000000" yMax="40.000000">Public</word>
+  </page>
+</doc>
+</body>
+</html>"#;
+
+    /// Tests parsing the pages and words from the bbox output
+    #[test]
+    fn test_parse_bbox_pages() {
+        let pages = parse_bbox_pages(BBOX_OUTPUT);
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].width, 612.0);
+        assert_eq!(pages[0].height, 792.0);
+        assert_eq!(pages[0].words.len(), 2);
+        assert_eq!(
+            pages[0].words[1],
+            Word {
+                text: "A&B".to_string(),
+                x_min: 83.8,
+                y_min: 57.208,
+                x_max: 120.5,
+                y_max: 70.492
+            }
+        );
+
+        assert_eq!(pages[1].width, 595.0);
+        assert_eq!(pages[1].words[0].text, "Public");
+    }
+
+    const LAYOUT_OUTPUT: &str = r#"<body>
+<doc>
+  <page width="612.000000" height="792.000000">
+    <flow>
+      <block xMin="56.800000" yMin="57.208000" xMax="174.800000" yMax="86.000000">
+        <line xMin="56.800000" yMin="57.208000" xMax="120.500000" yMax="70.492000">
+          <word xMin="56.800000" yMin="57.208000" xMax="80.248000" yMax="70.492000">First</word>
+          <word xMin="83.800000" yMin="57.208000" xMax="120.500000" yMax="70.492000">line</word>
+        </line>
+        <line xMin="56.800000" yMin="72.000000" xMax="174.800000" yMax="86.000000">
+          <word xMin="56.800000" yMin="72.000000" xMax="174.800000" yMax="86.000000">Second</word>
+        </line>
+      </block>
+      <block xMin="56.800000" yMin="100.000000" xMax="90.000000" yMax="112.000000">
+        <line xMin="56.800000" yMin="100.000000" xMax="90.000000" yMax="112.000000">
+          <word xMin="56.800000" yMin="100.000000" xMax="90.000000" yMax="112.000000">Next</word>
+        </line>
+      </block>
+    </flow>
+    <flow>
+      <block xMin="300.000000" yMin="57.208000" xMax="340.000000" yMax="70.492000">
+        <line xMin="300.000000" yMin="57.208000" xMax="340.000000" yMax="70.492000">
+          <word xMin="300.000000" yMin="57.208000" xMax="340.000000" yMax="70.492000">Column</word>
+        </line>
+      </block>
+    </flow>
+  </page>
+  <page width="612.000000" height="792.000000">
+  </page>
+</doc>
+</body>"#;
+
+    /// Tests parsing the flows, blocks and lines from the bbox layout output
+    #[test]
+    fn test_parse_layout_pages() {
+        let pages = parse_layout_pages(LAYOUT_OUTPUT);
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].flows.len(), 2);
+        assert!(pages[1].flows.is_empty());
+
+        let block = &pages[0].flows[0].blocks[0];
+        assert_eq!(block.bbox.x_max, 174.8);
+        assert_eq!(block.lines.len(), 2);
+        assert_eq!(block.lines[1].bbox.y_min, 72.0);
+        assert_eq!(block.lines[0].words[1].text, "line");
+        assert_eq!(block.text(), "First line\nSecond");
+
+        assert_eq!(pages[0].text(), "First line\nSecond\n\nNext\n\nColumn");
+        assert_eq!(pages[1].text(), "");
+    }
+
+    /// Tests parsing the records from the TSV output
+    #[test]
+    fn test_parse_tsv_records() {
+        let output = "\
+level\tpage_num\tpar_num\tblock_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0.000000\t0.000000\t612.000000\t792.000000\t-1\t###PAGE###
+3\t1\t0\t0\t0\t0\t56.800000\t57.208000\t63.700000\t13.284000\t-1\t###FLOW###
+4\t1\t0\t0\t0\t0\t56.800000\t57.208000\t63.700000\t13.284000\t-1\t###LINE###
+5\t1\t0\t0\t0\t0\t56.800000\t57.208000\t23.448000\t13.284000\t100\tSecret
+5\t1\t0\t0\t0\t1\t83.800000\t57.208000\t36.700000\t13.284000\t100\tA&B words
+malformed
+";
+
+        let records = parse_tsv_records(output);
+        assert_eq!(records.len(), 5);
+
+        assert_eq!(records[0].level, TsvLevel::Page);
+        assert_eq!(records[0].width, 612.0);
+        assert_eq!(records[0].confidence, None);
+        assert_eq!(records[1].level, TsvLevel::Paragraph);
+        assert_eq!(records[2].text, "###LINE###");
+
+        let word = &records[4];
+        assert_eq!(word.level, TsvLevel::Word);
+        assert_eq!(word.page, 1);
+        assert_eq!(word.word, 1);
+        assert_eq!(word.confidence, Some(100.0));
+        assert_eq!(word.text, "A&B words");
+        assert_eq!(word.bbox().x_max, 83.8 + 36.7);
+    }
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "zip")]
+
+use std::io::Cursor;
+
+use pdf_process::{
+    pdf_info, render_to_zip, OutputFormat, PageSelection, PdfInfoArgs, RenderArgs, ZipArgs,
+};
+use tokio::fs::read;
+use zip::ZipArchive;
+
+/// Tests rendering pages into a ZIP archive
+#[tokio::test]
+async fn test_render_to_zip() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+
+    let zip_args = ZipArgs::default().set_page_padding(2);
+    let output = render_to_zip(
+        &data,
+        &info,
+        OutputFormat::Png,
+        &PageSelection::List(vec![2, 1, 2]),
+        &RenderArgs::default(),
+        &zip_args,
+    )
+    .await
+    .unwrap();
+
+    let mut archive = ZipArchive::new(Cursor::new(output)).unwrap();
+    let names: Vec<&str> = archive.file_names().collect();
+    assert_eq!(names.len(), 2);
+    assert!(names.contains(&"page-01.png"));
+    assert!(names.contains(&"page-02.png"));
+
+    let file = archive.by_name("page-01.png").unwrap();
+    assert!(file.size() > 0);
+}
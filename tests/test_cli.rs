@@ -0,0 +1,78 @@
+#![cfg(feature = "cli")]
+
+use std::process::{Command, Output};
+
+use serde_json::Value;
+
+/// Runs the command line tool with the provided arguments
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pdf-process"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Parses the JSON printed by the command line tool
+fn parse(output: &[u8]) -> Value {
+    serde_json::from_slice(output).unwrap()
+}
+
+/// Tests failures are printed as JSON to stderr
+#[test]
+fn test_cli_error() {
+    let output = run(&["info", "./tests/samples/missing.pdf"]);
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let error = parse(&output.stderr);
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("failed to read ./tests/samples/missing.pdf"));
+}
+
+/// Tests printing the info summary
+#[test]
+fn test_cli_info() {
+    let output = run(&["info", "./tests/samples/test-pdf-2-pages.pdf"]);
+    assert!(output.status.success());
+
+    let info = parse(&output.stdout);
+    assert_eq!(info["pages"], 2);
+}
+
+/// Tests extracting the text from every page without a trailing empty page
+#[test]
+fn test_cli_text() {
+    let output = run(&["text", "./tests/samples/test-pdf-2-pages.pdf"]);
+    assert!(output.status.success());
+
+    let text = parse(&output.stdout);
+    assert_eq!(
+        text["pages"],
+        serde_json::json!(["Test pdf with text in it\n\n", "Test page 2\n\n"])
+    );
+}
+
+/// Tests splitting every page into separate files
+#[test]
+fn test_cli_split() {
+    let directory = tempfile::tempdir().unwrap();
+    let output = run(&[
+        "split",
+        "./tests/samples/test-pdf-2-pages.pdf",
+        "--output",
+        directory.path().to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    let split = parse(&output.stdout);
+    let files = split["files"].as_array().unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0]["page"], 1);
+    assert_eq!(files[1]["page"], 2);
+
+    assert!(directory.path().join("test-pdf-2-pages-1.pdf").exists());
+    assert!(directory.path().join("test-pdf-2-pages-2.pdf").exists());
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "compat")]
+
+use pdf_process::{
+    pdf_info, render_single_page_raw, JpegOptions, OutputFormat, PdfInfoArgs, PopplerCompat,
+    PopplerOption, RenderArgs,
+};
+use tokio::fs::read;
+
+/// Tests rendering with args adapted to the installed poppler
+#[tokio::test]
+async fn test_render_adapted_args() {
+    let data = read("./tests/samples/test-pdf.pdf").await.unwrap();
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+
+    let compat = PopplerCompat::detect().await.unwrap();
+    let args = RenderArgs::default().set_jpeg_options(JpegOptions::default().set_quality(80));
+
+    let adapted = compat.render_args(&args);
+    assert_eq!(
+        adapted.jpeg_options.is_some(),
+        compat.supports(PopplerOption::JpegOptions)
+    );
+
+    let output = render_single_page_raw(&data, &info, OutputFormat::Jpeg, 1, &adapted)
+        .await
+        .unwrap();
+    assert!(!output.is_empty());
+}
@@ -0,0 +1,20 @@
+use pdf_process::{export_html, pdf_info, ExportOptions, OutputFormat, PdfInfoArgs};
+use tokio::fs::read;
+
+/// Tests exporting a self-contained HTML document
+#[tokio::test]
+async fn test_export_html() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+
+    let options = ExportOptions::default()
+        .set_format(OutputFormat::Png)
+        .set_title("Test & Title");
+    let html = export_html(&data, &info, &options).await.unwrap();
+
+    assert!(html.contains("<title>Test &amp; Title</title>"));
+    assert!(html.contains("id=\"page-1\""));
+    assert!(html.contains("id=\"page-2\""));
+    assert!(html.contains("data:image/png;base64,"));
+    assert!(html.contains("Test page 2"));
+}
@@ -1,6 +1,13 @@
+#![cfg(any(feature = "image", feature = "image-0-24"))]
+
+use futures_util::StreamExt;
 use pdf_process::{
-    pdf_info, render_all_pages, render_pages, render_single_page, OutputFormat, Password,
-    PdfInfoArgs, PdfRenderError, RenderArgs,
+    changed_pages, info_and_first_page, page_signatures, pdf_info, render_all_pages,
+    render_all_pages_batched, render_all_pages_stream, render_page_to_writer, render_pages,
+    render_pages_with, render_single_page, render_single_page_luma8, render_single_page_monochrome,
+    render_single_page_raw, render_single_page_rgba8, render_thumbnail_strip, Binarization, Budget,
+    BudgetLimit, Crop, MonochromeDepth, MonochromeOptions, OutputFormat, Password, PdfInfoArgs,
+    PdfRenderError, RenderArgs, RenderColor, Resolution, ScaleTo, StreamOrder,
 };
 use tokio::fs::read;
 
@@ -30,6 +37,193 @@ async fn test_specific_page() {
         .unwrap();
 }
 
+/// Tests rendering a region of a page
+#[tokio::test]
+async fn test_cropped_page() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = RenderArgs::default().set_crop(Crop::new(10, 20, 120, 80));
+
+    let output = render_single_page(&data, &info, OutputFormat::Png, 1, &args)
+        .await
+        .unwrap();
+    assert_eq!((output.width(), output.height()), (120, 80));
+
+    let args = RenderArgs::default().set_crop(Crop::uniform(0, 0, 64));
+    let output = render_single_page(&data, &info, OutputFormat::Png, 2, &args)
+        .await
+        .unwrap();
+    assert_eq!((output.width(), output.height()), (64, 64));
+}
+
+/// Tests rendering a specific page into typed pixel buffers
+#[tokio::test]
+async fn test_specific_page_typed() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = RenderArgs::default();
+
+    let output = render_single_page_rgba8(&data, &info, OutputFormat::Png, 1, &args)
+        .await
+        .unwrap();
+    assert_eq!(
+        output.as_raw().len(),
+        (output.width() * output.height() * 4) as usize
+    );
+
+    let args = RenderArgs::default().set_render_color(RenderColor::Grayscale);
+    let output = render_single_page_luma8(&data, &info, OutputFormat::Png, 1, &args)
+        .await
+        .unwrap();
+    assert_eq!(
+        output.as_raw().len(),
+        (output.width() * output.height()) as usize
+    );
+}
+
+/// Tests rendering stops once the budget for the operation is used
+#[tokio::test]
+async fn test_all_pages_budget() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+
+    let budget = Budget::default().set_max_processes(1);
+    let args = RenderArgs::default().set_budget(budget.clone());
+    let err = render_all_pages(&data, &info, OutputFormat::Png, &args)
+        .await
+        .unwrap_err();
+
+    let PdfRenderError::BudgetExceeded(err) = err else {
+        panic!("expected budget exceeded, got {err:?}");
+    };
+    assert_eq!(err.limit, BudgetLimit::Processes);
+    assert_eq!(budget.usage().processes, 1);
+}
+
+/// Tests rendering a page as packed black and white pixels
+#[tokio::test]
+async fn test_specific_page_monochrome() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = RenderArgs::default();
+
+    for binarization in [Binarization::Renderer, Binarization::FloydSteinberg] {
+        let options = MonochromeOptions::default()
+            .set_binarization(binarization)
+            .set_bit_depth(MonochromeDepth::One);
+        let output = render_single_page_monochrome(&data, &info, 1, &args, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            output.pixels.len(),
+            output.width.div_ceil(8) as usize * output.height as usize
+        );
+        assert!(output.pixels.iter().any(|byte| *byte != 0));
+    }
+}
+
+/// Tests rendering a specific page without decoding the output
+#[tokio::test]
+async fn test_specific_page_raw() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = RenderArgs::default();
+
+    let output = render_single_page_raw(&data, &info, OutputFormat::Png, 1, &args)
+        .await
+        .unwrap();
+    assert!(output.starts_with(b"\x89PNG"));
+}
+
+/// Tests rendering all pages with a single process
+#[tokio::test]
+async fn test_all_pages_batched() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = RenderArgs::default();
+
+    let batched = render_all_pages_batched(&data, &info, OutputFormat::Png, &args)
+        .await
+        .unwrap();
+    let separate = render_all_pages(&data, &info, OutputFormat::Png, &args)
+        .await
+        .unwrap();
+    assert_eq!(batched.len(), 2);
+    assert_eq!(batched, separate);
+}
+
+/// Tests all pages are provided by the stream in both orders
+#[tokio::test]
+async fn test_all_pages_stream() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = RenderArgs::default();
+
+    let stream =
+        render_all_pages_stream(&data, &info, OutputFormat::Png, &args, StreamOrder::Ordered)
+            .await
+            .unwrap();
+    let pages: Vec<u32> = stream.map(|result| result.unwrap().0).collect().await;
+    assert_eq!(pages, [1, 2]);
+
+    let stream = render_all_pages_stream(
+        &data,
+        &info,
+        OutputFormat::Png,
+        &args,
+        StreamOrder::Unordered,
+    )
+    .await
+    .unwrap();
+    let mut pages: Vec<u32> = stream.map(|result| result.unwrap().0).collect().await;
+    pages.sort_unstable();
+    assert_eq!(pages, [1, 2]);
+}
+
+/// Tests streaming a page into a writer
+#[tokio::test]
+async fn test_page_to_writer() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let args = RenderArgs::default();
+
+    let mut output = Vec::new();
+    let written = render_page_to_writer(&data, OutputFormat::Png, 1, &args, &mut output)
+        .await
+        .unwrap();
+    assert_eq!(written, output.len());
+    assert!(output.starts_with(b"\x89PNG"));
+
+    let args = args.set_max_output_size(16);
+    let err = render_page_to_writer(&data, OutputFormat::Png, 1, &args, Vec::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PdfRenderError::OutputLimitExceeded(16)));
+}
+
+/// Tests rendering a page as WebP, rendered as PNG and re-encoded
+#[cfg(feature = "webp")]
+#[tokio::test]
+async fn test_specific_page_webp() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = RenderArgs::default();
+
+    let output = render_single_page_raw(&data, &info, OutputFormat::WebP, 1, &args)
+        .await
+        .unwrap();
+    assert!(output.starts_with(b"RIFF"));
+
+    // Decoding skips the re-encode
+    let image = render_single_page(&data, &info, OutputFormat::WebP, 1, &args)
+        .await
+        .unwrap();
+    assert!(image.width() > 0);
+}
+
 /// Tests rendering a specific set of pages
 #[tokio::test]
 async fn test_specific_pages() {
@@ -45,6 +239,29 @@ async fn test_specific_pages() {
     assert_eq!(output.len(), 2);
 }
 
+/// Tests rendering pages each with their own args
+#[tokio::test]
+async fn test_pages_with_args() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let pages = vec![
+        (
+            1,
+            RenderArgs::default().set_resolution(Resolution::uniform(150)),
+        ),
+        (2, RenderArgs::default().set_scale_to(ScaleTo::x(64))),
+    ];
+
+    let output = render_pages_with(&data, &info, OutputFormat::Png, pages)
+        .await
+        .unwrap();
+
+    assert_eq!(output.len(), 2);
+    assert_eq!(output[1].width(), 64);
+    assert!(output[0].width() > output[1].width());
+}
+
 /// Tests preventing attempts at rendering a page that goes out
 /// of bounds from the acceptable number of pages
 #[tokio::test]
@@ -142,3 +359,73 @@ async fn test_encrypted_with_incorrect_password() {
 
     assert!(matches!(err, PdfRenderError::IncorrectPassword));
 }
+
+/// Tests reading the info while rendering the first page
+#[tokio::test]
+async fn test_info_and_first_page() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let (info, _image) = info_and_first_page(&data, OutputFormat::Jpeg, &RenderArgs::default())
+        .await
+        .unwrap();
+    assert_eq!(info.pages(), Some(Ok(2)));
+}
+
+/// Tests the first page render is cancelled for encrypted files
+#[tokio::test]
+async fn test_info_and_first_page_encrypted() {
+    let data = read("./tests/samples/test-pdf-2-pages-encrypted.pdf")
+        .await
+        .unwrap();
+
+    let err = info_and_first_page(&data, OutputFormat::Jpeg, &RenderArgs::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PdfRenderError::PdfEncrypted));
+}
+
+/// Tests pages out of bounds are reported by pdftocairo when verification is skipped
+#[tokio::test]
+async fn test_out_of_bounds_unverified() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = RenderArgs::default().set_verify(false);
+    let err = render_single_page_raw(&data, &info, OutputFormat::Jpeg, 3, &args)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PdfRenderError::PageOutOfBounds(3, 2)));
+}
+
+/// Tests rendering the page thumbnails into a single strip
+#[tokio::test]
+async fn test_thumbnail_strip() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+
+    let strip = render_thumbnail_strip(&data, &info, 64, 10, &RenderArgs::default())
+        .await
+        .unwrap();
+
+    assert_eq!(strip.height(), 64);
+    // Two portrait A4 pages side by side
+    assert!(strip.width() > 64 && strip.width() < 128);
+}
+
+/// Tests the page signatures are stable and differ between pages
+#[tokio::test]
+async fn test_page_signatures() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+
+    let first = page_signatures(&data, &info, &RenderArgs::default())
+        .await
+        .unwrap();
+    let second = page_signatures(&data, &info, &RenderArgs::default())
+        .await
+        .unwrap();
+
+    assert_eq!(first.len(), 2);
+    assert_ne!(first[0], first[1]);
+    assert!(changed_pages(&first, &second).is_empty());
+}
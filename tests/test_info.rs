@@ -1,4 +1,8 @@
-use pdf_process::{pdf_info, Password, PdfInfoArgs, PdfInfoError};
+use pdf_process::{
+    classify_document, classify_password, page_dimensions, pdf_encryption_state, pdf_info,
+    pdf_is_encrypted, pdf_page_count, quick_page_count, ClassifyArgs, DocumentClass,
+    EncryptionAlgorithm, EncryptionState, Password, PasswordMatch, PdfInfoArgs, PdfInfoError,
+};
 use tokio::fs::read;
 
 /// Tests from actual files
@@ -13,6 +17,41 @@ async fn test_actual_files() {
     assert_eq!(info.pages(), Some(Ok(1)));
 }
 
+/// Tests reading the page count directly from the page tree
+#[tokio::test]
+async fn test_quick_page_count() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    assert_eq!(quick_page_count(&data), Some(2));
+    assert_eq!(
+        pdf_page_count(&data, &PdfInfoArgs::default())
+            .await
+            .unwrap(),
+        2
+    );
+
+    let data = read("./tests/samples/test-pdf.pdf").await.unwrap();
+    assert_eq!(quick_page_count(&data), Some(1));
+
+    // Encrypted files are left for pdfinfo
+    let data = read("./tests/samples/test-pdf-2-pages-encrypted.pdf")
+        .await
+        .unwrap();
+    assert_eq!(quick_page_count(&data), None);
+}
+
+/// Tests getting the dimensions of a specific page
+#[tokio::test]
+async fn test_page_dimensions() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let dimensions = page_dimensions(&data, 2, &PdfInfoArgs::default())
+        .await
+        .unwrap();
+
+    assert_eq!(dimensions.width_pts.round(), 595.0);
+    assert_eq!(dimensions.height_pts.round(), 842.0);
+    assert_eq!(dimensions.rotation, 0);
+}
+
 /// Tests getting pdfinfo from an encrypted file when the password is not set
 #[tokio::test]
 async fn test_encrypted() {
@@ -43,7 +82,9 @@ async fn test_encrypted_with_password() {
     assert!(encryption.is_copy_allowed());
     assert!(encryption.is_change_allowed());
     assert!(encryption.is_add_notes_allowed());
-    assert_eq!(encryption.algorithm(), Some("AES"));
+    assert_eq!(encryption.algorithm(), Some(EncryptionAlgorithm::Aes128));
+    assert_eq!(encryption.algorithm_raw(), Some("AES"));
+    assert_eq!(encryption.key_length(), Some(128));
 
     let args = PdfInfoArgs::default().set_password(Password::user("password"));
     let info = pdf_info(&data, &args).await.unwrap();
@@ -63,3 +104,46 @@ async fn test_encrypted_with_incorrect_password() {
 
     assert!(matches!(err, PdfInfoError::IncorrectPassword));
 }
+
+/// Tests classifying candidate passwords for an encrypted file
+#[tokio::test]
+async fn test_classify_password() {
+    let data = read("./tests/samples/test-pdf-2-pages-encrypted.pdf")
+        .await
+        .unwrap();
+
+    let result = classify_password(&data, "password").await.unwrap();
+    assert!(result.is_user());
+
+    let result = classify_password(&data, "incorrect").await.unwrap();
+    assert_eq!(result, PasswordMatch::Neither);
+}
+
+/// Tests checking the encryption state of files
+#[tokio::test]
+async fn test_encryption_state() {
+    let data = read("./tests/samples/test-pdf.pdf").await.unwrap();
+    let state = pdf_encryption_state(&data).await.unwrap();
+    assert_eq!(state, EncryptionState::Unencrypted);
+    assert!(!pdf_is_encrypted(&data).await.unwrap());
+
+    let data = read("./tests/samples/test-pdf-2-pages-encrypted.pdf")
+        .await
+        .unwrap();
+    let state = pdf_encryption_state(&data).await.unwrap();
+    assert_eq!(state, EncryptionState::PasswordRequired);
+    assert!(pdf_is_encrypted(&data).await.unwrap());
+}
+
+/// Tests classifying a digitally created file
+#[tokio::test]
+async fn test_classify_document() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+    let classification = classify_document(&data, &ClassifyArgs::default())
+        .await
+        .unwrap();
+
+    assert_eq!(classification.class, DocumentClass::BornDigital);
+    assert_eq!(classification.signals.page_count, 2);
+    assert!(classification.signals.scanned_pages.is_empty());
+}
@@ -0,0 +1,52 @@
+use pdf_process::{generate_preview, poppler_version, OutputFormat, PreviewOptions};
+use tokio::fs::read;
+
+/// Tests generating a preview with thumbnails for both pages
+#[tokio::test]
+async fn test_preview() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let options = PreviewOptions::default()
+        .set_format(OutputFormat::Png)
+        .set_max_thumbnails(5)
+        .set_max_text_chars(11);
+    let preview = generate_preview(&data, &options).await.unwrap();
+
+    assert_eq!(preview.info.pages(), Some(Ok(2)));
+    assert_eq!(preview.thumbnails.len(), 2);
+    assert_eq!(preview.text_excerpt.as_str(), "Test pdf wi");
+}
+
+/// Tests generating a preview without decoding the thumbnails
+#[cfg(any(feature = "image", feature = "image-0-24"))]
+#[tokio::test]
+async fn test_preview_bytes() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let options = PreviewOptions::default().set_decode(false);
+    let preview = generate_preview(&data, &options).await.unwrap();
+
+    assert_eq!(preview.thumbnails.len(), 1);
+    assert!(matches!(
+        preview.thumbnails[0],
+        pdf_process::ImageOrBytes::Bytes(_)
+    ));
+}
+
+/// Tests recording the poppler version and render parameters
+#[tokio::test]
+async fn test_preview_provenance() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let preview = generate_preview(&data, &PreviewOptions::default())
+        .await
+        .unwrap();
+    assert!(preview.provenance.is_none());
+
+    let options = PreviewOptions::default().set_record_provenance(true);
+    let preview = generate_preview(&data, &options).await.unwrap();
+
+    let provenance = preview.provenance.unwrap();
+    assert_eq!(provenance.poppler_version, poppler_version().await.unwrap());
+    assert_eq!(provenance.args[0], "-jpeg");
+}
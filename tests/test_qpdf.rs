@@ -0,0 +1,59 @@
+#![cfg(feature = "qpdf")]
+
+use pdf_process::{
+    pdf_info, reencrypt_pdf, strip_metadata, Password, PdfInfoArgs, PdfInfoError, PdfPermission,
+    PdfSecurity, QpdfError,
+};
+use tokio::fs::read;
+
+/// Tests changing the password and permissions of an encrypted file
+#[tokio::test]
+async fn test_reencrypt_pdf() {
+    let data = read("./tests/samples/test-pdf-2-pages-encrypted.pdf")
+        .await
+        .unwrap();
+
+    let security = PdfSecurity::new("rotated", "owner").set_permission(PdfPermission::Copy, false);
+    let output = reencrypt_pdf(&data, Some(&Password::user("password")), &security)
+        .await
+        .unwrap();
+
+    // Old password no longer opens the file
+    let args = PdfInfoArgs::default().set_password(Password::user("password"));
+    let err = pdf_info(&output, &args).await.unwrap_err();
+    assert!(matches!(err, PdfInfoError::IncorrectPassword));
+
+    let args = PdfInfoArgs::default().set_password(Password::user("rotated"));
+    let info = pdf_info(&output, &args).await.unwrap();
+    assert_eq!(info.pages(), Some(Ok(2)));
+
+    let encryption = info.encryption().unwrap().unwrap();
+    assert!(encryption.is_print_allowed());
+    assert!(!encryption.is_copy_allowed());
+}
+
+/// Tests re-encrypting with the wrong password
+#[tokio::test]
+async fn test_reencrypt_incorrect_password() {
+    let data = read("./tests/samples/test-pdf-2-pages-encrypted.pdf")
+        .await
+        .unwrap();
+
+    let security = PdfSecurity::new("rotated", "owner");
+    let err = reencrypt_pdf(&data, Some(&Password::user("incorrect")), &security)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, QpdfError::IncorrectPassword));
+}
+
+/// Tests removing the document metadata
+#[tokio::test]
+async fn test_strip_metadata() {
+    let data = read("./tests/samples/test-pdf.pdf").await.unwrap();
+    let output = strip_metadata(&data).await.unwrap();
+
+    let info = pdf_info(&output, &PdfInfoArgs::default()).await.unwrap();
+    assert_eq!(info.title(), None);
+    assert_eq!(info.producer(), None);
+    assert_eq!(info.pages(), Some(Ok(1)));
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "service")]
+
+use pdf_process::{
+    Password, PdfInfoError, PolicyViolation, PreviewService, ServiceError, ServicePolicy,
+};
+use tokio::fs::read;
+
+/// Tests previewing an encrypted upload with its password
+#[tokio::test]
+async fn test_preview_encrypted() {
+    let data = read("./tests/samples/test-pdf-2-pages-encrypted.pdf")
+        .await
+        .unwrap();
+    let password = Password::user("password");
+
+    // Rejected by the default policy even with the password
+    let service = PreviewService::new(ServicePolicy::default(), 1);
+    let err = service
+        .preview_with_password(&data, &password)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ServiceError::Policy(PolicyViolation::Encrypted)
+    ));
+
+    let service = PreviewService::new(ServicePolicy::default().set_allow_encrypted(true), 1);
+    let err = service.preview(&data).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ServiceError::Info(PdfInfoError::PdfEncrypted)
+    ));
+
+    let bundle = service
+        .preview_with_password(&data, &password)
+        .await
+        .unwrap();
+    assert_eq!(bundle.info.pages, Some(2));
+    assert!(!bundle.thumbnail.is_empty());
+}
@@ -0,0 +1,22 @@
+use pdf_process::{page_records, SidecarOptions};
+use tokio::fs::read;
+
+/// Tests getting the metadata records for each page
+#[tokio::test]
+async fn test_page_records() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let records = page_records(&data, &SidecarOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].page, 1);
+    assert_eq!(records[0].words, 6);
+    assert_eq!(records[1].words, 3);
+    assert_eq!(records[1].image_count, 0);
+
+    let dimensions = records[1].dimensions.unwrap();
+    assert_eq!(dimensions.width_pts.round(), 595.0);
+    assert_ne!(records[0].render_hash, records[1].render_hash);
+}
@@ -1,6 +1,10 @@
 use pdf_process::{
-    pdf_info, text_all_pages, text_all_pages_split, text_pages, text_single_page, Password,
-    PdfInfoArgs, PdfTextArgs, PdfTextError,
+    document_stats, find_first, pdf_info, text_all_pages, text_all_pages_bytes,
+    text_all_pages_split, text_excerpt, text_page_selection, text_pager, text_pages,
+    text_pages_numbered, text_pages_partial, text_pages_with_provenance, text_single_page,
+    text_single_page_bytes, text_tsv, text_with_boxes, text_with_layout, verify_redaction,
+    BidiMode, Eol, ExtractionMethod, FindOptions, PageBreak, PageSelection, Password, PdfInfoArgs,
+    PdfTextArgs, PdfTextError, TextEncoding, TsvLevel,
 };
 use tokio::fs::read;
 
@@ -24,6 +28,87 @@ async fn test_single_page() {
     assert_eq!(text.as_str(), expected);
 }
 
+/// Tests finding the first page containing some text
+#[tokio::test]
+async fn test_find_first() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let options = FindOptions::default().set_context_chars(5);
+
+    let found = find_first(&data, &info, "page 2", &options)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.page, 2);
+    assert_eq!(found.text, "page 2");
+    assert_eq!(found.context, "Test page 2\n\n");
+
+    let options = options.set_pages(PageSelection::Odd);
+    let found = find_first(&data, &info, "page 2", &options).await.unwrap();
+    assert_eq!(found, None);
+}
+
+/// Tests the completed pages are provided with any error
+#[tokio::test]
+async fn test_pages_partial() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = PdfTextArgs::default();
+
+    let result = text_pages_partial(&data, &info, vec![2, 1], &args).await;
+    assert!(result.is_complete());
+    assert_eq!(result.completed[0], (2, "Test page 2\n\n".to_string()));
+
+    let result = text_pages_partial(&data, &info, vec![1, 3], &args).await;
+    assert!(result.completed.is_empty());
+    assert!(matches!(
+        result.error,
+        Some(PdfTextError::PageOutOfBounds(3, 2))
+    ));
+}
+
+/// Tests the text of each page is tagged with how it was extracted
+#[tokio::test]
+async fn test_pages_with_provenance() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = PdfTextArgs::default();
+
+    let pages = text_pages_with_provenance(&data, &info, vec![2, 1], &args)
+        .await
+        .unwrap();
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[0].page, 2);
+    assert_eq!(pages[0].text, "Test page 2\n\n");
+    assert_eq!(pages[0].provenance.method, ExtractionMethod::ReadingOrder);
+    assert_eq!(pages[0].provenance.tool, "pdftotext");
+    assert_eq!(pages[1].provenance, pages[0].provenance);
+}
+
+/// Tests reading the text from a selection of pages
+#[tokio::test]
+async fn test_page_selection() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+    let args = PdfTextArgs::default();
+
+    let text = text_page_selection(&data, &info, &PageSelection::Even, &args)
+        .await
+        .unwrap();
+    assert_eq!(text, [(2, "Test page 2\n\n".to_string())]);
+
+    let selection = PageSelection::All.reversed();
+    let text = text_page_selection(&data, &info, &selection, &args)
+        .await
+        .unwrap();
+    let pages: Vec<u32> = text.iter().map(|(page, _)| *page).collect();
+    assert_eq!(pages, [2, 1]);
+}
+
 /// Tests reading the text from all pages
 #[tokio::test]
 async fn test_all_pages() {
@@ -38,6 +123,95 @@ async fn test_all_pages() {
     assert_eq!(text.as_str(), expected);
 }
 
+/// Tests converting a Hebrew line stored in visual order into logical order
+#[tokio::test]
+async fn test_rtl_sample() {
+    let data = read("./tests/samples/test-pdf-rtl.pdf").await.unwrap();
+
+    let visual = text_all_pages(&data, &PdfTextArgs::default().set_bidi(BidiMode::Keep))
+        .await
+        .unwrap();
+    let logical = text_all_pages(
+        &data,
+        &PdfTextArgs::default().set_bidi(BidiMode::VisualToLogical),
+    )
+    .await
+    .unwrap();
+
+    // The Hebrew word is reversed while the number keeps its order
+    assert!(visual.contains("1500") && logical.contains("1500"));
+    assert_ne!(visual.contains("שלום"), logical.contains("שלום"));
+    assert_ne!(visual.contains("םולש"), logical.contains("םולש"));
+}
+
+/// Tests joining vertical Japanese text into a single line
+#[tokio::test]
+async fn test_vertical_cjk_sample() {
+    let data = read("./tests/samples/test-pdf-vertical-cjk.pdf")
+        .await
+        .unwrap();
+
+    let text = text_all_pages(&data, &PdfTextArgs::default())
+        .await
+        .unwrap();
+    assert!(["日", "本", "語"].iter().all(|value| text.contains(value)));
+
+    let args = PdfTextArgs::default().set_join_vertical_cjk(true);
+    let text = text_all_pages(&data, &args).await.unwrap();
+    assert!(text.contains("日本語"));
+}
+
+/// Tests reading the pdftotext output in the encoding without decoding it
+#[tokio::test]
+async fn test_text_bytes() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+
+    let args = PdfTextArgs::default().set_encoding(TextEncoding::Ucs2);
+    let text = text_single_page_bytes(&data, &info, 2, &args)
+        .await
+        .unwrap();
+    let expected: Vec<u8> = "Test page 2\n\n\u{c}"
+        .encode_utf16()
+        .flat_map(u16::to_be_bytes)
+        .collect();
+    assert_eq!(text, expected);
+
+    let args = PdfTextArgs::default().set_encoding(TextEncoding::Latin1);
+    let text = text_all_pages_bytes(&data, &args).await.unwrap();
+    assert_eq!(text, b"Test pdf with text in it\n\n\x0cTest page 2\n\n\x0c");
+}
+
+/// Tests reading all the pages with DOS line endings
+#[tokio::test]
+async fn test_all_pages_eol() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let args = PdfTextArgs::default().set_eol(Eol::Dos);
+    let text = text_all_pages(&data, &args).await.unwrap();
+    assert_eq!(
+        text.as_str(),
+        "Test pdf with text in it\r\n\r\n\r\nTest page 2\r\n\r\n\r\n"
+    );
+}
+
+/// Tests the page breaks are left out by pdftotext
+#[tokio::test]
+async fn test_all_pages_no_page_breaks() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let args = PdfTextArgs::default()
+        .set_no_page_breaks(true)
+        .set_page_break(PageBreak::Keep);
+    let text = text_all_pages(&data, &args).await.unwrap();
+    assert_eq!(text.as_str(), "Test pdf with text in it\n\nTest page 2\n\n");
+
+    // Splitting into pages still uses the page breaks
+    let pages = text_all_pages_split(&data, &args).await.unwrap();
+    assert_eq!(pages.len(), 3);
+}
+
 /// Tests reading specific pages text
 #[tokio::test]
 async fn test_pages() {
@@ -73,6 +247,31 @@ async fn test_all_pages_split() {
     assert_eq!(text, expected);
 }
 
+/// Tests the page break handling options
+#[tokio::test]
+async fn test_page_break_options() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let args = PdfTextArgs::default().set_page_break(PageBreak::Separator("<hr>".to_string()));
+    let text = text_all_pages(&data, &args).await.unwrap();
+    assert_eq!(text, "Test pdf with text in it\n\n<hr>Test page 2\n\n<hr>");
+
+    let args = PdfTextArgs::default().set_page_break(PageBreak::Keep);
+    let text = text_all_pages(&data, &args).await.unwrap();
+    assert_eq!(text.matches('\u{c}').count(), 2);
+
+    let args = PdfTextArgs::default().set_page_break(PageBreak::None);
+    let text = text_all_pages(&data, &args).await.unwrap();
+    assert_eq!(text, "Test pdf with text in it\n\nTest page 2\n\n");
+
+    let args = PdfTextArgs::default().set_trim_trailing_page(true);
+    let text = text_all_pages_split(&data, &args).await.unwrap();
+    assert_eq!(
+        text,
+        vec!["Test pdf with text in it\n\n", "Test page 2\n\n"]
+    );
+}
+
 /// Tests preventing attempts extracting text on a page that goes out
 /// of bounds from the acceptable number of pages
 #[tokio::test]
@@ -166,3 +365,154 @@ async fn test_encrypted_with_incorrect_password() {
         .unwrap_err();
     assert!(matches!(err, PdfTextError::IncorrectPassword));
 }
+
+/// Tests reading a limited excerpt of text from the start of the PDF
+#[tokio::test]
+async fn test_excerpt() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let text = text_excerpt(&data, 8, &PdfTextArgs::default())
+        .await
+        .unwrap();
+    assert_eq!(text.as_str(), "Test pdf");
+
+    let expected = "Test pdf with text in it\n\n\nTest";
+    let text = text_excerpt(&data, expected.chars().count(), &PdfTextArgs::default())
+        .await
+        .unwrap();
+    assert_eq!(text.as_str(), expected);
+}
+
+/// Tests lazily reading the text one page at a time
+#[tokio::test]
+async fn test_pager() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+
+    let args = PdfTextArgs::default();
+    let mut pager = text_pager(&data, &info, &args).await.unwrap();
+    assert_eq!(pager.remaining(), 2);
+
+    let (page, text) = pager.next().await.unwrap().unwrap();
+    assert_eq!(page, 1);
+    assert_eq!(text.as_str(), "Test pdf with text in it\n\n");
+    assert_eq!(pager.remaining(), 1);
+
+    let (page, text) = pager.next().await.unwrap().unwrap();
+    assert_eq!(page, 2);
+    assert_eq!(text.as_str(), "Test page 2\n\n");
+
+    assert!(pager.next().await.is_none());
+}
+
+/// Tests computing the text statistics for all pages
+#[tokio::test]
+async fn test_document_stats() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let stats = document_stats(&data, &PdfTextArgs::default())
+        .await
+        .unwrap();
+    assert_eq!(stats.words, 9);
+    assert_eq!(stats.pages.len(), 2);
+    assert_eq!(stats.pages[0].words, 6);
+    assert_eq!(stats.pages[1].words, 3);
+}
+
+/// Tests finding text that should have been redacted
+#[tokio::test]
+async fn test_verify_redaction() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let hits = verify_redaction(&data, &["text in", "missing"], &PdfTextArgs::default())
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].term.as_str(), "text in");
+    assert_eq!(hits[0].page, 1);
+}
+
+/// Tests reading the words of each page with their bounding boxes
+#[tokio::test]
+async fn test_with_boxes() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let pages = text_with_boxes(&data, &PdfTextArgs::default())
+        .await
+        .unwrap();
+    assert_eq!(pages.len(), 2);
+
+    let words: Vec<&str> = pages[1]
+        .words
+        .iter()
+        .map(|word| word.text.as_str())
+        .collect();
+    assert_eq!(words, ["Test", "page", "2"]);
+
+    for word in pages.iter().flat_map(|page| &page.words) {
+        assert!(word.x_min < word.x_max && word.y_min < word.y_max);
+        assert!(word.x_max <= pages[0].width && word.y_max <= pages[0].height);
+    }
+}
+
+/// Tests reading the words of each page grouped into blocks and lines
+#[tokio::test]
+async fn test_with_layout() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let pages = text_with_layout(&data, &PdfTextArgs::default())
+        .await
+        .unwrap();
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[0].text(), "Test pdf with text in it");
+    assert_eq!(pages[1].text(), "Test page 2");
+
+    let block = &pages[1].flows[0].blocks[0];
+    assert!(block.lines[0]
+        .words
+        .iter()
+        .all(|word| { word.x_min >= block.bbox.x_min && word.x_max <= block.bbox.x_max }));
+}
+
+/// Tests reading the records from the TSV output
+#[tokio::test]
+async fn test_tsv() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let records = text_tsv(&data, &PdfTextArgs::default()).await.unwrap();
+
+    let pages = records
+        .iter()
+        .filter(|record| record.level == TsvLevel::Page)
+        .count();
+    assert_eq!(pages, 2);
+
+    let words: Vec<&str> = records
+        .iter()
+        .filter(|record| record.level == TsvLevel::Word && record.page == 2)
+        .map(|record| record.text.as_str())
+        .collect();
+    assert_eq!(words, ["Test", "page", "2"]);
+}
+
+/// Tests reading the text from pages paired with their page numbers
+#[tokio::test]
+async fn test_pages_numbered() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let info = pdf_info(&data, &PdfInfoArgs::default()).await.unwrap();
+
+    let args = PdfTextArgs::default().set_dedup_pages(true);
+    let text = text_pages_numbered(&data, &info, vec![2, 1, 2], &args)
+        .await
+        .unwrap();
+    assert_eq!(
+        text,
+        vec![
+            (2, "Test page 2\n\n".to_string()),
+            (1, "Test pdf with text in it\n\n".to_string()),
+            (2, "Test page 2\n\n".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,61 @@
+use pdf_process::{run_poppler_tool, Password, PopplerTool, PopplerToolArgs, PopplerToolError};
+use tokio::fs::read;
+
+/// Tests running a tool directly with the PDF on stdin
+#[tokio::test]
+async fn test_run_poppler_tool() {
+    let data = read("./tests/samples/test-pdf-2-pages.pdf").await.unwrap();
+
+    let output = run_poppler_tool(
+        PopplerTool::PdfToText,
+        &["-f", "2", "-l", "2", "-", "-"],
+        &data,
+        &PopplerToolArgs::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Test page 2\n\n\u{c}"
+    );
+}
+
+/// Tests failures are classified the same as the other functions
+#[tokio::test]
+async fn test_run_poppler_tool_errors() {
+    let data = read("./tests/samples/test-pdf-2-pages-encrypted.pdf")
+        .await
+        .unwrap();
+
+    let err = run_poppler_tool(
+        PopplerTool::PdfInfo,
+        &["-"],
+        &data,
+        &PopplerToolArgs::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, PopplerToolError::PdfEncrypted));
+
+    let args = PopplerToolArgs::default().set_password(Password::user("password"));
+    let output = run_poppler_tool(PopplerTool::PdfInfo, &["-"], &data, &args)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Pages:"));
+
+    let err = run_poppler_tool(
+        PopplerTool::PdfInfo,
+        &["-"],
+        b"A",
+        &PopplerToolArgs::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, PopplerToolError::NotPdfFile));
+
+    let args = PopplerToolArgs::default().set_max_output_size(4);
+    let err = run_poppler_tool(PopplerTool::PdfToText, &["-", "-"], &data, &args)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PopplerToolError::OutputLimitExceeded(4)));
+}